@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use service::accuracy::GameAccuracy;
+use uuid::Uuid;
+
+/// Default number of games whose accuracy is kept in memory.
+pub const DEFAULT_ACCURACY_CACHE_CAPACITY: usize = 1024;
+
+/// Remembers computed game accuracies, since evaluating every position of a
+/// game is expensive. Entries are keyed by the number of moves analysed, so
+/// a game that has moved on since is evaluated again.
+pub struct AccuracyCache {
+    capacity: usize,
+    entries: Mutex<HashMap<Uuid, (usize, GameAccuracy)>>,
+}
+
+impl AccuracyCache {
+    pub fn new(capacity: usize) -> Self {
+        AccuracyCache {
+            capacity: capacity.max(1),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The accuracy computed for `game_id` when it had `moves` moves.
+    pub fn get(&self, game_id: Uuid, moves: usize) -> Option<GameAccuracy> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(&game_id)
+            .filter(|(analysed, _)| *analysed == moves)
+            .map(|(_, accuracy)| accuracy.clone())
+    }
+
+    /// Stores `accuracy` for `game_id`, evicting an arbitrary game when full.
+    pub fn insert(&self, game_id: Uuid, accuracy: GameAccuracy) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= self.capacity && !entries.contains_key(&game_id) {
+            if let Some(evicted) = entries.keys().next().copied() {
+                entries.remove(&evicted);
+            }
+        }
+        entries.insert(game_id, (accuracy.moves_analyzed, accuracy));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accuracy(moves_analyzed: usize) -> GameAccuracy {
+        GameAccuracy {
+            white: Some(90.0),
+            black: Some(80.0),
+            moves_analyzed,
+        }
+    }
+
+    #[test]
+    fn test_new_moves_invalidate_entry() {
+        let cache = AccuracyCache::new(4);
+        let game_id = Uuid::new_v4();
+        cache.insert(game_id, accuracy(10));
+        assert_eq!(cache.get(game_id, 10), Some(accuracy(10)));
+        assert_eq!(cache.get(game_id, 11), None);
+    }
+
+    #[test]
+    fn test_capacity_is_bounded() {
+        let cache = AccuracyCache::new(2);
+        let games: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        for game_id in &games {
+            cache.insert(*game_id, accuracy(1));
+        }
+        let cached = games.iter().filter(|id| cache.get(**id, 1).is_some()).count();
+        assert_eq!(cached, 2);
+        assert!(cache.get(games[2], 1).is_some());
+    }
+}
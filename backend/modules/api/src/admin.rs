@@ -0,0 +1,284 @@
+use actix_web::{
+    HttpResponse, get, post,
+    web::{self, Json, Path, Query},
+};
+use db_entity::game::ResultSide;
+use dto::admin::{
+    AdminAuditEntry, AdminAuditQuery, AdminAuditResponse, ForceResultRequest, ForceResultResponse,
+    RecomputeRatingsRequest, RecomputeRatingsResponse, RotateServerRequest, RotateServerResponse,
+};
+use error::error::ApiError;
+use sea_orm::DatabaseConnection;
+use serde_json::json;
+use security::AuthenticatedUser;
+use service::admin_audit::AdminAuditService;
+use service::game_results::GameResultService;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::config::AppConfig;
+use crate::registry::{RegistryAdmin, RegistryAdminError};
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/registry/server",
+    request_body = RotateServerRequest,
+    responses(
+        (status = 200, description = "set_server transaction submitted", body = RotateServerResponse),
+        (status = 400, description = "Invalid server address", body = InvalidCredentialsResponse),
+        (status = 401, description = "Unauthorized", body = InvalidCredentialsResponse),
+        (status = 403, description = "Caller is not an admin", body = InvalidCredentialsResponse),
+        (status = 502, description = "Soroban RPC rejected the transaction", body = InvalidCredentialsResponse),
+        (status = 503, description = "Registry administration is not configured", body = InvalidCredentialsResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    ),
+    tag = "Admin"
+)]
+#[post("/registry/server")]
+pub async fn rotate_registry_server(
+    payload: Json<RotateServerRequest>,
+    user: AuthenticatedUser,
+    config: web::Data<AppConfig>,
+    db: web::Data<DatabaseConnection>,
+    registry: Option<web::Data<RegistryAdmin>>,
+) -> HttpResponse {
+    let admin_id = match config.admin_players.authorize(&user) {
+        Ok(admin_id) => admin_id,
+        Err(forbidden) => return forbidden,
+    };
+
+    if let Err(errors) = payload.0.validate() {
+        return ApiError::ValidationError(errors).error_response();
+    }
+
+    let registry = match registry {
+        Some(registry) => registry,
+        None => {
+            return HttpResponse::ServiceUnavailable().json(json!({
+                "error": "Registry administration is not configured",
+                "code": 503
+            }));
+        }
+    };
+
+    match registry.set_server(&payload.0.new_server).await {
+        Ok(tx_hash) => {
+            log::info!(
+                "User {} rotated registry server to {} (tx {})",
+                admin_id,
+                payload.0.new_server,
+                tx_hash
+            );
+            // The transaction is already on its way, so a failed audit write
+            // is logged rather than reported as a failed rotation
+            if let Err(e) = AdminAuditService::record(
+                db.get_ref(),
+                admin_id,
+                "set_server",
+                &payload.0.new_server,
+                &tx_hash,
+            )
+            .await
+            {
+                log::error!("Failed to audit set_server transaction {}: {}", tx_hash, e);
+            }
+            HttpResponse::Ok().json(RotateServerResponse {
+                message: "Registry server rotated".to_string(),
+                tx_hash,
+            })
+        }
+        Err(e @ RegistryAdminError::InvalidAddress(_)) => HttpResponse::BadRequest().json(json!({
+            "error": e.to_string(),
+            "code": 400
+        })),
+        Err(e) => {
+            log::error!("Failed to rotate registry server: {}", e);
+            HttpResponse::BadGateway().json(json!({
+                "error": e.to_string(),
+                "code": 502
+            }))
+        }
+    }
+}
+
+/// Default and largest page size of the admin audit log.
+const DEFAULT_AUDIT_PAGE: u64 = 20;
+const MAX_AUDIT_PAGE: u64 = 100;
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/audit",
+    params(
+        ("limit" = Option<u64>, Query, description = "Entries per page, at most 100"),
+        ("offset" = Option<u64>, Query, description = "Entries to skip")
+    ),
+    responses(
+        (status = 200, description = "Contract transactions submitted by admins, most recent first", body = AdminAuditResponse),
+        (status = 401, description = "Unauthorized", body = InvalidCredentialsResponse),
+        (status = 403, description = "Caller is not an admin", body = InvalidCredentialsResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    ),
+    tag = "Admin"
+)]
+#[get("/audit")]
+pub async fn list_admin_audit(
+    query: Query<AdminAuditQuery>,
+    user: AuthenticatedUser,
+    config: web::Data<AppConfig>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    if let Err(forbidden) = config.admin_players.authorize(&user) {
+        return forbidden;
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_AUDIT_PAGE).clamp(1, MAX_AUDIT_PAGE);
+    let offset = query.offset.unwrap_or(0);
+
+    match AdminAuditService::list(db.get_ref(), limit, offset).await {
+        Ok((entries, total)) => HttpResponse::Ok().json(AdminAuditResponse {
+            entries: entries.into_iter().map(AdminAuditEntry::from).collect(),
+            total,
+        }),
+        Err(e) => {
+            log::error!("Failed to list admin audit log: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "error": "Internal server error",
+                "code": 500
+            }))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/games/{id}/result",
+    params(
+        ("id" = String, Path, description = "Game ID in UUID format", format = "uuid")
+    ),
+    request_body = ForceResultRequest,
+    responses(
+        (status = 200, description = "Result overridden and audited", body = ForceResultResponse),
+        (status = 400, description = "Invalid request parameters", body = InvalidCredentialsResponse),
+        (status = 401, description = "Unauthorized", body = InvalidCredentialsResponse),
+        (status = 403, description = "Caller is not an admin", body = InvalidCredentialsResponse),
+        (status = 404, description = "Game not found", body = NotFoundResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    ),
+    tag = "Admin"
+)]
+#[post("/{id}/result")]
+pub async fn force_game_result(
+    id: Path<Uuid>,
+    payload: Json<ForceResultRequest>,
+    user: AuthenticatedUser,
+    config: web::Data<AppConfig>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let admin_id = match config.admin_players.authorize(&user) {
+        Ok(admin_id) => admin_id,
+        Err(forbidden) => return forbidden,
+    };
+
+    let request = payload.into_inner();
+    if let Err(errors) = request.validate() {
+        return ApiError::ValidationError(errors).error_response();
+    }
+
+    let game_id = id.into_inner();
+    let result = request.result.outcome().map_or(ResultSide::Ongoing, ResultSide::from);
+
+    match GameResultService::force_result(db.get_ref(), game_id, result, admin_id, &request.reason).await {
+        Ok(true) => {
+            log::info!(
+                "User {} forced result of game {} to {:?}: {}",
+                admin_id,
+                game_id,
+                request.result,
+                request.reason
+            );
+            HttpResponse::Ok().json(ForceResultResponse {
+                message: "Game result updated".to_string(),
+                game_id,
+                result: request.result,
+            })
+        }
+        Ok(false) => HttpResponse::NotFound().json(json!({
+            "error": "Game not found",
+            "code": 404
+        })),
+        Err(e) => {
+            log::error!("Failed to force result of game {}: {}", game_id, e);
+            HttpResponse::InternalServerError().json(json!({
+                "error": "Internal server error",
+                "code": 500
+            }))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/ratings/recompute",
+    request_body = RecomputeRatingsRequest,
+    responses(
+        (status = 200, description = "Ratings replayed over the range", body = RecomputeRatingsResponse),
+        (status = 400, description = "`to` is not after `from`", body = InvalidCredentialsResponse),
+        (status = 401, description = "Unauthorized", body = InvalidCredentialsResponse),
+        (status = 403, description = "Caller is not an admin", body = InvalidCredentialsResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    ),
+    tag = "Admin"
+)]
+#[post("/ratings/recompute")]
+pub async fn recompute_ratings(
+    payload: Json<RecomputeRatingsRequest>,
+    user: AuthenticatedUser,
+    config: web::Data<AppConfig>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let admin_id = match config.admin_players.authorize(&user) {
+        Ok(admin_id) => admin_id,
+        Err(forbidden) => return forbidden,
+    };
+
+    let RecomputeRatingsRequest { from, to } = payload.into_inner();
+    if to <= from {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "`to` must be after `from`",
+            "code": 400
+        }));
+    }
+
+    match GameResultService::recompute_ratings(db.get_ref(), from, to).await {
+        Ok(summary) => {
+            log::info!(
+                "User {} recomputed ratings for games started in [{}, {}): {} games, {} ratings",
+                admin_id,
+                from,
+                to,
+                summary.games_replayed,
+                summary.ratings_updated
+            );
+            HttpResponse::Ok().json(RecomputeRatingsResponse {
+                message: "Ratings recomputed".to_string(),
+                games_replayed: summary.games_replayed,
+                ratings_updated: summary.ratings_updated,
+            })
+        }
+        Err(e) => {
+            log::error!("Failed to recompute ratings for [{}, {}): {}", from, to, e);
+            HttpResponse::InternalServerError().json(json!({
+                "error": "Internal server error",
+                "code": 500
+            }))
+        }
+    }
+}
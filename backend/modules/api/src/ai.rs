@@ -1,15 +1,20 @@
 use actix_web::{
     HttpResponse, post,
-    web::Json,
+    web::{self, Bytes, Json},
 };
+use chess::bitboard::position::Position;
 use dto::{
-    ai::{AiSuggestionRequest, AiSuggestionResponse, PositionAnalysisRequest, PositionAnalysisResponse},
+    ai::{AiSuggestionRequest, AnalysisDepthUpdate, PositionAnalysisRequest},
     responses::ValidationErrorResponse,
 };
 use error::error::ApiError;
+use futures_util::stream;
 use serde_json::json;
+use service::engine::{DepthUpdates, SearchLimits, UciEngines};
 use validator::Validate;
 
+use crate::config::AppConfig;
+
 #[utoipa::path(
     post,
     path = "/v1/ai/suggest",
@@ -103,3 +108,98 @@ pub async fn analyze_position(payload: Json<PositionAnalysisRequest>) -> HttpRes
         }
     }
 }
+
+#[utoipa::path(
+    post,
+    path = "/v1/ai/analyze/stream",
+    request_body = PositionAnalysisRequest,
+    responses(
+        (status = 200, description = "Server-sent events, one per completed engine depth", body = AnalysisDepthUpdate, content_type = "text/event-stream"),
+        (status = 400, description = "Invalid FEN position, unknown engine, or a movetime over the server's maximum", body = ValidationErrorResponse),
+        (status = 503, description = "No analysis engine is configured", body = InvalidCredentialsResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    ),
+    tag = "AI"
+)]
+#[post("/analyze/stream")]
+pub async fn analyze_position_stream(
+    payload: Json<PositionAnalysisRequest>,
+    engines: Option<web::Data<UciEngines>>,
+    config: web::Data<AppConfig>,
+) -> HttpResponse {
+    if let Err(errors) = payload.0.validate() {
+        return ApiError::ValidationError(errors).error_response();
+    }
+
+    if Position::from_fen(&payload.fen).is_none() {
+        return HttpResponse::BadRequest().json(ValidationErrorResponse {
+            error: "Invalid FEN position or parameters".to_string(),
+            code: 400,
+            details: None,
+        });
+    }
+
+    if let Some(movetime) = payload.movetime_ms.filter(|&ms| ms > config.max_engine_movetime_ms) {
+        return HttpResponse::BadRequest().json(json!({
+            "error": format!(
+                "movetime_ms is {}; at most {} is allowed",
+                movetime, config.max_engine_movetime_ms
+            ),
+            "code": 400
+        }));
+    }
+
+    let engines = match engines {
+        Some(engines) => engines,
+        None => {
+            return HttpResponse::ServiceUnavailable().json(json!({
+                "error": "No analysis engine is configured",
+                "code": 503
+            }));
+        }
+    };
+    let engine = match engines.get(payload.engine.as_deref()) {
+        Some(engine) => engine,
+        None => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": format!("Unknown engine: {}", payload.engine.as_deref().unwrap_or_default()),
+                "code": 400
+            }));
+        }
+    };
+
+    // A depth search still stops at the time limit, as the engine is told to
+    let limits = SearchLimits {
+        depth: payload.depth,
+        movetime_ms: payload.movetime_ms.or(payload.time_limit_ms),
+    };
+    let updates = match DepthUpdates::start(engine.as_ref(), &payload.fen, limits, payload.time_limit_ms) {
+        Ok(updates) => updates,
+        Err(e) => {
+            log::error!("Failed to start analysis: {}", e);
+            return HttpResponse::ServiceUnavailable().json(json!({
+                "error": "Analysis engine is unavailable",
+                "code": 503
+            }));
+        }
+    };
+
+    let events = stream::unfold(updates, |mut updates| async move {
+        let update = updates.next().await?;
+        let event = AnalysisDepthUpdate {
+            depth: update.depth,
+            eval: update.eval,
+            mate: update.mate,
+            best_move: update.best_move,
+        };
+        let data = serde_json::to_string(&event).ok()?;
+        Some((Ok::<_, actix_web::Error>(Bytes::from(format!("data: {}\n\n", data))), updates))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(events)
+}
@@ -0,0 +1,99 @@
+use actix_web::{HttpResponse, post, web::Json};
+use chess::bitboard::board::{Color, Square};
+use chess::bitboard::position::Position;
+use dto::{
+    analysis::{
+        LegalDestination, LegalMovesRequest, LegalMovesResponse, StaticEvalRequest,
+        StaticEvalResponse,
+    },
+    responses::ValidationErrorResponse,
+};
+
+#[utoipa::path(
+    post,
+    path = "/v1/analysis/legal-moves",
+    request_body = LegalMovesRequest,
+    responses(
+        (status = 200, description = "Legal destinations of the piece on the square", body = LegalMovesResponse),
+        (status = 400, description = "Invalid FEN position or square", body = ValidationErrorResponse)
+    ),
+    tag = "Analysis"
+)]
+#[post("/legal-moves")]
+pub async fn legal_moves(payload: Json<LegalMovesRequest>) -> HttpResponse {
+    let position = match Position::from_fen(&payload.fen) {
+        Some(position) => position,
+        None => return bad_request("Invalid FEN position"),
+    };
+    let square = match Square::from_name(&payload.square) {
+        Some(square) => square,
+        None => return bad_request("Invalid square"),
+    };
+
+    // A pawn reaching the last rank has one move per promotion piece; report
+    // each destination once and flag it instead
+    let mut destinations: Vec<LegalDestination> = Vec::new();
+    for mv in position.legal_moves_from(square) {
+        let name = mv.dest.name();
+        if !destinations.iter().any(|d| d.square == name) {
+            destinations.push(LegalDestination {
+                square: name,
+                promotion: mv.promotion.is_some(),
+            });
+        }
+    }
+
+    HttpResponse::Ok().json(LegalMovesResponse {
+        square: square.name(),
+        destinations,
+    })
+}
+
+/// Centipawns credited per legal move the side to move has over its opponent
+const MOBILITY_WEIGHT_CP: i32 = 10;
+
+#[utoipa::path(
+    post,
+    path = "/v1/analysis/static-eval",
+    request_body = StaticEvalRequest,
+    responses(
+        (status = 200, description = "Material and mobility estimate for the side to move", body = StaticEvalResponse),
+        (status = 400, description = "Invalid FEN position", body = ValidationErrorResponse)
+    ),
+    tag = "Analysis"
+)]
+#[post("/static-eval")]
+pub async fn static_eval(payload: Json<StaticEvalRequest>) -> HttpResponse {
+    let position = match Position::from_fen(&payload.fen) {
+        Some(position) => position,
+        None => return bad_request("Invalid FEN position"),
+    };
+
+    let material_balance = match position.turn {
+        Color::White => position.board.material_balance(),
+        Color::Black => -position.board.material_balance(),
+    };
+
+    // The opponent's moves are counted as if it were their turn; the en
+    // passant square only ever belongs to the side to move
+    let opponent = Position {
+        turn: position.turn.opposite(),
+        ep_square: None,
+        ..position
+    };
+    let mobility = position.legal_moves().len() as i32 - opponent.legal_moves().len() as i32;
+
+    HttpResponse::Ok().json(StaticEvalResponse {
+        material_balance,
+        mobility,
+        score_cp: material_balance * 100 + mobility * MOBILITY_WEIGHT_CP,
+    })
+}
+
+fn bad_request(error: &str) -> HttpResponse {
+    HttpResponse::BadRequest().json(ValidationErrorResponse {
+        error: error.to_string(),
+        code: 400,
+        details: None,
+    })
+}
@@ -1,9 +1,17 @@
-use actix_web::{web, HttpResponse, post};
+use actix_web::{http::{header, StatusCode}, web, HttpResponse, get, post};
 use validator::Validate;
 
-use dto::auth::{RegisterRequest, LoginRequest, AuthResponse, ErrorResponse};
+use dto::auth::{
+    RegisterRequest, LoginRequest, AuthResponse, ErrorResponse, VerifyEmailQuery, VerifyEmailResponse,
+    ForgotPasswordRequest, ResetPasswordRequest, MessageResponse,
+};
 use security::JwtService;
 use sea_orm::DatabaseConnection;
+use service::auth::AuthService;
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::lockout::LoginLockout;
 
 /// Register a new user
 #[utoipa::path(
@@ -12,13 +20,14 @@ use sea_orm::DatabaseConnection;
     request_body = RegisterRequest,
     responses(
         (status = 201, description = "User registered successfully", body = AuthResponse),
-        (status = 400, description = "Validation error", body = ErrorResponse)
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 409, description = "Username or email already registered", body = ErrorResponse)
     ),
     tag = "Authentication"
 )]
 #[post("/register")]
 pub async fn register(
-    _db: web::Data<DatabaseConnection>,
+    db: web::Data<DatabaseConnection>,
     payload: web::Json<RegisterRequest>,
 ) -> HttpResponse {
     // Validate input
@@ -29,6 +38,28 @@ pub async fn register(
         });
     }
 
+    match AuthService::register(db.get_ref(), &payload.username, &payload.email, &payload.password).await {
+        // No mailer is wired up yet, so the verification link is only logged
+        Ok(Some((player, token))) => log::debug!(
+            "Email verification link for player {}: /v1/auth/verify?token={}",
+            player.id,
+            token
+        ),
+        Ok(None) => {
+            return HttpResponse::Conflict().json(ErrorResponse {
+                message: "Username or email already registered".to_string(),
+                code: "USER_EXISTS".to_string(),
+            })
+        }
+        Err(e) => {
+            log::error!("Failed to register {}: {}", payload.username, e);
+            return HttpResponse::InternalServerError().json(ErrorResponse {
+                message: "Failed to register user".to_string(),
+                code: "REGISTRATION_ERROR".to_string(),
+            });
+        }
+    }
+
     // For now, return a mock response
     HttpResponse::Created().json(AuthResponse {
         access_token: "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...".to_string(),
@@ -47,15 +78,19 @@ pub async fn register(
     responses(
         (status = 200, description = "Login successful", body = AuthResponse),
         (status = 400, description = "Validation error", body = ErrorResponse),
-        (status = 401, description = "Invalid credentials", body = ErrorResponse)
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+        (status = 403, description = "Email not verified", body = ErrorResponse),
+        (status = 423, description = "Account locked after repeated failed logins", body = ErrorResponse)
     ),
     tag = "Authentication"
 )]
 #[post("/login")]
 pub async fn login(
-    _db: web::Data<DatabaseConnection>,
+    db: web::Data<DatabaseConnection>,
     payload: web::Json<LoginRequest>,
     jwt_service: web::Data<JwtService>,
+    config: web::Data<AppConfig>,
+    lockout: web::Data<LoginLockout>,
 ) -> HttpResponse {
     // Validate input
     if let Err(errors) = payload.validate() {
@@ -65,15 +100,50 @@ pub async fn login(
         });
     }
 
-    // For now, return a mock response with generated token
-    match jwt_service.generate_token(1, &payload.username) {
+    if let Some(remaining) = lockout.locked_for(&payload.username).await {
+        return HttpResponse::build(StatusCode::LOCKED)
+            .insert_header((header::RETRY_AFTER, remaining.as_secs().max(1).to_string()))
+            .json(ErrorResponse {
+                message: "Too many failed login attempts; try again later".to_string(),
+                code: "ACCOUNT_LOCKED".to_string(),
+            });
+    }
+
+    let player = match AuthService::authenticate(db.get_ref(), &payload.username, &payload.password).await {
+        Ok(Some(player)) => player,
+        Ok(None) => {
+            lockout.record_failure(&payload.username).await;
+            return HttpResponse::Unauthorized().json(ErrorResponse {
+                message: "Invalid username or password".to_string(),
+                code: "INVALID_CREDENTIALS".to_string(),
+            });
+        }
+        Err(e) => {
+            log::error!("Failed to authenticate {}: {}", payload.username, e);
+            return HttpResponse::InternalServerError().json(ErrorResponse {
+                message: "Failed to authenticate".to_string(),
+                code: "DATABASE_ERROR".to_string(),
+            });
+        }
+    };
+    lockout.clear(&payload.username).await;
+
+    if config.require_email_verification && !player.email_verified {
+        return HttpResponse::Forbidden().json(ErrorResponse {
+            message: "Email address has not been verified".to_string(),
+            code: "EMAIL_NOT_VERIFIED".to_string(),
+        });
+    }
+
+    // The integer id is still a placeholder; the player id identifies the caller
+    match jwt_service.generate_player_token(1, player.id, &player.username) {
         Ok(token) => {
             HttpResponse::Ok().json(AuthResponse {
                 access_token: token,
                 token_type: "Bearer".to_string(),
-                expires_in: 3600,
+                expires_in: jwt_service.access_expiration(),
                 user_id: 1,
-                username: payload.username.clone(),
+                username: player.username,
             })
         }
         Err(_) => {
@@ -84,3 +154,132 @@ pub async fn login(
         }
     }
 }
+
+/// Verify a player's email address with the token issued at registration
+#[utoipa::path(
+    get,
+    path = "/v1/auth/verify",
+    params(
+        ("token" = String, Query, description = "Verification token issued at registration", format = "uuid")
+    ),
+    responses(
+        (status = 200, description = "Email verified", body = VerifyEmailResponse),
+        (status = 400, description = "Token is invalid, expired or already used", body = ErrorResponse)
+    ),
+    tag = "Authentication"
+)]
+#[get("/verify")]
+pub async fn verify_email(
+    db: web::Data<DatabaseConnection>,
+    query: web::Query<VerifyEmailQuery>,
+) -> HttpResponse {
+    let invalid = || {
+        HttpResponse::BadRequest().json(ErrorResponse {
+            message: "Verification token is invalid, expired or already used".to_string(),
+            code: "INVALID_VERIFICATION_TOKEN".to_string(),
+        })
+    };
+
+    let token = match Uuid::parse_str(&query.token) {
+        Ok(token) => token,
+        Err(_) => return invalid(),
+    };
+
+    match AuthService::verify_email(db.get_ref(), token).await {
+        Ok(Some(player_id)) => HttpResponse::Ok().json(VerifyEmailResponse {
+            player_id,
+            email_verified: true,
+        }),
+        Ok(None) => invalid(),
+        Err(e) => {
+            log::error!("Failed to verify email token: {}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                message: "Failed to verify email".to_string(),
+                code: "DATABASE_ERROR".to_string(),
+            })
+        }
+    }
+}
+
+/// Request a password reset token for an account
+#[utoipa::path(
+    post,
+    path = "/v1/auth/forgot-password",
+    request_body = ForgotPasswordRequest,
+    responses(
+        (status = 202, description = "A reset token is sent if the email belongs to an account", body = MessageResponse),
+        (status = 400, description = "Validation error", body = ErrorResponse)
+    ),
+    tag = "Authentication"
+)]
+#[post("/forgot-password")]
+pub async fn forgot_password(
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<ForgotPasswordRequest>,
+) -> HttpResponse {
+    if let Err(errors) = payload.validate() {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            message: format!("Validation failed: {:?}", errors),
+            code: "VALIDATION_ERROR".to_string(),
+        });
+    }
+
+    match AuthService::request_password_reset(db.get_ref(), &payload.email).await {
+        // No mailer is wired up yet, so the reset token is only logged
+        Ok(Some(token)) => log::debug!("Password reset token for {}: {}", payload.email, token),
+        Ok(None) => {}
+        Err(e) => {
+            log::error!("Failed to issue password reset token: {}", e);
+            return HttpResponse::InternalServerError().json(ErrorResponse {
+                message: "Failed to request password reset".to_string(),
+                code: "DATABASE_ERROR".to_string(),
+            });
+        }
+    }
+
+    // Same answer whether or not the account exists, so emails cannot be enumerated
+    HttpResponse::Accepted().json(MessageResponse {
+        message: "If the email belongs to an account, a reset link has been sent".to_string(),
+    })
+}
+
+/// Reset a password with a token from `forgot-password`
+#[utoipa::path(
+    post,
+    path = "/v1/auth/reset-password",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 200, description = "Password reset", body = MessageResponse),
+        (status = 400, description = "Validation error, or token invalid, expired or already used", body = ErrorResponse)
+    ),
+    tag = "Authentication"
+)]
+#[post("/reset-password")]
+pub async fn reset_password(
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<ResetPasswordRequest>,
+) -> HttpResponse {
+    if let Err(errors) = payload.validate() {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            message: format!("Validation failed: {:?}", errors),
+            code: "VALIDATION_ERROR".to_string(),
+        });
+    }
+
+    match AuthService::reset_password(db.get_ref(), &payload.token, &payload.new_password).await {
+        Ok(true) => HttpResponse::Ok().json(MessageResponse {
+            message: "Password has been reset".to_string(),
+        }),
+        Ok(false) => HttpResponse::BadRequest().json(ErrorResponse {
+            message: "Reset token is invalid, expired or already used".to_string(),
+            code: "INVALID_RESET_TOKEN".to_string(),
+        }),
+        Err(e) => {
+            log::error!("Failed to reset password: {}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                message: "Failed to reset password".to_string(),
+                code: "DATABASE_ERROR".to_string(),
+            })
+        }
+    }
+}
@@ -1,32 +1,286 @@
 use std::env;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+
+use db::db::db::PoolConfig;
+use security::AdminPlayers;
+
+use crate::rate_limit::IpCidr;
+use crate::ws::HeartbeatConfig;
 
 #[derive(Debug, Clone)]
 pub struct AppConfig {
+    /// Address the HTTP server binds to (`SERVER_ADDR`, default `127.0.0.1:8080`).
+    pub server_addr: String,
+    /// HTTP worker threads (`WORKERS`). `None` starts one per CPU core.
+    pub workers: Option<usize>,
+    /// Database connection pool sizing (`DB_MAX_CONNECTIONS`, default 10, and
+    /// `DB_MIN_CONNECTIONS`, default 1).
+    pub db_pool: PoolConfig,
+    /// Requests per second allowed on `/v1/auth` and `/v1/admin` (`AUTH_RATE_LIMIT_PER_SEC`, default 1).
     pub auth_rate_limit_per_sec: u64,
+    /// Requests allowed in a burst on `/v1/auth` and `/v1/admin` (`AUTH_RATE_LIMIT_BURST`, default 5).
     pub auth_rate_limit_burst: u32,
+    /// Requests per second allowed on the other routes (`GAME_RATE_LIMIT_PER_SEC`, default 10).
     pub game_rate_limit_per_sec: u64,
+    /// Requests allowed in a burst on the other routes (`GAME_RATE_LIMIT_BURST`, default 20).
     pub game_rate_limit_burst: u32,
+    /// Origins allowed by CORS, as the raw comma-separated `ALLOWED_ORIGINS`
+    /// list. `None` allows any origin for development.
+    pub cors_allowed_origins: Option<String>,
+    /// Seconds browsers may cache a preflight response (`CORS_MAX_AGE`, default 3600).
+    pub cors_max_age: usize,
+    /// Allow credentialed CORS requests (`CORS_ALLOW_CREDENTIALS`, default false).
+    pub cors_allow_credentials: bool,
+    /// Seconds an idempotency key is remembered (`IDEMPOTENCY_TTL_SECS`, default 86400).
+    pub idempotency_ttl_secs: u64,
+    /// Peers in these networks bypass rate limiting (`RATE_LIMIT_ALLOWLIST`, comma-separated CIDRs).
+    pub rate_limit_allowlist: Vec<IpCidr>,
+    /// Players allowed to use the `/v1/admin` endpoints (`ADMIN_PLAYER_IDS`, comma-separated UUIDs).
+    pub admin_players: AdminPlayers,
+    /// Seconds between websocket pings (`WS_HEARTBEAT_INTERVAL_SECS`, default 15).
+    pub ws_heartbeat_interval_secs: u64,
+    /// Seconds without a pong before a websocket is dropped (`WS_CLIENT_TIMEOUT_SECS`, default 25).
+    pub ws_client_timeout_secs: u64,
+    /// Refuse logins until the player has verified their email (`REQUIRE_EMAIL_VERIFICATION`, default false).
+    pub require_email_verification: bool,
+    /// Consecutive failed logins that lock an account (`LOGIN_MAX_FAILURES`, default 5).
+    pub login_max_failures: u32,
+    /// Seconds an account stays locked after its last failed login (`LOGIN_LOCKOUT_SECS`, default 900).
+    pub login_lockout_secs: u64,
+    /// Ongoing games a player may have at once before creating or joining
+    /// another is refused (`MAX_ACTIVE_GAMES_PER_USER`, default 5).
+    pub max_active_games_per_user: u64,
+    /// Origins allowed to open websockets (`ALLOWED_ORIGINS`, comma-separated).
+    /// `None` when unset or empty, which allows any origin for development.
+    pub ws_allowed_origins: Option<Vec<String>>,
+    /// Longest move list, in plies, that is replayed to analyse a game
+    /// (`MAX_REPLAY_PLIES`, default 500). Longer games are refused with 422.
+    pub max_replay_plies: usize,
+    /// Longest engine search, in milliseconds, a request may ask for with
+    /// `movetime_ms` (`MAX_ENGINE_MOVETIME_MS`, default 10000). Longer ones are refused with 400.
+    pub max_engine_movetime_ms: u32,
+}
+
+/// Reads settings through a variable lookup, remembering the value each one
+/// ended up with and why any given value was not used.
+struct VarReader<F> {
+    lookup: F,
+    effective: Vec<(&'static str, String)>,
+    warnings: Vec<String>,
+}
+
+impl<F: Fn(&str) -> Option<String>> VarReader<F> {
+    fn raw(&self, name: &str) -> Option<String> {
+        (self.lookup)(name).filter(|value| !value.trim().is_empty())
+    }
+
+    /// The parsed value of `name`, or `default` when it is unset or malformed.
+    fn parse<T: FromStr + Display>(&mut self, name: &'static str, default: T) -> T {
+        let value = match self.raw(name) {
+            None => default,
+            Some(raw) => match raw.trim().parse() {
+                Ok(value) => value,
+                Err(_) => {
+                    self.warnings
+                        .push(format!("Invalid {} value '{}', using default {}", name, raw, default));
+                    default
+                }
+            },
+        };
+        self.record(name, value.to_string());
+        value
+    }
+
+    fn record(&mut self, name: &'static str, value: String) {
+        self.effective.push((name, value));
+    }
 }
 
 impl AppConfig {
+    /// Reads the configuration from the environment and logs a warning for
+    /// each value that was ignored.
     pub fn from_env() -> Self {
-        Self {
-            auth_rate_limit_per_sec: env::var("AUTH_RATE_LIMIT_PER_SEC")
-                .unwrap_or_else(|_| "1".to_string())
-                .parse()
-                .unwrap_or(1),
-            auth_rate_limit_burst: env::var("AUTH_RATE_LIMIT_BURST")
-                .unwrap_or_else(|_| "5".to_string())
-                .parse()
-                .unwrap_or(5),
-            game_rate_limit_per_sec: env::var("GAME_RATE_LIMIT_PER_SEC")
-                .unwrap_or_else(|_| "10".to_string()) // Looser limit for games
-                .parse()
-                .unwrap_or(10),
-            game_rate_limit_burst: env::var("GAME_RATE_LIMIT_BURST")
-                .unwrap_or_else(|_| "20".to_string())
-                .parse()
-                .unwrap_or(20),
+        let (config, warnings) = Self::from_vars(|name| env::var(name).ok());
+        for warning in warnings {
+            log::warn!("{}", warning);
+        }
+        config
+    }
+
+    /// Builds the configuration from `lookup`, which returns the raw value of
+    /// a variable, and logs the effective value of each setting. Unset and
+    /// empty variables take their documented default; malformed ones do too,
+    /// and are described in the returned warnings.
+    pub fn from_vars(lookup: impl Fn(&str) -> Option<String>) -> (Self, Vec<String>) {
+        let mut vars = VarReader {
+            lookup,
+            effective: Vec::new(),
+            warnings: Vec::new(),
+        };
+
+        let server_addr = vars.raw("SERVER_ADDR").unwrap_or_else(|| "127.0.0.1:8080".to_string());
+        vars.record("SERVER_ADDR", server_addr.clone());
+
+        let workers = match vars.raw("WORKERS") {
+            None => None,
+            Some(raw) => match raw.trim().parse::<usize>() {
+                Ok(workers) if workers > 0 => Some(workers),
+                _ => {
+                    vars.warnings
+                        .push(format!("Invalid WORKERS value '{}', using one worker per CPU core", raw));
+                    None
+                }
+            },
+        };
+        vars.record("WORKERS", workers.map_or("one per CPU core".to_string(), |w| w.to_string()));
+
+        let (db_pool, invalid) = PoolConfig::parse(
+            vars.raw("DB_MAX_CONNECTIONS").as_deref(),
+            vars.raw("DB_MIN_CONNECTIONS").as_deref(),
+        );
+        vars.warnings.extend(invalid);
+        vars.record("DB_MAX_CONNECTIONS", db_pool.max_connections.to_string());
+        vars.record("DB_MIN_CONNECTIONS", db_pool.min_connections.to_string());
+
+        let cors_allowed_origins = (vars.lookup)("ALLOWED_ORIGINS");
+        vars.record(
+            "ALLOWED_ORIGINS",
+            cors_allowed_origins.clone().unwrap_or_else(|| "any".to_string()),
+        );
+
+        let allowlist = vars.raw("RATE_LIMIT_ALLOWLIST").unwrap_or_default();
+        let (rate_limit_allowlist, invalid) = Self::parse_allowlist(&allowlist);
+        for error in invalid {
+            vars.warnings.push(format!("Ignoring RATE_LIMIT_ALLOWLIST entry: {}", error));
+        }
+        vars.record("RATE_LIMIT_ALLOWLIST", allowlist);
+
+        let (admin_players, invalid) = AdminPlayers::parse(&vars.raw("ADMIN_PLAYER_IDS").unwrap_or_default());
+        for entry in invalid {
+            vars.warnings.push(format!("Ignoring ADMIN_PLAYER_IDS entry '{}'", entry));
+        }
+        vars.record(
+            "ADMIN_PLAYER_IDS",
+            admin_players.0.iter().map(ToString::to_string).collect::<Vec<_>>().join(","),
+        );
+
+        let config = Self {
+            server_addr,
+            workers,
+            db_pool,
+            auth_rate_limit_per_sec: vars.parse("AUTH_RATE_LIMIT_PER_SEC", 1),
+            auth_rate_limit_burst: vars.parse("AUTH_RATE_LIMIT_BURST", 5),
+            // Looser limit for games
+            game_rate_limit_per_sec: vars.parse("GAME_RATE_LIMIT_PER_SEC", 10),
+            game_rate_limit_burst: vars.parse("GAME_RATE_LIMIT_BURST", 20),
+            ws_allowed_origins: Self::parse_origins(cors_allowed_origins.as_deref().unwrap_or_default()),
+            cors_allowed_origins,
+            cors_max_age: vars.parse("CORS_MAX_AGE", 3600),
+            cors_allow_credentials: vars.parse("CORS_ALLOW_CREDENTIALS", false),
+            idempotency_ttl_secs: vars.parse("IDEMPOTENCY_TTL_SECS", 86400),
+            rate_limit_allowlist,
+            admin_players,
+            ws_heartbeat_interval_secs: vars.parse("WS_HEARTBEAT_INTERVAL_SECS", 15),
+            ws_client_timeout_secs: vars.parse("WS_CLIENT_TIMEOUT_SECS", 25),
+            require_email_verification: vars.parse("REQUIRE_EMAIL_VERIFICATION", false),
+            login_max_failures: vars.parse("LOGIN_MAX_FAILURES", 5),
+            login_lockout_secs: vars.parse("LOGIN_LOCKOUT_SECS", 900),
+            max_active_games_per_user: vars.parse("MAX_ACTIVE_GAMES_PER_USER", 5),
+            max_replay_plies: vars.parse("MAX_REPLAY_PLIES", 500),
+            max_engine_movetime_ms: vars.parse("MAX_ENGINE_MOVETIME_MS", 10000),
+        };
+
+        for (name, value) in &vars.effective {
+            log::info!("{} = {}", name, value);
+        }
+        (config, vars.warnings)
+    }
+
+    pub fn ws_heartbeat(&self) -> HeartbeatConfig {
+        HeartbeatConfig {
+            interval: Duration::from_secs(self.ws_heartbeat_interval_secs),
+            client_timeout: Duration::from_secs(self.ws_client_timeout_secs),
+        }
+    }
+
+    /// A client timeout no longer than the ping interval would drop healthy
+    /// connections between pings. Returns a description of the problem if so.
+    pub fn ws_heartbeat_misconfiguration(&self) -> Option<String> {
+        if self.ws_heartbeat_interval_secs == 0 {
+            Some("WS_HEARTBEAT_INTERVAL_SECS must be greater than zero".to_string())
+        } else if self.ws_client_timeout_secs <= self.ws_heartbeat_interval_secs {
+            Some(format!(
+                "WS_CLIENT_TIMEOUT_SECS ({}) must be greater than WS_HEARTBEAT_INTERVAL_SECS ({})",
+                self.ws_client_timeout_secs, self.ws_heartbeat_interval_secs
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Parses a comma-separated CIDR list, returning the valid networks and
+    /// why each invalid entry was skipped.
+    pub fn parse_allowlist(value: &str) -> (Vec<IpCidr>, Vec<String>) {
+        let mut allowlist = Vec::new();
+        let mut invalid = Vec::new();
+        for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            match entry.parse() {
+                Ok(cidr) => allowlist.push(cidr),
+                Err(e) => invalid.push(e),
+            }
+        }
+        (allowlist, invalid)
+    }
+
+    /// Parses a comma-separated origin list; `None` if it names no origin.
+    pub fn parse_origins(value: &str) -> Option<Vec<String>> {
+        let origins: Vec<String> = value
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .map(str::to_string)
+            .collect();
+        (!origins.is_empty()).then_some(origins)
+    }
+
+    /// Whether a websocket handshake with this `Origin` header may proceed.
+    /// Browsers always send one, so requests without it (native clients) are
+    /// not cross-site and are let through.
+    pub fn ws_origin_allowed(&self, origin: Option<&str>) -> bool {
+        match (&self.ws_allowed_origins, origin) {
+            (None, _) | (_, None) => true,
+            (Some(allowed), Some(origin)) => allowed.iter().any(|a| a == "*" || a == origin),
+        }
+    }
+
+    /// Browsers reject credentialed CORS responses for wildcard origins, so
+    /// credentials are only honoured alongside an explicit `ALLOWED_ORIGINS` list.
+    /// Returns a description of the problem when that combination is violated.
+    pub fn cors_misconfiguration(&self, allowed_origins: Option<&str>) -> Option<String> {
+        if !self.cors_allow_credentials {
+            return None;
+        }
+
+        let has_specific_origins = allowed_origins
+            .map(|origins| {
+                let origins: Vec<&str> = origins
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|origin| !origin.is_empty())
+                    .collect();
+                !origins.is_empty() && !origins.contains(&"*")
+            })
+            .unwrap_or(false);
+
+        if has_specific_origins {
+            None
+        } else {
+            Some(
+                "CORS_ALLOW_CREDENTIALS is enabled but ALLOWED_ORIGINS does not list specific origins; credentials will not be allowed"
+                    .to_string(),
+            )
         }
     }
 }
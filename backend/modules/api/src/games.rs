@@ -1,9 +1,13 @@
 use actix_web::{
-    HttpResponse, delete, get, post, put,
+    HttpRequest, HttpResponse, delete, get, post, put,
     web::{self, Json, Path, Query},
 };
 use dto::{
-    games::{CreateGameRequest, GameDisplayDTO, MakeMoveRequest, JoinGameRequest, GameStatus, ListGamesQuery},
+    games::{
+        BatchGamesRequest, CreateGameRequest, GameAccuracyResponse, GameDisplayDTO, MakeMoveRequest, JoinGameRequest, GameStatus,
+        ListGamesQuery, PlayerColor,
+    },
+    ids::GameId,
     responses::{InvalidCredentialsResponse, NotFoundResponse},
 };
 use error::error::ApiError;
@@ -12,17 +16,28 @@ use validator::Validate;
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
-use sea_orm::DatabaseConnection;
+use sea_orm::{ActiveEnum, DatabaseConnection, DbErr};
+use service::accuracy::{AccuracyService, PositionEvaluator};
 use service::games::GameService;
+use db_entity::game;
+use security::AuthenticatedUser;
+use crate::accuracy::AccuracyCache;
+use crate::config::AppConfig;
+use crate::idempotency::{idempotency_scope, IdempotencyStore, IDEMPOTENCY_KEY_HEADER, MAX_IDEMPOTENCY_KEY_LEN};
+use crate::registry::canonical_game_id;
 
 #[utoipa::path(
     post,
     path = "/v1/games",
     request_body = CreateGameRequest,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-generated key; retries with the same key return the originally created game")
+    ),
     responses(
         (status = 201, description = "Game created successfully", body = GameDisplayDTO),
         (status = 400, description = "Invalid request parameters", body = InvalidCredentialsResponse),
-        (status = 401, description = "Unauthorized", body = InvalidCredentialsResponse)
+        (status = 401, description = "Unauthorized", body = InvalidCredentialsResponse),
+        (status = 409, description = "Player already has the maximum number of ongoing games")
     ),
     security(
         ("jwt_auth" = [])
@@ -30,23 +45,91 @@ use service::games::GameService;
     tag = "Games"
 )]
 #[post("")]
-pub async fn create_game(payload: Json<CreateGameRequest>) -> HttpResponse {
-    match payload.0.validate() {
-        Ok(_) => {
-            // The real implementation would create a game in the database
-            // For now, we'll just return a mock response
-            HttpResponse::Created().json(json!({
-                "message": "Game created successfully",
-                "data": {
-                    "game": {
-                        "id": Uuid::new_v4(),
-                        "status": "waiting"
-                    }
-                }
-            }))
+pub async fn create_game(
+    req: HttpRequest,
+    payload: Json<CreateGameRequest>,
+    user: Option<AuthenticatedUser>,
+    idempotency: Option<web::Data<IdempotencyStore>>,
+    db: Option<web::Data<DatabaseConnection>>,
+    config: Option<web::Data<AppConfig>>,
+) -> HttpResponse {
+    if let Err(errors) = payload.0.validate() {
+        return ApiError::ValidationError(errors).error_response();
+    }
+
+    // The limit needs to know who is creating the game, so anonymous requests skip it
+    if let (Some(user), Some(db), Some(config)) = (&user, &db, &config) {
+        let count =
+            GameService::count_active_games_for_username(db.get_ref(), &user.claims().username).await;
+        if let Some(resp) = active_game_limit_response(count, config.max_active_games_per_user) {
+            return resp;
         }
-        Err(errors) => ApiError::ValidationError(errors).error_response(),
     }
+
+    let idempotency_key = match req.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        None => None,
+        Some(value) => match value.to_str() {
+            Ok(key) if !key.trim().is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN => {
+                Some(key.trim().to_string())
+            }
+            _ => {
+                return HttpResponse::BadRequest().json(json!({
+                    "error": format!("{} must be 1-{} visible characters", IDEMPOTENCY_KEY_HEADER, MAX_IDEMPOTENCY_KEY_LEN),
+                    "code": 400
+                }));
+            }
+        },
+    };
+
+    // Games between two known addresses get the id they are recorded under on-chain,
+    // with the creation time in milliseconds as the nonce
+    let created_at = chrono::Utc::now();
+    let new_game_id = match (&payload.0.white_address, &payload.0.black_address) {
+        (Some(white), Some(black)) => {
+            match canonical_game_id(white, black, created_at.timestamp_millis() as u64) {
+                Ok(id) => id,
+                Err(e) => {
+                    return HttpResponse::BadRequest().json(json!({
+                        "error": e.to_string(),
+                        "code": 400
+                    }));
+                }
+            }
+        }
+        _ => Uuid::new_v4(),
+    };
+
+    // The real implementation would create a game in the database
+    // For now, we'll just return a mock response
+    let game_id = match (idempotency_key, idempotency) {
+        (Some(key), Some(store)) => {
+            // Keys are scoped per player, so an authenticated caller is required
+            let user = match user {
+                Some(user) => user,
+                None => {
+                    return HttpResponse::Unauthorized().json(json!({
+                        "error": format!("{} requires an authenticated user", IDEMPOTENCY_KEY_HEADER),
+                        "code": 401
+                    }));
+                }
+            };
+            store.get_or_insert(&idempotency_scope(&user), &key, new_game_id).await.0
+        }
+        _ => new_game_id,
+    };
+
+    HttpResponse::Created().json(json!({
+        "message": "Game created successfully",
+        "data": {
+            "game": {
+                "id": game_id,
+                "created_at": created_at.to_rfc3339(),
+                "status": "waiting",
+                "variant": game::GameVariant::from_time_control(payload.0.time_control, payload.0.increment).to_value(),
+                "player_color": PlayerColor::resolve(payload.0.player_color)
+            }
+        }
+    }))
 }
 
 #[utoipa::path(
@@ -65,18 +148,159 @@ pub async fn create_game(payload: Json<CreateGameRequest>) -> HttpResponse {
     tag = "Games"
 )]
 #[get("/{id}")]
-pub async fn get_game(id: Path<Uuid>) -> HttpResponse {
-    // The real implementation would fetch the game from the database
-    // For now, we'll just return a mock response
-    HttpResponse::Ok().json(json!({
-        "message": "Game found",
-        "data": {
-            "game": {
-                "id": id.into_inner(),
-                "status": "in_progress"
+pub async fn get_game(id: Path<GameId>, db: web::Data<DatabaseConnection>) -> HttpResponse {
+    match GameService::find_game(db.get_ref(), id.into_inner().into()).await {
+        Ok(Some(game)) => HttpResponse::Ok().json(json!({
+            "message": "Game found",
+            "data": {
+                "game": GameDisplayDTO::from(game)
+            }
+        })),
+        Ok(None) => ApiError::NotFound("Game".to_string()).error_response(),
+        Err(e) => ApiError::DatabaseError(e).error_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/games/{id}/summary",
+    params(
+        ("id" = String, Path, description = "Game ID in UUID format", format = "uuid")
+    ),
+    responses(
+        (status = 200, description = "Game summary; rating fields are omitted for unrated games", body = GameSummary),
+        (status = 404, description = "Game not found", body = NotFoundResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    ),
+    tag = "Games"
+)]
+#[get("/{id}/summary")]
+pub async fn get_game_summary(id: Path<GameId>, db: web::Data<DatabaseConnection>) -> HttpResponse {
+    match GameService::game_summary(db.get_ref(), id.into_inner().into()).await {
+        Ok(Some(summary)) => HttpResponse::Ok().json(json!({
+            "message": "Game summary found",
+            "data": {
+                "summary": summary
+            }
+        })),
+        Ok(None) => ApiError::NotFound("Game".to_string()).error_response(),
+        Err(e) => ApiError::DatabaseError(e).error_response(),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/games/batch",
+    request_body = BatchGamesRequest,
+    responses(
+        (status = 200, description = "Games found, in the order requested; unknown ids are skipped", body = Vec<GameDisplayDTO>),
+        (status = 400, description = "No ids, more than 100 ids or a malformed id", body = InvalidCredentialsResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    ),
+    tag = "Games"
+)]
+#[post("/batch")]
+pub async fn get_games_batch(
+    payload: Json<BatchGamesRequest>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    if let Err(errors) = payload.0.validate() {
+        return ApiError::ValidationError(errors).error_response();
+    }
+
+    let ids: Vec<Uuid> = payload.0.ids.into_iter().map(Uuid::from).collect();
+    match GameService::games_by_ids(db.get_ref(), &ids).await {
+        Ok(games) => HttpResponse::Ok().json(json!({
+            "message": "Games found",
+            "data": {
+                "games": games.into_iter().map(GameDisplayDTO::from).collect::<Vec<_>>()
+            }
+        })),
+        Err(e) => ApiError::DatabaseError(e).error_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/games/{id}/accuracy",
+    params(
+        ("id" = String, Path, description = "Game ID in UUID format", format = "uuid")
+    ),
+    responses(
+        (status = 200, description = "Per-side accuracy from the centipawn loss of each move", body = GameAccuracyResponse),
+        (status = 404, description = "Game not found", body = NotFoundResponse),
+        (status = 422, description = "The game has too many moves to replay, or a stored move is illegal")
+    ),
+    security(
+        ("jwt_auth" = [])
+    ),
+    tag = "Games"
+)]
+#[get("/{id}/accuracy")]
+pub async fn get_game_accuracy(
+    id: Path<GameId>,
+    db: web::Data<DatabaseConnection>,
+    evaluator: web::Data<dyn PositionEvaluator>,
+    cache: web::Data<AccuracyCache>,
+    config: web::Data<AppConfig>,
+) -> HttpResponse {
+    let game_id = Uuid::from(id.into_inner());
+    let moves = match AccuracyService::game_moves(db.get_ref(), game_id).await {
+        Ok(Some(moves)) => moves,
+        Ok(None) => return ApiError::NotFound("Game".to_string()).error_response(),
+        Err(e) => return ApiError::DatabaseError(e).error_response(),
+    };
+    if moves.len() > config.max_replay_plies {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "error": format!(
+                "Game has {} moves; at most {} can be replayed",
+                moves.len(),
+                config.max_replay_plies
+            ),
+            "code": 422
+        }));
+    }
+
+    let accuracy = match cache.get(game_id, moves.len()) {
+        Some(accuracy) => accuracy,
+        None => {
+            // Evaluating every position is CPU bound, so keep it off the async workers
+            let evaluator = evaluator.into_inner();
+            let computed = web::block(move || AccuracyService::accuracy(evaluator.as_ref(), &moves)).await;
+            match computed {
+                Ok(Ok(accuracy)) => {
+                    cache.insert(game_id, accuracy.clone());
+                    accuracy
+                }
+                Ok(Err(illegal)) => {
+                    // Replay stops at the first illegal move, which is named in the error
+                    log::error!("Cannot replay game {}: {}", game_id, illegal);
+                    return HttpResponse::UnprocessableEntity().json(json!({
+                        "error": illegal.to_string(),
+                        "code": 422
+                    }));
+                }
+                Err(e) => {
+                    log::error!("Accuracy computation for game {} failed: {}", game_id, e);
+                    return HttpResponse::InternalServerError().json(json!({
+                        "error": "Failed to compute accuracy",
+                        "code": 500
+                    }));
+                }
             }
         }
-    }))
+    };
+
+    HttpResponse::Ok().json(GameAccuracyResponse {
+        game_id,
+        white_accuracy: accuracy.white,
+        black_accuracy: accuracy.black,
+        moves_analyzed: accuracy.moves_analyzed,
+    })
 }
 
 #[utoipa::path(
@@ -97,7 +321,7 @@ pub async fn get_game(id: Path<Uuid>) -> HttpResponse {
     tag = "Games"
 )]
 #[put("/{id}/move")]
-pub async fn make_move(id: Path<Uuid>, payload: Json<MakeMoveRequest>) -> HttpResponse {
+pub async fn make_move(id: Path<GameId>, payload: Json<MakeMoveRequest>) -> HttpResponse {
     match payload.0.validate() {
         Ok(_) => {
             // The real implementation would validate and make the move
@@ -119,6 +343,9 @@ pub async fn make_move(id: Path<Uuid>, payload: Json<MakeMoveRequest>) -> HttpRe
 
 
 
+/// Response header carrying how many games match a `list_games` query in total.
+pub const TOTAL_COUNT_HEADER: &str = "X-Total-Count";
+
 #[utoipa::path(
     get,
     path = "/v1/games",
@@ -126,10 +353,13 @@ pub async fn make_move(id: Path<Uuid>, payload: Json<MakeMoveRequest>) -> HttpRe
         ("status" = Option<String>, Query, description = "Filter games by status (waiting, in_progress, completed, aborted)"),
         ("player_id" = Option<String>, Query, description = "Filter games by player ID", format = "uuid"),
         ("page" = Option<i32>, Query, description = "Page number for pagination"),
-        ("limit" = Option<i32>, Query, description = "Number of items per page")
+        ("limit" = Option<i32>, Query, description = "Number of items per page"),
+        ("active" = Option<bool>, Query, description = "Only ongoing games, most recently started first"),
+        ("offset" = Option<u64>, Query, description = "Games to skip when `active` is set")
     ),
     responses(
-        (status = 200, description = "List of games", body = Vec<GameDisplayDTO>)
+        (status = 200, description = "List of games", body = Vec<GameDisplayDTO>,
+            headers(("X-Total-Count" = u64, description = "Games matching the filters across all pages")))
     ),
     security(
         ("jwt_auth" = [])
@@ -161,14 +391,51 @@ pub async fn list_games(
     let limit = query.limit.unwrap_or(10);
     let cursor = query.cursor.clone();
 
-    match GameService::list_games(
+    // Lobby view: ongoing games by start time, paginated by offset
+    if query.active == Some(true) {
+        let offset = query.offset.unwrap_or(0);
+        let listed = match GameService::list_active_games(db.get_ref(), limit, offset).await {
+            Ok(games) => GameService::count_active_games(db.get_ref())
+                .await
+                .map(|total| (games, total)),
+            Err(e) => Err(e),
+        };
+        return match listed {
+            Ok((games, total)) => HttpResponse::Ok()
+                .insert_header((TOTAL_COUNT_HEADER, total.to_string()))
+                .json(json!({
+                    "message": "Games found",
+                    "data": {
+                        "games": games.into_iter().map(game_summary).collect::<Vec<_>>(),
+                        "next_cursor": null,
+                        "limit": limit,
+                        "offset": offset
+                    }
+                })),
+            Err(e) => {
+                eprintln!("Error listing active games: {}", e);
+                HttpResponse::InternalServerError().json(json!({
+                    "message": "Internal server error"
+                }))
+            }
+        };
+    }
+
+    let listed = match GameService::list_games(
         db.get_ref(),
         cursor,
         limit,
         query.player_id,
         status_enum,
     ).await {
-        Ok((games, next_cursor)) => {
+        Ok(page) => GameService::count_games(db.get_ref(), query.player_id, status_enum)
+            .await
+            .map(|total| (page, total)),
+        Err(e) => Err(e),
+    };
+
+    match listed {
+        Ok(((games, next_cursor), total)) => {
             // Map Entity Models to DTOs
             // We need a mapper. For now I will do manual mapping or basic json.
             // GameDisplayDTO matches fields mostly? 
@@ -176,32 +443,19 @@ pub async fn list_games(
             // game::Model has `result: Option<ResultSide>` (Enum). DTO has `Result: GameResult` (Enum).
             // This mapping might be verbose. For the optimization task, I will do a best-effort mapping inline.
             
-            let game_dtos: Vec<serde_json::Value> = games.into_iter().map(|g| {
-                // Return generic JSON for now to avoid extensive DTO mapping boilerplate 
-                // if mapper isn't available, but we should try to match structure.
-                json!({
-                    "id": g.id,
-                    "white_player_id": g.white_player,
-                    "black_player_id": g.black_player,
-                    "status": if g.result.is_some() { "completed" } else { "in_progress" }, // simplified
-                    "result": g.result,
-                    "current_fen": g.fen,
-                    "time_control": 600, // placeholder as it's not in Game entity directly (duration_sec is there but it's different?)
-                    "increment": 0,
-                    "created_at": g.created_at,
-                    "started_at": g.started_at,
-                })
-            }).collect();
+            let game_dtos: Vec<serde_json::Value> = games.into_iter().map(game_summary).collect();
 
             // Construct response with cursor
-            HttpResponse::Ok().json(json!({
-                "message": "Games found",
-                "data": {
-                    "games": game_dtos,
-                    "next_cursor": next_cursor,
-                    "limit": limit
-                }
-            }))
+            HttpResponse::Ok()
+                .insert_header((TOTAL_COUNT_HEADER, total.to_string()))
+                .json(json!({
+                    "message": "Games found",
+                    "data": {
+                        "games": game_dtos,
+                        "next_cursor": next_cursor,
+                        "limit": limit
+                    }
+                }))
         },
         Err(e) => {
             eprintln!("Error listing games: {}", e);
@@ -212,6 +466,23 @@ pub async fn list_games(
     }
 }
 
+fn game_summary(g: game::Model) -> serde_json::Value {
+    // Return generic JSON for now to avoid extensive DTO mapping boilerplate 
+    // if mapper isn't available, but we should try to match structure.
+    json!({
+        "id": g.id,
+        "white_player_id": g.white_player,
+        "black_player_id": g.black_player,
+        "status": if g.result.is_some() { "completed" } else { "in_progress" }, // simplified
+        "result": g.result,
+        "current_fen": g.fen,
+        "time_control": 600, // placeholder as it's not in Game entity directly (duration_sec is there but it's different?)
+        "increment": 0,
+        "created_at": g.created_at,
+        "started_at": g.started_at,
+    })
+}
+
 #[utoipa::path(
     post,
     path = "/v1/games/{id}/join",
@@ -222,7 +493,8 @@ pub async fn list_games(
     responses(
         (status = 200, description = "Joined game successfully", body = GameDisplayDTO),
         (status = 400, description = "Cannot join game", body = InvalidCredentialsResponse),
-        (status = 404, description = "Game not found", body = NotFoundResponse)
+        (status = 404, description = "Game not found", body = NotFoundResponse),
+        (status = 409, description = "Player already has the maximum number of ongoing games")
     ),
     security(
         ("jwt_auth" = [])
@@ -230,9 +502,24 @@ pub async fn list_games(
     tag = "Games"
 )]
 #[post("/{id}/join")]
-pub async fn join_game(id: Path<Uuid>, payload: Json<JoinGameRequest>) -> HttpResponse {
+pub async fn join_game(
+    id: Path<GameId>,
+    payload: Json<JoinGameRequest>,
+    user: Option<AuthenticatedUser>,
+    db: Option<web::Data<DatabaseConnection>>,
+    config: Option<web::Data<AppConfig>>,
+) -> HttpResponse {
     match payload.0.validate() {
         Ok(_) => {
+            // Counted for the caller, not for whoever the request body names
+            if let (Some(user), Some(db), Some(config)) = (&user, &db, &config) {
+                let count =
+                    GameService::count_active_games_for_username(db.get_ref(), &user.claims().username).await;
+                if let Some(resp) = active_game_limit_response(count, config.max_active_games_per_user) {
+                    return resp;
+                }
+            }
+
             // The real implementation would add the player to the game
             // For now, we'll just return a mock response
             HttpResponse::Ok().json(json!({
@@ -250,6 +537,18 @@ pub async fn join_game(id: Path<Uuid>, payload: Json<JoinGameRequest>) -> HttpRe
     }
 }
 
+/// Refuses with 409 once a player already has `limit` ongoing games.
+fn active_game_limit_response(count: Result<u64, DbErr>, limit: u64) -> Option<HttpResponse> {
+    match count {
+        Ok(count) if count >= limit => Some(HttpResponse::Conflict().json(json!({
+            "error": format!("Players may have at most {} ongoing games", limit),
+            "code": 409
+        }))),
+        Ok(_) => None,
+        Err(err) => Some(ApiError::DatabaseError(err).error_response()),
+    }
+}
+
 #[utoipa::path(
     delete,
     path = "/v1/games/{id}",
@@ -266,7 +565,7 @@ pub async fn join_game(id: Path<Uuid>, payload: Json<JoinGameRequest>) -> HttpRe
     tag = "Games"
 )]
 #[delete("/{id}")]
-pub async fn abandon_game(id: Path<Uuid>) -> HttpResponse {
+pub async fn abandon_game(id: Path<GameId>) -> HttpResponse {
     // The real implementation would mark the game as abandoned
     // For now, we'll just return a mock response
     HttpResponse::Ok().json(json!({
@@ -0,0 +1,186 @@
+use async_trait::async_trait;
+use security::AuthenticatedUser;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use uuid::Uuid;
+
+/// Header clients send to make `POST /v1/games` safe to retry.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Maximum accepted length of an idempotency key.
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// Prefix of the stored idempotency keys.
+pub const IDEMPOTENCY_KEY_PREFIX: &str = "idempotency:games:";
+
+/// Whose idempotency keys a request uses: the player when the token names
+/// one, otherwise the user account.
+pub fn idempotency_scope(user: &AuthenticatedUser) -> String {
+    match user.player_id() {
+        Some(player_id) => format!("player:{}", player_id),
+        None => format!("user:{}", user.user_id()),
+    }
+}
+
+#[derive(Debug)]
+pub struct IdempotencyError(pub String);
+
+impl fmt::Display for IdempotencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "idempotency store error: {}", self.0)
+    }
+}
+
+impl std::error::Error for IdempotencyError {}
+
+impl From<redis::RedisError> for IdempotencyError {
+    fn from(e: redis::RedisError) -> Self {
+        IdempotencyError(e.to_string())
+    }
+}
+
+/// Game ids remembered per key that expire on their own.
+#[async_trait]
+pub trait IdempotencyBackend: Send + Sync {
+    /// Stores `game_id` under `key` for `ttl` unless the key already holds an
+    /// id. Returns the stored id and whether it was already there.
+    async fn get_or_insert(&self, key: &str, game_id: Uuid, ttl: Duration) -> Result<(Uuid, bool), IdempotencyError>;
+}
+
+/// [`IdempotencyBackend`] backed by Redis `SET NX PX`, shared by every instance.
+pub struct RedisIdempotencyBackend {
+    client: redis::Client,
+}
+
+impl RedisIdempotencyBackend {
+    pub fn new(redis_url: &str) -> Result<Self, IdempotencyError> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+        })
+    }
+}
+
+#[async_trait]
+impl IdempotencyBackend for RedisIdempotencyBackend {
+    async fn get_or_insert(&self, key: &str, game_id: Uuid, ttl: Duration) -> Result<(Uuid, bool), IdempotencyError> {
+        let mut conn = self.client.get_multiplexed_tokio_connection().await?;
+        let inserted: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(game_id.to_string())
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis().max(1) as u64)
+            .query_async(&mut conn)
+            .await?;
+        if inserted.is_some() {
+            return Ok((game_id, false));
+        }
+
+        let stored: Option<String> = redis::cmd("GET").arg(key).query_async(&mut conn).await?;
+        match stored.as_deref().map(Uuid::parse_str) {
+            Some(Ok(stored)) => Ok((stored, true)),
+            Some(Err(e)) => Err(IdempotencyError(format!("malformed game id under {}: {}", key, e))),
+            // Expired between the two commands
+            None => Ok((game_id, false)),
+        }
+    }
+}
+
+/// In-process [`IdempotencyBackend`], used when no Redis is configured.
+#[derive(Default)]
+pub struct MemoryIdempotencyBackend {
+    entries: Mutex<HashMap<String, (Uuid, Instant)>>,
+}
+
+#[async_trait]
+impl IdempotencyBackend for MemoryIdempotencyBackend {
+    async fn get_or_insert(&self, key: &str, game_id: Uuid, ttl: Duration) -> Result<(Uuid, bool), IdempotencyError> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, (_, expires_at)| *expires_at > now);
+
+        if let Some((stored, _)) = entries.get(key) {
+            return Ok((*stored, true));
+        }
+        entries.insert(key.to_string(), (game_id, now + ttl));
+        Ok((game_id, false))
+    }
+}
+
+/// Remembers which game was created for a given (scope, idempotency key) pair
+/// so retried requests return the original game instead of creating a new one.
+/// Entries expire after the configured TTL.
+///
+/// Backend errors are logged and the request goes ahead as a new one, so an
+/// unreachable Redis does not stop games from being created.
+pub struct IdempotencyStore {
+    backend: Box<dyn IdempotencyBackend>,
+    ttl: Duration,
+}
+
+impl IdempotencyStore {
+    /// A store kept in this process only.
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_backend(Box::new(MemoryIdempotencyBackend::default()), ttl)
+    }
+
+    pub fn with_backend(backend: Box<dyn IdempotencyBackend>, ttl: Duration) -> Self {
+        IdempotencyStore { backend, ttl }
+    }
+
+    /// Returns the game id stored for this key, or records `game_id` when the
+    /// key is new or expired. The boolean is `true` when the id was replayed
+    /// from an earlier request.
+    pub async fn get_or_insert(&self, scope: &str, key: &str, game_id: Uuid) -> (Uuid, bool) {
+        let entry_key = format!("{}{}:{}", IDEMPOTENCY_KEY_PREFIX, scope, key);
+        match self.backend.get_or_insert(&entry_key, game_id, self.ttl).await {
+            Ok(entry) => entry,
+            Err(e) => {
+                log::warn!("{}", e);
+                (game_id, false)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_keys_are_scoped() {
+        let store = IdempotencyStore::new(Duration::from_secs(60));
+        let (first, replayed) = store.get_or_insert("user:1", "abc", Uuid::new_v4()).await;
+        assert!(!replayed);
+        let (other_user, replayed) = store.get_or_insert("user:2", "abc", Uuid::new_v4()).await;
+        assert!(!replayed);
+        assert_ne!(first, other_user);
+        assert_eq!(store.get_or_insert("user:1", "abc", Uuid::new_v4()).await, (first, true));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_expired_keys_create_a_new_game() {
+        let store = IdempotencyStore::new(Duration::from_secs(60));
+        let (first, _) = store.get_or_insert("user:1", "abc", Uuid::new_v4()).await;
+        tokio::time::advance(Duration::from_secs(61)).await;
+        let (second, replayed) = store.get_or_insert("user:1", "abc", Uuid::new_v4()).await;
+        assert!(!replayed);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_scope_prefers_the_player_id() {
+        let jwt_service = security::JwtService::new("test_secret".to_string(), 3600);
+        let player_id = Uuid::new_v4();
+        let claims = |token: String| jwt_service.validate_token(&token).unwrap();
+
+        let with_player = AuthenticatedUser(claims(jwt_service.generate_player_token(7, player_id, "alice").unwrap()));
+        assert_eq!(idempotency_scope(&with_player), format!("player:{}", player_id));
+        let without_player = AuthenticatedUser(claims(jwt_service.generate_token(7, "alice").unwrap()));
+        assert_eq!(idempotency_scope(&without_player), "user:7");
+    }
+}
@@ -1,9 +1,19 @@
+pub mod accuracy;
 pub mod auth;
 pub mod ai;
+pub mod analysis;
 pub mod openapi;
 pub mod ws;
+pub mod pubsub;
 mod test;
 pub mod config;
+pub mod idempotency;
+pub mod lockout;
+pub mod metrics;
+pub mod rate_limit;
+pub mod registry;
+pub mod registry_events;
+pub mod admin;
 pub mod server;
 pub mod players;
 pub mod games;
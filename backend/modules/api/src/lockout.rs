@@ -0,0 +1,217 @@
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Prefix of the per-account failed login counters.
+pub const FAILED_LOGINS_KEY_PREFIX: &str = "login:failures:";
+
+fn failed_logins_key(username: &str) -> String {
+    format!("{}{}", FAILED_LOGINS_KEY_PREFIX, username.to_lowercase())
+}
+
+#[derive(Debug)]
+pub struct LockoutError(pub String);
+
+impl fmt::Display for LockoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "login lockout error: {}", self.0)
+    }
+}
+
+impl std::error::Error for LockoutError {}
+
+impl From<redis::RedisError> for LockoutError {
+    fn from(e: redis::RedisError) -> Self {
+        LockoutError(e.to_string())
+    }
+}
+
+/// Counters of consecutive failed logins that expire on their own.
+#[async_trait]
+pub trait FailedLoginCounter: Send + Sync {
+    /// Increments the counter for `key` and (re)starts its expiry. Returns the new count.
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u32, LockoutError>;
+
+    /// The current count for `key` and how long until it expires.
+    async fn get(&self, key: &str) -> Result<Option<(u32, Duration)>, LockoutError>;
+
+    async fn clear(&self, key: &str) -> Result<(), LockoutError>;
+}
+
+/// [`FailedLoginCounter`] backed by Redis `INCR` / `PEXPIRE`, shared by every instance.
+pub struct RedisFailedLoginCounter {
+    client: redis::Client,
+}
+
+impl RedisFailedLoginCounter {
+    pub fn new(redis_url: &str) -> Result<Self, LockoutError> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+        })
+    }
+}
+
+#[async_trait]
+impl FailedLoginCounter for RedisFailedLoginCounter {
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u32, LockoutError> {
+        let mut conn = self.client.get_multiplexed_tokio_connection().await?;
+        let (count,): (u32,) = redis::pipe()
+            .atomic()
+            .incr(key, 1)
+            .pexpire(key, ttl.as_millis() as usize)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        Ok(count)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<(u32, Duration)>, LockoutError> {
+        let mut conn = self.client.get_multiplexed_tokio_connection().await?;
+        let (count, ttl_ms): (Option<u32>, i64) = redis::pipe()
+            .get(key)
+            .pttl(key)
+            .query_async(&mut conn)
+            .await?;
+        // PTTL is negative when the key is gone or has no expiry
+        Ok(count
+            .filter(|_| ttl_ms > 0)
+            .map(|count| (count, Duration::from_millis(ttl_ms as u64))))
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), LockoutError> {
+        let mut conn = self.client.get_multiplexed_tokio_connection().await?;
+        conn.del::<_, ()>(key).await?;
+        Ok(())
+    }
+}
+
+/// In-process [`FailedLoginCounter`], used when no Redis is configured.
+#[derive(Default)]
+pub struct MemoryFailedLoginCounter {
+    entries: Mutex<HashMap<String, (u32, Instant)>>,
+}
+
+#[async_trait]
+impl FailedLoginCounter for MemoryFailedLoginCounter {
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u32, LockoutError> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, (_, expires_at)| *expires_at > now);
+
+        let entry = entries.entry(key.to_string()).or_insert((0, now));
+        entry.0 += 1;
+        entry.1 = now + ttl;
+        Ok(entry.0)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<(u32, Duration)>, LockoutError> {
+        let now = Instant::now();
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(entries
+            .get(key)
+            .filter(|(_, expires_at)| *expires_at > now)
+            .map(|(count, expires_at)| (*count, *expires_at - now)))
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), LockoutError> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(key);
+        Ok(())
+    }
+}
+
+/// Locks an account for `cooldown` once it has `max_failures` consecutive
+/// failed logins. Every failure restarts the cooldown and a successful login
+/// clears the count.
+///
+/// Counter errors are logged and treated as "not locked", so an unreachable
+/// Redis does not stop everyone from logging in.
+pub struct LoginLockout {
+    counter: Box<dyn FailedLoginCounter>,
+    max_failures: u32,
+    cooldown: Duration,
+}
+
+impl LoginLockout {
+    pub fn new(counter: Box<dyn FailedLoginCounter>, max_failures: u32, cooldown: Duration) -> Self {
+        Self {
+            counter,
+            max_failures,
+            cooldown,
+        }
+    }
+
+    /// How much longer `username` stays locked, or `None` if it may log in.
+    pub async fn locked_for(&self, username: &str) -> Option<Duration> {
+        match self.counter.get(&failed_logins_key(username)).await {
+            Ok(Some((count, remaining))) if count >= self.max_failures => Some(remaining),
+            Ok(_) => None,
+            Err(e) => {
+                log::warn!("{}", e);
+                None
+            }
+        }
+    }
+
+    pub async fn record_failure(&self, username: &str) {
+        if let Err(e) = self
+            .counter
+            .increment(&failed_logins_key(username), self.cooldown)
+            .await
+        {
+            log::warn!("{}", e);
+        }
+    }
+
+    pub async fn clear(&self, username: &str) {
+        if let Err(e) = self.counter.clear(&failed_logins_key(username)).await {
+            log::warn!("{}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_lockout_after_max_failures_until_cooldown() {
+        let lockout = LoginLockout::new(
+            Box::new(MemoryFailedLoginCounter::default()),
+            3,
+            Duration::from_secs(60),
+        );
+
+        for _ in 0..2 {
+            lockout.record_failure("Alice").await;
+        }
+        assert_eq!(lockout.locked_for("alice").await, None);
+
+        lockout.record_failure("alice").await;
+        assert_eq!(lockout.locked_for("alice").await, Some(Duration::from_secs(60)));
+        assert_eq!(lockout.locked_for("bob").await, None);
+
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert_eq!(lockout.locked_for("alice").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_clear_resets_failures() {
+        let lockout = LoginLockout::new(
+            Box::new(MemoryFailedLoginCounter::default()),
+            2,
+            Duration::from_secs(60),
+        );
+
+        lockout.record_failure("alice").await;
+        lockout.clear("alice").await;
+        lockout.record_failure("alice").await;
+        assert_eq!(lockout.locked_for("alice").await, None);
+    }
+}
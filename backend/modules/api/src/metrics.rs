@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use actix_web::{web, HttpResponse};
+use redis::AsyncCommands;
+
+/// Redis hash the socket gateway counts the moves it refused in, one field per reason.
+pub const GATEWAY_REJECTED_MOVES_KEY: &str = "metrics:gateway:rejected_moves";
+
+/// Why a websocket move was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MoveRejection {
+    /// The session is not seated at the board: a spectator, or not one of the game's players
+    NotInRoom,
+    /// The mover's color is not the side to move
+    OutOfTurn,
+    /// Not a legal move in the game's current position
+    Illegal,
+}
+
+impl MoveRejection {
+    /// Label value used for the `reason` label.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MoveRejection::NotInRoom => "not_in_room",
+            MoveRejection::OutOfTurn => "out_of_turn",
+            MoveRejection::Illegal => "illegal",
+        }
+    }
+}
+
+/// Counters served on `/metrics`.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Moves the actix websocket handler refused, by reason
+    rejected_moves: Mutex<BTreeMap<MoveRejection, u64>>,
+    /// Redis the socket gateway exports its counters to; without it they are not served
+    gateway: Option<redis::Client>,
+}
+
+impl Metrics {
+    /// Also serve the counters the socket gateway exports to the Redis at `redis_url`.
+    pub fn with_gateway(redis_url: &str) -> Result<Self, redis::RedisError> {
+        Ok(Metrics {
+            gateway: Some(redis::Client::open(redis_url)?),
+            ..Metrics::default()
+        })
+    }
+
+    /// Count a move the actix websocket handler refused.
+    pub fn record_rejected_move(&self, reason: MoveRejection) {
+        let mut counts = self.rejected_moves.lock().unwrap_or_else(|e| e.into_inner());
+        *counts.entry(reason).or_insert(0) += 1;
+    }
+
+    /// How many moves this server refused for `reason`.
+    pub fn rejected_moves(&self, reason: MoveRejection) -> u64 {
+        let counts = self.rejected_moves.lock().unwrap_or_else(|e| e.into_inner());
+        counts.get(&reason).copied().unwrap_or(0)
+    }
+
+    /// Moves the socket gateway refused, by reason. Empty without Redis, and
+    /// when it cannot be reached so the rest of the scrape still succeeds.
+    pub async fn gateway_rejected_moves(&self) -> BTreeMap<String, u64> {
+        let client = match &self.gateway {
+            Some(client) => client,
+            None => return BTreeMap::new(),
+        };
+        let counts = async {
+            let mut conn = client.get_multiplexed_tokio_connection().await?;
+            conn.hgetall(GATEWAY_REJECTED_MOVES_KEY).await
+        };
+        counts.await.unwrap_or_else(|e: redis::RedisError| {
+            log::warn!("Failed to read gateway metrics: {}", e);
+            BTreeMap::new()
+        })
+    }
+
+    /// The counters in the Prometheus text format.
+    pub fn render(&self, gateway_rejected_moves: &BTreeMap<String, u64>) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP xlmate_rejected_moves_total Moves refused by the websocket move handlers.\n");
+        out.push_str("# TYPE xlmate_rejected_moves_total counter\n");
+        let counts = self.rejected_moves.lock().unwrap_or_else(|e| e.into_inner());
+        for (reason, count) in counts.iter() {
+            let _ = writeln!(
+                out,
+                "xlmate_rejected_moves_total{{server=\"api\",reason=\"{}\"}} {}",
+                reason.as_str(),
+                count
+            );
+        }
+        for (reason, count) in gateway_rejected_moves {
+            let _ = writeln!(
+                out,
+                "xlmate_rejected_moves_total{{server=\"gateway\",reason=\"{}\"}} {}",
+                escape_label(reason),
+                count
+            );
+        }
+        out
+    }
+}
+
+/// Label values read from Redis are not trusted, so quote them per the text format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Prometheus scrape endpoint
+pub async fn render_metrics(metrics: web::Data<Metrics>) -> HttpResponse {
+    let gateway_rejected_moves = metrics.gateway_rejected_moves().await;
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(metrics.render(&gateway_rejected_moves))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App};
+
+    #[actix_web::test]
+    async fn test_metrics_route_serves_prometheus_text() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Metrics::default()))
+                .route("/metrics", web::get().to(render_metrics)),
+        )
+        .await;
+
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/metrics").to_request()).await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers().get("content-type").unwrap(), "text/plain; version=0.0.4");
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert!(body.contains("# TYPE xlmate_rejected_moves_total counter\n"));
+    }
+
+    #[actix_web::test]
+    async fn test_gateway_counts_are_rendered_by_reason() {
+        let gateway = BTreeMap::from([
+            ("out_of_turn".to_string(), 3),
+            ("bad\"reason".to_string(), 1),
+        ]);
+        let body = Metrics::default().render(&gateway);
+        assert!(body.contains("xlmate_rejected_moves_total{server=\"gateway\",reason=\"out_of_turn\"} 3\n"));
+        assert!(body.contains("xlmate_rejected_moves_total{server=\"gateway\",reason=\"bad\\\"reason\"} 1\n"));
+        assert!(!body.contains("game_id"));
+    }
+
+    #[actix_web::test]
+    async fn test_api_rejections_are_rendered_alongside_the_gateway() {
+        let metrics = Metrics::default();
+        metrics.record_rejected_move(MoveRejection::OutOfTurn);
+        metrics.record_rejected_move(MoveRejection::OutOfTurn);
+        metrics.record_rejected_move(MoveRejection::Illegal);
+        assert_eq!(metrics.rejected_moves(MoveRejection::OutOfTurn), 2);
+        assert_eq!(metrics.rejected_moves(MoveRejection::NotInRoom), 0);
+
+        let body = metrics.render(&BTreeMap::from([("out_of_turn".to_string(), 5)]));
+        assert!(body.contains("xlmate_rejected_moves_total{server=\"api\",reason=\"out_of_turn\"} 2\n"));
+        assert!(body.contains("xlmate_rejected_moves_total{server=\"api\",reason=\"illegal\"} 1\n"));
+        assert!(body.contains("xlmate_rejected_moves_total{server=\"gateway\",reason=\"out_of_turn\"} 5\n"));
+    }
+}
@@ -1,5 +1,5 @@
 use utoipa::OpenApi;
-use crate::{players, games, auth, ai};
+use crate::{players, games, auth, ai, analysis, admin};
 use utoipa::openapi::security::{SecurityScheme, HttpAuthScheme, HttpBuilder};
 use utoipa::Modify;
 
@@ -29,12 +29,18 @@ impl Modify for SecurityAddon {
         // Player endpoints
         players::add_player,
         players::find_player_by_id,
+        players::current_player,
         players::update_player,
         players::delete_player,
+        players::head_to_head,
+        players::active_games,
         
         // Game endpoints
         games::create_game,
         games::get_game,
+        games::get_games_batch,
+        games::get_game_accuracy,
+        games::get_game_summary,
         games::make_move,
         games::list_games,
         games::join_game,
@@ -43,10 +49,23 @@ impl Modify for SecurityAddon {
         // Authentication endpoints
         auth::login,
         auth::register,
+        auth::verify_email,
+        auth::forgot_password,
+        auth::reset_password,
         
         // AI suggestion endpoints
         ai::get_ai_suggestion,
         ai::analyze_position,
+        ai::analyze_position_stream,
+        // Analysis endpoints
+        analysis::legal_moves,
+        analysis::static_eval,
+
+        // Admin endpoints
+        admin::rotate_registry_server,
+        admin::force_game_result,
+        admin::recompute_ratings,
+        admin::list_admin_audit,
     ),
     components(
         schemas(
@@ -54,16 +73,22 @@ impl Modify for SecurityAddon {
             dto::players::NewPlayer,
             dto::players::UpdatePlayer,
             dto::players::DisplayPlayer,
+            dto::players::ActiveGame,
             dto::players::UpdatedPlayer,
+            dto::players::HeadToHead,
             
             // Game schemas
             dto::games::CreateGameRequest,
             dto::games::GameDisplayDTO,
+            dto::games::BatchGamesRequest,
             dto::games::MakeMoveRequest,
             dto::games::JoinGameRequest,
             dto::games::GameStatus,
             dto::games::GameResult,
             dto::games::ListGamesQuery,
+            dto::games::GameAccuracyResponse,
+            dto::games::GameSummary,
+            dto::games::SideSummary,
             
             // Auth schemas
             dto::auth::LoginRequest,
@@ -71,6 +96,10 @@ impl Modify for SecurityAddon {
             dto::auth::RegisterRequest,
             dto::auth::TokenResponse,
             dto::auth::UserInfo,
+            dto::auth::VerifyEmailResponse,
+            dto::auth::ForgotPasswordRequest,
+            dto::auth::ResetPasswordRequest,
+            dto::auth::MessageResponse,
             
             // AI schemas
             dto::ai::AiSuggestionRequest,
@@ -78,6 +107,24 @@ impl Modify for SecurityAddon {
             dto::ai::PositionAnalysisRequest,
             dto::ai::PositionAnalysisResponse,
             dto::ai::AlternativeMove,
+            dto::ai::AnalysisDepthUpdate,
+            // Analysis schemas
+            dto::analysis::LegalMovesRequest,
+            dto::analysis::LegalDestination,
+            dto::analysis::LegalMovesResponse,
+            dto::analysis::StaticEvalRequest,
+            dto::analysis::StaticEvalResponse,
+
+            // Admin schemas
+            dto::admin::RotateServerRequest,
+            dto::admin::RotateServerResponse,
+            dto::admin::ForceResultRequest,
+            dto::admin::ForceResultResponse,
+            dto::admin::RecomputeRatingsRequest,
+            dto::admin::RecomputeRatingsResponse,
+            dto::admin::AdminAuditQuery,
+            dto::admin::AdminAuditEntry,
+            dto::admin::AdminAuditResponse,
             
             // Response schemas
             dto::responses::PlayerAdded,
@@ -94,6 +141,8 @@ impl Modify for SecurityAddon {
         (name = "Games", description = "Game management operations"),
         (name = "Authentication", description = "Authentication operations"),
         (name = "AI", description = "AI suggestion operations"),
+        (name = "Analysis", description = "Rules-based position analysis"),
+        (name = "Admin", description = "Operator-only administration and dispute resolution"),
         (name = "WebSocket", description = "WebSocket communication protocol")
     ),
     info(
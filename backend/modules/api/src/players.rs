@@ -1,15 +1,18 @@
 use actix_web::{
     HttpResponse, delete, get, post, put,
-    web::{Json, Path},
+    web::{self, Json, Path},
 };
 use dto::{
-    players::{DisplayPlayer, NewPlayer, UpdatePlayer, UpdatedPlayer},
+    ids::PlayerId,
+    players::{ActiveGame, DisplayPlayer, HeadToHead, NewPlayer, UpdatePlayer, UpdatedPlayer},
     responses::{
         InvalidCredentialsResponse, NotFoundResponse, PlayerAdded, PlayerDeleted, PlayerFound,
         PlayerUpdated,
     },
 };
 use error::error::ApiError;
+use sea_orm::DatabaseConnection;
+use security::AuthenticatedUser;
 use serde_json::json;
 use validator::Validate;
 
@@ -17,7 +20,8 @@ use service::players::{
     add_player as add_new_player, delete_player as delete_player_by_id,
     find_player_by_id as get_single_player_by_id, update_player as update_player_by_id,
 };
-use uuid::Uuid;
+use service::auth::AuthService;
+use service::games::GameService;
 
 #[utoipa::path(
     post,
@@ -57,8 +61,8 @@ pub async fn add_player(payload: Json<NewPlayer>) -> HttpResponse {
     )
 )]
 #[get("/{id}")]
-pub async fn find_player_by_id(id: Path<Uuid>) -> HttpResponse {
-    let player = get_single_player_by_id(id.into_inner()).await;
+pub async fn find_player_by_id(id: Path<PlayerId>) -> HttpResponse {
+    let player = get_single_player_by_id(id.into_inner().into()).await;
 
     match player {
         Ok(plyr) => HttpResponse::Ok().json(json!({
@@ -71,6 +75,34 @@ pub async fn find_player_by_id(id: Path<Uuid>) -> HttpResponse {
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/v1/players/me",
+    responses(
+        (status = 200, description = "Profile of the authenticated player", body=PlayerFound),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 404, description = "The account was disabled or deleted", body=NotFoundResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/me")]
+pub async fn current_player(user: AuthenticatedUser, db: web::Data<DatabaseConnection>) -> HttpResponse {
+    // Tokens carry a placeholder integer id, so the player is found by username
+    let username = &user.claims().username;
+    match AuthService::enabled_player(db.get_ref(), username).await {
+        Ok(Some(plyr)) => HttpResponse::Ok().json(json!({
+            "message":"Player found",
+            "data":{
+                "player": DisplayPlayer::from(plyr)
+            }
+        })),
+        Ok(None) => ApiError::NotFound(format!("Player {}", username)).error_response(),
+        Err(err) => ApiError::DatabaseError(err).error_response(),
+    }
+}
+
 #[utoipa::path(
     put,
     path = "/v1/players/{id}",
@@ -83,10 +115,10 @@ pub async fn find_player_by_id(id: Path<Uuid>) -> HttpResponse {
     )
 )]
 #[put("/{id}")]
-pub async fn update_player(id: Path<Uuid>, payload: Json<UpdatePlayer>) -> HttpResponse {
+pub async fn update_player(id: Path<PlayerId>, payload: Json<UpdatePlayer>) -> HttpResponse {
     match payload.0.validate() {
         Ok(_) => {
-            let player = update_player_by_id(id.into_inner(), payload.0).await;
+            let player = update_player_by_id(id.into_inner().into(), payload.0).await;
 
             match player {
                 Ok(plyr) => HttpResponse::Ok().json(json!({
@@ -114,8 +146,8 @@ pub async fn update_player(id: Path<Uuid>, payload: Json<UpdatePlayer>) -> HttpR
     )
 )]
 #[delete("/{id}")]
-pub async fn delete_player(id: Path<Uuid>) -> HttpResponse {
-    match delete_player_by_id(id.into_inner()).await {
+pub async fn delete_player(id: Path<PlayerId>) -> HttpResponse {
+    match delete_player_by_id(id.into_inner().into()).await {
         Ok(_) => HttpResponse::Ok().json(json!({
             "message":"Player deleted",
             "data":{}
@@ -123,3 +155,56 @@ pub async fn delete_player(id: Path<Uuid>) -> HttpResponse {
         Err(err) => err.error_response(),
     }
 }
+
+#[utoipa::path(
+    get,
+    path = "/v1/players/{id}/vs/{opponent_id}",
+    params(
+        ("id" = String, Path, description = "Player ID in UUID format", format="uuid"),
+        ("opponent_id" = String, Path, description = "Opponent ID in UUID format", format="uuid")
+    ),
+    responses(
+        (status = 200, description = "Head-to-head record of the player against the opponent", body=HeadToHead),
+        (status = 500, description = "Internal server error")
+    )
+)]
+#[get("/{id}/vs/{opponent_id}")]
+pub async fn head_to_head(
+    path: Path<(PlayerId, PlayerId)>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let (id, opponent_id) = path.into_inner();
+
+    match GameService::head_to_head(db.get_ref(), id.into(), opponent_id.into()).await {
+        Ok(record) => HttpResponse::Ok().json(json!({
+            "message":"Head-to-head record found",
+            "data": record
+        })),
+        Err(err) => ApiError::DatabaseError(err).error_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/players/{id}/active-games",
+    params(
+        ("id" = String, Path, description = "Player ID in UUID format", format="uuid")
+    ),
+    responses(
+        (status = 200, description = "Ongoing games of the player as white or black", body=[ActiveGame]),
+        (status = 500, description = "Internal server error")
+    )
+)]
+#[get("/{id}/active-games")]
+pub async fn active_games(
+    id: Path<PlayerId>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    match GameService::active_games_for_player(db.get_ref(), id.into_inner().into()).await {
+        Ok(games) => HttpResponse::Ok().json(json!({
+            "message":"Active games found",
+            "data": games
+        })),
+        Err(err) => ApiError::DatabaseError(err).error_response(),
+    }
+}
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use redis::AsyncCommands;
+use std::fmt;
+use tokio::sync::mpsc;
+
+/// Prefix of the per-game channels lobby broadcasts are published on.
+pub const GAME_CHANNEL_PREFIX: &str = "lobby:game:";
+
+/// Channel carrying broadcasts for `game_id`.
+pub fn game_channel(game_id: &str) -> String {
+    format!("{}{}", GAME_CHANNEL_PREFIX, game_id)
+}
+
+/// Game id of a channel built by [`game_channel`].
+pub fn game_id_of(channel: &str) -> Option<&str> {
+    channel.strip_prefix(GAME_CHANNEL_PREFIX)
+}
+
+#[derive(Debug)]
+pub struct PubSubError(pub String);
+
+impl fmt::Display for PubSubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pub/sub error: {}", self.0)
+    }
+}
+
+impl std::error::Error for PubSubError {}
+
+impl From<redis::RedisError> for PubSubError {
+    fn from(e: redis::RedisError) -> Self {
+        PubSubError(e.to_string())
+    }
+}
+
+/// Message bus shared by every server instance, so a broadcast for a game
+/// reaches players connected to any instance.
+#[async_trait]
+pub trait LobbyPubSub: Send + Sync {
+    async fn publish(&self, channel: String, payload: String) -> Result<(), PubSubError>;
+
+    /// Subscribes to every game channel. Each item is `(channel, payload)`.
+    async fn subscribe(&self) -> Result<mpsc::UnboundedReceiver<(String, String)>, PubSubError>;
+}
+
+/// [`LobbyPubSub`] backed by Redis `PUBLISH` / `PSUBSCRIBE`.
+pub struct RedisPubSub {
+    client: redis::Client,
+}
+
+impl RedisPubSub {
+    pub fn new(redis_url: &str) -> Result<Self, PubSubError> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+        })
+    }
+}
+
+#[async_trait]
+impl LobbyPubSub for RedisPubSub {
+    async fn publish(&self, channel: String, payload: String) -> Result<(), PubSubError> {
+        let mut conn = self.client.get_multiplexed_tokio_connection().await?;
+        conn.publish::<_, _, ()>(channel, payload).await?;
+        Ok(())
+    }
+
+    async fn subscribe(&self) -> Result<mpsc::UnboundedReceiver<(String, String)>, PubSubError> {
+        let mut pubsub = self.client.get_async_connection().await?.into_pubsub();
+        pubsub.psubscribe(format!("{}*", GAME_CHANNEL_PREFIX)).await?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut messages = pubsub.on_message();
+            while let Some(msg) = messages.next().await {
+                let payload: String = match msg.get_payload() {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        log::warn!("Dropping undecodable lobby message: {}", e);
+                        continue;
+                    }
+                };
+                if tx.send((msg.get_channel_name().to_string(), payload)).is_err() {
+                    break;
+                }
+            }
+            log::warn!("Redis lobby subscription ended");
+        });
+
+        Ok(rx)
+    }
+}
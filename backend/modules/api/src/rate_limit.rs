@@ -0,0 +1,138 @@
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use actix_governor::{KeyExtractor, PeerIpKeyExtractor, SimpleKeyExtractionError};
+use actix_web::dev::ServiceRequest;
+
+/// An IPv4 or IPv6 network in CIDR notation, e.g. `10.0.0.0/8` or `::1/128`.
+/// A bare address is treated as a single-host network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpCidr {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpCidr {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = prefix_mask(self.prefix_len, 32) as u32;
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = prefix_mask(self.prefix_len, 128);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+fn prefix_mask(prefix_len: u8, bits: u8) -> u128 {
+    if prefix_len == 0 {
+        0
+    } else {
+        (u128::MAX << (128 - prefix_len as u32)) >> (128 - bits as u32)
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.trim().split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s.trim(), None),
+        };
+        let network: IpAddr = addr
+            .parse()
+            .map_err(|_| format!("Invalid IP address in CIDR '{}'", s))?;
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|len| *len <= max_prefix)
+                .ok_or_else(|| format!("Invalid prefix length in CIDR '{}'", s))?,
+            None => max_prefix,
+        };
+
+        Ok(IpCidr {
+            network,
+            prefix_len,
+        })
+    }
+}
+
+/// Rate-limiting key: peers are limited per IP, allowlisted peers share a key that is never limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitKey {
+    Peer(IpAddr),
+    Allowlisted,
+}
+
+/// Key extractor that limits by peer IP like `PeerIpKeyExtractor`, but lets
+/// peers inside the configured CIDRs (health checkers, admin tooling) bypass the limit.
+#[derive(Debug, Clone, Default)]
+pub struct AllowlistKeyExtractor {
+    allowlist: Arc<Vec<IpCidr>>,
+}
+
+impl AllowlistKeyExtractor {
+    pub fn new(allowlist: Vec<IpCidr>) -> Self {
+        AllowlistKeyExtractor {
+            allowlist: Arc::new(allowlist),
+        }
+    }
+}
+
+impl KeyExtractor for AllowlistKeyExtractor {
+    type Key = RateLimitKey;
+    type KeyExtractionError = SimpleKeyExtractionError<&'static str>;
+
+    fn extract(&self, req: &ServiceRequest) -> Result<Self::Key, Self::KeyExtractionError> {
+        if let Some(peer) = req.peer_addr() {
+            if self.allowlist.iter().any(|cidr| cidr.contains(peer.ip())) {
+                return Ok(RateLimitKey::Allowlisted);
+            }
+        }
+
+        PeerIpKeyExtractor.extract(req).map(RateLimitKey::Peer)
+    }
+
+    fn whitelisted_keys(&self) -> Vec<Self::Key> {
+        vec![RateLimitKey::Allowlisted]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cidr_contains() {
+        let cidr: IpCidr = "10.0.0.0/8".parse().unwrap();
+        assert!(cidr.contains("10.1.2.3".parse().unwrap()));
+        assert!(!cidr.contains("11.0.0.1".parse().unwrap()));
+        assert!(!cidr.contains("::1".parse().unwrap()));
+
+        let host: IpCidr = "192.168.1.5".parse().unwrap();
+        assert!(host.contains("192.168.1.5".parse().unwrap()));
+        assert!(!host.contains("192.168.1.6".parse().unwrap()));
+
+        let v6: IpCidr = "fd00::/8".parse().unwrap();
+        assert!(v6.contains("fd12:3456::1".parse().unwrap()));
+        assert!(!v6.contains("fe80::1".parse().unwrap()));
+
+        let any: IpCidr = "0.0.0.0/0".parse().unwrap();
+        assert!(any.contains("8.8.8.8".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_invalid_cidr() {
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("not-an-ip/8".parse::<IpCidr>().is_err());
+        assert!("10.0.0.0/abc".parse::<IpCidr>().is_err());
+    }
+}
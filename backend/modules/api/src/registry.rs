@@ -0,0 +1,722 @@
+//! Off-chain administration of the `GameRegistry` Soroban contract.
+//!
+//! `RegistryAdmin` builds, signs and submits contract invocations with the
+//! configured admin keypair. All network access goes through `SorobanRpc`,
+//! so the transaction building can be exercised without a live RPC node.
+
+use std::env;
+use std::fmt;
+
+use async_trait::async_trait;
+use ed25519_dalek::{Signer, SigningKey};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use stellar_strkey::{ed25519, Strkey};
+use stellar_xdr::curr::{
+    AccountId, ContractId, DecoratedSignature, Hash, HostFunction, InvokeContractArgs,
+    InvokeHostFunctionOp, LedgerEntryData, LedgerKey, LedgerKeyAccount, Limits, Memo,
+    MuxedAccount, Operation, OperationBody, Preconditions, PublicKey, ReadXdr, ScAddress,
+    ScString, ScSymbol, ScVal, SequenceNumber, Signature, SignatureHint, SorobanAuthorizationEntry,
+    SorobanTransactionData, Transaction, TransactionEnvelope, TransactionExt,
+    TransactionV1Envelope, Uint256, WriteXdr,
+};
+use uuid::Uuid;
+
+/// Contract function that replaces the authorized game server.
+pub const SET_SERVER_FN: &str = "set_server";
+
+/// Contract function that stores a finished game's result.
+pub const RECORD_GAME_FN: &str = "record_game";
+
+/// Inclusion fee in stroops; the simulated resource fee is added on top.
+pub const BASE_FEE: u32 = 100;
+
+/// Prefix of the hashed input of `canonical_game_id`, versioned so the scheme
+/// can change without reusing ids.
+const GAME_ID_DOMAIN: &[u8] = b"xlmate:game-id:v1";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryAdminError {
+    /// The supplied address is not a valid account (`G...`) or contract (`C...`) strkey.
+    InvalidAddress(String),
+    /// The admin keypair or contract id in the configuration is unusable.
+    Config(String),
+    /// The RPC node rejected the request or could not be reached.
+    Rpc(String),
+    /// A transaction could not be encoded or decoded.
+    Xdr(String),
+}
+
+impl fmt::Display for RegistryAdminError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryAdminError::InvalidAddress(v) => write!(f, "Invalid Stellar address: {}", v),
+            RegistryAdminError::Config(v) => write!(f, "Registry admin misconfigured: {}", v),
+            RegistryAdminError::Rpc(v) => write!(f, "Soroban RPC error: {}", v),
+            RegistryAdminError::Xdr(v) => write!(f, "XDR error: {}", v),
+        }
+    }
+}
+
+impl std::error::Error for RegistryAdminError {}
+
+impl From<stellar_xdr::curr::Error> for RegistryAdminError {
+    fn from(value: stellar_xdr::curr::Error) -> Self {
+        Self::Xdr(value.to_string())
+    }
+}
+
+/// Settings for talking to the registry contract. Every field is required;
+/// the admin endpoint stays disabled when any of them is missing.
+#[derive(Debug, Clone)]
+pub struct RegistryAdminConfig {
+    pub rpc_url: String,
+    pub network_passphrase: String,
+    pub contract_id: String,
+    pub admin_secret_key: String,
+}
+
+impl RegistryAdminConfig {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            rpc_url: env::var("SOROBAN_RPC_URL").ok()?,
+            network_passphrase: env::var("STELLAR_NETWORK_PASSPHRASE").ok()?,
+            contract_id: env::var("GAME_REGISTRY_CONTRACT_ID").ok()?,
+            admin_secret_key: env::var("REGISTRY_ADMIN_SECRET_KEY").ok()?,
+        })
+    }
+}
+
+/// Outcome of `simulateTransaction` that is needed to make a transaction submittable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedTransaction {
+    pub transaction_data: SorobanTransactionData,
+    pub auth: Vec<SorobanAuthorizationEntry>,
+    pub min_resource_fee: u64,
+}
+
+/// The subset of the Soroban JSON-RPC API used by `RegistryAdmin`.
+#[async_trait]
+pub trait SorobanRpc: Send + Sync {
+    /// Current sequence number of `account`.
+    async fn get_sequence(&self, account: &AccountId) -> Result<i64, RegistryAdminError>;
+
+    async fn simulate_transaction(
+        &self,
+        envelope: &TransactionEnvelope,
+    ) -> Result<SimulatedTransaction, RegistryAdminError>;
+
+    /// Submits a signed transaction and returns its hash.
+    async fn send_transaction(&self, envelope: &TransactionEnvelope) -> Result<String, RegistryAdminError>;
+}
+
+/// `SorobanRpc` over HTTP JSON-RPC.
+pub struct HttpSorobanRpc {
+    client: reqwest::Client,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse<T> {
+    result: Option<T>,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct LedgerEntriesResult {
+    pub(crate) entries: Option<Vec<LedgerEntryResult>>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct LedgerEntryResult {
+    pub(crate) xdr: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulateResult {
+    error: Option<String>,
+    transaction_data: Option<String>,
+    min_resource_fee: Option<String>,
+    results: Option<Vec<SimulateHostFunctionResult>>,
+}
+
+#[derive(Deserialize)]
+struct SimulateHostFunctionResult {
+    auth: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendResult {
+    status: String,
+    hash: String,
+    error_result_xdr: Option<String>,
+}
+
+impl HttpSorobanRpc {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+        }
+    }
+
+    pub(crate) async fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, RegistryAdminError> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: JsonRpcResponse<T> = self
+            .client
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| RegistryAdminError::Rpc(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| RegistryAdminError::Rpc(e.to_string()))?;
+
+        match (response.result, response.error) {
+            (Some(result), _) => Ok(result),
+            (None, Some(error)) => Err(RegistryAdminError::Rpc(format!("{}: {}", method, error.message))),
+            (None, None) => Err(RegistryAdminError::Rpc(format!("{}: empty response", method))),
+        }
+    }
+}
+
+#[async_trait]
+impl SorobanRpc for HttpSorobanRpc {
+    async fn get_sequence(&self, account: &AccountId) -> Result<i64, RegistryAdminError> {
+        let key = LedgerKey::Account(LedgerKeyAccount {
+            account_id: account.clone(),
+        });
+        let result: LedgerEntriesResult = self
+            .call("getLedgerEntries", json!({ "keys": [key.to_xdr_base64(Limits::none())?] }))
+            .await?;
+
+        let entry = result
+            .entries
+            .and_then(|entries| entries.into_iter().next())
+            .ok_or_else(|| RegistryAdminError::Rpc("admin account not found".to_string()))?;
+        match LedgerEntryData::from_xdr_base64(entry.xdr, Limits::none())? {
+            LedgerEntryData::Account(account) => Ok(account.seq_num.0),
+            _ => Err(RegistryAdminError::Rpc("unexpected ledger entry for admin account".to_string())),
+        }
+    }
+
+    async fn simulate_transaction(
+        &self,
+        envelope: &TransactionEnvelope,
+    ) -> Result<SimulatedTransaction, RegistryAdminError> {
+        let result: SimulateResult = self
+            .call(
+                "simulateTransaction",
+                json!({ "transaction": envelope.to_xdr_base64(Limits::none())? }),
+            )
+            .await?;
+
+        if let Some(error) = result.error {
+            return Err(RegistryAdminError::Rpc(format!("simulation failed: {}", error)));
+        }
+        let transaction_data = result
+            .transaction_data
+            .ok_or_else(|| RegistryAdminError::Rpc("simulation returned no transaction data".to_string()))?;
+        let min_resource_fee = result
+            .min_resource_fee
+            .unwrap_or_default()
+            .parse()
+            .map_err(|_| RegistryAdminError::Rpc("simulation returned an invalid resource fee".to_string()))?;
+        let auth = result
+            .results
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|r| r.auth.unwrap_or_default())
+            .map(|entry| SorobanAuthorizationEntry::from_xdr_base64(entry, Limits::none()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SimulatedTransaction {
+            transaction_data: SorobanTransactionData::from_xdr_base64(transaction_data, Limits::none())?,
+            auth,
+            min_resource_fee,
+        })
+    }
+
+    async fn send_transaction(&self, envelope: &TransactionEnvelope) -> Result<String, RegistryAdminError> {
+        let result: SendResult = self
+            .call(
+                "sendTransaction",
+                json!({ "transaction": envelope.to_xdr_base64(Limits::none())? }),
+            )
+            .await?;
+
+        if result.status == "ERROR" {
+            return Err(RegistryAdminError::Rpc(format!(
+                "transaction {} rejected: {}",
+                result.hash,
+                result.error_result_xdr.unwrap_or_default()
+            )));
+        }
+        Ok(result.hash)
+    }
+}
+
+/// Game id for a game between `white` and `black` (account or contract
+/// strkeys), made unique by `nonce`, e.g. the creation time in milliseconds.
+///
+/// The id is a UUID built from a SHA-256 hash of both addresses and the nonce,
+/// so the same inputs always give the same id and any change gives another.
+/// It is meant to be both the game's primary key and the `game_id` passed to
+/// the contract's `record_game`, so the two cannot drift apart or collide.
+pub fn canonical_game_id(white: &str, black: &str, nonce: u64) -> Result<Uuid, RegistryAdminError> {
+    let mut hasher = Sha256::new();
+    hasher.update(GAME_ID_DOMAIN);
+    // The XDR form is independent of how the strkey was written
+    for address in [white, black] {
+        hasher.update(RegistryAdmin::parse_address(address)?.to_xdr(Limits::none())?);
+    }
+    hasher.update(nonce.to_be_bytes());
+
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    Ok(uuid::Builder::from_custom_bytes(bytes).into_uuid())
+}
+
+/// A finished game as passed to the contract's `record_game`.
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    /// Account or contract strkeys of both sides and the winner
+    pub white: String,
+    pub black: String,
+    pub winner: String,
+    /// The game's creation time in milliseconds, the nonce its id was
+    /// created with; see [`canonical_game_id`]
+    pub nonce: u64,
+    /// When the game ended, in seconds since the epoch
+    pub timestamp: u64,
+    pub move_count: u32,
+    /// SHA-256 of the game's PGN
+    pub pgn_hash: [u8; 32],
+}
+
+/// Signs and submits admin-only invocations on the `GameRegistry` contract.
+pub struct RegistryAdmin {
+    rpc: Box<dyn SorobanRpc>,
+    network_id: [u8; 32],
+    contract: ScAddress,
+    signing_key: SigningKey,
+}
+
+impl RegistryAdmin {
+    pub fn new(config: &RegistryAdminConfig, rpc: Box<dyn SorobanRpc>) -> Result<Self, RegistryAdminError> {
+        let contract = match Strkey::from_string(&config.contract_id) {
+            Ok(Strkey::Contract(contract)) => ScAddress::Contract(ContractId(Hash(contract.0))),
+            _ => return Err(RegistryAdminError::Config("contract id must be a C... strkey".to_string())),
+        };
+        let secret = ed25519::PrivateKey::from_string(&config.admin_secret_key)
+            .map_err(|_| RegistryAdminError::Config("admin secret key must be an S... strkey".to_string()))?;
+
+        Ok(Self {
+            rpc,
+            network_id: Sha256::digest(config.network_passphrase.as_bytes()).into(),
+            contract,
+            signing_key: SigningKey::from_bytes(&secret.0),
+        })
+    }
+
+    /// Public key of the admin keypair as a `G...` strkey.
+    pub fn admin_address(&self) -> String {
+        ed25519::PublicKey(self.signing_key.verifying_key().to_bytes()).to_string()
+    }
+
+    /// Parses an account (`G...`) or contract (`C...`) strkey into a contract address.
+    pub fn parse_address(address: &str) -> Result<ScAddress, RegistryAdminError> {
+        match Strkey::from_string(address.trim()) {
+            Ok(Strkey::PublicKeyEd25519(key)) => Ok(ScAddress::Account(AccountId(
+                PublicKey::PublicKeyTypeEd25519(Uint256(key.0)),
+            ))),
+            Ok(Strkey::Contract(contract)) => Ok(ScAddress::Contract(ContractId(Hash(contract.0)))),
+            _ => Err(RegistryAdminError::InvalidAddress(address.to_string())),
+        }
+    }
+
+    fn admin_account(&self) -> AccountId {
+        AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+            self.signing_key.verifying_key().to_bytes(),
+        )))
+    }
+
+    /// Builds the unsigned, unsimulated `set_server(new_server)` invocation for
+    /// an admin account whose current sequence number is `sequence`.
+    pub fn build_set_server_tx(
+        &self,
+        new_server: &ScAddress,
+        sequence: i64,
+        auth: Vec<SorobanAuthorizationEntry>,
+    ) -> Result<Transaction, RegistryAdminError> {
+        self.build_invoke_tx(SET_SERVER_FN, vec![ScVal::Address(new_server.clone())], sequence, auth)
+    }
+
+    /// Builds the unsigned, unsimulated invocation of `function` with `args`.
+    fn build_invoke_tx(
+        &self,
+        function: &str,
+        args: Vec<ScVal>,
+        sequence: i64,
+        auth: Vec<SorobanAuthorizationEntry>,
+    ) -> Result<Transaction, RegistryAdminError> {
+        let invoke = InvokeContractArgs {
+            contract_address: self.contract.clone(),
+            function_name: ScSymbol(function.try_into()?),
+            args: args.try_into()?,
+        };
+        let operation = Operation {
+            source_account: None,
+            body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                host_function: HostFunction::InvokeContract(invoke),
+                auth: auth.try_into()?,
+            }),
+        };
+
+        Ok(Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256(self.signing_key.verifying_key().to_bytes())),
+            fee: BASE_FEE,
+            seq_num: SequenceNumber(sequence + 1),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![operation].try_into()?,
+            ext: TransactionExt::V0,
+        })
+    }
+
+    fn sign(&self, tx: Transaction) -> Result<TransactionEnvelope, RegistryAdminError> {
+        let hash = tx.hash(self.network_id)?;
+        let public_key = self.signing_key.verifying_key().to_bytes();
+        let signature = DecoratedSignature {
+            hint: SignatureHint([public_key[28], public_key[29], public_key[30], public_key[31]]),
+            signature: Signature(self.signing_key.sign(&hash).to_bytes().to_vec().try_into()?),
+        };
+
+        Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx,
+            signatures: vec![signature].try_into()?,
+        }))
+    }
+
+    /// Rotates the registry's authorized server to `new_server` and returns the
+    /// transaction hash. The address is validated before any RPC call is made.
+    pub async fn set_server(&self, new_server: &str) -> Result<String, RegistryAdminError> {
+        let new_server = Self::parse_address(new_server)?;
+        self.invoke(SET_SERVER_FN, vec![ScVal::Address(new_server)]).await
+    }
+
+    /// Records a finished game on-chain under its [`canonical_game_id`] and
+    /// returns that id with the transaction hash. The signing key must be the
+    /// registry's authorized server. Addresses are validated before any RPC
+    /// call is made.
+    pub async fn record_game(&self, record: &GameRecord) -> Result<(Uuid, String), RegistryAdminError> {
+        let game_id = canonical_game_id(&record.white, &record.black, record.nonce)?;
+        let args = vec![
+            ScVal::String(ScString(game_id.to_string().try_into()?)),
+            ScVal::Address(Self::parse_address(&record.winner)?),
+            ScVal::Address(Self::parse_address(&record.white)?),
+            ScVal::Address(Self::parse_address(&record.black)?),
+            ScVal::U64(record.timestamp),
+            ScVal::U32(record.move_count),
+            ScVal::Bytes(record.pgn_hash.to_vec().try_into()?),
+        ];
+        let hash = self.invoke(RECORD_GAME_FN, args).await?;
+        Ok((game_id, hash))
+    }
+
+    /// Simulates, signs and submits an invocation of `function` from the
+    /// admin account and returns the transaction hash.
+    async fn invoke(&self, function: &str, args: Vec<ScVal>) -> Result<String, RegistryAdminError> {
+        let sequence = self.rpc.get_sequence(&self.admin_account()).await?;
+
+        // Simulate first to learn the footprint, resource fee and auth entries
+        let draft = self.build_invoke_tx(function, args.clone(), sequence, Vec::new())?;
+        let simulation = self
+            .rpc
+            .simulate_transaction(&TransactionEnvelope::Tx(TransactionV1Envelope {
+                tx: draft,
+                signatures: Default::default(),
+            }))
+            .await?;
+
+        let mut tx = self.build_invoke_tx(function, args, sequence, simulation.auth)?;
+        tx.fee = u32::try_from(simulation.min_resource_fee)
+            .ok()
+            .and_then(|fee| fee.checked_add(BASE_FEE))
+            .ok_or_else(|| RegistryAdminError::Rpc("simulated resource fee is too large".to_string()))?;
+        tx.ext = TransactionExt::V1(simulation.transaction_data);
+
+        self.rpc.send_transaction(&self.sign(tx)?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Verifier;
+    use std::sync::{Arc, Mutex};
+
+    const PASSPHRASE: &str = "Test SDF Network ; September 2015";
+
+    #[derive(Default)]
+    struct MockRpc {
+        simulated: Mutex<Vec<TransactionEnvelope>>,
+        sent: Mutex<Vec<TransactionEnvelope>>,
+    }
+
+    #[async_trait]
+    impl SorobanRpc for Arc<MockRpc> {
+        async fn get_sequence(&self, _account: &AccountId) -> Result<i64, RegistryAdminError> {
+            Ok(41)
+        }
+
+        async fn simulate_transaction(
+            &self,
+            envelope: &TransactionEnvelope,
+        ) -> Result<SimulatedTransaction, RegistryAdminError> {
+            self.simulated.lock().unwrap().push(envelope.clone());
+            Ok(SimulatedTransaction {
+                transaction_data: SorobanTransactionData::default(),
+                auth: Vec::new(),
+                min_resource_fee: 5_000,
+            })
+        }
+
+        async fn send_transaction(&self, envelope: &TransactionEnvelope) -> Result<String, RegistryAdminError> {
+            self.sent.lock().unwrap().push(envelope.clone());
+            Ok("a1b2c3".to_string())
+        }
+    }
+
+    fn config() -> RegistryAdminConfig {
+        RegistryAdminConfig {
+            rpc_url: "http://localhost:8000/soroban/rpc".to_string(),
+            network_passphrase: PASSPHRASE.to_string(),
+            contract_id: stellar_strkey::Contract([9; 32]).to_string(),
+            admin_secret_key: ed25519::PrivateKey([7; 32]).to_string(),
+        }
+    }
+
+    fn admin_with_mock() -> (RegistryAdmin, Arc<MockRpc>) {
+        let rpc = Arc::new(MockRpc::default());
+        (RegistryAdmin::new(&config(), Box::new(rpc.clone())).unwrap(), rpc)
+    }
+
+    #[actix_rt::test]
+    async fn set_server_submits_signed_invocation() {
+        let (admin, rpc) = admin_with_mock();
+        let new_server = ed25519::PublicKey([3; 32]).to_string();
+
+        let hash = admin.set_server(&new_server).await.unwrap();
+        assert_eq!(hash, "a1b2c3");
+        assert_eq!(rpc.simulated.lock().unwrap().len(), 1);
+
+        let sent = rpc.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        let TransactionEnvelope::Tx(envelope) = &sent[0] else {
+            panic!("expected a v1 transaction envelope");
+        };
+        let tx = &envelope.tx;
+        assert_eq!(tx.seq_num, SequenceNumber(42));
+        assert_eq!(tx.fee, BASE_FEE + 5_000);
+        assert!(matches!(tx.ext, TransactionExt::V1(_)));
+        assert_eq!(
+            tx.source_account,
+            MuxedAccount::Ed25519(Uint256(admin.signing_key.verifying_key().to_bytes()))
+        );
+
+        let [operation] = tx.operations.as_slice() else {
+            panic!("expected a single operation");
+        };
+        let OperationBody::InvokeHostFunction(op) = &operation.body else {
+            panic!("expected an InvokeHostFunction operation");
+        };
+        let HostFunction::InvokeContract(invoke) = &op.host_function else {
+            panic!("expected a contract invocation");
+        };
+        assert_eq!(invoke.contract_address, ScAddress::Contract(ContractId(Hash([9; 32]))));
+        assert_eq!(invoke.function_name.to_utf8_string_lossy(), SET_SERVER_FN);
+        assert_eq!(
+            invoke.args.as_slice(),
+            [ScVal::Address(ScAddress::Account(AccountId(
+                PublicKey::PublicKeyTypeEd25519(Uint256([3; 32]))
+            )))]
+        );
+
+        // Signed by the admin key over the network-bound transaction hash
+        let [signature] = envelope.signatures.as_slice() else {
+            panic!("expected a single signature");
+        };
+        let hash = tx.hash(admin.network_id).unwrap();
+        let signature = ed25519_dalek::Signature::from_slice(signature.signature.as_slice()).unwrap();
+        assert!(admin.signing_key.verifying_key().verify(&hash, &signature).is_ok());
+    }
+
+    #[actix_rt::test]
+    async fn set_server_accepts_contract_addresses() {
+        let (admin, rpc) = admin_with_mock();
+        let new_server = stellar_strkey::Contract([5; 32]).to_string();
+
+        admin.set_server(&new_server).await.unwrap();
+
+        let sent = rpc.sent.lock().unwrap();
+        let TransactionEnvelope::Tx(envelope) = &sent[0] else {
+            panic!("expected a v1 transaction envelope");
+        };
+        let OperationBody::InvokeHostFunction(op) = &envelope.tx.operations[0].body else {
+            panic!("expected an InvokeHostFunction operation");
+        };
+        let HostFunction::InvokeContract(invoke) = &op.host_function else {
+            panic!("expected a contract invocation");
+        };
+        assert_eq!(
+            invoke.args.as_slice(),
+            [ScVal::Address(ScAddress::Contract(ContractId(Hash([5; 32]))))]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn invalid_address_is_rejected_before_rpc() {
+        let (admin, rpc) = admin_with_mock();
+
+        for address in ["", "not-an-address", &ed25519::PrivateKey([1; 32]).to_string()] {
+            assert_eq!(
+                admin.set_server(address).await,
+                Err(RegistryAdminError::InvalidAddress(address.to_string()))
+            );
+        }
+        assert!(rpc.simulated.lock().unwrap().is_empty());
+        assert!(rpc.sent.lock().unwrap().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn record_game_uses_the_canonical_game_id() {
+        let (admin, rpc) = admin_with_mock();
+        let record = GameRecord {
+            white: ed25519::PublicKey([1; 32]).to_string(),
+            black: ed25519::PublicKey([2; 32]).to_string(),
+            winner: ed25519::PublicKey([1; 32]).to_string(),
+            nonce: 1_700_000_000_000,
+            timestamp: 1_700_000_600,
+            move_count: 42,
+            pgn_hash: [4; 32],
+        };
+
+        let (game_id, hash) = admin.record_game(&record).await.unwrap();
+        assert_eq!(hash, "a1b2c3");
+        assert_eq!(game_id, canonical_game_id(&record.white, &record.black, record.nonce).unwrap());
+
+        let sent = rpc.sent.lock().unwrap();
+        let TransactionEnvelope::Tx(envelope) = &sent[0] else {
+            panic!("expected a v1 transaction envelope");
+        };
+        let OperationBody::InvokeHostFunction(op) = &envelope.tx.operations[0].body else {
+            panic!("expected an InvokeHostFunction operation");
+        };
+        let HostFunction::InvokeContract(invoke) = &op.host_function else {
+            panic!("expected a contract invocation");
+        };
+        assert_eq!(invoke.function_name.to_utf8_string_lossy(), RECORD_GAME_FN);
+        let white = ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([1; 32]))));
+        let black = ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([2; 32]))));
+        assert_eq!(
+            invoke.args.as_slice(),
+            [
+                ScVal::String(ScString(game_id.to_string().try_into().unwrap())),
+                ScVal::Address(white.clone()),
+                ScVal::Address(white),
+                ScVal::Address(black),
+                ScVal::U64(1_700_000_600),
+                ScVal::U32(42),
+                ScVal::Bytes(vec![4; 32].try_into().unwrap()),
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn record_game_rejects_invalid_addresses_before_rpc() {
+        let (admin, rpc) = admin_with_mock();
+        let record = GameRecord {
+            white: ed25519::PublicKey([1; 32]).to_string(),
+            black: ed25519::PublicKey([2; 32]).to_string(),
+            winner: "not-an-address".to_string(),
+            nonce: 1,
+            timestamp: 1,
+            move_count: 1,
+            pgn_hash: [0; 32],
+        };
+
+        assert_eq!(
+            admin.record_game(&record).await,
+            Err(RegistryAdminError::InvalidAddress("not-an-address".to_string()))
+        );
+        assert!(rpc.simulated.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn config_requires_contract_and_secret_strkeys() {
+        let mut bad_contract = config();
+        bad_contract.contract_id = ed25519::PublicKey([9; 32]).to_string();
+        assert!(matches!(
+            RegistryAdmin::new(&bad_contract, Box::new(Arc::new(MockRpc::default()))),
+            Err(RegistryAdminError::Config(_))
+        ));
+
+        let mut bad_secret = config();
+        bad_secret.admin_secret_key = "SNOTASECRET".to_string();
+        assert!(matches!(
+            RegistryAdmin::new(&bad_secret, Box::new(Arc::new(MockRpc::default()))),
+            Err(RegistryAdminError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn canonical_game_id_is_reproducible() {
+        let white = ed25519::PublicKey([1; 32]).to_string();
+        let black = ed25519::PublicKey([2; 32]).to_string();
+
+        let id = canonical_game_id(&white, &black, 1_700_000_000_000).unwrap();
+        assert_eq!(id, canonical_game_id(&white, &black, 1_700_000_000_000).unwrap());
+        // Surrounding whitespace does not change the address
+        assert_eq!(id, canonical_game_id(&format!(" {}", white), &black, 1_700_000_000_000).unwrap());
+        assert_eq!(id.get_version_num(), 8);
+    }
+
+    #[test]
+    fn canonical_game_id_differs_for_different_games() {
+        let white = ed25519::PublicKey([1; 32]).to_string();
+        let black = ed25519::PublicKey([2; 32]).to_string();
+        let contract = stellar_strkey::Contract([1; 32]).to_string();
+
+        let id = canonical_game_id(&white, &black, 1).unwrap();
+        assert_ne!(id, canonical_game_id(&white, &black, 2).unwrap());
+        assert_ne!(id, canonical_game_id(&black, &white, 1).unwrap());
+        // Same key bytes, but an account and a contract are different addresses
+        assert_ne!(id, canonical_game_id(&contract, &black, 1).unwrap());
+        assert_eq!(
+            canonical_game_id("not-an-address", &black, 1),
+            Err(RegistryAdminError::InvalidAddress("not-an-address".to_string()))
+        );
+    }
+
+    #[test]
+    fn admin_address_matches_secret() {
+        let (admin, _) = admin_with_mock();
+        let expected = SigningKey::from_bytes(&[7; 32]).verifying_key().to_bytes();
+        assert_eq!(admin.admin_address(), ed25519::PublicKey(expected).to_string());
+    }
+}
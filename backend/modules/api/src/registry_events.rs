@@ -0,0 +1,653 @@
+//! Ingestion of `GameFinalized` events emitted by the `GameRegistry` contract.
+//!
+//! `GameFinalizedWorker` polls the contract's events, resolves which side won
+//! from the recorded on-chain result and finalizes the matching `game` row.
+//! The RPC's paging cursor is persisted, and `GameResultService::finalize_game`
+//! ignores games that already have a result, so events are applied at most once.
+
+use std::env;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chess::outcome::Outcome;
+use db_entity::game::ResultSide;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Deserialize;
+use serde_json::json;
+use service::game_results::{EventCursor, GameResultService};
+use stellar_xdr::curr::{
+    ContractDataDurability, LedgerEntryData, LedgerKey, LedgerKeyContractData, Limits, ReadXdr,
+    ScAddress, ScString, ScSymbol, ScVal, ScVec, WriteXdr,
+};
+use uuid::Uuid;
+
+use crate::registry::{HttpSorobanRpc, LedgerEntriesResult, RegistryAdminError};
+
+/// Topic symbol the registry publishes when a game result is recorded.
+pub const GAME_FINALIZED_TOPIC: &str = "GameFinalized";
+
+/// Maximum number of events requested per poll.
+pub const EVENT_PAGE_LIMIT: u32 = 100;
+
+/// A parsed `GameFinalized` event: topics `(GameFinalized, game_id)`, data
+/// `(winner, timestamp, pgn_hash)`. Events from before the registry recorded
+/// PGN hashes carry only `(winner, timestamp)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameFinalizedEvent {
+    pub ledger: u32,
+    pub game_id: String,
+    pub winner: ScAddress,
+    pub timestamp: u64,
+    /// SHA-256 of the game's PGN as recorded on-chain
+    pub pgn_hash: Option<[u8; 32]>,
+}
+
+/// The `GameResult` stored by the registry for a game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedGame {
+    pub winner: ScAddress,
+    pub white: ScAddress,
+    pub black: ScAddress,
+    pub timestamp: u64,
+}
+
+/// Parses a contract event into a `GameFinalizedEvent`, returning `None` for
+/// any other event shape.
+pub fn parse_game_finalized(ledger: u32, topics: &[ScVal], value: &ScVal) -> Option<GameFinalizedEvent> {
+    let [ScVal::Symbol(name), ScVal::String(game_id)] = topics else {
+        return None;
+    };
+    if name.0.as_slice() != GAME_FINALIZED_TOPIC.as_bytes() {
+        return None;
+    }
+    let ScVal::Vec(Some(data)) = value else {
+        return None;
+    };
+    let (winner, timestamp, pgn_hash) = match data.0.as_slice() {
+        [ScVal::Address(winner), ScVal::U64(timestamp)] => (winner, timestamp, None),
+        [ScVal::Address(winner), ScVal::U64(timestamp), ScVal::Bytes(hash)] => {
+            (winner, timestamp, Some(<[u8; 32]>::try_from(hash.0.as_slice()).ok()?))
+        }
+        _ => return None,
+    };
+
+    Some(GameFinalizedEvent {
+        ledger,
+        game_id: game_id.0.to_utf8_string().ok()?,
+        winner: winner.clone(),
+        timestamp: *timestamp,
+        pgn_hash,
+    })
+}
+
+/// Parses the registry's `GameResult` struct, stored as a symbol-keyed map.
+pub fn parse_recorded_game(value: &ScVal) -> Option<RecordedGame> {
+    let ScVal::Map(Some(map)) = value else {
+        return None;
+    };
+    let field = |name: &str| {
+        map.0.iter().find_map(|entry| match &entry.key {
+            ScVal::Symbol(key) if key.0.as_slice() == name.as_bytes() => Some(&entry.val),
+            _ => None,
+        })
+    };
+    let address = |name: &str| match field(name) {
+        Some(ScVal::Address(address)) => Some(address.clone()),
+        _ => None,
+    };
+
+    Some(RecordedGame {
+        winner: address("winner")?,
+        white: address("white")?,
+        black: address("black")?,
+        timestamp: match field("timestamp") {
+            Some(ScVal::U64(timestamp)) => *timestamp,
+            _ => return None,
+        },
+    })
+}
+
+/// Where a page of events starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventsFrom {
+    /// The first event in this ledger
+    Ledger(u32),
+    /// The event after the one an earlier page's cursor points at
+    Cursor(String),
+}
+
+/// A page of `GameFinalized` events, oldest first, with the RPC's cursor
+/// for the next page when it reported one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventPage {
+    pub events: Vec<GameFinalizedEvent>,
+    pub cursor: Option<String>,
+}
+
+/// Read access to the registry contract needed by `GameFinalizedWorker`.
+#[async_trait]
+pub trait RegistryEventSource: Send + Sync {
+    /// Up to `limit` `GameFinalized` events starting at `from`.
+    async fn game_finalized_events(&self, from: EventsFrom, limit: u32) -> Result<EventPage, RegistryAdminError>;
+
+    async fn recorded_game(&self, game_id: &str) -> Result<RecordedGame, RegistryAdminError>;
+}
+
+/// `RegistryEventSource` backed by Soroban RPC `getEvents` / `getLedgerEntries`.
+pub struct HttpRegistryEvents {
+    rpc: HttpSorobanRpc,
+    contract_id: String,
+    contract: ScAddress,
+}
+
+#[derive(Deserialize)]
+struct GetEventsResult {
+    events: Vec<RpcEvent>,
+    /// Points past the last event scanned; older nodes leave it out
+    cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RpcEvent {
+    /// Paging token of the event itself
+    id: String,
+    ledger: u32,
+    topic: Vec<String>,
+    value: String,
+}
+
+impl HttpRegistryEvents {
+    pub fn new(rpc_url: &str, contract_id: &str) -> Result<Self, RegistryAdminError> {
+        let contract = match contract_id.parse() {
+            Ok(contract @ ScAddress::Contract(_)) => contract,
+            _ => return Err(RegistryAdminError::Config("contract id must be a C... strkey".to_string())),
+        };
+        Ok(Self {
+            rpc: HttpSorobanRpc::new(rpc_url),
+            contract_id: contract_id.to_string(),
+            contract,
+        })
+    }
+}
+
+#[async_trait]
+impl RegistryEventSource for HttpRegistryEvents {
+    async fn game_finalized_events(&self, from: EventsFrom, limit: u32) -> Result<EventPage, RegistryAdminError> {
+        let topic = ScVal::Symbol(ScSymbol(GAME_FINALIZED_TOPIC.try_into()?));
+        let mut params = json!({
+            "filters": [{
+                "type": "contract",
+                "contractIds": [self.contract_id],
+                "topics": [[topic.to_xdr_base64(Limits::none())?, "*"]]
+            }],
+            "pagination": { "limit": limit }
+        });
+        // The RPC refuses a start ledger alongside a cursor
+        match from {
+            EventsFrom::Ledger(ledger) => params["startLedger"] = json!(ledger),
+            EventsFrom::Cursor(cursor) => params["pagination"]["cursor"] = json!(cursor),
+        }
+        let result: GetEventsResult = self.rpc.call("getEvents", params).await?;
+
+        // Without a cursor from the node, continue after the last event returned
+        let cursor = result.cursor.or_else(|| result.events.last().map(|event| event.id.clone()));
+        let mut events = Vec::with_capacity(result.events.len());
+        for event in result.events {
+            let topics = event
+                .topic
+                .iter()
+                .map(|topic| ScVal::from_xdr_base64(topic, Limits::none()))
+                .collect::<Result<Vec<_>, _>>()?;
+            let value = ScVal::from_xdr_base64(&event.value, Limits::none())?;
+            match parse_game_finalized(event.ledger, &topics, &value) {
+                Some(parsed) => events.push(parsed),
+                None => log::warn!("Skipping malformed GameFinalized event in ledger {}", event.ledger),
+            }
+        }
+        Ok(EventPage { events, cursor })
+    }
+
+    async fn recorded_game(&self, game_id: &str) -> Result<RecordedGame, RegistryAdminError> {
+        // Mirrors the contract's `DataKey::Game(game_id)`
+        let key = ScVal::Vec(Some(ScVec(
+            vec![
+                ScVal::Symbol(ScSymbol("Game".try_into()?)),
+                ScVal::String(ScString(game_id.try_into()?)),
+            ]
+            .try_into()?,
+        )));
+        let ledger_key = LedgerKey::ContractData(LedgerKeyContractData {
+            contract: self.contract.clone(),
+            key,
+            durability: ContractDataDurability::Persistent,
+        });
+        let result: LedgerEntriesResult = self
+            .rpc
+            .call("getLedgerEntries", json!({ "keys": [ledger_key.to_xdr_base64(Limits::none())?] }))
+            .await?;
+
+        let entry = result
+            .entries
+            .and_then(|entries| entries.into_iter().next())
+            .ok_or_else(|| RegistryAdminError::Rpc(format!("game {} is not recorded on chain", game_id)))?;
+        match LedgerEntryData::from_xdr_base64(entry.xdr, Limits::none())? {
+            LedgerEntryData::ContractData(data) => parse_recorded_game(&data.val)
+                .ok_or_else(|| RegistryAdminError::Xdr(format!("unexpected GameResult for {}", game_id))),
+            _ => Err(RegistryAdminError::Rpc(format!("unexpected ledger entry for game {}", game_id))),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WorkerError {
+    Source(RegistryAdminError),
+    Database(DbErr),
+}
+
+impl fmt::Display for WorkerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkerError::Source(e) => write!(f, "{}", e),
+            WorkerError::Database(e) => write!(f, "Database error {}", e),
+        }
+    }
+}
+
+impl From<RegistryAdminError> for WorkerError {
+    fn from(value: RegistryAdminError) -> Self {
+        Self::Source(value)
+    }
+}
+
+impl From<DbErr> for WorkerError {
+    fn from(value: DbErr) -> Self {
+        Self::Database(value)
+    }
+}
+
+/// Polling settings; the worker only starts when the contract id is configured.
+#[derive(Debug, Clone)]
+pub struct RegistryEventsConfig {
+    pub rpc_url: String,
+    pub contract_id: String,
+    /// Ledger to start from when no progress has been stored yet.
+    pub start_ledger: u32,
+    pub poll_interval: Duration,
+}
+
+impl RegistryEventsConfig {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            rpc_url: env::var("SOROBAN_RPC_URL").ok()?,
+            contract_id: env::var("GAME_REGISTRY_CONTRACT_ID").ok()?,
+            start_ledger: env::var("REGISTRY_EVENTS_START_LEDGER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1),
+            poll_interval: Duration::from_secs(
+                env::var("REGISTRY_EVENTS_POLL_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(15),
+            ),
+        })
+    }
+}
+
+pub struct GameFinalizedWorker {
+    source: Box<dyn RegistryEventSource>,
+    db: Arc<DatabaseConnection>,
+    cursor_key: String,
+    start_ledger: u32,
+}
+
+impl GameFinalizedWorker {
+    pub fn new(
+        source: Box<dyn RegistryEventSource>,
+        db: Arc<DatabaseConnection>,
+        contract_id: &str,
+        start_ledger: u32,
+    ) -> Self {
+        Self {
+            source,
+            db,
+            cursor_key: format!("game_registry:{}:{}", contract_id, GAME_FINALIZED_TOPIC),
+            start_ledger,
+        }
+    }
+
+    /// Fetches and applies one page of events. Returns how many games were finalized.
+    ///
+    /// Pages follow the RPC's cursor, which can end part-way through a ledger.
+    /// Streams stored before the RPC reported one resume from the ledger after
+    /// the last one processed.
+    pub async fn poll_once(&self) -> Result<usize, WorkerError> {
+        let cursor = GameResultService::event_cursor(&self.db, &self.cursor_key).await?;
+        let from = match &cursor {
+            Some(EventCursor { paging_token: Some(token), .. }) => EventsFrom::Cursor(token.clone()),
+            Some(cursor) => EventsFrom::Ledger(cursor.last_ledger + 1),
+            None => EventsFrom::Ledger(self.start_ledger.max(1)),
+        };
+        let page = self.source.game_finalized_events(from.clone(), EVENT_PAGE_LIMIT).await?;
+
+        // Only a start ledger can hand back events that were processed already
+        let processed = match (&from, &cursor) {
+            (EventsFrom::Ledger(_), Some(cursor)) => Some(cursor.last_ledger),
+            _ => None,
+        };
+        let mut applied = 0;
+        let mut last_ledger = cursor.as_ref().map(|cursor| cursor.last_ledger);
+        for event in page.events.iter().filter(|e| processed.is_none_or(|p| e.ledger > p)) {
+            if self.apply(event).await? {
+                applied += 1;
+            }
+            last_ledger = last_ledger.max(Some(event.ledger));
+        }
+
+        let paging_token = page
+            .cursor
+            .or_else(|| cursor.as_ref().and_then(|cursor| cursor.paging_token.clone()));
+        // A stream that has not seen an event yet still keeps the RPC's cursor
+        let last_ledger = match last_ledger {
+            Some(ledger) => ledger,
+            None if paging_token.is_some() => self.start_ledger.saturating_sub(1),
+            None => return Ok(applied),
+        };
+        let next = EventCursor { last_ledger, paging_token };
+        if cursor.as_ref() != Some(&next) {
+            GameResultService::set_event_cursor(&self.db, &self.cursor_key, &next).await?;
+        }
+
+        Ok(applied)
+    }
+
+    async fn apply(&self, event: &GameFinalizedEvent) -> Result<bool, WorkerError> {
+        let game_id = match Uuid::parse_str(&event.game_id) {
+            Ok(game_id) => game_id,
+            Err(_) => {
+                log::warn!("Ignoring GameFinalized for non-UUID game id {}", event.game_id);
+                return Ok(false);
+            }
+        };
+
+        let recorded = self.source.recorded_game(&event.game_id).await?;
+        let outcome = match Outcome::from_winner(&event.winner, &recorded.white, &recorded.black) {
+            Some(outcome) => outcome,
+            None => {
+                log::warn!("GameFinalized winner for {} played neither side", event.game_id);
+                return Ok(false);
+            }
+        };
+
+        Ok(GameResultService::finalize_game(&self.db, game_id, ResultSide::from(outcome)).await?)
+    }
+
+    /// Polls forever, logging failures and retrying on the next tick.
+    pub async fn run(self, poll_interval: Duration) {
+        let mut interval = actix_web::rt::time::interval(poll_interval);
+        loop {
+            interval.tick().await;
+            match self.poll_once().await {
+                Ok(0) => {}
+                Ok(applied) => log::info!("Finalized {} game(s) from registry events", applied),
+                Err(e) => log::warn!("Registry event ingestion failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, Utc};
+    use db_entity::{contract_event_cursor, game, player};
+    use sea_orm::{DbBackend, MockDatabase, MockExecResult};
+    use stellar_xdr::curr::{AccountId, PublicKey, ScBytes, ScMap, ScMapEntry, Uint256};
+
+    const CONTRACT_ID: &str = "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+
+    fn account(byte: u8) -> ScAddress {
+        ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([byte; 32]))))
+    }
+
+    fn symbol(name: &str) -> ScVal {
+        ScVal::Symbol(ScSymbol(name.try_into().unwrap()))
+    }
+
+    struct MockSource {
+        events: Vec<GameFinalizedEvent>,
+        cursor: Option<String>,
+        recorded: RecordedGame,
+        requests: std::sync::Mutex<Vec<EventsFrom>>,
+    }
+
+    #[async_trait]
+    impl RegistryEventSource for Arc<MockSource> {
+        async fn game_finalized_events(&self, from: EventsFrom, _limit: u32) -> Result<EventPage, RegistryAdminError> {
+            self.requests.lock().unwrap().push(from.clone());
+            // Deliberately ignores the start ledger, like a replaying RPC node,
+            // but nothing comes after the cursor it hands out
+            let events = match from {
+                EventsFrom::Cursor(cursor) if Some(&cursor) == self.cursor.as_ref() => Vec::new(),
+                _ => self.events.clone(),
+            };
+            Ok(EventPage {
+                events,
+                cursor: self.cursor.clone(),
+            })
+        }
+
+        async fn recorded_game(&self, _game_id: &str) -> Result<RecordedGame, RegistryAdminError> {
+            Ok(self.recorded.clone())
+        }
+    }
+
+    fn game_model(id: Uuid, white: Uuid, black: Uuid) -> game::Model {
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+        game::Model {
+            id,
+            white_player: white,
+            black_player: black,
+            fen: "fen".to_string(),
+            pgn: serde_json::json!({}),
+            result: None,
+            draw_reason: None,
+            variant: game::GameVariant::Standard,
+            started_at: now,
+            duration_sec: 600,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn player_model(id: Uuid) -> player::Model {
+        player::Model {
+            id,
+            username: id.to_string(),
+            email: format!("{}@example.com", id),
+            password_hash: Vec::new(),
+            biography: String::new(),
+            country: "NG".to_string(),
+            flair: String::new(),
+            real_name: String::new(),
+            location: None,
+            fide_rating: Some(1500),
+            social_links: None,
+            is_enabled: true,
+            email_verified: true,
+            blitz_rating: None,
+            rapid_rating: None,
+            classical_rating: None,
+            bullet_rating: None,
+        }
+    }
+
+    #[test]
+    fn parses_game_finalized_event() {
+        let game_id = Uuid::new_v4().to_string();
+        let topics = [symbol(GAME_FINALIZED_TOPIC), ScVal::String(ScString(game_id.as_str().try_into().unwrap()))];
+        let value = ScVal::Vec(Some(ScVec(
+            vec![ScVal::Address(account(1)), ScVal::U64(1_700_000_000)].try_into().unwrap(),
+        )));
+
+        assert_eq!(
+            parse_game_finalized(42, &topics, &value),
+            Some(GameFinalizedEvent {
+                ledger: 42,
+                game_id,
+                winner: account(1),
+                timestamp: 1_700_000_000,
+                pgn_hash: None,
+            })
+        );
+        assert_eq!(parse_game_finalized(42, &[symbol("GameStarted"), topics[1].clone()], &value), None);
+        assert_eq!(parse_game_finalized(42, &topics, &ScVal::U64(1)), None);
+    }
+
+    #[test]
+    fn parses_game_finalized_event_with_pgn_hash() {
+        let topics = [symbol(GAME_FINALIZED_TOPIC), ScVal::String(ScString("game-1".try_into().unwrap()))];
+        let event_with_hash = |hash: Vec<u8>| {
+            ScVal::Vec(Some(ScVec(
+                vec![ScVal::Address(account(1)), ScVal::U64(7), ScVal::Bytes(ScBytes(hash.try_into().unwrap()))]
+                    .try_into()
+                    .unwrap(),
+            )))
+        };
+
+        let parsed = parse_game_finalized(42, &topics, &event_with_hash(vec![0xab; 32])).unwrap();
+        assert_eq!(parsed.pgn_hash, Some([0xab; 32]));
+        assert_eq!(parse_game_finalized(42, &topics, &event_with_hash(vec![0xab; 31])), None);
+    }
+
+    #[test]
+    fn parses_recorded_game() {
+        let entry = |key: &str, val: ScVal| ScMapEntry { key: symbol(key), val };
+        let value = ScVal::Map(Some(ScMap(
+            vec![
+                entry("black", ScVal::Address(account(2))),
+                entry("timestamp", ScVal::U64(7)),
+                entry("white", ScVal::Address(account(1))),
+                entry("winner", ScVal::Address(account(2))),
+            ]
+            .try_into()
+            .unwrap(),
+        )));
+
+        assert_eq!(
+            parse_recorded_game(&value),
+            Some(RecordedGame {
+                winner: account(2),
+                white: account(1),
+                black: account(2),
+                timestamp: 7,
+            })
+        );
+    }
+
+    fn recorded_game() -> RecordedGame {
+        RecordedGame {
+            winner: account(2),
+            white: account(1),
+            black: account(2),
+            timestamp: 1_700_000_000,
+        }
+    }
+
+    #[actix_rt::test]
+    async fn pages_continue_from_the_rpc_cursor() {
+        const TOKEN: &str = "0000000429496733696-0000000001";
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+        let cursor_key = format!("game_registry:{}:{}", CONTRACT_ID, GAME_FINALIZED_TOPIC);
+
+        let db = MockDatabase::new(DbBackend::Postgres)
+            // First poll: nothing stored yet and no events
+            .append_query_results([Vec::<contract_event_cursor::Model>::new()])
+            // Second poll: the stored cursor
+            .append_query_results([vec![contract_event_cursor::Model {
+                source: cursor_key,
+                last_ledger: 99,
+                paging_token: Some(TOKEN.to_string()),
+                updated_at: now,
+            }]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            .into_connection();
+        let db = Arc::new(db);
+
+        let source = Arc::new(MockSource {
+            events: Vec::new(),
+            cursor: Some(TOKEN.to_string()),
+            recorded: recorded_game(),
+            requests: Default::default(),
+        });
+        let worker = GameFinalizedWorker::new(Box::new(source.clone()), db.clone(), CONTRACT_ID, 100);
+
+        assert_eq!(worker.poll_once().await.unwrap(), 0);
+        assert_eq!(worker.poll_once().await.unwrap(), 0);
+        // Neither repeats nor skips events within a ledger, however the pages fall
+        assert_eq!(
+            *source.requests.lock().unwrap(),
+            [EventsFrom::Ledger(100), EventsFrom::Cursor(TOKEN.to_string())]
+        );
+
+        drop(worker);
+        let log = format!("{:?}", Arc::try_unwrap(db).unwrap().into_transaction_log());
+        assert_eq!(log.matches(r#"INSERT INTO \"contract_event_cursor\""#).count(), 1);
+        assert!(log.contains(TOKEN));
+    }
+
+    #[actix_rt::test]
+    async fn replayed_event_updates_game_once() {
+        let (game_id, white, black) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+        let cursor_key = format!("game_registry:{}:{}", CONTRACT_ID, GAME_FINALIZED_TOPIC);
+
+        let db = MockDatabase::new(DbBackend::Postgres)
+            // First poll: no cursor yet, the game is still running
+            .append_query_results([Vec::<contract_event_cursor::Model>::new()])
+            .append_query_results([vec![game_model(game_id, white, black)]])
+            .append_query_results([vec![player_model(white), player_model(black)]])
+            // Second poll: the cursor has caught up with the event
+            .append_query_results([vec![contract_event_cursor::Model {
+                source: cursor_key,
+                last_ledger: 100,
+                paging_token: None,
+                updated_at: now,
+            }]])
+            .append_exec_results((0..5).map(|_| MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }))
+            .into_connection();
+        let db = Arc::new(db);
+
+        let source = MockSource {
+            events: vec![GameFinalizedEvent {
+                ledger: 100,
+                game_id: game_id.to_string(),
+                winner: account(2),
+                timestamp: 1_700_000_000,
+                pgn_hash: None,
+            }],
+            cursor: None,
+            recorded: recorded_game(),
+            requests: Default::default(),
+        };
+        let worker = GameFinalizedWorker::new(Box::new(Arc::new(source)), db.clone(), CONTRACT_ID, 1);
+
+        assert_eq!(worker.poll_once().await.unwrap(), 1);
+        assert_eq!(worker.poll_once().await.unwrap(), 0);
+
+        drop(worker);
+        let log = format!("{:?}", Arc::try_unwrap(db).unwrap().into_transaction_log());
+        assert_eq!(log.matches(r#"UPDATE \"smdb\".\"game\""#).count(), 1);
+        assert!(log.contains("black_wins"));
+        assert!(log.contains(r#"INSERT INTO \"contract_event_cursor\""#));
+        assert!(log.contains("BigInt(Some(100))"));
+    }
+}
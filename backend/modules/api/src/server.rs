@@ -1,21 +1,34 @@
 // src/server.rs
 
-use actix_web::{web, App, HttpResponse, HttpServer, Responder};
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use dto::responses::NotFoundResponse;
 use actix_cors::Cors;
 use dotenv::dotenv;
-use sea_orm::{Database, DatabaseConnection};
+use sea_orm::{ConnectOptions, Database};
+use service::accuracy::{EngineEvaluator, MaterialEvaluator, PositionEvaluator};
+use service::engine::{UciEngine, UciEngines, UciProcess};
 use std::env;
 use security::JwtService;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 use utoipa_redoc::{Redoc, Servable};
 use actix::Actor;
-use crate::players::{add_player, delete_player, find_player_by_id, update_player};
-use crate::games::{create_game, get_game, make_move, list_games, join_game, abandon_game};
-use crate::auth::{login, register}; // refresh_token, logout
-use crate::ai::{get_ai_suggestion, analyze_position};
+use crate::players::{active_games, add_player, current_player, delete_player, find_player_by_id, head_to_head, update_player};
+use crate::games::{create_game, get_game, get_games_batch, get_game_accuracy, get_game_summary, make_move, list_games, join_game, abandon_game, TOTAL_COUNT_HEADER};
+use crate::auth::{forgot_password, login, register, reset_password, verify_email}; // refresh_token, logout
+use crate::ai::{get_ai_suggestion, analyze_position, analyze_position_stream};
+use crate::analysis::{legal_moves, static_eval};
+use crate::pubsub::RedisPubSub;
 use crate::ws::{LobbyState, ws_route};
+use crate::metrics::{render_metrics, Metrics};
 use crate::config::AppConfig;
+use crate::accuracy::{AccuracyCache, DEFAULT_ACCURACY_CACHE_CAPACITY};
+use crate::idempotency::{IdempotencyBackend, IdempotencyStore, MemoryIdempotencyBackend, RedisIdempotencyBackend};
+use crate::lockout::{FailedLoginCounter, LoginLockout, MemoryFailedLoginCounter, RedisFailedLoginCounter};
+use crate::rate_limit::AllowlistKeyExtractor;
+use crate::registry::{HttpSorobanRpc, RegistryAdmin, RegistryAdminConfig};
+use crate::admin::{force_game_result, list_admin_audit, recompute_ratings, rotate_registry_server};
+use crate::registry_events::{GameFinalizedWorker, HttpRegistryEvents, RegistryEventsConfig};
 use actix_governor::{Governor, GovernorConfigBuilder};
 
 use crate::openapi::ApiDoc;
@@ -30,6 +43,46 @@ async fn greet() -> impl Responder {
     HttpResponse::Ok().json(serde_json::json!({"message": "Welcome to XLMate API"}))
 }
 
+/// Fallback for unmatched routes, returning the standard `NotFoundResponse` envelope
+pub async fn not_found(req: HttpRequest) -> HttpResponse {
+    HttpResponse::NotFound().json(NotFoundResponse {
+        error: format!("No route for {} {}", req.method(), req.path()),
+        code: 404,
+    })
+}
+
+/// Builds the CORS middleware from `AppConfig` and the comma-separated `ALLOWED_ORIGINS` list.
+/// Falls back to allowing any origin in development, in which case credentials stay disabled.
+pub fn build_cors(config: &AppConfig, allowed_origins: Option<&str>) -> Cors {
+    let mut cors = Cors::default()
+        .allow_any_method()
+        .allow_any_header()
+        // Paging controls read the total from this header
+        .expose_headers([TOTAL_COUNT_HEADER])
+        .max_age(config.cors_max_age);
+
+    // Get allowed origins from environment variable, fallback to all origins in development
+    if let Some(allowed_origins) = allowed_origins {
+        // Parse comma-separated list of allowed origins
+        for origin in allowed_origins.split(',').map(str::trim) {
+            cors = match origin {
+                "" => cors,
+                "*" => cors.allow_any_origin(),
+                origin => cors.allowed_origin(origin),
+            };
+        }
+    } else {
+        // In development, allow all origins by default
+        cors = cors.allow_any_origin();
+    }
+
+    if config.cors_allow_credentials && config.cors_misconfiguration(allowed_origins).is_none() {
+        cors = cors.supports_credentials();
+    }
+
+    cors
+}
+
 /// Main server initialization function
 pub async fn main() -> std::io::Result<()> {
     let openapi = ApiDoc::openapi();
@@ -41,20 +94,39 @@ pub async fn main() -> std::io::Result<()> {
     env_logger::init();
 
     // Load configuration from environment
-    let server_addr = env::var("SERVER_ADDR").unwrap_or_else(|_| "127.0.0.1:8080".to_string());
+    let config = AppConfig::from_env();
+    if let Some(warning) = config.cors_misconfiguration(config.cors_allowed_origins.as_deref()) {
+        log::warn!("{}", warning);
+    }
+    if let Some(problem) = config.ws_heartbeat_misconfiguration() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, problem));
+    }
+    let server_addr = config.server_addr.clone();
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env");
     let jwt_secret = env::var("JWT_SECRET_KEY")
         .unwrap_or_else(|_| "xlmate_dev_secret_key_change_in_production".to_string());
-    let jwt_expiration = env::var("JWT_EXPIRATION_SECS")
+    // JWT_EXPIRATION_SECS is the older name of the access token lifetime
+    let jwt_expiration = env::var("JWT_ACCESS_EXPIRATION_SECS")
+        .or_else(|_| env::var("JWT_EXPIRATION_SECS"))
         .unwrap_or_else(|_| "3600".to_string())
         .parse::<usize>()
         .unwrap_or(3600);
+    let jwt_refresh_expiration = env::var("JWT_REFRESH_EXPIRATION_SECS")
+        .ok()
+        .and_then(|secs| secs.parse::<usize>().ok())
+        .unwrap_or(security::jwt::DEFAULT_REFRESH_EXPIRATION_SECS);
 
     eprintln!("Initializing XLMate Backend Server");
     eprintln!("Server address: {}", server_addr);
 
     // Connect to database
-    let db = match Database::connect(&database_url).await {
+    eprintln!(
+        "Database pool size: min {} / max {} connections",
+        config.db_pool.min_connections, config.db_pool.max_connections
+    );
+    let mut connect_options = ConnectOptions::new(database_url);
+    config.db_pool.apply(&mut connect_options);
+    let db = match Database::connect(connect_options).await {
         Ok(conn) => {
             eprintln!("Database connection successful");
             conn
@@ -69,16 +141,124 @@ pub async fn main() -> std::io::Result<()> {
     };
 
     // Initialize JWT service
-    let jwt_service = JwtService::new(jwt_secret.clone(), jwt_expiration);
+    let jwt_service = JwtService::new(jwt_secret.clone(), jwt_expiration)
+        .with_refresh_expiration(jwt_refresh_expiration);
     let db = std::sync::Arc::new(db); // Wrap db in Arc
 
-    // Create a shared LobbyState actor
-    let lobby = LobbyState::new().start();
+    // Counters served on /metrics; the socket gateway exports its counters to Redis
+    let metrics = match env::var("REDIS_URL").ok().map(|url| Metrics::with_gateway(&url)) {
+        Some(Ok(metrics)) => metrics,
+        Some(Err(e)) => {
+            log::warn!("Gateway metrics disabled: {}", e);
+            Metrics::default()
+        }
+        None => Metrics::default(),
+    };
+    let metrics = web::Data::new(metrics);
 
-    // Load AppConfig
-    let config = AppConfig::from_env();
+    // Create a shared LobbyState actor; with REDIS_URL set, broadcasts are
+    // relayed through Redis so players on other instances receive them too
+    let lobby = match env::var("REDIS_URL").ok().map(|url| RedisPubSub::new(&url)) {
+        Some(Ok(pubsub)) => LobbyState::with_pubsub(std::sync::Arc::new(pubsub)),
+        Some(Err(e)) => {
+            log::warn!("Lobby pub/sub disabled: {}", e);
+            LobbyState::new()
+        }
+        None => LobbyState::new(),
+    }
+    .with_metrics(metrics.clone().into_inner())
+    .start();
+
+    // Idempotency keys live in Redis when available so retries reaching another instance are deduplicated
+    let idempotency_backend: Box<dyn IdempotencyBackend> =
+        match env::var("REDIS_URL").ok().map(|url| RedisIdempotencyBackend::new(&url)) {
+            Some(Ok(backend)) => Box::new(backend),
+            Some(Err(e)) => {
+                log::warn!("Idempotency keys fall back to a per-instance store: {}", e);
+                Box::new(MemoryIdempotencyBackend::default())
+            }
+            None => Box::new(MemoryIdempotencyBackend::default()),
+        };
+    let idempotency_store = web::Data::new(IdempotencyStore::with_backend(
+        idempotency_backend,
+        std::time::Duration::from_secs(config.idempotency_ttl_secs),
+    ));
+
+    // Streaming analysis runs UCI engine binaries; without one it answers 503.
+    // UCI_ENGINES names several, UCI_ENGINE_PATH a single "default" one
+    let uci_engine: Option<web::Data<UciEngines>> = env::var("UCI_ENGINES")
+        .ok()
+        .and_then(|spec| UciEngines::from_spec(&spec))
+        .or_else(|| {
+            env::var("UCI_ENGINE_PATH").ok().map(|path| {
+                UciEngines::new("default", std::sync::Arc::new(UciProcess::new(path)) as std::sync::Arc<dyn UciEngine>)
+            })
+        })
+        .map(web::Data::new);
+
+    // Accuracy is computed once per game position count and shared by all workers.
+    // Positions are scored by the default engine, or by counting material without one
+    let accuracy_cache = web::Data::new(AccuracyCache::new(DEFAULT_ACCURACY_CACHE_CAPACITY));
+    let position_evaluator: std::sync::Arc<dyn PositionEvaluator> =
+        match uci_engine.as_ref().and_then(|engines| engines.get(None)) {
+            Some(engine) => std::sync::Arc::new(EngineEvaluator::new(engine.clone())),
+            None => std::sync::Arc::new(MaterialEvaluator::default()),
+        };
+    let position_evaluator: web::Data<dyn PositionEvaluator> = web::Data::from(position_evaluator);
+
+    // Failed login counters live in Redis when available so a lockout holds across instances
+    let failed_logins: Box<dyn FailedLoginCounter> =
+        match env::var("REDIS_URL").ok().map(|url| RedisFailedLoginCounter::new(&url)) {
+            Some(Ok(counter)) => Box::new(counter),
+            Some(Err(e)) => {
+                log::warn!("Login lockout falls back to per-instance counters: {}", e);
+                Box::new(MemoryFailedLoginCounter::default())
+            }
+            None => Box::new(MemoryFailedLoginCounter::default()),
+        };
+    let login_lockout = web::Data::new(LoginLockout::new(
+        failed_logins,
+        config.login_max_failures,
+        std::time::Duration::from_secs(config.login_lockout_secs),
+    ));
+
+    // Registry administration is optional; without its settings the admin endpoint answers 503
+    let registry_admin = match RegistryAdminConfig::from_env() {
+        Some(registry_config) => {
+            let rpc = Box::new(HttpSorobanRpc::new(registry_config.rpc_url.clone()));
+            match RegistryAdmin::new(&registry_config, rpc) {
+                Ok(admin) => {
+                    eprintln!("Registry admin account: {}", admin.admin_address());
+                    Some(web::Data::new(admin))
+                }
+                Err(e) => {
+                    log::warn!("Registry administration disabled: {}", e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Keep game results in sync with results recorded on chain
+    if let Some(events_config) = RegistryEventsConfig::from_env() {
+        match HttpRegistryEvents::new(&events_config.rpc_url, &events_config.contract_id) {
+            Ok(source) => {
+                let worker = GameFinalizedWorker::new(
+                    Box::new(source),
+                    db.clone(),
+                    &events_config.contract_id,
+                    events_config.start_ledger,
+                );
+                actix_web::rt::spawn(worker.run(events_config.poll_interval));
+                eprintln!("GameFinalized ingestion enabled");
+            }
+            Err(e) => log::warn!("GameFinalized ingestion disabled: {}", e),
+        }
+    }
 
     eprintln!("Starting HTTP server on {}", server_addr);
+    let workers = config.workers;
 
     // Define the app factory closure
     let app_factory = move || {
@@ -87,30 +267,11 @@ pub async fn main() -> std::io::Result<()> {
         let jwt_secret = jwt_secret.clone();
         
         // Configure CORS middleware with environment variables for flexibility
-        let cors = {
-            let mut cors = Cors::default()
-                .allow_any_method()
-                .allow_any_header()
-                .max_age(3600);
-            
-            // Get allowed origins from environment variable, fallback to all origins in development
-            if let Ok(allowed_origins) = env::var("ALLOWED_ORIGINS") {
-                // Parse comma-separated list of allowed origins
-                let origins: Vec<&str> = allowed_origins.split(',').collect();
-                for origin in origins {
-                    cors = cors.allowed_origin(origin.trim());
-                }
-                // We don't print here to avoid spamming logs on every worker start
-            } else {
-                // In development, allow all origins by default
-                cors = cors.allow_any_origin();
-            }
-            
-            cors
-        };
+        let cors = build_cors(&config, config.cors_allowed_origins.as_deref());
         
         // Configure Governor for Auth (Strict)
         let auth_governor_conf = GovernorConfigBuilder::default()
+            .key_extractor(AllowlistKeyExtractor::new(config.rate_limit_allowlist.clone()))
             .per_second(config.auth_rate_limit_per_sec)
             .burst_size(config.auth_rate_limit_burst)
             .use_headers()
@@ -119,29 +280,41 @@ pub async fn main() -> std::io::Result<()> {
 
         // Configure Governor for Games/General (Loose)
         let game_governor_conf = GovernorConfigBuilder::default()
+            .key_extractor(AllowlistKeyExtractor::new(config.rate_limit_allowlist.clone()))
             .per_second(config.game_rate_limit_per_sec)
             .burst_size(config.game_rate_limit_burst)
             .use_headers()
             .finish()
             .unwrap();
 
-        App::new()
+        let mut app = App::new()
             // Global middleware
             .wrap(cors)
             // App data
             .app_data(web::Data::from(db.clone()))
             .app_data(web::Data::new(jwt_service.clone()))
             .app_data(web::Data::new(lobby.clone()))
+            .app_data(idempotency_store.clone())
+            .app_data(login_lockout.clone())
+            .app_data(accuracy_cache.clone())
+            .app_data(position_evaluator.clone())
+            .app_data(web::Data::new(config.clone()))
+            .app_data(metrics.clone())
             // WebSocket route mounting
             .route("/ws/{game_id}", web::get().to(ws_route))
             // Register your routes
             .route("/health", web::get().to(health))
+            .route("/metrics", web::get().to(render_metrics))
             .route("/", web::get().to(greet))
             // Player routes
             .service(
                 web::scope("/v1/players")
                     .service(add_player)
+                    // Registered before /{id} so "me" is not parsed as a player id
+                    .service(current_player)
                     .service(find_player_by_id)
+                    .service(head_to_head)
+                    .service(active_games)
                     .service(update_player)
                     .service(delete_player),
             )
@@ -150,11 +323,15 @@ pub async fn main() -> std::io::Result<()> {
                 web::scope("/v1/games")
                     .wrap(Governor::new(&game_governor_conf))
                     .service(create_game)
+                    .service(get_games_batch)
                     .service(get_game)
+                    .service(get_game_accuracy)
+                    .service(get_game_summary)
                     .service(list_games)
                     .service(join_game)
                     .service(make_move)
-                    .service(abandon_game),
+                    .service(abandon_game)
+                    .service(force_game_result),
             )
             // Auth routes
             .service(
@@ -162,6 +339,9 @@ pub async fn main() -> std::io::Result<()> {
                     .wrap(Governor::new(&auth_governor_conf))
                     .service(login)
                     .service(register)
+                    .service(verify_email)
+                    .service(forgot_password)
+                    .service(reset_password)
                     // Protected route with JWT authentication
                     // Note: main uses JwtService, but we stick to JwtAuthMiddleware for route protection
                     // as it was working in our feature.
@@ -186,8 +366,19 @@ pub async fn main() -> std::io::Result<()> {
             .service(
                 web::scope("/v1/ai")
                     .service(get_ai_suggestion)
+                    .service(analyze_position_stream)
                     .service(analyze_position),
             )
+            // Analysis routes
+            .service(web::scope("/v1/analysis").service(legal_moves).service(static_eval))
+            // Admin routes
+            .service(
+                web::scope("/v1/admin")
+                    .wrap(Governor::new(&auth_governor_conf))
+                    .service(rotate_registry_server)
+                    .service(recompute_ratings)
+                    .service(list_admin_audit),
+            )
             // Swagger UI integration
             .service(
                 SwaggerUi::new("/api/docs/{_:.*}")
@@ -204,15 +395,21 @@ pub async fn main() -> std::io::Result<()> {
                     .content_type("text/markdown")
                     .body(crate::openapi::websocket_documentation())
             }))
+            // JSON 404 for any unmatched route or method
+            .default_service(web::route().to(not_found));
+
+        if let Some(registry_admin) = registry_admin.clone() {
+            app = app.app_data(registry_admin);
+        }
+        if let Some(uci_engine) = uci_engine.clone() {
+            app = app.app_data(uci_engine);
+        }
+        app
     };
 
     let mut server = HttpServer::new(app_factory).bind(&server_addr)?;
-
-    if let Ok(workers_str) = env::var("WORKERS") {
-        if let Ok(workers) = workers_str.parse::<usize>() {
-            println!("Setting worker count to {}", workers);
-            server = server.workers(workers);
-        }
+    if let Some(workers) = workers {
+        server = server.workers(workers);
     }
 
     server.run().await
@@ -0,0 +1,147 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use actix_web::{test, web, App};
+use chess::bitboard::position::Position;
+use chrono::{FixedOffset, Utc};
+use db_entity::game::{self, GameVariant, ResultSide};
+use sea_orm::{DatabaseConnection, DbBackend, MockDatabase};
+use serde_json::{json, Value};
+use service::accuracy::PositionEvaluator;
+use uuid::Uuid;
+
+use crate::accuracy::AccuracyCache;
+use crate::config::AppConfig;
+use crate::games::get_game_accuracy;
+
+/// Engine stand-in that rates every position equal, so every move is the best move
+#[derive(Default)]
+struct BestMovesEngine {
+    evaluations: AtomicUsize,
+}
+
+impl PositionEvaluator for BestMovesEngine {
+    fn evaluate(&self, _: &Position) -> i32 {
+        self.evaluations.fetch_add(1, Ordering::SeqCst);
+        25
+    }
+}
+
+fn finished_game(id: Uuid, moves: &[&str]) -> game::Model {
+    let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+    game::Model {
+        id,
+        white_player: Uuid::new_v4(),
+        black_player: Uuid::new_v4(),
+        fen: String::new(),
+        pgn: json!({ "moves": moves }),
+        result: Some(ResultSide::Draw),
+        draw_reason: None,
+        variant: GameVariant::Standard,
+        started_at: now,
+        duration_sec: 600,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+async fn get_accuracy(db: DatabaseConnection, engine: Arc<BestMovesEngine>, game_id: Uuid, requests: usize) -> Vec<(u16, Value)> {
+    get_accuracy_with_limit(db, engine, game_id, requests, 500).await
+}
+
+async fn get_accuracy_with_limit(
+    db: DatabaseConnection,
+    engine: Arc<BestMovesEngine>,
+    game_id: Uuid,
+    requests: usize,
+    max_replay_plies: usize,
+) -> Vec<(u16, Value)> {
+    let config = AppConfig {
+        max_replay_plies,
+        ..AppConfig::from_env()
+    };
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db))
+            .app_data(web::Data::from(engine as Arc<dyn PositionEvaluator>))
+            .app_data(web::Data::new(AccuracyCache::new(8)))
+            .app_data(web::Data::new(config))
+            .service(web::scope("/v1/games").service(get_game_accuracy)),
+    )
+    .await;
+
+    let mut responses = Vec::new();
+    for _ in 0..requests {
+        let req = test::TestRequest::get()
+            .uri(&format!("/v1/games/{}/accuracy", game_id))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let status = resp.status().as_u16();
+        responses.push((status, test::read_body_json(resp).await));
+    }
+    responses
+}
+
+#[actix_web::test]
+async fn test_best_moves_yield_full_accuracy_and_are_cached() {
+    let game_id = Uuid::new_v4();
+    let game = finished_game(game_id, &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4"]);
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![game.clone()], vec![game]])
+        .into_connection();
+    let engine = Arc::new(BestMovesEngine::default());
+
+    let responses = get_accuracy(db, engine.clone(), game_id, 2).await;
+    let (status, body) = &responses[0];
+    assert_eq!(*status, 200);
+    assert_eq!(body["game_id"], game_id.to_string());
+    assert!(body["white_accuracy"].as_f64().unwrap() > 99.9);
+    assert!(body["black_accuracy"].as_f64().unwrap() > 99.9);
+    assert_eq!(body["moves_analyzed"], 5);
+
+    // The second request is answered from the cache without re-evaluating
+    assert_eq!(responses[1], responses[0]);
+    assert_eq!(engine.evaluations.load(Ordering::SeqCst), 6);
+}
+
+#[actix_web::test]
+async fn test_accuracy_of_unknown_game_is_not_found() {
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([Vec::<game::Model>::new()])
+        .into_connection();
+    let responses = get_accuracy(db, Arc::new(BestMovesEngine::default()), Uuid::new_v4(), 1).await;
+    assert_eq!(responses[0].0, 404);
+}
+
+#[actix_web::test]
+async fn test_games_longer_than_the_replay_limit_are_refused() {
+    let game_id = Uuid::new_v4();
+    let game = finished_game(game_id, &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4"]);
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![game]])
+        .into_connection();
+    let engine = Arc::new(BestMovesEngine::default());
+
+    let responses = get_accuracy_with_limit(db, engine.clone(), game_id, 1, 4).await;
+    let (status, body) = &responses[0];
+    assert_eq!(*status, 422);
+    assert_eq!(body["error"], "Game has 5 moves; at most 4 can be replayed");
+    // Nothing was replayed
+    assert_eq!(engine.evaluations.load(Ordering::SeqCst), 0);
+}
+
+#[actix_web::test]
+async fn test_replay_stops_at_the_first_illegal_move() {
+    let game_id = Uuid::new_v4();
+    // Black's knight cannot reach e4, and nothing after it is looked at
+    let game = finished_game(game_id, &["e2e4", "e7e5", "g1f3", "b8e4", "not-a-move"]);
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![game]])
+        .into_connection();
+
+    let responses = get_accuracy(db, Arc::new(BestMovesEngine::default()), game_id, 1).await;
+    let (status, body) = &responses[0];
+    assert_eq!(*status, 422);
+    assert_eq!(body["code"], 422);
+    assert_eq!(body["error"], "Illegal move b8e4 at ply 3");
+}
@@ -0,0 +1,350 @@
+use actix_web::{test, web, App};
+use async_trait::async_trait;
+use chrono::{FixedOffset, Utc};
+use db_entity::{admin_audit, game_rating_change};
+use db_entity::game::{self, GameVariant, ResultSide};
+use sea_orm::{DatabaseConnection, DbBackend, MockDatabase, MockExecResult, Value as DbValue};
+use security::{AdminPlayers, JwtService};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use stellar_xdr::curr::{AccountId, SorobanTransactionData, TransactionEnvelope};
+use uuid::Uuid;
+
+use crate::admin::{force_game_result, list_admin_audit, recompute_ratings, rotate_registry_server};
+use crate::config::AppConfig;
+use crate::registry::{
+    RegistryAdmin, RegistryAdminConfig, RegistryAdminError, SimulatedTransaction, SorobanRpc,
+};
+
+const ADMIN_ID: Uuid = Uuid::from_u128(0x8a3c2f9e_51b4_4c57_9d0e_3f2b6a7c1d04);
+
+struct AcceptingRpc;
+
+#[async_trait]
+impl SorobanRpc for AcceptingRpc {
+    async fn get_sequence(&self, _account: &AccountId) -> Result<i64, RegistryAdminError> {
+        Ok(1)
+    }
+
+    async fn simulate_transaction(
+        &self,
+        _envelope: &TransactionEnvelope,
+    ) -> Result<SimulatedTransaction, RegistryAdminError> {
+        Ok(SimulatedTransaction {
+            transaction_data: SorobanTransactionData::default(),
+            auth: Vec::new(),
+            min_resource_fee: 100,
+        })
+    }
+
+    async fn send_transaction(&self, _envelope: &TransactionEnvelope) -> Result<String, RegistryAdminError> {
+        Ok("deadbeef".to_string())
+    }
+}
+
+fn registry_admin() -> RegistryAdmin {
+    let config = RegistryAdminConfig {
+        rpc_url: "http://localhost:8000/soroban/rpc".to_string(),
+        network_passphrase: "Test SDF Network ; September 2015".to_string(),
+        contract_id: stellar_strkey::Contract([9; 32]).to_string(),
+        admin_secret_key: stellar_strkey::ed25519::PrivateKey([7; 32]).to_string(),
+    };
+    RegistryAdmin::new(&config, Box::new(AcceptingRpc)).unwrap()
+}
+
+fn rotate_request(token: &str, new_server: &str) -> actix_web::test::TestRequest {
+    test::TestRequest::post()
+        .uri("/v1/admin/registry/server")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "new_server": new_server }))
+}
+
+fn empty_db() -> Arc<DatabaseConnection> {
+    Arc::new(MockDatabase::new(DbBackend::Postgres).into_connection())
+}
+
+macro_rules! admin_app {
+    ($jwt_service:expr, $registry:expr) => {
+        admin_app!($jwt_service, $registry, empty_db())
+    };
+    ($jwt_service:expr, $registry:expr, $db:expr) => {{
+        let mut app = App::new()
+            .app_data(web::Data::new($jwt_service))
+            .app_data(web::Data::new(AppConfig {
+                admin_players: AdminPlayers(vec![ADMIN_ID]),
+                ..AppConfig::from_env()
+            }))
+            .app_data(web::Data::from($db))
+            .service(
+                web::scope("/v1/admin")
+                    .service(rotate_registry_server)
+                    .service(list_admin_audit),
+            );
+        if let Some(registry) = $registry {
+            app = app.app_data(web::Data::new(registry));
+        }
+        test::init_service(app).await
+    }};
+}
+
+#[actix_web::test]
+async fn test_admin_rotates_registry_server() {
+    let jwt_service = JwtService::new("test_secret".to_string(), 3600);
+    let token = jwt_service.generate_player_token(1, ADMIN_ID, "operator").unwrap();
+    let db = Arc::new(
+        MockDatabase::new(DbBackend::Postgres)
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            .into_connection(),
+    );
+    let app = admin_app!(jwt_service, Some(registry_admin()), db.clone());
+
+    let new_server = stellar_strkey::ed25519::PublicKey([3; 32]).to_string();
+    let resp = test::call_service(&app, rotate_request(&token, &new_server).to_request()).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["tx_hash"], "deadbeef");
+
+    drop(app);
+    let log = format!("{:?}", Arc::try_unwrap(db).unwrap().into_transaction_log());
+    assert_eq!(log.matches(r#"INSERT INTO \"smdb\".\"admin_audit\""#).count(), 1);
+    assert!(log.contains("set_server"));
+    assert!(log.contains(&ADMIN_ID.to_string()));
+    assert!(log.contains(&new_server));
+    assert!(log.contains("deadbeef"));
+}
+
+fn audit_entry(action: &str) -> admin_audit::Model {
+    admin_audit::Model {
+        id: Uuid::new_v4(),
+        actor_id: ADMIN_ID,
+        action: action.to_string(),
+        target: stellar_strkey::ed25519::PublicKey([3; 32]).to_string(),
+        tx_hash: "deadbeef".to_string(),
+        created_at: Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap()),
+    }
+}
+
+#[actix_web::test]
+async fn test_admin_lists_audit_log() {
+    let jwt_service = JwtService::new("test_secret".to_string(), 3600);
+    let token = jwt_service.generate_player_token(1, ADMIN_ID, "operator").unwrap();
+    let db = Arc::new(
+        MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![audit_entry("set_server")]])
+            .append_query_results([vec![BTreeMap::from([("num_items", DbValue::BigInt(Some(3)))])]])
+            .into_connection(),
+    );
+    let app = admin_app!(jwt_service, None::<RegistryAdmin>, db);
+
+    let req = test::TestRequest::get()
+        .uri("/v1/admin/audit?limit=1&offset=2")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["total"], 3);
+    assert_eq!(body["entries"][0]["action"], "set_server");
+    assert_eq!(body["entries"][0]["tx_hash"], "deadbeef");
+}
+
+#[actix_web::test]
+async fn test_non_admin_cannot_list_audit_log() {
+    let jwt_service = JwtService::new("test_secret".to_string(), 3600);
+    let token = jwt_service.generate_player_token(1, Uuid::new_v4(), "player").unwrap();
+    let app = admin_app!(jwt_service, None::<RegistryAdmin>);
+
+    let req = test::TestRequest::get()
+        .uri("/v1/admin/audit")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+}
+
+#[actix_web::test]
+async fn test_invalid_server_address_is_rejected() {
+    let jwt_service = JwtService::new("test_secret".to_string(), 3600);
+    let token = jwt_service.generate_player_token(1, ADMIN_ID, "operator").unwrap();
+    let app = admin_app!(jwt_service, Some(registry_admin()));
+
+    let resp = test::call_service(&app, rotate_request(&token, "GNOTANADDRESS").to_request()).await;
+    assert_eq!(resp.status(), 400);
+    let body: Value = test::read_body_json(resp).await;
+    assert!(body["error"].as_str().unwrap().contains("GNOTANADDRESS"));
+}
+
+#[actix_web::test]
+async fn test_non_admin_is_forbidden() {
+    let jwt_service = JwtService::new("test_secret".to_string(), 3600);
+    let token = jwt_service.generate_player_token(1, Uuid::new_v4(), "player").unwrap();
+    // Tokens without a player id only carry the placeholder integer id
+    let anonymous_token = jwt_service.generate_token(1, "operator").unwrap();
+    let app = admin_app!(jwt_service, Some(registry_admin()));
+
+    let new_server = stellar_strkey::ed25519::PublicKey([3; 32]).to_string();
+    let resp = test::call_service(&app, rotate_request(&token, &new_server).to_request()).await;
+    assert_eq!(resp.status(), 403);
+    let resp = test::call_service(&app, rotate_request(&anonymous_token, &new_server).to_request()).await;
+    assert_eq!(resp.status(), 403);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/v1/admin/registry/server")
+            .set_json(json!({ "new_server": new_server }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 401);
+}
+
+#[actix_web::test]
+async fn test_unconfigured_registry_returns_503() {
+    let jwt_service = JwtService::new("test_secret".to_string(), 3600);
+    let token = jwt_service.generate_player_token(1, ADMIN_ID, "operator").unwrap();
+    let app = admin_app!(jwt_service, None::<RegistryAdmin>);
+
+    let new_server = stellar_strkey::ed25519::PublicKey([3; 32]).to_string();
+    let resp = test::call_service(&app, rotate_request(&token, &new_server).to_request()).await;
+    assert_eq!(resp.status(), 503);
+}
+
+fn finished_game(id: Uuid) -> game::Model {
+    let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+    game::Model {
+        id,
+        white_player: Uuid::new_v4(),
+        black_player: Uuid::new_v4(),
+        fen: "fen".to_string(),
+        pgn: json!({}),
+        result: Some(ResultSide::WhiteWins),
+        draw_reason: None,
+        variant: GameVariant::Standard,
+        started_at: now,
+        duration_sec: 600,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+macro_rules! games_app {
+    ($jwt_service:expr, $db:expr) => {{
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new($jwt_service))
+                .app_data(web::Data::new(AppConfig {
+                    admin_players: AdminPlayers(vec![ADMIN_ID]),
+                    ..AppConfig::from_env()
+                }))
+                .app_data(web::Data::new($db))
+                .service(web::scope("/v1/games").service(force_game_result)),
+        )
+        .await
+    }};
+}
+
+fn force_result_request(token: &str, game_id: Uuid) -> actix_web::test::TestRequest {
+    test::TestRequest::post()
+        .uri(&format!("/v1/games/{}/result", game_id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "result": "draw", "reason": "Server outage during the game" }))
+}
+
+#[actix_web::test]
+async fn test_admin_forces_game_result() {
+    let jwt_service = JwtService::new("test_secret".to_string(), 3600);
+    let token = jwt_service.generate_player_token(1, ADMIN_ID, "operator").unwrap();
+    let game_id = Uuid::new_v4();
+    let db: DatabaseConnection = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![finished_game(game_id)]])
+        .append_exec_results((0..2).map(|_| MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        }))
+        .into_connection();
+    let app = games_app!(jwt_service, db);
+
+    let resp = test::call_service(&app, force_result_request(&token, game_id).to_request()).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["game_id"], game_id.to_string());
+    assert_eq!(body["result"], "draw");
+}
+
+#[actix_web::test]
+async fn test_non_admin_cannot_force_game_result() {
+    let jwt_service = JwtService::new("test_secret".to_string(), 3600);
+    let token = jwt_service.generate_player_token(1, Uuid::new_v4(), "player").unwrap();
+    // No mocked results: the handler must reject before touching the database
+    let db: DatabaseConnection = MockDatabase::new(DbBackend::Postgres).into_connection();
+    let app = games_app!(jwt_service, db);
+
+    let resp = test::call_service(&app, force_result_request(&token, Uuid::new_v4()).to_request()).await;
+    assert_eq!(resp.status(), 403);
+}
+
+macro_rules! ratings_app {
+    ($jwt_service:expr, $db:expr) => {{
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new($jwt_service))
+                .app_data(web::Data::new(AppConfig {
+                    admin_players: AdminPlayers(vec![ADMIN_ID]),
+                    ..AppConfig::from_env()
+                }))
+                .app_data(web::Data::new($db))
+                .service(web::scope("/v1/admin").service(recompute_ratings)),
+        )
+        .await
+    }};
+}
+
+fn recompute_request(token: &str, from: &str, to: &str) -> actix_web::test::TestRequest {
+    test::TestRequest::post()
+        .uri("/v1/admin/ratings/recompute")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "from": from, "to": to }))
+}
+
+#[actix_web::test]
+async fn test_admin_recomputes_ratings() {
+    let jwt_service = JwtService::new("test_secret".to_string(), 3600);
+    let token = jwt_service.generate_player_token(1, ADMIN_ID, "operator").unwrap();
+    let db: DatabaseConnection = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![finished_game(Uuid::new_v4())]])
+        // No earlier rating snapshots
+        .append_query_results([Vec::<game_rating_change::Model>::new()])
+        // The game's snapshots, then both players' ratings
+        .append_exec_results((0..3).map(|_| MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        }))
+        .into_connection();
+    let app = ratings_app!(jwt_service, db);
+
+    let req = recompute_request(&token, "2026-01-01T00:00:00Z", "2026-02-01T00:00:00Z");
+    let resp = test::call_service(&app, req.to_request()).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["games_replayed"], 1);
+    assert_eq!(body["ratings_updated"], 2);
+}
+
+#[actix_web::test]
+async fn test_recompute_ratings_requires_admin_and_a_valid_range() {
+    let jwt_service = JwtService::new("test_secret".to_string(), 3600);
+    let player_token = jwt_service.generate_player_token(1, Uuid::new_v4(), "player").unwrap();
+    let admin_token = jwt_service.generate_player_token(1, ADMIN_ID, "operator").unwrap();
+    let db: DatabaseConnection = MockDatabase::new(DbBackend::Postgres).into_connection();
+    let app = ratings_app!(jwt_service, db);
+
+    let req = recompute_request(&player_token, "2026-01-01T00:00:00Z", "2026-02-01T00:00:00Z");
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), 403);
+
+    let req = recompute_request(&admin_token, "2026-02-01T00:00:00Z", "2026-01-01T00:00:00Z");
+    assert_eq!(test::call_service(&app, req.to_request()).await.status(), 400);
+}
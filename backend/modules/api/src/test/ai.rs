@@ -0,0 +1,135 @@
+use std::sync::{Arc, Mutex};
+
+use actix_web::{test, web, App};
+use dto::ai::AnalysisDepthUpdate;
+use serde_json::{json, Value};
+use service::engine::{SearchLimits, UciEngine, UciEngines};
+use tokio::sync::mpsc;
+
+use crate::ai::analyze_position_stream;
+use crate::config::AppConfig;
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Engine stand-in that prints a fixed search transcript and remembers the
+/// `go` commands it was sent
+#[derive(Default)]
+struct ScriptedEngine {
+    lines: Vec<&'static str>,
+    commands: Mutex<Vec<String>>,
+}
+
+impl UciEngine for ScriptedEngine {
+    fn go(&self, _fen: &str, limits: SearchLimits) -> Result<mpsc::Receiver<String>, String> {
+        self.commands.lock().unwrap().push(limits.go_command());
+        let (tx, rx) = mpsc::channel(self.lines.len().max(1));
+        for line in &self.lines {
+            tx.try_send(line.to_string()).unwrap();
+        }
+        Ok(rx)
+    }
+}
+
+fn stream_request(depth: u8) -> actix_web::test::TestRequest {
+    analysis_request(json!({ "fen": START_FEN, "depth": depth }))
+}
+
+fn analysis_request(body: Value) -> actix_web::test::TestRequest {
+    test::TestRequest::post().uri("/v1/ai/analyze/stream").set_json(body)
+}
+
+fn engines(engine: &Arc<ScriptedEngine>) -> web::Data<UciEngines> {
+    web::Data::new(UciEngines::new("stockfish", engine.clone() as Arc<dyn UciEngine>))
+}
+
+macro_rules! ai_app {
+    ($($data:expr),*) => {
+        test::init_service(
+            App::new()
+                $(.app_data($data))*
+                .app_data(web::Data::new(AppConfig {
+                    max_engine_movetime_ms: 5000,
+                    ..AppConfig::from_env()
+                }))
+                .service(web::scope("/v1/ai").service(analyze_position_stream)),
+        )
+        .await
+    };
+}
+
+#[actix_web::test]
+async fn test_stream_emits_one_update_per_depth_in_order() {
+    let engine = Arc::new(ScriptedEngine {
+        lines: vec![
+            "info string NNUE evaluation enabled",
+            "info depth 1 seldepth 1 score cp 18 nodes 20 pv d2d4",
+            "info depth 2 seldepth 2 score cp 12 nodes 80 pv e2e4 e7e5",
+            "info depth 2 seldepth 3 score cp 24 nodes 120 pv e2e4 c7c5",
+            "info depth 3 currmove g1f3 currmovenumber 2",
+            "info depth 3 seldepth 4 score cp 30 nodes 400 pv g1f3 d7d5",
+            "bestmove g1f3 ponder d7d5",
+        ],
+        ..Default::default()
+    });
+    let app = ai_app!(engines(&engine));
+
+    let resp = test::call_service(&app, stream_request(3).to_request()).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("content-type").unwrap(), "text/event-stream");
+
+    let body = test::read_body(resp).await;
+    let updates: Vec<AnalysisDepthUpdate> = std::str::from_utf8(&body)
+        .unwrap()
+        .split("\n\n")
+        .filter_map(|event| event.strip_prefix("data: "))
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect();
+
+    let summary: Vec<(u8, f32, &str)> =
+        updates.iter().map(|u| (u.depth, u.eval, u.best_move.as_str())).collect();
+    // Depth 2 reports its last line, since the engine revised it before moving on
+    assert_eq!(summary, vec![(1, 0.18, "d2d4"), (2, 0.24, "e2e4"), (3, 0.3, "g1f3")]);
+    assert_eq!(*engine.commands.lock().unwrap(), ["go depth 3"]);
+}
+
+#[actix_web::test]
+async fn test_movetime_is_sent_as_go_movetime() {
+    let engine = Arc::new(ScriptedEngine {
+        lines: vec!["info depth 1 score cp 18 pv d2d4", "bestmove d2d4"],
+        ..Default::default()
+    });
+    let app = ai_app!(engines(&engine));
+
+    let req = analysis_request(json!({ "fen": START_FEN, "movetime_ms": 2000, "engine": "stockfish" }));
+    let resp = test::call_service(&app, req.to_request()).await;
+    assert_eq!(resp.status(), 200);
+    test::read_body(resp).await;
+    assert_eq!(*engine.commands.lock().unwrap(), ["go movetime 2000"]);
+}
+
+#[actix_web::test]
+async fn test_invalid_search_limits_are_rejected() {
+    let engine = Arc::new(ScriptedEngine::default());
+    let app = ai_app!(engines(&engine));
+
+    for body in [
+        // Over the server's 5000 ms maximum
+        json!({ "fen": START_FEN, "movetime_ms": 5001 }),
+        // Depth and movetime are mutually exclusive, and one is required
+        json!({ "fen": START_FEN, "depth": 10, "movetime_ms": 2000 }),
+        json!({ "fen": START_FEN }),
+        json!({ "fen": START_FEN, "depth": 10, "engine": "komodo" }),
+    ] {
+        let resp = test::call_service(&app, analysis_request(body.clone()).to_request()).await;
+        assert_eq!(resp.status(), 400, "{}", body);
+    }
+    assert!(engine.commands.lock().unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn test_stream_without_engine_is_unavailable() {
+    let app = ai_app!();
+
+    let resp = test::call_service(&app, stream_request(10).to_request()).await;
+    assert_eq!(resp.status(), 503);
+}
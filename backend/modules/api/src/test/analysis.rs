@@ -0,0 +1,125 @@
+use actix_web::{test, web, App};
+use serde_json::{json, Value};
+
+use crate::analysis::{legal_moves, static_eval};
+
+async fn post_legal_moves(fen: &str, square: &str) -> (u16, Value) {
+    let app = test::init_service(
+        App::new().service(web::scope("/v1/analysis").service(legal_moves)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/analysis/legal-moves")
+        .set_json(json!({ "fen": fen, "square": square }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let status = resp.status().as_u16();
+    (status, test::read_body_json(resp).await)
+}
+
+#[actix_web::test]
+async fn test_knight_moves_from_start_position() {
+    let (status, body) = post_legal_moves(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "g1",
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body["square"], "g1");
+
+    let mut squares: Vec<&str> = body["destinations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| {
+            assert_eq!(d["promotion"], false);
+            d["square"].as_str().unwrap()
+        })
+        .collect();
+    squares.sort();
+    assert_eq!(squares, vec!["f3", "h3"]);
+}
+
+#[actix_web::test]
+async fn test_pinned_piece_has_no_moves() {
+    let (status, body) = post_legal_moves("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1", "e2").await;
+    assert_eq!(status, 200);
+    assert_eq!(body["destinations"], json!([]));
+}
+
+#[actix_web::test]
+async fn test_empty_square_has_no_moves() {
+    let (status, body) = post_legal_moves(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "e4",
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body["destinations"], json!([]));
+}
+
+#[actix_web::test]
+async fn test_promotion_is_reported_once_per_destination() {
+    let (status, body) = post_legal_moves("7k/P7/8/8/8/8/8/K7 w - - 0 1", "a7").await;
+    assert_eq!(status, 200);
+    assert_eq!(body["destinations"], json!([{ "square": "a8", "promotion": true }]));
+}
+
+#[actix_web::test]
+async fn test_invalid_fen_is_bad_request() {
+    let (status, body) = post_legal_moves("not a fen", "e2").await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], 400);
+}
+
+async fn post_static_eval(fen: &str) -> (u16, Value) {
+    let app = test::init_service(
+        App::new().service(web::scope("/v1/analysis").service(static_eval)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/analysis/static-eval")
+        .set_json(json!({ "fen": fen }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let status = resp.status().as_u16();
+    (status, test::read_body_json(resp).await)
+}
+
+#[actix_web::test]
+async fn test_static_eval_of_start_position_is_level() {
+    let (status, body) =
+        post_static_eval("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").await;
+    assert_eq!(status, 200);
+    assert_eq!(body["material_balance"], 0);
+    assert_eq!(body["mobility"], 0);
+    assert_eq!(body["score_cp"], 0);
+}
+
+#[actix_web::test]
+async fn test_static_eval_up_a_queen() {
+    let fen = "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR";
+
+    let (status, body) = post_static_eval(&format!("{} w KQkq - 0 1", fen)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["material_balance"], 9);
+    // The empty d8 gives the black king one extra move
+    assert_eq!(body["mobility"], -1);
+    assert_eq!(body["score_cp"], 890);
+
+    // The same position with black to move is scored from black's side
+    let (status, body) = post_static_eval(&format!("{} b KQkq - 0 1", fen)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["material_balance"], -9);
+    assert_eq!(body["mobility"], 1);
+    assert_eq!(body["score_cp"], -890);
+}
+
+#[actix_web::test]
+async fn test_static_eval_rejects_malformed_fen() {
+    let (status, body) = post_static_eval("rnbqkbnr/pppppppp/8/8 w KQkq - 0 1").await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "Invalid FEN position");
+}
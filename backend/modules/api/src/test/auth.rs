@@ -0,0 +1,288 @@
+use actix_web::{test, web, App};
+use chrono::{Duration, FixedOffset, Utc};
+use db_entity::{email_verification_token, password_reset_token, player};
+use sea_orm::{DatabaseConnection, DbBackend, MockDatabase, MockExecResult};
+use security::JwtService;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use service::helper::password;
+
+use crate::auth::{forgot_password, login, reset_password, verify_email};
+use crate::config::AppConfig;
+use crate::lockout::{LoginLockout, MemoryFailedLoginCounter};
+
+const PASSWORD: &str = "SecurePass123!";
+
+fn player_model(email_verified: bool) -> player::Model {
+    player::Model {
+        id: Uuid::new_v4(),
+        username: "chess_master".to_string(),
+        email: "user@example.com".to_string(),
+        password_hash: password::hash_password(PASSWORD).unwrap().into_bytes(),
+        biography: String::new(),
+        country: String::new(),
+        flair: String::new(),
+        real_name: String::new(),
+        location: None,
+        fide_rating: None,
+        social_links: None,
+        is_enabled: true,
+        email_verified,
+        blitz_rating: None,
+        rapid_rating: None,
+        classical_rating: None,
+        bullet_rating: None,
+    }
+}
+
+fn lockout(max_failures: u32) -> web::Data<LoginLockout> {
+    web::Data::new(LoginLockout::new(
+        Box::new(MemoryFailedLoginCounter::default()),
+        max_failures,
+        std::time::Duration::from_secs(60),
+    ))
+}
+
+macro_rules! login_app {
+    ($db:expr, $config:expr, $lockout:expr) => {
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new($db))
+                .app_data(web::Data::new(JwtService::new("test-secret".to_string(), 3600)))
+                .app_data(web::Data::new($config))
+                .app_data($lockout)
+                .service(web::scope("/v1/auth").service(login)),
+        )
+        .await
+    };
+}
+
+fn login_request(password: &str) -> test::TestRequest {
+    test::TestRequest::post()
+        .uri("/v1/auth/login")
+        .set_json(json!({ "username": "chess_master", "password": password }))
+}
+
+async fn login_status(db: DatabaseConnection, require_email_verification: bool) -> (u16, Value) {
+    let config = AppConfig {
+        require_email_verification,
+        ..AppConfig::from_env()
+    };
+    let app = login_app!(db, config, lockout(5));
+
+    let resp = test::call_service(&app, login_request(PASSWORD).to_request()).await;
+    let status = resp.status().as_u16();
+    (status, test::read_body_json(resp).await)
+}
+
+#[actix_web::test]
+async fn test_login_blocked_until_email_verified() {
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![player_model(false)]])
+        .into_connection();
+    let (status, body) = login_status(db, true).await;
+    assert_eq!(status, 403);
+    assert_eq!(body["code"], "EMAIL_NOT_VERIFIED");
+
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![player_model(true)]])
+        .into_connection();
+    let (status, _) = login_status(db, true).await;
+    assert_eq!(status, 200);
+}
+
+#[actix_web::test]
+async fn test_login_ignores_verification_when_not_required() {
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![player_model(false)]])
+        .into_connection();
+    let (status, body) = login_status(db, false).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["username"], "chess_master");
+}
+
+#[actix_web::test]
+async fn test_login_rejects_unknown_user() {
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([Vec::<player::Model>::new()])
+        .into_connection();
+    let (status, body) = login_status(db, false).await;
+    assert_eq!(status, 401);
+    assert_eq!(body["code"], "INVALID_CREDENTIALS");
+}
+
+#[actix_web::test]
+async fn test_account_locks_after_repeated_failures() {
+    tokio::time::pause();
+    let player = player_model(true);
+    // Locked attempts are refused before the database is consulted
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results((0..5).map(|_| vec![player.clone()]))
+        .into_connection();
+    let app = login_app!(db, AppConfig::from_env(), lockout(3));
+
+    for _ in 0..3 {
+        let resp = test::call_service(&app, login_request("WrongPass123!").to_request()).await;
+        assert_eq!(resp.status(), 401);
+    }
+
+    // Even the right password is refused while locked
+    let resp = test::call_service(&app, login_request(PASSWORD).to_request()).await;
+    assert_eq!(resp.status(), 423);
+    assert_eq!(resp.headers().get("retry-after").unwrap(), "60");
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "ACCOUNT_LOCKED");
+
+    tokio::time::advance(std::time::Duration::from_secs(61)).await;
+    let resp = test::call_service(&app, login_request(PASSWORD).to_request()).await;
+    assert_eq!(resp.status(), 200);
+
+    // Success cleared the count, so a single failure does not lock again
+    let resp = test::call_service(&app, login_request("WrongPass123!").to_request()).await;
+    assert_eq!(resp.status(), 401);
+}
+
+#[actix_web::test]
+async fn test_verify_email_endpoint() {
+    let (token, player_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![email_verification_token::Model {
+            token,
+            player_id,
+            expires_at: now + Duration::hours(1),
+            used_at: None,
+            created_at: now,
+        }]])
+        .append_exec_results((0..2).map(|_| MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        }))
+        .into_connection();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db))
+            .service(web::scope("/v1/auth").service(verify_email)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/auth/verify?token={}", token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["player_id"], player_id.to_string());
+    assert_eq!(body["email_verified"], true);
+
+    let req = test::TestRequest::get()
+        .uri("/v1/auth/verify?token=not-a-token")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "INVALID_VERIFICATION_TOKEN");
+}
+
+fn reset_token_model(expires_in: Duration, used: bool) -> password_reset_token::Model {
+    let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+    password_reset_token::Model {
+        token_hash: "0".repeat(64),
+        player_id: Uuid::new_v4(),
+        expires_at: now + expires_in,
+        used_at: used.then_some(now),
+        created_at: now,
+    }
+}
+
+async fn post_reset(db: DatabaseConnection, requests: usize) -> Vec<(u16, Value)> {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db))
+            .service(web::scope("/v1/auth").service(reset_password)),
+    )
+    .await;
+
+    let mut responses = Vec::new();
+    for _ in 0..requests {
+        let req = test::TestRequest::post()
+            .uri("/v1/auth/reset-password")
+            .set_json(json!({ "token": "reset-token", "new_password": "NewSecurePass123!" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let status = resp.status().as_u16();
+        responses.push((status, test::read_body_json(resp).await));
+    }
+    responses
+}
+
+#[actix_web::test]
+async fn test_reset_password_succeeds_once() {
+    // The second lookup sees the token as redeemed by the first
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([
+            vec![reset_token_model(Duration::minutes(5), false)],
+            vec![reset_token_model(Duration::minutes(5), true)],
+        ])
+        .append_exec_results((0..2).map(|_| MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        }))
+        .into_connection();
+
+    let responses = post_reset(db, 2).await;
+    assert_eq!(responses[0].0, 200);
+    assert_eq!(responses[1].0, 400);
+    assert_eq!(responses[1].1["code"], "INVALID_RESET_TOKEN");
+}
+
+#[actix_web::test]
+async fn test_reset_password_rejects_expired_token() {
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![reset_token_model(Duration::minutes(-1), false)]])
+        .into_connection();
+
+    let responses = post_reset(db, 1).await;
+    assert_eq!(responses[0].0, 400);
+    assert_eq!(responses[0].1["code"], "INVALID_RESET_TOKEN");
+}
+
+#[actix_web::test]
+async fn test_reset_password_enforces_length_rules() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(MockDatabase::new(DbBackend::Postgres).into_connection()))
+            .service(web::scope("/v1/auth").service(reset_password)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/auth/reset-password")
+        .set_json(json!({ "token": "reset-token", "new_password": "short" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "VALIDATION_ERROR");
+}
+
+#[actix_web::test]
+async fn test_forgot_password_does_not_reveal_unknown_emails() {
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([Vec::<player::Model>::new()])
+        .into_connection();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db))
+            .service(web::scope("/v1/auth").service(forgot_password)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/auth/forgot-password")
+        .set_json(json!({ "email": "nobody@example.com" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 202);
+}
@@ -0,0 +1,229 @@
+use actix_web::{test, web, App};
+use chrono::{FixedOffset, Utc};
+use db_entity::game::{self, GameVariant, ResultSide};
+use db_entity::game_rating_change;
+use std::collections::BTreeMap;
+
+use sea_orm::{DatabaseConnection, DbBackend, MockDatabase, Value as DbValue};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::games::{get_game, get_game_summary, get_games_batch, list_games, TOTAL_COUNT_HEADER};
+
+fn game(id: Uuid, result: Option<ResultSide>, moves: &[&str]) -> game::Model {
+    let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+    game::Model {
+        id,
+        white_player: Uuid::new_v4(),
+        black_player: Uuid::new_v4(),
+        fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string(),
+        pgn: json!({ "moves": moves }),
+        result,
+        draw_reason: None,
+        variant: GameVariant::Standard,
+        started_at: now,
+        duration_sec: 600,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+async fn batch(db: DatabaseConnection, body: Value) -> (u16, Value) {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db))
+            .service(web::scope("/v1/games").service(get_games_batch)),
+    )
+    .await;
+    let req = test::TestRequest::post().uri("/v1/games/batch").set_json(body).to_request();
+    let resp = test::call_service(&app, req).await;
+    let status = resp.status().as_u16();
+    (status, test::read_body_json(resp).await)
+}
+
+#[actix_web::test]
+async fn test_batch_returns_known_games_in_request_order() {
+    let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let unknown = Uuid::new_v4();
+    // The database returns rows in its own order
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![
+            game(third, Some(ResultSide::Draw), &[]),
+            game(first, Some(ResultSide::WhiteWins), &["e2e4", "e7e5"]),
+            game(second, Some(ResultSide::Ongoing), &[]),
+        ]])
+        .into_connection();
+
+    let (status, body) = batch(db, json!({ "ids": [second, unknown, first, third] })).await;
+
+    assert_eq!(status, 200);
+    let games = body["data"]["games"].as_array().unwrap();
+    let ids: Vec<&str> = games.iter().map(|g| g["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, [second.to_string(), first.to_string(), third.to_string()]);
+    assert_eq!(games[0]["status"], "in_progress");
+    assert_eq!(games[1]["result"], "white_win");
+    assert_eq!(games[1]["move_history"], json!(["e2e4", "e7e5"]));
+    assert_eq!(games[2]["result"], "draw");
+}
+
+#[actix_web::test]
+async fn test_batch_size_is_capped() {
+    let empty = || MockDatabase::new(DbBackend::Postgres).into_connection();
+
+    let too_many: Vec<Uuid> = (0..101).map(|_| Uuid::new_v4()).collect();
+    let (status, body) = batch(empty(), json!({ "ids": too_many })).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], 400);
+
+    let (status, _) = batch(empty(), json!({ "ids": [] })).await;
+    assert_eq!(status, 400);
+}
+
+async fn fetch(db: DatabaseConnection, id: Uuid) -> (u16, Value) {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db))
+            .service(web::scope("/v1/games").service(get_games_batch).service(get_game)),
+    )
+    .await;
+    let req = test::TestRequest::get().uri(&format!("/v1/games/{}", id)).to_request();
+    let resp = test::call_service(&app, req).await;
+    let status = resp.status().as_u16();
+    (status, test::read_body_json(resp).await)
+}
+
+#[actix_web::test]
+async fn test_get_game_returns_the_stored_game() {
+    let id = Uuid::new_v4();
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![game(id, Some(ResultSide::BlackWins), &["f2f3"])]])
+        .into_connection();
+
+    let (status, body) = fetch(db, id).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["data"]["game"]["id"], id.to_string());
+    assert_eq!(body["data"]["game"]["result"], "black_win");
+}
+
+#[actix_web::test]
+async fn test_get_game_is_not_found_for_an_unknown_id() {
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([Vec::<game::Model>::new()])
+        .into_connection();
+
+    let (status, body) = fetch(db, Uuid::new_v4()).await;
+    assert_eq!(status, 404);
+    assert_eq!(body, json!({ "error": "Game not found", "code": 404 }));
+}
+
+async fn summary(db: DatabaseConnection, id: Uuid) -> (u16, Value) {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db))
+            .service(web::scope("/v1/games").service(get_game_summary)),
+    )
+    .await;
+    let req = test::TestRequest::get().uri(&format!("/v1/games/{}/summary", id)).to_request();
+    let resp = test::call_service(&app, req).await;
+    let status = resp.status().as_u16();
+    (status, test::read_body_json(resp).await)
+}
+
+fn rating_change(game_id: Uuid, player_id: Uuid, before: i32, after: i32) -> game_rating_change::Model {
+    game_rating_change::Model {
+        game_id,
+        player_id,
+        pool: "blitz".to_string(),
+        rating_before: before,
+        rating_after: after,
+        created_at: Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap()),
+    }
+}
+
+#[actix_web::test]
+async fn test_summary_of_a_finished_rated_game() {
+    let id = Uuid::new_v4();
+    let finished = game(id, Some(ResultSide::WhiteWins), &["e2e4", "e7e5", "d1h5"]);
+    let (white, black) = (finished.white_player, finished.black_player);
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![finished]])
+        .append_query_results([vec![
+            rating_change(id, white, 1500, 1510),
+            rating_change(id, black, 1500, 1490),
+        ]])
+        .into_connection();
+
+    let (status, body) = summary(db, id).await;
+    assert_eq!(status, 200);
+    assert_eq!(
+        body["data"]["summary"],
+        json!({
+            "game_id": id,
+            "status": "completed",
+            "result": "white_win",
+            "move_count": 3,
+            "duration_sec": 600,
+            "pool": "blitz",
+            "white": { "player_id": white, "rating_before": 1500, "rating_after": 1510, "rating_delta": 10 },
+            "black": { "player_id": black, "rating_before": 1500, "rating_after": 1490, "rating_delta": -10 },
+        })
+    );
+}
+
+#[actix_web::test]
+async fn test_summary_of_an_unrated_game_has_no_ratings() {
+    let id = Uuid::new_v4();
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![game(id, Some(ResultSide::Abandoned), &["e2e4"])]])
+        .append_query_results([Vec::<game_rating_change::Model>::new()])
+        .into_connection();
+
+    let (status, body) = summary(db, id).await;
+    assert_eq!(status, 200);
+    let summary = &body["data"]["summary"];
+    assert_eq!(summary["move_count"], 1);
+    assert!(summary.get("pool").is_none());
+    assert!(summary["white"].get("rating_delta").is_none());
+    assert!(summary["black"].get("rating_before").is_none());
+}
+
+#[actix_web::test]
+async fn test_summary_is_not_found_for_an_unknown_id() {
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([Vec::<game::Model>::new()])
+        .into_connection();
+
+    let (status, body) = summary(db, Uuid::new_v4()).await;
+    assert_eq!(status, 404);
+    assert_eq!(body, json!({ "error": "Game not found", "code": 404 }));
+}
+
+#[actix_web::test]
+async fn test_list_games_reports_the_total_across_pages() {
+    let player = Uuid::new_v4();
+    // One more game than the page holds is fetched to find the next cursor
+    let page: Vec<_> = (0..3)
+        .map(|_| game(Uuid::new_v4(), Some(ResultSide::WhiteWins), &[]))
+        .collect();
+    let db = MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([page])
+        .append_query_results([vec![BTreeMap::from([("num_items", DbValue::BigInt(Some(7)))])]])
+        .into_connection();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db))
+            .service(web::scope("/v1/games").service(list_games)),
+    )
+    .await;
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/games?player_id={}&status=completed&limit=2", player))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get(TOTAL_COUNT_HEADER).unwrap(), "7");
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["games"].as_array().unwrap().len(), 2);
+    assert!(body["data"]["next_cursor"].is_string());
+}
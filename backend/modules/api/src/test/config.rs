@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use db::db::db::PoolConfig;
+use security::AdminPlayers;
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+
+fn from_vars(vars: &[(&str, &str)]) -> (AppConfig, Vec<String>) {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    AppConfig::from_vars(|name| vars.get(name).cloned())
+}
+
+#[test]
+fn test_unset_variables_take_documented_defaults() {
+    let (config, warnings) = from_vars(&[]);
+    assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+
+    assert_eq!(config.server_addr, "127.0.0.1:8080");
+    assert_eq!(config.workers, None);
+    assert_eq!(config.db_pool, PoolConfig::default());
+    assert_eq!(config.auth_rate_limit_per_sec, 1);
+    assert_eq!(config.auth_rate_limit_burst, 5);
+    assert_eq!(config.game_rate_limit_per_sec, 10);
+    assert_eq!(config.game_rate_limit_burst, 20);
+    assert_eq!(config.cors_allowed_origins, None);
+    assert_eq!(config.cors_max_age, 3600);
+    assert!(!config.cors_allow_credentials);
+    assert_eq!(config.ws_allowed_origins, None);
+    assert_eq!(config.admin_players, AdminPlayers::default());
+}
+
+#[test]
+fn test_malformed_values_warn_and_fall_back_to_defaults() {
+    let (config, warnings) = from_vars(&[
+        ("WORKERS", "0"),
+        ("AUTH_RATE_LIMIT_PER_SEC", "fast"),
+        ("GAME_RATE_LIMIT_BURST", "-3"),
+        ("CORS_ALLOW_CREDENTIALS", "yes"),
+        ("ADMIN_PLAYER_IDS", "8a3c2f9e-51b4-4c57-9d0e-3f2b6a7c1d04, 1"),
+        ("CORS_MAX_AGE", " "),
+        ("RATE_LIMIT_ALLOWLIST", "10.0.0.0/8, not-a-cidr"),
+        ("DB_MAX_CONNECTIONS", "many"),
+    ]);
+
+    assert_eq!(config.workers, None);
+    assert_eq!(config.auth_rate_limit_per_sec, 1);
+    assert_eq!(config.game_rate_limit_burst, 20);
+    assert!(!config.cors_allow_credentials);
+    assert_eq!(
+        config.admin_players,
+        AdminPlayers(vec![Uuid::parse_str("8a3c2f9e-51b4-4c57-9d0e-3f2b6a7c1d04").unwrap()])
+    );
+    // Blank values count as unset rather than malformed
+    assert_eq!(config.cors_max_age, 3600);
+
+    assert_eq!(config.rate_limit_allowlist.len(), 1);
+    assert_eq!(config.db_pool, PoolConfig::default());
+
+    assert_eq!(warnings.len(), 7, "warnings: {:?}", warnings);
+    for name in [
+        "WORKERS",
+        "AUTH_RATE_LIMIT_PER_SEC",
+        "GAME_RATE_LIMIT_BURST",
+        "CORS_ALLOW_CREDENTIALS",
+        "ADMIN_PLAYER_IDS",
+        "RATE_LIMIT_ALLOWLIST",
+        "DB_MAX_CONNECTIONS",
+    ] {
+        assert!(
+            warnings.iter().any(|warning| warning.contains(name)),
+            "no warning for {} in {:?}",
+            name,
+            warnings
+        );
+    }
+    assert!(warnings.iter().any(|warning| warning.contains("'fast'") && warning.contains("default 1")));
+}
+
+#[test]
+fn test_valid_values_are_used() {
+    let (config, warnings) = from_vars(&[
+        ("SERVER_ADDR", "0.0.0.0:9000"),
+        ("WORKERS", "4"),
+        ("DB_MAX_CONNECTIONS", "25"),
+        ("DB_MIN_CONNECTIONS", "5"),
+        ("GAME_RATE_LIMIT_PER_SEC", "50"),
+        ("ALLOWED_ORIGINS", "https://xlmate.app, https://admin.xlmate.app"),
+    ]);
+    assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+
+    assert_eq!(config.server_addr, "0.0.0.0:9000");
+    assert_eq!(config.workers, Some(4));
+    assert_eq!(config.db_pool.max_connections, 25);
+    assert_eq!(config.db_pool.min_connections, 5);
+    assert_eq!(config.game_rate_limit_per_sec, 50);
+    assert_eq!(
+        config.cors_allowed_origins.as_deref(),
+        Some("https://xlmate.app, https://admin.xlmate.app")
+    );
+    assert_eq!(
+        config.ws_allowed_origins,
+        Some(vec!["https://xlmate.app".to_string(), "https://admin.xlmate.app".to_string()])
+    );
+}
@@ -0,0 +1,93 @@
+use actix_web::{http::header, test, web, App, HttpResponse};
+
+use crate::config::AppConfig;
+use crate::server::build_cors;
+
+fn config(allow_credentials: bool) -> AppConfig {
+    AppConfig {
+        cors_max_age: 600,
+        cors_allow_credentials: allow_credentials,
+        ..AppConfig::from_env()
+    }
+}
+
+#[actix_web::test]
+async fn test_cors_credentials_with_specific_origins() {
+    let config = config(true);
+    let origins = Some("https://xlmate.app");
+    assert!(config.cors_misconfiguration(origins).is_none());
+
+    let app = test::init_service(
+        App::new()
+            .wrap(build_cors(&config, origins))
+            .route("/health", web::get().to(HttpResponse::Ok)),
+    )
+    .await;
+
+    let req = test::TestRequest::default()
+        .method(actix_web::http::Method::OPTIONS)
+        .uri("/health")
+        .insert_header((header::ORIGIN, "https://xlmate.app"))
+        .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "GET"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let headers = resp.headers();
+    assert_eq!(
+        headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+        "https://xlmate.app"
+    );
+    assert_eq!(
+        headers.get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS).unwrap(),
+        "true"
+    );
+    assert_eq!(headers.get(header::ACCESS_CONTROL_MAX_AGE).unwrap(), "600");
+}
+
+#[actix_web::test]
+async fn test_cors_credentials_disabled_for_wildcard_origins() {
+    let config = config(true);
+    assert!(config.cors_misconfiguration(None).is_some());
+    assert!(config.cors_misconfiguration(Some("*")).is_some());
+
+    let app = test::init_service(
+        App::new()
+            .wrap(build_cors(&config, None))
+            .route("/health", web::get().to(HttpResponse::Ok)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/health")
+        .insert_header((header::ORIGIN, "https://elsewhere.example"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert!(resp
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+        .is_none());
+}
+
+#[actix_web::test]
+async fn test_cors_without_credentials() {
+    let config = config(false);
+    assert!(config.cors_misconfiguration(None).is_none());
+
+    let app = test::init_service(
+        App::new()
+            .wrap(build_cors(&config, Some("https://xlmate.app")))
+            .route("/health", web::get().to(HttpResponse::Ok)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/health")
+        .insert_header((header::ORIGIN, "https://xlmate.app"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert!(resp
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+        .is_none());
+}
@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+
+use actix_web::{test, web, App};
+use db_entity::player;
+use sea_orm::{DatabaseConnection, DbBackend, MockDatabase, Value};
+use security::JwtService;
+use serde_json::{json, Value as JsonValue};
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::games::{create_game, join_game};
+
+const LIMIT: u64 = 2;
+
+fn player_model() -> player::Model {
+    player::Model {
+        id: Uuid::new_v4(),
+        username: "alice".to_string(),
+        email: "alice@example.com".to_string(),
+        password_hash: Vec::new(),
+        biography: String::new(),
+        country: String::new(),
+        flair: String::new(),
+        real_name: String::new(),
+        location: None,
+        fide_rating: None,
+        social_links: None,
+        is_enabled: true,
+        email_verified: true,
+        blitz_rating: None,
+        rapid_rating: None,
+        classical_rating: None,
+        bullet_rating: None,
+    }
+}
+
+fn count_row(count: i64) -> BTreeMap<&'static str, Value> {
+    BTreeMap::from([("num_items", Value::BigInt(Some(count)))])
+}
+
+fn creator_db(ongoing: i64) -> DatabaseConnection {
+    MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([vec![player_model()]])
+        .append_query_results([vec![count_row(ongoing)]])
+        .into_connection()
+}
+
+macro_rules! games_app {
+    ($db:expr) => {
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new($db))
+                .app_data(web::Data::new(JwtService::new("test_secret".to_string(), 3600)))
+                .app_data(web::Data::new(AppConfig {
+                    max_active_games_per_user: LIMIT,
+                    ..AppConfig::from_env()
+                }))
+                .service(web::scope("/v1/games").service(create_game).service(join_game)),
+        )
+        .await
+    };
+}
+
+fn create_request() -> test::TestRequest {
+    let token = JwtService::new("test_secret".to_string(), 3600)
+        .generate_token(7, "alice")
+        .unwrap();
+    test::TestRequest::post()
+        .uri("/v1/games")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "time_control": 300, "increment": 5 }))
+}
+
+#[actix_web::test]
+async fn test_player_at_limit_cannot_create_until_a_game_finishes() {
+    let app = games_app!(creator_db(LIMIT as i64));
+    let resp = test::call_service(&app, create_request().to_request()).await;
+    assert_eq!(resp.status(), 409);
+    let body: JsonValue = test::read_body_json(resp).await;
+    assert_eq!(body["code"], 409);
+
+    // One of the games has finished, so it no longer counts
+    let app = games_app!(creator_db(LIMIT as i64 - 1));
+    let resp = test::call_service(&app, create_request().to_request()).await;
+    assert_eq!(resp.status(), 201);
+}
+
+#[actix_web::test]
+async fn test_player_at_limit_cannot_join() {
+    let app = games_app!(creator_db(LIMIT as i64));
+    let token = JwtService::new("test_secret".to_string(), 3600)
+        .generate_token(7, "alice")
+        .unwrap();
+
+    // The body names someone else, but the limit is the caller's
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/games/{}/join", Uuid::new_v4()))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "player_id": Uuid::new_v4() }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 409);
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use actix_web::{test, web, App};
+use security::JwtService;
+use serde_json::{json, Value};
+
+use crate::games::create_game;
+use crate::idempotency::IdempotencyStore;
+
+fn create_request(token: &str, key: Option<&str>) -> actix_web::test::TestRequest {
+    let mut req = test::TestRequest::post()
+        .uri("/v1/games")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({
+            "time_control": 300,
+            "increment": 5,
+            "player_color": "white"
+        }));
+    if let Some(key) = key {
+        req = req.insert_header(("Idempotency-Key", key));
+    }
+    req
+}
+
+fn game_id(body: &Value) -> String {
+    body["data"]["game"]["id"].as_str().unwrap().to_string()
+}
+
+#[actix_web::test]
+async fn test_same_idempotency_key_returns_one_game() {
+    let jwt_service = JwtService::new("test_secret".to_string(), 3600);
+    let token = jwt_service.generate_token(7, "alice").unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(jwt_service))
+            .app_data(web::Data::new(IdempotencyStore::new(Duration::from_secs(60))))
+            .service(web::scope("/v1/games").service(create_game)),
+    )
+    .await;
+
+    let first: Value =
+        test::call_and_read_body_json(&app, create_request(&token, Some("retry-1")).to_request()).await;
+    let second: Value =
+        test::call_and_read_body_json(&app, create_request(&token, Some("retry-1")).to_request()).await;
+    assert_eq!(game_id(&first), game_id(&second));
+
+    let third: Value =
+        test::call_and_read_body_json(&app, create_request(&token, Some("retry-2")).to_request()).await;
+    assert_ne!(game_id(&first), game_id(&third));
+}
+
+#[actix_web::test]
+async fn test_idempotency_key_requires_authentication() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(JwtService::new("test_secret".to_string(), 3600)))
+            .app_data(web::Data::new(IdempotencyStore::new(Duration::from_secs(60))))
+            .service(web::scope("/v1/games").service(create_game)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/games")
+        .insert_header(("Idempotency-Key", "retry-1"))
+        .set_json(json!({ "time_control": 300, "increment": 5 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+}
@@ -0,0 +1,110 @@
+use actix_web::{test, web, App};
+use chrono::{FixedOffset, Utc};
+use db_entity::game::{self, GameVariant};
+use sea_orm::{DbBackend, MockDatabase};
+use serde_json::{json, Value as JsonValue};
+use uuid::Uuid;
+
+use crate::games::{create_game, get_game, join_game};
+use crate::registry::canonical_game_id;
+
+const GAME_ID: &str = "123e4567-e89b-12d3-a456-426614174000";
+const PLAYER_ID: &str = "9b2f4c1e-2a4d-4f6b-8c3e-5d7a9b1c2e3f";
+
+fn stored_game() -> game::Model {
+    let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+    game::Model {
+        id: Uuid::parse_str(GAME_ID).unwrap(),
+        white_player: Uuid::new_v4(),
+        black_player: Uuid::new_v4(),
+        fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string(),
+        pgn: json!({ "moves": [] }),
+        result: None,
+        draw_reason: None,
+        variant: GameVariant::Standard,
+        started_at: now,
+        duration_sec: 600,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+// get_game looks the game up, so the mock database holds the one it asks for
+macro_rules! games_app {
+    () => {
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(
+                    MockDatabase::new(DbBackend::Postgres)
+                        .append_query_results([vec![stored_game()]])
+                        .into_connection(),
+                ))
+                .service(web::scope("/v1/games").service(get_game).service(join_game)),
+        )
+        .await
+    };
+}
+
+#[actix_web::test]
+async fn test_well_formed_ids_pass_through_unchanged() {
+    let app = games_app!();
+
+    let req = test::TestRequest::get().uri(&format!("/v1/games/{}", GAME_ID)).to_request();
+    let body: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"]["game"]["id"], GAME_ID);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/games/{}/join", GAME_ID))
+        .set_json(json!({ "player_id": PLAYER_ID }))
+        .to_request();
+    let body: JsonValue = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"]["game"]["id"], GAME_ID);
+    assert_eq!(body["data"]["game"]["player_id"], PLAYER_ID);
+}
+
+#[actix_web::test]
+async fn test_malformed_ids_are_rejected_at_the_boundary() {
+    let app = games_app!();
+
+    let req = test::TestRequest::get().uri("/v1/games/not-a-uuid").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/games/{}/join", GAME_ID))
+        .set_json(json!({ "player_id": "not-a-uuid" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    // Ids are UUID strings on the wire, never numbers
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/games/{}/join", GAME_ID))
+        .set_json(json!({ "player_id": 42 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_games_between_known_addresses_get_the_canonical_id() {
+    let app = test::init_service(App::new().service(web::scope("/v1/games").service(create_game))).await;
+    let white = stellar_strkey::ed25519::PublicKey([1; 32]).to_string();
+    let black = stellar_strkey::ed25519::PublicKey([2; 32]).to_string();
+
+    let req = test::TestRequest::post()
+        .uri("/v1/games")
+        .set_json(json!({ "time_control": 300, "increment": 5, "white_address": white, "black_address": black }))
+        .to_request();
+    let body: JsonValue = test::call_and_read_body_json(&app, req).await;
+    let created_at = chrono::DateTime::parse_from_rfc3339(body["data"]["game"]["created_at"].as_str().unwrap()).unwrap();
+    let expected = canonical_game_id(&white, &black, created_at.timestamp_millis() as u64).unwrap();
+    assert_eq!(body["data"]["game"]["id"], expected.to_string());
+
+    let req = test::TestRequest::post()
+        .uri("/v1/games")
+        .set_json(json!({ "time_control": 300, "increment": 5, "white_address": white, "black_address": "nope" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+}
@@ -1,5 +1,31 @@
 #[cfg(test)]
 mod rate_limit;
+#[cfg(test)]
+mod config;
+#[cfg(test)]
+mod cors;
+#[cfg(test)]
+mod idempotency;
+#[cfg(test)]
+mod not_found;
+#[cfg(test)]
+mod admin;
+#[cfg(test)]
+mod analysis;
+#[cfg(test)]
+mod auth;
+#[cfg(test)]
+mod accuracy;
+#[cfg(test)]
+mod ai;
+#[cfg(test)]
+mod game_limit;
+#[cfg(test)]
+mod ids;
+#[cfg(test)]
+mod batch_games;
+#[cfg(test)]
+mod players;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,31 @@
+use actix_web::{test, web, App, HttpResponse};
+use serde_json::Value;
+
+use crate::server::not_found;
+
+#[actix_web::test]
+async fn test_unknown_route_returns_json_404() {
+    let app = test::init_service(
+        App::new()
+            .route("/health", web::get().to(HttpResponse::Ok))
+            .default_service(web::route().to(not_found)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/v1/does-not-exist").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/json"
+    );
+
+    let body: Value = test::read_body_json(resp).await;
+    assert!(body["error"].as_str().unwrap().contains("/v1/does-not-exist"));
+    assert_eq!(body["code"], 404);
+
+    // Matched routes are unaffected
+    let req = test::TestRequest::get().uri("/health").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
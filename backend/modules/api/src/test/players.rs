@@ -0,0 +1,91 @@
+use actix_web::{test, web, App};
+use db_entity::player;
+use sea_orm::{DatabaseConnection, DbBackend, MockDatabase};
+use security::JwtService;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::players::{current_player, find_player_by_id};
+
+fn player_model() -> player::Model {
+    player::Model {
+        id: Uuid::new_v4(),
+        username: "alice".to_string(),
+        email: "alice@example.com".to_string(),
+        password_hash: Vec::new(),
+        biography: String::new(),
+        country: String::new(),
+        flair: String::new(),
+        real_name: String::new(),
+        location: None,
+        fide_rating: None,
+        social_links: None,
+        is_enabled: true,
+        email_verified: true,
+        blitz_rating: None,
+        rapid_rating: None,
+        classical_rating: None,
+        bullet_rating: None,
+    }
+}
+
+fn db_with(players: Vec<player::Model>) -> DatabaseConnection {
+    MockDatabase::new(DbBackend::Postgres)
+        .append_query_results([players])
+        .into_connection()
+}
+
+macro_rules! players_app {
+    ($db:expr) => {
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new($db))
+                .app_data(web::Data::new(JwtService::new("test_secret".to_string(), 3600)))
+                .service(
+                    web::scope("/v1/players")
+                        .service(current_player)
+                        .service(find_player_by_id),
+                ),
+        )
+        .await
+    };
+}
+
+fn me_request() -> test::TestRequest {
+    let token = JwtService::new("test_secret".to_string(), 3600)
+        .generate_token(7, "alice")
+        .unwrap();
+    test::TestRequest::get()
+        .uri("/v1/players/me")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+}
+
+#[actix_web::test]
+async fn test_me_returns_the_authenticated_player() {
+    let player = player_model();
+    let app = players_app!(db_with(vec![player.clone()]));
+
+    let resp = test::call_service(&app, me_request().to_request()).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["player"]["username"], "alice");
+    assert_eq!(body["data"]["player"]["id"], player.id.to_string());
+}
+
+#[actix_web::test]
+async fn test_me_requires_a_token() {
+    let app = players_app!(db_with(vec![player_model()]));
+
+    let req = test::TestRequest::get().uri("/v1/players/me").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+}
+
+#[actix_web::test]
+async fn test_me_is_not_found_for_a_disabled_account() {
+    // Disabled players are filtered out by the query, so it finds nothing
+    let app = players_app!(db_with(Vec::new()));
+
+    let resp = test::call_service(&app, me_request().to_request()).await;
+    assert_eq!(resp.status(), 404);
+}
@@ -1,4 +1,6 @@
 use actix_governor::{Governor, GovernorConfigBuilder};
+use crate::config::AppConfig;
+use crate::rate_limit::AllowlistKeyExtractor;
 use actix_web::{test, web, App, HttpResponse, Responder};
 use std::time::Duration;
 use std::thread;
@@ -90,3 +92,54 @@ async fn test_game_rate_limiting() {
     let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), 429);
 }
+
+#[actix_web::test]
+async fn test_allowlisted_cidr_bypasses_rate_limit() {
+    // Burst of 2, with 10.0.0.0/8 allowlisted
+    let (allowlist, invalid) = AppConfig::parse_allowlist("10.0.0.0/8, not-a-cidr");
+    assert_eq!(allowlist.len(), 1);
+    assert_eq!(invalid.len(), 1);
+
+    let governor_conf = GovernorConfigBuilder::default()
+        .key_extractor(AllowlistKeyExtractor::new(allowlist))
+        .per_second(1)
+        .burst_size(2)
+        .use_headers()
+        .finish()
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .service(
+                web::scope("/v1/auth")
+                    .wrap(Governor::new(&governor_conf))
+                    .route("/login", web::post().to(mock_handler))
+            )
+    ).await;
+
+    // Allowlisted peer can go well past the burst
+    for _ in 0..5 {
+        let req = test::TestRequest::post()
+            .uri("/v1/auth/login")
+            .peer_addr("10.1.2.3:4000".parse().unwrap())
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+    }
+
+    // A normal peer is still limited after the burst
+    for _ in 0..2 {
+        let req = test::TestRequest::post()
+            .uri("/v1/auth/login")
+            .peer_addr("192.168.1.20:4000".parse().unwrap())
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+    }
+    let req = test::TestRequest::post()
+        .uri("/v1/auth/login")
+        .peer_addr("192.168.1.20:4000".parse().unwrap())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 429);
+}
@@ -1,23 +1,44 @@
 use actix::prelude::*;
-use actix_web::{HttpRequest, HttpResponse, Error, web};
+use actix_web::{http::header, HttpRequest, HttpResponse, Error, web};
 use actix_web_actors::ws;
-use serde::{Serialize};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::env;
+use std::sync::Arc;
+use tokio::time::Instant;
+use uuid::Uuid;
 use security::jwt::Claims;
 use jsonwebtoken::{decode, DecodingKey, Validation, Algorithm};
-use actix_web::error::ErrorUnauthorized;
+use actix_web::error::{ErrorForbidden, ErrorUnauthorized};
+use chess::bitboard::board::{Color, Role};
+use chess::bitboard::position::{Move, Position};
+use sea_orm::DatabaseConnection;
 use serde_json::{Value, json};
+use service::games::GameService;
+
+use crate::config::AppConfig;
+use crate::metrics::{Metrics, MoveRejection};
+use crate::pubsub::{game_channel, game_id_of, LobbyPubSub};
 
 /// Core WebSocket message types
-#[derive(Message, Serialize, Clone, Debug, PartialEq)]
+#[derive(Message, Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[rtype(result = "()")]
 #[serde(tag = "type", content = "payload")]
 pub enum WsMessage {
-    Move { from: String, to: String, san: String, fen: String },
+    Move {
+        from: String,
+        to: String,
+        san: String,
+        fen: String,
+        /// Piece a promoted pawn became: "q", "r", "b" or "n"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        promotion: Option<String>,
+    },
     Clock { white: u32, black: u32 },
     End   { result: String, final_fen: String },
     Error { code: u16, message: String },
+    /// How many spectators are watching; players are not counted
+    SpectatorCount { count: usize },
 }
 
 /// Actor messages
@@ -26,6 +47,8 @@ pub enum WsMessage {
 pub struct Connect {
     pub game_id: String,
     pub addr: Recipient<WsMessage>,
+    /// Watching rather than playing; counted in `SpectatorCount`
+    pub spectator: bool,
 }
 
 #[derive(Message)]
@@ -42,27 +65,162 @@ pub struct Broadcast {
     pub message: WsMessage,
 }
 
-/// Lobby state actor
+/// A move a player sent over their websocket. The lobby plays it on the
+/// game's current position and broadcasts it, or refuses it
+#[derive(Message)]
+#[rtype(result = "Result<(), MoveRejection>")]
+pub struct SubmitMove {
+    pub game_id: String,
+    /// The color the sending session plays; `None` for spectators and
+    /// sessions that are not one of the game's players
+    pub color: Option<Color>,
+    pub from: String,
+    pub to: String,
+    pub promotion: Option<String>,
+}
+
+/// A broadcast received from the shared pub/sub bus
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Relay {
+    channel: String,
+    payload: String,
+}
+
+/// What instances publish on a game channel
+#[derive(Serialize, Deserialize)]
+struct LobbyEnvelope {
+    /// Instance that published the message; it has already delivered it locally
+    origin: String,
+    message: WsMessage,
+}
+
+/// Lobby state actor. Sessions are local to this instance; with a pub/sub bus
+/// configured, broadcasts are also relayed to sessions on other instances.
 pub struct LobbyState {
     sessions: HashMap<String, HashSet<Recipient<WsMessage>>>,
+    /// The subset of `sessions` that joined as spectators
+    spectators: HashMap<String, HashSet<Recipient<WsMessage>>>,
+    /// Position after the last move broadcast in each game with sessions
+    /// here; games without one are at the starting position
+    positions: HashMap<String, Position>,
+    instance_id: String,
+    pubsub: Option<Arc<dyn LobbyPubSub>>,
+    metrics: Arc<Metrics>,
 }
 
 impl LobbyState {
     pub fn new() -> Self {
-        LobbyState { sessions: HashMap::new() }
+        LobbyState {
+            sessions: HashMap::new(),
+            spectators: HashMap::new(),
+            positions: HashMap::new(),
+            instance_id: Uuid::new_v4().to_string(),
+            pubsub: None,
+            metrics: Arc::new(Metrics::default()),
+        }
+    }
+
+    pub fn with_pubsub(pubsub: Arc<dyn LobbyPubSub>) -> Self {
+        LobbyState {
+            pubsub: Some(pubsub),
+            ..Self::new()
+        }
+    }
+
+    /// Count rejected moves in `metrics`, e.g. the ones served on `/metrics`
+    pub fn with_metrics(self, metrics: Arc<Metrics>) -> Self {
+        LobbyState { metrics, ..self }
+    }
+
+    /// The move `msg` describes and the position it leads to, if its sender
+    /// may make it in the game's current position. A pawn reaching the last
+    /// rank becomes a queen unless the move names another piece
+    fn play(&self, msg: &SubmitMove) -> Result<(Move, Position), MoveRejection> {
+        let color = msg.color.ok_or(MoveRejection::NotInRoom)?;
+        let position = self.positions.get(&msg.game_id).copied().unwrap_or_default();
+        if color != position.turn {
+            return Err(MoveRejection::OutOfTurn);
+        }
+        let uci = format!("{}{}{}", msg.from, msg.to, msg.promotion.as_deref().unwrap_or_default());
+        let mut mv = Move::from_uci(&uci.to_ascii_lowercase()).ok_or(MoveRejection::Illegal)?;
+        if mv.promotion.is_none() && !position.legal_moves().contains(&mv) {
+            mv.promotion = Some(Role::Queen);
+        }
+        let after = position.play(mv).ok_or(MoveRejection::Illegal)?;
+        Ok((mv, after))
+    }
+
+    /// Keep up with moves broadcast by other instances in games that have
+    /// sessions here
+    fn follow(&mut self, game_id: &str, message: &WsMessage) {
+        if !self.sessions.contains_key(game_id) {
+            return;
+        }
+        if let WsMessage::Move { fen, .. } = message {
+            if let Some(position) = Position::from_fen(fen) {
+                self.positions.insert(game_id.to_string(), position);
+            }
+        }
+    }
+
+    fn deliver_local(&self, game_id: &str, message: &WsMessage) {
+        if let Some(set) = self.sessions.get(game_id) {
+            for recipient in set.iter() {
+                // backpressure: drop if send fails
+                recipient.do_send(message.clone());
+            }
+        }
+    }
+
+    // Counts cover this instance's sessions only, so they are not published
+    // on the pub/sub bus
+    fn announce_spectators(&self, game_id: &str) {
+        let count = self.spectators.get(game_id).map_or(0, HashSet::len);
+        self.deliver_local(game_id, &WsMessage::SpectatorCount { count });
     }
 }
 
 impl Actor for LobbyState {
     type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Context<Self>) {
+        let pubsub = match self.pubsub.clone() {
+            Some(pubsub) => pubsub,
+            None => return,
+        };
+        let addr = ctx.address();
+        // Hold other messages until subscribed so no broadcast is missed
+        ctx.wait(
+            async move {
+                match pubsub.subscribe().await {
+                    Ok(mut rx) => {
+                        actix::spawn(async move {
+                            while let Some((channel, payload)) = rx.recv().await {
+                                addr.do_send(Relay { channel, payload });
+                            }
+                        });
+                    }
+                    Err(e) => tracing::error!(event = "error", error = %e, "lobby pub/sub subscription failed"),
+                }
+            }
+            .into_actor(self),
+        );
+    }
 }
 
 impl Handler<Connect> for LobbyState {
     type Result = ();
 
     fn handle(&mut self, msg: Connect, _: &mut Context<Self>) {
-        let entry = self.sessions.entry(msg.game_id).or_default();
-        entry.insert(msg.addr);
+        let entry = self.sessions.entry(msg.game_id.clone()).or_default();
+        entry.insert(msg.addr.clone());
+        if msg.spectator {
+            let watching = self.spectators.entry(msg.game_id.clone()).or_default();
+            if watching.insert(msg.addr) {
+                self.announce_spectators(&msg.game_id);
+            }
+        }
     }
 }
 
@@ -74,6 +232,15 @@ impl Handler<Disconnect> for LobbyState {
             set.remove(&msg.addr);
             if set.is_empty() {
                 self.sessions.remove(&msg.game_id);
+                self.positions.remove(&msg.game_id);
+            }
+        }
+        if let Some(watching) = self.spectators.get_mut(&msg.game_id) {
+            if watching.remove(&msg.addr) {
+                if watching.is_empty() {
+                    self.spectators.remove(&msg.game_id);
+                }
+                self.announce_spectators(&msg.game_id);
             }
         }
     }
@@ -83,11 +250,107 @@ impl Handler<Broadcast> for LobbyState {
     type Result = ();
 
     fn handle(&mut self, msg: Broadcast, _: &mut Context<Self>) {
-        if let Some(set) = self.sessions.get(&msg.game_id) {
-            for recipient in set.iter() {
-                // backpressure: drop if send fails
-                let _ = recipient.do_send(msg.message.clone());
+        self.deliver_local(&msg.game_id, &msg.message);
+
+        if let Some(pubsub) = self.pubsub.clone() {
+            let envelope = LobbyEnvelope {
+                origin: self.instance_id.clone(),
+                message: msg.message,
+            };
+            let payload = match serde_json::to_string(&envelope) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    tracing::error!(
+                        event = "error",
+                        game_id = %msg.game_id,
+                        error = %e,
+                        "failed to encode lobby broadcast"
+                    );
+                    return;
+                }
+            };
+            let game_id = msg.game_id.clone();
+            let channel = game_channel(&msg.game_id);
+            actix::spawn(async move {
+                if let Err(e) = pubsub.publish(channel, payload).await {
+                    tracing::warn!(
+                        event = "error",
+                        game_id = %game_id,
+                        error = %e,
+                        "failed to publish lobby broadcast"
+                    );
+                }
+            });
+        }
+    }
+}
+
+impl Handler<Relay> for LobbyState {
+    type Result = ();
+
+    fn handle(&mut self, msg: Relay, _: &mut Context<Self>) {
+        let game_id = match game_id_of(&msg.channel) {
+            Some(game_id) => game_id,
+            None => return,
+        };
+        match serde_json::from_str::<LobbyEnvelope>(&msg.payload) {
+            Ok(envelope) if envelope.origin != self.instance_id => {
+                self.follow(game_id, &envelope.message);
+                self.deliver_local(game_id, &envelope.message)
             }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(
+                event = "error",
+                game_id = %game_id,
+                category = ?e.classify(),
+                "ignoring malformed lobby message"
+            ),
+        }
+    }
+}
+
+impl Handler<SubmitMove> for LobbyState {
+    type Result = Result<(), MoveRejection>;
+
+    fn handle(&mut self, msg: SubmitMove, ctx: &mut Context<Self>) -> Self::Result {
+        let (mv, after) = match self.play(&msg) {
+            Ok(played) => played,
+            Err(reason) => {
+                self.metrics.record_rejected_move(reason);
+                return Err(reason);
+            }
+        };
+        let position = self.positions.get(&msg.game_id).copied().unwrap_or_default();
+        self.positions.insert(msg.game_id.clone(), after);
+
+        // Broadcast what the server worked out, not what the client claimed
+        let message = WsMessage::Move {
+            from: mv.orig.name(),
+            to: mv.dest.name(),
+            san: position.san(mv).unwrap_or_default(),
+            fen: after.to_fen(),
+            promotion: mv.promotion.map(|role| role.char().to_string()),
+        };
+        ctx.notify(Broadcast { game_id: msg.game_id, message });
+        Ok(())
+    }
+}
+
+/// Ping cadence of a websocket session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeartbeatConfig {
+    /// How often the server pings to detect dead connections
+    pub interval: std::time::Duration,
+    /// Terminate the connection if no pong arrives within this long
+    pub client_timeout: std::time::Duration,
+}
+
+impl Default for HeartbeatConfig {
+    /// Ping every 15 seconds, allowing a 10 second grace period
+    fn default() -> Self {
+        HeartbeatConfig {
+            interval: std::time::Duration::from_secs(15),
+            client_timeout: std::time::Duration::from_secs(25),
         }
     }
 }
@@ -96,23 +359,84 @@ impl Handler<Broadcast> for LobbyState {
 pub struct WsSession {
     pub game_id: String,
     pub lobby: Addr<LobbyState>,
-    hb: std::time::Instant,
+    pub spectator: bool,
+    /// The color this session plays, if it is one of the game's players
+    pub color: Option<Color>,
+    /// The authenticated player, when the token names one
+    pub player_id: Option<Uuid>,
+    heartbeat: HeartbeatConfig,
+    hb: Instant,
 }
 
 impl WsSession {
-    /// Server sends a ping every 15 seconds to detect dead connections
-    const HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
-    /// Terminate connection if no pong received within 25 seconds (15s interval + 10s grace)
-    const CLIENT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(25);
+    pub fn new(game_id: String, lobby: Addr<LobbyState>, heartbeat: HeartbeatConfig) -> Self {
+        WsSession {
+            game_id,
+            lobby,
+            spectator: false,
+            color: None,
+            player_id: None,
+            heartbeat,
+            hb: Instant::now(),
+        }
+    }
+
+    /// Join the game as a spectator instead of a player
+    pub fn spectating(mut self) -> Self {
+        self.spectator = true;
+        self
+    }
+
+    /// Identify the session as `player_id`'s in its logs
+    pub fn for_player(mut self, player_id: Uuid) -> Self {
+        self.player_id = Some(player_id);
+        self
+    }
+
+    /// Seat the session at the board: it may move `color`'s pieces
+    pub fn playing(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Pass a move the client sent on to the lobby, answering with an error
+    /// if it is refused. Other client messages are ignored
+    fn submit_move(&self, text: &str, ctx: &mut ws::WebsocketContext<Self>) {
+        let (from, to, promotion) = match serde_json::from_str(text) {
+            Ok(WsMessage::Move { from, to, promotion, .. }) => (from, to, promotion),
+            _ => return,
+        };
+        let submit = SubmitMove {
+            game_id: self.game_id.clone(),
+            color: if self.spectator { None } else { self.color },
+            from,
+            to,
+            promotion,
+        };
+        self.lobby
+            .send(submit)
+            .into_actor(self)
+            .map(|result, _, ctx| {
+                if let Ok(Err(reason)) = result {
+                    ctx.notify(WsMessage::Error {
+                        code: 400,
+                        message: format!("Move rejected: {}", reason.as_str()),
+                    });
+                }
+            })
+            .spawn(ctx);
+    }
 
     fn hb(&self, ctx: &mut ws::WebsocketContext<Self>) {
-        ctx.run_interval(Self::HEARTBEAT_INTERVAL, |act, ctx| {
-            let elapsed = std::time::Instant::now().duration_since(act.hb);
-            if elapsed > Self::CLIENT_TIMEOUT {
-                log::warn!(
-                    "WebSocket timeout for game {}: no pong in {}s, terminating connection",
-                    act.game_id,
-                    elapsed.as_secs()
+        ctx.run_interval(self.heartbeat.interval, |act, ctx| {
+            let elapsed = Instant::now().duration_since(act.hb);
+            if elapsed > act.heartbeat.client_timeout {
+                tracing::warn!(
+                    event = "disconnect",
+                    game_id = %act.game_id,
+                    player_id = act.player_id.map(tracing::field::display),
+                    idle_secs = elapsed.as_secs(),
+                    "websocket heartbeat timed out, terminating connection"
                 );
                 ctx.stop();
                 return;
@@ -126,13 +450,24 @@ impl Actor for WsSession {
     type Context = ws::WebsocketContext<Self>;
 
     fn started(&mut self, ctx: &mut Self::Context) {
+        tracing::info!(
+            event = "connect",
+            game_id = %self.game_id,
+            player_id = self.player_id.map(tracing::field::display),
+            "websocket connected"
+        );
         self.hb(ctx);
         let addr = ctx.address().recipient();
-        self.lobby.do_send(Connect { game_id: self.game_id.clone(), addr });
+        self.lobby.do_send(Connect { game_id: self.game_id.clone(), addr, spectator: self.spectator });
     }
 
     fn stopped(&mut self, ctx: &mut Self::Context) {
-        log::info!("WebSocket disconnected for game: {}", self.game_id);
+        tracing::info!(
+            event = "disconnect",
+            game_id = %self.game_id,
+            player_id = self.player_id.map(tracing::field::display),
+            "websocket disconnected"
+        );
         let addr = ctx.address().recipient();
         self.lobby.do_send(Disconnect { game_id: self.game_id.clone(), addr });
     }
@@ -142,13 +477,14 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsSession {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
         match msg {
             Ok(ws::Message::Ping(msg)) => {
-                self.hb = std::time::Instant::now();
+                self.hb = Instant::now();
                 ctx.pong(&msg);
             }
             Ok(ws::Message::Pong(_)) => {
-                self.hb = std::time::Instant::now();
+                self.hb = Instant::now();
             }
-            Ok(ws::Message::Text(_)) | Ok(ws::Message::Binary(_)) => {}
+            Ok(ws::Message::Text(text)) => self.submit_move(&text, ctx),
+            Ok(ws::Message::Binary(_)) => {}
             Ok(ws::Message::Close(reason)) => {
                 ctx.close(reason);
                 ctx.stop();
@@ -172,15 +508,51 @@ impl Handler<WsMessage> for WsSession {
     }
 }
 
+/// Query parameters of the websocket handshake
+#[derive(Deserialize, Default)]
+pub struct WsParams {
+    /// `?spectate=true` joins as a spectator
+    #[serde(default)]
+    pub spectate: bool,
+}
+
+/// The color `player_id` plays in the game, if they are one of its players.
+/// Sessions without a seat can watch but not move
+async fn seat_of(db: Option<&DatabaseConnection>, game_id: &str, player_id: Option<Uuid>) -> Option<Color> {
+    let (db, game_id, player_id) = (db?, Uuid::parse_str(game_id).ok()?, player_id?);
+    let game = match GameService::find_game(db, game_id).await {
+        Ok(game) => game?,
+        Err(e) => {
+            tracing::warn!(event = "error", game_id = %game_id, error = %e, "failed to look up websocket seat");
+            return None;
+        }
+    };
+    if game.white_player == player_id {
+        Some(Color::White)
+    } else if game.black_player == player_id {
+        Some(Color::Black)
+    } else {
+        None
+    }
+}
+
 /// WebSocket route handler with auth
 pub async fn ws_route(
     req: HttpRequest,
     stream: web::Payload,
     lobby: web::Data<Addr<LobbyState>>,
+    config: web::Data<AppConfig>,
+    db: Option<web::Data<DatabaseConnection>>,
 ) -> Result<HttpResponse, Error> {
+    // Websockets are not covered by CORS, so other sites are refused here
+    let origin = req.headers().get(header::ORIGIN).map(|h| h.to_str().unwrap_or(""));
+    if !config.ws_origin_allowed(origin) {
+        return Err(ErrorForbidden("Origin not allowed"));
+    }
+
     // Validate JWT token from header
     let auth_header = req.headers().get("Authorization").and_then(|h| h.to_str().ok());
-    if let Some(header) = auth_header {
+    let claims = if let Some(header) = auth_header {
         if !header.starts_with("Bearer ") {
             return Err(ErrorUnauthorized("Invalid authorization token format"));
         }
@@ -188,25 +560,41 @@ pub async fn ws_route(
         let secret = env::var("JWT_SECRET_KEY").unwrap_or_else(|_| "development_secret_key".to_string());
         let validation = Validation::new(Algorithm::HS256);
         decode::<Claims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation)
-            .map_err(|_| ErrorUnauthorized("Invalid or expired token"))?;
+            .map_err(|_| ErrorUnauthorized("Invalid or expired token"))?
+            .claims
     } else {
         return Err(ErrorUnauthorized("Missing authorization token"));
-    }
+    };
 
+    let params = web::Query::<WsParams>::from_query(req.query_string())
+        .map(web::Query::into_inner)
+        .unwrap_or_default();
     let game_id = req.match_info().get("game_id").unwrap_or("").to_string();
-    ws::start(
-        WsSession { game_id, lobby: lobby.get_ref().clone(), hb: std::time::Instant::now() },
-        &req,
-        stream,
-    )
+    let mut session = WsSession::new(game_id.clone(), lobby.get_ref().clone(), config.ws_heartbeat());
+    if let Some(player_id) = claims.player_id {
+        session = session.for_player(player_id);
+    }
+    if params.spectate {
+        session = session.spectating();
+    } else if let Some(color) = seat_of(db.as_ref().map(|db| db.get_ref()), &game_id, claims.player_id).await {
+        session = session.playing(color);
+    }
+    ws::start(session, &req, stream)
 }
 
 // Unit tests for LobbyState and session
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pubsub::PubSubError;
     use actix::prelude::*;
-    use tokio::sync::mpsc::unbounded_channel;
+    use actix_web::error::PayloadError;
+    use actix_web::web::Bytes;
+    use futures_util::StreamExt;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
     struct TestRecipient {
         tx: tokio::sync::mpsc::UnboundedSender<WsMessage>,
@@ -224,6 +612,151 @@ mod tests {
         }
     }
 
+    /// In-process stand-in for Redis: every subscriber sees every publish
+    #[derive(Default)]
+    struct MockPubSub {
+        subscribers: Mutex<Vec<UnboundedSender<(String, String)>>>,
+    }
+
+    #[async_trait]
+    impl LobbyPubSub for MockPubSub {
+        async fn publish(&self, channel: String, payload: String) -> Result<(), PubSubError> {
+            for subscriber in self.subscribers.lock().unwrap().iter() {
+                let _ = subscriber.send((channel.clone(), payload.clone()));
+            }
+            Ok(())
+        }
+
+        async fn subscribe(&self) -> Result<UnboundedReceiver<(String, String)>, PubSubError> {
+            let (tx, rx) = unbounded_channel();
+            self.subscribers.lock().unwrap().push(tx);
+            Ok(rx)
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_broadcast_reaches_clients_on_other_instance() {
+        let bus = Arc::new(MockPubSub::default());
+        let lobby_a = LobbyState::with_pubsub(bus.clone()).start();
+        let lobby_b = LobbyState::with_pubsub(bus.clone()).start();
+        let (tx_a, mut rx_a) = unbounded_channel();
+        let (tx_b, mut rx_b) = unbounded_channel();
+        let game_id = "game456".to_string();
+        lobby_a
+            .send(Connect { game_id: game_id.clone(), addr: TestRecipient { tx: tx_a }.start().recipient(), spectator: false })
+            .await
+            .unwrap();
+        lobby_b
+            .send(Connect { game_id: game_id.clone(), addr: TestRecipient { tx: tx_b }.start().recipient(), spectator: false })
+            .await
+            .unwrap();
+
+        let msg = WsMessage::Clock { white: 30, black: 45 };
+        lobby_b.send(Broadcast { game_id: game_id.clone(), message: msg.clone() }).await.unwrap();
+
+        let remote = tokio::time::timeout(Duration::from_secs(1), rx_a.recv()).await.unwrap();
+        assert_eq!(remote, Some(msg.clone()));
+        assert_eq!(rx_b.recv().await, Some(msg));
+        // The publishing instance ignores its own message coming back from the bus
+        assert!(tokio::time::timeout(Duration::from_millis(100), rx_b.recv()).await.is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_session_times_out_after_configured_heartbeat() {
+        tokio::time::pause();
+        let heartbeat = HeartbeatConfig {
+            interval: Duration::from_secs(30),
+            client_timeout: Duration::from_secs(90),
+        };
+        let session = WsSession::new("game789".to_string(), LobbyState::new().start(), heartbeat);
+        let silent_client = futures_util::stream::pending::<Result<Bytes, PayloadError>>();
+        let mut frames = Box::pin(ws::WebsocketContext::create(session, silent_client));
+
+        let started = Instant::now();
+        let mut sent = Vec::new();
+        while let Some(chunk) = frames.next().await {
+            sent.extend_from_slice(&chunk.unwrap());
+        }
+
+        // The session pinged (an empty ping frame is 0x89 0x00) until the
+        // first tick past the 90s timeout, then closed the stream
+        let elapsed = started.elapsed();
+        assert!(elapsed > heartbeat.client_timeout, "closed early after {:?}", elapsed);
+        assert!(elapsed < heartbeat.client_timeout + heartbeat.interval, "closed late after {:?}", elapsed);
+        assert!(!sent.is_empty());
+        assert!(sent.chunks(2).all(|frame| frame == [0x89, 0x00]));
+    }
+
+    #[test]
+    fn test_heartbeat_timeout_must_exceed_interval() {
+        let config = |interval, timeout| AppConfig {
+            ws_heartbeat_interval_secs: interval,
+            ws_client_timeout_secs: timeout,
+            ..AppConfig::from_env()
+        };
+
+        assert!(config(15, 25).ws_heartbeat_misconfiguration().is_none());
+        assert!(config(30, 30).ws_heartbeat_misconfiguration().is_some());
+        assert!(config(0, 25).ws_heartbeat_misconfiguration().is_some());
+        assert_eq!(
+            config(60, 180).ws_heartbeat(),
+            HeartbeatConfig {
+                interval: Duration::from_secs(60),
+                client_timeout: Duration::from_secs(180),
+            }
+        );
+    }
+
+    fn handshake(origin: Option<&str>) -> actix_web::test::TestRequest {
+        let token = security::JwtService::new("development_secret_key".to_string(), 3600)
+            .generate_token(1, "alice")
+            .unwrap();
+        let mut req = actix_web::test::TestRequest::get()
+            .uri("/ws/game123")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .insert_header((header::UPGRADE, "websocket"))
+            .insert_header((header::CONNECTION, "upgrade"))
+            .insert_header((header::SEC_WEBSOCKET_VERSION, "13"))
+            .insert_header((header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ=="));
+        if let Some(origin) = origin {
+            req = req.insert_header((header::ORIGIN, origin));
+        }
+        req
+    }
+
+    #[actix_web::test]
+    async fn test_ws_origin_checked_against_allowlist() {
+        use actix_web::{test, App};
+
+        let config = AppConfig {
+            ws_allowed_origins: AppConfig::parse_origins("https://xlmate.app, https://admin.xlmate.app"),
+            ..AppConfig::from_env()
+        };
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(LobbyState::new().start()))
+                .app_data(web::Data::new(config))
+                .route("/ws/{game_id}", web::get().to(ws_route)),
+        )
+        .await;
+
+        let resp = test::call_service(&app, handshake(Some("https://xlmate.app")).to_request()).await;
+        assert_eq!(resp.status(), 101);
+
+        let resp = test::call_service(&app, handshake(Some("https://evil.example")).to_request()).await;
+        assert_eq!(resp.status(), 403);
+    }
+
+    #[test]
+    fn test_any_origin_allowed_without_allowlist() {
+        let config = AppConfig {
+            ws_allowed_origins: AppConfig::parse_origins(" , "),
+            ..AppConfig::from_env()
+        };
+        assert!(config.ws_allowed_origins.is_none());
+        assert!(config.ws_origin_allowed(Some("https://anywhere.example")));
+    }
+
     #[actix_web::test]
     async fn test_broadcast_to_two_clients() {
         let lobby = LobbyState::new().start();
@@ -232,8 +765,8 @@ mod tests {
         let recipient1 = TestRecipient { tx: tx1 }.start().recipient();
         let recipient2 = TestRecipient { tx: tx2 }.start().recipient();
         let game_id = "game123".to_string();
-        lobby.send(Connect { game_id: game_id.clone(), addr: recipient1.clone() }).await.unwrap();
-        lobby.send(Connect { game_id: game_id.clone(), addr: recipient2.clone() }).await.unwrap();
+        lobby.send(Connect { game_id: game_id.clone(), addr: recipient1.clone(), spectator: false }).await.unwrap();
+        lobby.send(Connect { game_id: game_id.clone(), addr: recipient2.clone(), spectator: false }).await.unwrap();
         let msg = WsMessage::Clock { white: 60, black: 60 };
         lobby.send(Broadcast { game_id: game_id.clone(), message: msg.clone() }).await.unwrap();
         let received1 = rx1.recv().await.unwrap();
@@ -241,4 +774,89 @@ mod tests {
         assert_eq!(received1, msg);
         assert_eq!(received2, msg);
     }
+
+    #[actix_web::test]
+    async fn test_spectator_count_excludes_players() {
+        let lobby = LobbyState::new().start();
+        let game_id = "game321".to_string();
+        let (tx_player, mut rx_player) = unbounded_channel();
+        let player = TestRecipient { tx: tx_player }.start().recipient();
+        lobby.send(Connect { game_id: game_id.clone(), addr: player, spectator: false }).await.unwrap();
+
+        let mut watchers = Vec::new();
+        for _ in 0..2 {
+            let (tx, rx) = unbounded_channel();
+            let addr = TestRecipient { tx }.start().recipient();
+            lobby.send(Connect { game_id: game_id.clone(), addr: addr.clone(), spectator: true }).await.unwrap();
+            watchers.push((addr, rx));
+        }
+
+        // Joining as a player announced nothing; each spectator bumped the count
+        assert_eq!(rx_player.recv().await, Some(WsMessage::SpectatorCount { count: 1 }));
+        assert_eq!(rx_player.recv().await, Some(WsMessage::SpectatorCount { count: 2 }));
+        assert_eq!(watchers[1].1.recv().await, Some(WsMessage::SpectatorCount { count: 2 }));
+
+        let (addr, _) = watchers.remove(0);
+        lobby.send(Disconnect { game_id: game_id.clone(), addr }).await.unwrap();
+        assert_eq!(rx_player.recv().await, Some(WsMessage::SpectatorCount { count: 1 }));
+    }
+
+    fn submit(game_id: &str, color: Option<Color>, from: &str, to: &str) -> SubmitMove {
+        SubmitMove {
+            game_id: game_id.to_string(),
+            color,
+            from: from.to_string(),
+            to: to.to_string(),
+            promotion: None,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_moves_are_checked_against_the_senders_color() {
+        let metrics = Arc::new(Metrics::default());
+        let lobby = LobbyState::new().with_metrics(metrics.clone()).start();
+        let (tx, mut rx) = unbounded_channel();
+        let game_id = "game-moves";
+        lobby
+            .send(Connect { game_id: game_id.to_string(), addr: TestRecipient { tx }.start().recipient(), spectator: false })
+            .await
+            .unwrap();
+
+        let (white, black) = (Some(Color::White), Some(Color::Black));
+        assert_eq!(lobby.send(submit(game_id, None, "e2", "e4")).await.unwrap(), Err(MoveRejection::NotInRoom));
+        assert_eq!(lobby.send(submit(game_id, black, "e7", "e5")).await.unwrap(), Err(MoveRejection::OutOfTurn));
+        // Black cannot move white's pieces either
+        assert_eq!(lobby.send(submit(game_id, black, "e2", "e4")).await.unwrap(), Err(MoveRejection::OutOfTurn));
+        assert_eq!(lobby.send(submit(game_id, white, "e2", "e5")).await.unwrap(), Err(MoveRejection::Illegal));
+
+        assert_eq!(lobby.send(submit(game_id, white, "g1", "f3")).await.unwrap(), Ok(()));
+        match rx.recv().await {
+            Some(WsMessage::Move { from, to, san, fen, promotion }) => {
+                assert_eq!((from.as_str(), to.as_str(), san.as_str(), promotion), ("g1", "f3", "Nf3", None));
+                assert_eq!(fen, "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1");
+            }
+            other => panic!("expected the move to be broadcast, got {:?}", other),
+        }
+        // Now it is black's turn, and white has to wait
+        assert_eq!(lobby.send(submit(game_id, white, "e2", "e4")).await.unwrap(), Err(MoveRejection::OutOfTurn));
+        assert_eq!(lobby.send(submit(game_id, black, "e7", "e5")).await.unwrap(), Ok(()));
+
+        assert_eq!(metrics.rejected_moves(MoveRejection::NotInRoom), 1);
+        assert_eq!(metrics.rejected_moves(MoveRejection::OutOfTurn), 3);
+        assert_eq!(metrics.rejected_moves(MoveRejection::Illegal), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_position_is_dropped_with_the_last_session() {
+        let lobby = LobbyState::new().start();
+        let game_id = "game-left";
+        let (tx, _rx) = unbounded_channel();
+        let addr = TestRecipient { tx }.start().recipient();
+        lobby.send(Connect { game_id: game_id.to_string(), addr: addr.clone(), spectator: false }).await.unwrap();
+        assert_eq!(lobby.send(submit(game_id, Some(Color::White), "e2", "e4")).await.unwrap(), Ok(()));
+
+        lobby.send(Disconnect { game_id: game_id.to_string(), addr }).await.unwrap();
+        // Forgotten, so the game is back at the starting position
+        assert_eq!(lobby.send(submit(game_id, Some(Color::White), "e2", "e4")).await.unwrap(), Ok(()));
+    }
 }
@@ -0,0 +1,34 @@
+//! Prints the perft divide of a position, one root move per line, in the
+//! `move: nodes` format most engines use.
+//!
+//! Usage: `cargo run --bin perft -- "<fen>" <depth>`
+
+use chess::bitboard::position::Position;
+use std::env;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() != 2 {
+        eprintln!("usage: perft \"<fen>\" <depth>");
+        process::exit(2);
+    }
+
+    let position = Position::from_fen(&args[0]).unwrap_or_else(|| {
+        eprintln!("invalid FEN: {}", args[0]);
+        process::exit(2);
+    });
+    let depth: u32 = args[1].parse().unwrap_or_else(|_| {
+        eprintln!("invalid depth: {}", args[1]);
+        process::exit(2);
+    });
+
+    let mut divide = position.perft_divide(depth);
+    divide.sort_by_key(|(mv, _)| mv.uci());
+    for (mv, nodes) in &divide {
+        println!("{}: {}", mv.uci(), nodes);
+    }
+    println!();
+    println!("Moves: {}", divide.len());
+    println!("Nodes: {}", divide.iter().map(|(_, nodes)| nodes).sum::<u64>());
+}
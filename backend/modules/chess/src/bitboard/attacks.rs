@@ -0,0 +1,90 @@
+use super::board::{Bitboard, Color, Square};
+
+const KNIGHT_DELTAS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_DELTAS: [(i8, i8); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+/// The square `(file, rank)` steps away from `s`, if it is still on the board.
+fn offset(s: Square, file_step: i8, rank_step: i8) -> Option<Square> {
+    let file = (s.value % 8) as i8 + file_step;
+    let rank = (s.value / 8) as i8 + rank_step;
+    if (0..8).contains(&file) && (0..8).contains(&rank) {
+        Some(Square {
+            value: (rank * 8 + file) as u8,
+        })
+    } else {
+        None
+    }
+}
+
+fn step_attacks(s: Square, deltas: &[(i8, i8)]) -> Bitboard {
+    deltas
+        .iter()
+        .filter_map(|&(df, dr)| offset(s, df, dr))
+        .fold(Bitboard::EMPTY, |acc, target| acc | target.bitboard())
+}
+
+/// Squares reached by sliding from `s` in each direction, stopping at (and
+/// including) the first occupied square.
+fn slider_attacks(s: Square, occupied: Bitboard, directions: &[(i8, i8)]) -> Bitboard {
+    let mut attacks = Bitboard::EMPTY;
+    for &(df, dr) in directions {
+        let mut current = s;
+        while let Some(next) = offset(current, df, dr) {
+            attacks = attacks | next.bitboard();
+            if (occupied & next.bitboard()).0 != 0 {
+                break;
+            }
+            current = next;
+        }
+    }
+    attacks
+}
+
+pub fn knight_attacks(s: Square) -> Bitboard {
+    step_attacks(s, &KNIGHT_DELTAS)
+}
+
+pub fn king_attacks(s: Square) -> Bitboard {
+    step_attacks(s, &KING_DELTAS)
+}
+
+/// Squares a pawn of `color` on `s` attacks (diagonally forward).
+pub fn pawn_attacks(color: Color, s: Square) -> Bitboard {
+    let forward = match color {
+        Color::White => 1,
+        Color::Black => -1,
+    };
+    step_attacks(s, &[(-1, forward), (1, forward)])
+}
+
+pub fn rook_attacks(s: Square, occupied: Bitboard) -> Bitboard {
+    slider_attacks(s, occupied, &ROOK_DIRECTIONS)
+}
+
+pub fn bishop_attacks(s: Square, occupied: Bitboard) -> Bitboard {
+    slider_attacks(s, occupied, &BISHOP_DIRECTIONS)
+}
+
+pub fn queen_attacks(s: Square, occupied: Bitboard) -> Bitboard {
+    rook_attacks(s, occupied) | bishop_attacks(s, occupied)
+}
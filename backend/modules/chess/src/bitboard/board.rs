@@ -1,9 +1,13 @@
 
 use std::collections::HashMap;
+use std::fmt;
 use std::ops::{BitAnd, BitOr, BitXor, Not};
 
+use super::attacks::{bishop_attacks, king_attacks, knight_attacks, pawn_attacks, queen_attacks, rook_attacks};
+use super::position::Move;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Bitboard(pub u64);
 
 impl Bitboard {
@@ -25,6 +29,10 @@ impl Bitboard {
         self.0.count_ones()
     }
 
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
     /// Convert the bitboard to a vector of squares.
     pub fn to_squares(self) -> Vec<Square> {
         let mut squares = Vec::new();
@@ -37,9 +45,25 @@ impl Bitboard {
         squares
     }
 
+    /// Mirrors the squares across the middle of the board, so rank 1 becomes rank 8.
+    pub fn flip_vertical(self) -> Bitboard {
+        Bitboard(self.0.swap_bytes())
+    }
+
+    /// Whether two or more bits are set, without counting them: clearing the
+    /// lowest set bit leaves something behind.
+    pub fn more_than_one(self) -> bool {
+        self.0 & self.0.wrapping_sub(1) != 0
+    }
+
+    /// Whether exactly one bit is set.
+    pub fn is_single(self) -> bool {
+        self.0 != 0 && !self.more_than_one()
+    }
+
     /// If exactly one bit is set, returns that square.
     pub fn single_square(self) -> Option<Square> {
-        if self.0 != 0 && (self.0 & (self.0 - 1)) == 0 {
+        if self.is_single() {
             Some(Square {
                 value: self.0.trailing_zeros() as u8,
             })
@@ -104,6 +128,110 @@ pub enum Role {
     King,
 }
 
+/// Error returned when a string does not name a color.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError(pub String);
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid color {:?}, expected \"white\" or \"black\"", self.0)
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Color::White => "white",
+            Color::Black => "black",
+        })
+    }
+}
+
+impl std::str::FromStr for Color {
+    type Err = ParseColorError;
+
+    /// Parses `white` or `black`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "white" => Ok(Color::White),
+            "black" => Ok(Color::Black),
+            _ => Err(ParseColorError(s.to_string())),
+        }
+    }
+}
+
+impl Role {
+    pub const ALL: [Role; 6] = [Role::Pawn, Role::Knight, Role::Bishop, Role::Rook, Role::Queen, Role::King];
+
+    /// Lowercase FEN letter of the role.
+    pub fn char(self) -> char {
+        match self {
+            Role::Pawn => 'p',
+            Role::Knight => 'n',
+            Role::Bishop => 'b',
+            Role::Rook => 'r',
+            Role::Queen => 'q',
+            Role::King => 'k',
+        }
+    }
+
+    /// Role for a FEN letter of either case.
+    pub fn from_char(c: char) -> Option<Role> {
+        match c.to_ascii_lowercase() {
+            'p' => Some(Role::Pawn),
+            'n' => Some(Role::Knight),
+            'b' => Some(Role::Bishop),
+            'r' => Some(Role::Rook),
+            'q' => Some(Role::Queen),
+            'k' => Some(Role::King),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned when a string does not name a role.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRoleError(pub String);
+
+impl fmt::Display for ParseRoleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid role {:?}, expected a piece name or FEN letter", self.0)
+    }
+}
+
+impl std::error::Error for ParseRoleError {}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Role::Pawn => "pawn",
+            Role::Knight => "knight",
+            Role::Bishop => "bishop",
+            Role::Rook => "rook",
+            Role::Queen => "queen",
+            Role::King => "king",
+        })
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = ParseRoleError;
+
+    /// Parses a piece name such as `knight` or a FEN letter such as `N`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Role::from_char(c).ok_or_else(|| ParseRoleError(s.to_string()));
+        }
+        Role::ALL
+            .into_iter()
+            .find(|role| role.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ParseRoleError(s.to_string()))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Square {
     pub value: u8, // 0..63 representing the square.
@@ -114,6 +242,36 @@ impl Square {
     pub fn bitboard(self) -> Bitboard {
         Bitboard(1u64 << self.value)
     }
+
+    /// Builds a square from 0-based file (a = 0) and rank (1 = 0) indices.
+    pub fn from_coords(file: u8, rank: u8) -> Option<Square> {
+        if file < 8 && rank < 8 {
+            Some(Square { value: rank * 8 + file })
+        } else {
+            None
+        }
+    }
+
+    pub fn file(self) -> u8 {
+        self.value % 8
+    }
+
+    pub fn rank(self) -> u8 {
+        self.value / 8
+    }
+
+    /// Algebraic name such as `e4`.
+    pub fn name(self) -> String {
+        format!("{}{}", (b'a' + self.file()) as char, self.rank() + 1)
+    }
+
+    /// Parses an algebraic name such as `e4`.
+    pub fn from_name(name: &str) -> Option<Square> {
+        match name.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Square::from_coords(file - b'a', rank - b'1'),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -126,11 +284,84 @@ pub struct Piece {
     pub role: Role,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CastlingSide {
+    KingSide,
+    QueenSide,
+}
+
+impl CastlingSide {
+    /// File the king lands on.
+    pub fn king_to_file(self) -> u8 {
+        match self {
+            CastlingSide::KingSide => 6,
+            CastlingSide::QueenSide => 2,
+        }
+    }
+
+    /// File the rook lands on.
+    pub fn rook_to_file(self) -> u8 {
+        match self {
+            CastlingSide::KingSide => 5,
+            CastlingSide::QueenSide => 3,
+        }
+    }
+
+    /// File the rook starts on.
+    pub fn rook_from_file(self) -> u8 {
+        match self {
+            CastlingSide::KingSide => 7,
+            CastlingSide::QueenSide => 0,
+        }
+    }
+}
+
+/// Castling rights, stored as the bitboard of rooks that may still castle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CastlingRights(pub Bitboard);
+
+impl CastlingRights {
+    pub const NONE: CastlingRights = CastlingRights(Bitboard::EMPTY);
+    /// Both sides may castle either way (rooks on a1, h1, a8 and h8).
+    pub const ALL: CastlingRights = CastlingRights(Bitboard(0x8100000000000081));
+
+    /// The starting square of the rook used to castle on `side`.
+    pub fn rook_square(color: Color, side: CastlingSide) -> Square {
+        Square::from_coords(side.rook_from_file(), back_rank(color)).unwrap()
+    }
+
+    pub fn has(self, color: Color, side: CastlingSide) -> bool {
+        !(self.0 & Self::rook_square(color, side).bitboard()).is_empty()
+    }
+
+    /// Drops any right tied to a rook on one of `squares`.
+    pub fn without(self, squares: Bitboard) -> CastlingRights {
+        CastlingRights(self.0 & !squares)
+    }
+
+    /// Drops both rights of `color`, e.g. once its king has moved.
+    pub fn without_color(self, color: Color) -> CastlingRights {
+        let rank = match color {
+            Color::White => Bitboard::FIRST_RANK,
+            Color::Black => Bitboard::LAST_RANK,
+        };
+        self.without(rank)
+    }
+}
+
+/// Rank index of `color`'s back rank.
+fn back_rank(color: Color) -> u8 {
+    match color {
+        Color::White => 0,
+        Color::Black => 7,
+    }
+}
+
 /// A mapping of squares to pieces.
 pub type PieceMap = HashMap<Square, Piece>;
 
 /// Holds bitboards for each color.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ByColor {
     pub white: Bitboard,
     pub black: Bitboard,
@@ -191,8 +422,59 @@ impl ByColor {
     }
 }
 
+/// Number of pieces of each role.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RoleCounts {
+    pub pawn: u32,
+    pub knight: u32,
+    pub bishop: u32,
+    pub rook: u32,
+    pub queen: u32,
+    pub king: u32,
+}
+
+impl RoleCounts {
+    pub fn get(&self, role: Role) -> u32 {
+        match role {
+            Role::Pawn => self.pawn,
+            Role::Knight => self.knight,
+            Role::Bishop => self.bishop,
+            Role::Rook => self.rook,
+            Role::Queen => self.queen,
+            Role::King => self.king,
+        }
+    }
+
+    fn get_mut(&mut self, role: Role) -> &mut u32 {
+        match role {
+            Role::Pawn => &mut self.pawn,
+            Role::Knight => &mut self.knight,
+            Role::Bishop => &mut self.bishop,
+            Role::Rook => &mut self.rook,
+            Role::Queen => &mut self.queen,
+            Role::King => &mut self.king,
+        }
+    }
+}
+
+/// Piece counts for each color.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Material {
+    pub white: RoleCounts,
+    pub black: RoleCounts,
+}
+
+impl Material {
+    pub fn get(&self, color: Color) -> RoleCounts {
+        match color {
+            Color::White => self.white,
+            Color::Black => self.black,
+        }
+    }
+}
+
 /// Holds bitboards for each role.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ByRole {
     pub pawn: Bitboard,
     pub knight: Bitboard,
@@ -324,7 +606,8 @@ impl ByRole {
 }
 
 /// The main Board struct representing the chess board.
-#[derive(Debug, Clone, Copy)]
+/// Boards are equal when they hold the same pieces on the same squares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Board {
     pub occupied: Bitboard,
     pub by_color: ByColor,
@@ -395,6 +678,49 @@ impl Board {
         self.by_color.get(piece.color) & self.by_role.get(piece.role)
     }
 
+    /// Number of pieces of the given color and role.
+    pub fn count_of(&self, piece: Piece) -> u32 {
+        self.by_piece(piece).count()
+    }
+
+    /// Piece counts per role for each side.
+    pub fn material_map(&self) -> Material {
+        let counts = |color: Color| {
+            let mut counts = RoleCounts::default();
+            self.by_role.foreach(|role, bb| {
+                *counts.get_mut(role) = (bb & self.by_color.get(color)).count();
+            });
+            counts
+        };
+        Material {
+            white: counts(Color::White),
+            black: counts(Color::Black),
+        }
+    }
+
+    /// White's material advantage in pawns, counting minor pieces as 3,
+    /// rooks as 5 and queens as 9.
+    pub fn material_balance(&self) -> i32 {
+        let value = |counts: RoleCounts| {
+            (counts.pawn + 3 * counts.knight + 3 * counts.bishop + 5 * counts.rook + 9 * counts.queen) as i32
+        };
+        let material = self.material_map();
+        value(material.white) - value(material.black)
+    }
+
+    /// The board mirrored top to bottom with the colors swapped, so each
+    /// side's pieces stand where the other side's did.
+    pub fn flip_vertical(&self) -> Board {
+        Board {
+            occupied: self.occupied.flip_vertical(),
+            by_color: ByColor {
+                white: self.black().flip_vertical(),
+                black: self.white().flip_vertical(),
+            },
+            by_role: self.by_role.map(Bitboard::flip_vertical),
+        }
+    }
+
     /// Returns the role at a square, if any.
     pub fn role_at(&self, s: Square) -> Option<Role> {
         self.by_role.find_role(|b| (b.0 & s.bitboard().0) != 0)
@@ -432,16 +758,91 @@ impl Board {
     }
 
 
-    // ISSUE #1: Implement the `attackers` function.
-    pub fn attackers() -> Bitboard {
-        //Write your code here
-        Bitboard::EMPTY // Temporary placeholder
+    /// Returns the pieces of color `attacker` that attack `sq`, with sliders
+    /// blocked by `occupied`.
+    pub fn attackers(&self, sq: Square, attacker: Color, occupied: Bitboard) -> Bitboard {
+        let pieces = (knight_attacks(sq) & self.knights())
+            | (king_attacks(sq) & self.kings())
+            | (pawn_attacks(attacker.opposite(), sq) & self.pawns())
+            | (bishop_attacks(sq, occupied) & (self.bishops() | self.queens()))
+            | (rook_attacks(sq, occupied) & (self.rooks() | self.queens()));
+
+        pieces & self.color(attacker)
     }
 
-    /// Returns true if there is any attack on the square.
-    pub fn attacks() -> bool {
-         //Write your code here
-         false // Temporary placeholder
+    /// Returns true if any piece of color `by` attacks `sq`.
+    pub fn attacks(&self, sq: Square, by: Color, occ: Bitboard) -> bool {
+        !self.attackers(sq, by, occ).is_empty()
+    }
+
+    /// Every square attacked by a piece of color `by`, with sliders blocked by
+    /// the current occupancy.
+    pub fn attacked_squares(&self, by: Color) -> Bitboard {
+        let ours = self.color(by);
+        let mut attacked = Bitboard::EMPTY;
+        for sq in (self.pawns() & ours).to_squares() {
+            attacked = attacked | pawn_attacks(by, sq);
+        }
+        for sq in (self.knights() & ours).to_squares() {
+            attacked = attacked | knight_attacks(sq);
+        }
+        for sq in (self.bishops() & ours).to_squares() {
+            attacked = attacked | bishop_attacks(sq, self.occupied);
+        }
+        for sq in (self.rooks() & ours).to_squares() {
+            attacked = attacked | rook_attacks(sq, self.occupied);
+        }
+        for sq in (self.queens() & ours).to_squares() {
+            attacked = attacked | queen_attacks(sq, self.occupied);
+        }
+        for sq in (self.kings() & ours).to_squares() {
+            attacked = attacked | king_attacks(sq);
+        }
+        attacked
+    }
+
+    /// Returns true if `color` may castle on `side`: the right is still held,
+    /// king and rook are on their starting squares, every square between them
+    /// is empty in `occ`, and the king is not in check and does not pass
+    /// through or land on an attacked square.
+    pub fn can_castle(
+        &self,
+        color: Color,
+        side: CastlingSide,
+        rights: CastlingRights,
+        occ: Bitboard,
+    ) -> bool {
+        if !rights.has(color, side) {
+            return false;
+        }
+
+        let rank = back_rank(color);
+        let king = Square::from_coords(4, rank).unwrap();
+        let rook = CastlingRights::rook_square(color, side);
+        if self.piece_at(king) != Some(Piece { color, role: Role::King })
+            || self.piece_at(rook) != Some(Piece { color, role: Role::Rook })
+        {
+            return false;
+        }
+
+        let between = |a: u8, b: u8| {
+            (a.min(b) + 1..a.max(b))
+                .map(|file| Square::from_coords(file, rank).unwrap().bitboard())
+                .fold(Bitboard::EMPTY, |acc, bb| acc | bb)
+        };
+        if !(between(king.file(), rook.file()) & occ).is_empty() {
+            return false;
+        }
+
+        // The king itself must not shield the squares it moves through
+        let occ = occ & !king.bitboard();
+        let king_to = side.king_to_file();
+        let path = between(king.file(), king_to)
+            | king.bitboard()
+            | Square::from_coords(king_to, rank).unwrap().bitboard();
+        path.to_squares()
+            .into_iter()
+            .all(|sq| !self.attacks(sq, color.opposite(), occ))
     }
 
     // ISSUE #2: Implement the `slider_blockers` function.
@@ -565,6 +966,32 @@ impl Board {
         // For now, we'll just return the new board
         Some(new_board)
     }
+
+    /// The enemy piece `mv` would capture, or `None` for a quiet move. A pawn
+    /// moving diagonally onto an empty square captures en passant, taking the
+    /// pawn beside its origin. The board is left untouched.
+    pub fn captured_by(&self, mv: &Move) -> Option<Piece> {
+        let mover = self.piece_at(mv.orig)?;
+        let target = match self.piece_at(mv.dest) {
+            Some(piece) => piece,
+            None if mover.role == Role::Pawn && mv.orig.file() != mv.dest.file() => {
+                self.piece_at(Square::from_coords(mv.dest.file(), mv.orig.rank())?)?
+            }
+            None => return None,
+        };
+        (target.color != mover.color).then_some(target)
+    }
+
+    /// Moves the piece on `orig` to `dest`, removing any enemy piece standing there.
+    /// Returns `None` if `orig` is empty or `dest` holds a piece of the same color.
+    pub fn capture_move(&self, orig: Square, dest: Square) -> Option<Board> {
+        let piece = self.piece_at(orig)?;
+        if self.color_at(dest) == Some(piece.color) {
+            return None;
+        }
+
+        Some(self.discard_by_square(orig).put_or_replace(piece, dest))
+    }
     
     // Helper function to find slider blockers
     fn find_slider_blockers(board: &Board, our_king: Square, us: Color) -> Bitboard {
@@ -624,7 +1051,7 @@ impl Board {
                 
                 // Check if there's exactly one piece on the ray
                 let pieces_on_ray = ray & occupied_except_king;
-                if pieces_on_ray.count() == 1 {
+                if pieces_on_ray.is_single() {
                     // There's exactly one blocker
                     let blocker = pieces_on_ray & board.by_color.get(us);
                     if blocker.0 != 0 {
@@ -690,7 +1117,7 @@ impl Board {
                 
                 // Check if there's exactly one piece on the ray
                 let pieces_on_ray = ray & occupied_except_king;
-                if pieces_on_ray.count() == 1 {
+                if pieces_on_ray.is_single() {
                     // There's exactly one blocker
                     let blocker = pieces_on_ray & board.by_color.get(us);
                     if blocker.0 != 0 {
@@ -741,6 +1168,12 @@ impl Board {
         map_of_pieces
     }
 
+    /// Builds a board from a mapping of pieces; the inverse of `piece_map`.
+    pub fn from_piece_map(map: &HashMap<Square, Piece>) -> Board {
+        map.iter()
+            .fold(Board::empty(), |board, (&square, &piece)| board.put_or_replace(piece, square))
+    }
+
     /// Returns a mapping of pieces for a given color.
     pub fn pieces_of(&self, color: Color) -> HashMap<Square, Piece> {
         self.piece_map()
@@ -764,3 +1197,58 @@ impl Board {
     }
 }
 
+
+/// Error returned by [`BoardBuilder::build`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardBuildError {
+    /// A piece was placed on a string that does not name a square.
+    InvalidSquare(String),
+    /// A side has more than one king.
+    TooManyKings(Color),
+}
+
+impl fmt::Display for BoardBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardBuildError::InvalidSquare(name) => write!(f, "invalid square: {}", name),
+            BoardBuildError::TooManyKings(color) => write!(f, "{} has more than one king", color),
+        }
+    }
+}
+
+impl std::error::Error for BoardBuildError {}
+
+/// Places pieces on algebraic squares to set up a board, e.g.
+/// `BoardBuilder::new().piece(Color::White, Role::King, "e1").build()`.
+/// A later piece on the same square replaces the earlier one.
+#[derive(Debug, Clone, Default)]
+pub struct BoardBuilder {
+    pieces: Vec<(Piece, String)>,
+}
+
+impl BoardBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn piece(mut self, color: Color, role: Role, square: &str) -> Self {
+        self.pieces.push((Piece { color, role }, square.to_string()));
+        self
+    }
+
+    /// The board holding every placed piece. Fails on the first square that
+    /// does not parse, or when either side ends up with two kings.
+    pub fn build(self) -> Result<Board, BoardBuildError> {
+        let mut board = Board::empty();
+        for (piece, name) in self.pieces {
+            let square = Square::from_name(&name).ok_or(BoardBuildError::InvalidSquare(name))?;
+            board = board.put_or_replace(piece, square);
+        }
+        for color in [Color::White, Color::Black] {
+            if board.king_of(color).more_than_one() {
+                return Err(BoardBuildError::TooManyKings(color));
+            }
+        }
+        Ok(board)
+    }
+}
@@ -0,0 +1,81 @@
+use super::board::{Board, CastlingRights, Piece, Square};
+
+/// Information needed to undo a single applied move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UndoInfo {
+    pub orig: Square,
+    pub dest: Square,
+    pub moved: Piece,
+    pub captured: Option<Piece>,
+    /// Castling rights before the move.
+    pub castling_rights: CastlingRights,
+    /// En passant square before the move, if any.
+    pub ep_square: Option<Square>,
+}
+
+/// Stack of undo records for the moves applied to a board, so the most
+/// recent move can be taken back in O(1) without replaying the game.
+#[derive(Debug, Clone, Default)]
+pub struct MoveHistory {
+    entries: Vec<UndoInfo>,
+}
+
+impl MoveHistory {
+    pub fn new() -> Self {
+        MoveHistory { entries: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The undo record of the most recently applied move.
+    pub fn last(&self) -> Option<&UndoInfo> {
+        self.entries.last()
+    }
+
+    /// Applies `orig` -> `dest` via `Board::capture_move` and records what is
+    /// needed to undo it, including the rights in effect before the move.
+    pub fn apply(
+        &mut self,
+        board: &Board,
+        orig: Square,
+        dest: Square,
+        castling_rights: CastlingRights,
+        ep_square: Option<Square>,
+    ) -> Option<Board> {
+        let moved = board.piece_at(orig)?;
+        let captured = board.piece_at(dest);
+        let next = board.capture_move(orig, dest)?;
+
+        self.entries.push(UndoInfo {
+            orig,
+            dest,
+            moved,
+            captured,
+            castling_rights,
+            ep_square,
+        });
+
+        Some(next)
+    }
+
+    /// Undoes the most recent move on `board`, returning the restored board
+    /// together with its undo record (which carries the prior rights).
+    pub fn undo(&mut self, board: &Board) -> Option<(Board, UndoInfo)> {
+        let info = self.entries.pop()?;
+
+        let mut restored = board
+            .discard_by_square(info.dest)
+            .put_or_replace(info.moved, info.orig);
+        if let Some(captured) = info.captured {
+            restored = restored.put_or_replace(captured, info.dest);
+        }
+
+        Some((restored, info))
+    }
+}
@@ -1,2 +1,5 @@
 pub mod board;
-pub mod bitboard; 
\ No newline at end of file
+pub mod bitboard;
+pub mod history;
+pub mod attacks;
+pub mod position;
@@ -0,0 +1,541 @@
+use super::attacks::{bishop_attacks, king_attacks, knight_attacks, pawn_attacks, queen_attacks, rook_attacks};
+use super::board::{Bitboard, Board, CastlingRights, CastlingSide, Color, Piece, Role, Square};
+
+const BACK_RANK: [Role; 8] = [
+    Role::Rook,
+    Role::Knight,
+    Role::Bishop,
+    Role::Queen,
+    Role::King,
+    Role::Bishop,
+    Role::Knight,
+    Role::Rook,
+];
+const PROMOTION_ROLES: [Role; 4] = [Role::Queen, Role::Rook, Role::Bishop, Role::Knight];
+
+/// A move from `orig` to `dest`, with the promotion role for pawns reaching the last rank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub orig: Square,
+    pub dest: Square,
+    pub promotion: Option<Role>,
+}
+
+impl Move {
+    /// UCI notation such as `e2e4` or `e7e8q`.
+    pub fn uci(&self) -> String {
+        let promotion = match self.promotion {
+            Some(Role::Queen) => "q",
+            Some(Role::Rook) => "r",
+            Some(Role::Bishop) => "b",
+            Some(Role::Knight) => "n",
+            _ => "",
+        };
+        format!("{}{}{}", self.orig.name(), self.dest.name(), promotion)
+    }
+
+    /// Parses UCI notation such as `e2e4` or `e7e8q`.
+    pub fn from_uci(uci: &str) -> Option<Move> {
+        let orig = Square::from_name(uci.get(0..2)?)?;
+        let dest = Square::from_name(uci.get(2..4)?)?;
+        let promotion = match uci.get(4..)? {
+            "" => None,
+            "q" => Some(Role::Queen),
+            "r" => Some(Role::Rook),
+            "b" => Some(Role::Bishop),
+            "n" => Some(Role::Knight),
+            _ => return None,
+        };
+        Some(Move { orig, dest, promotion })
+    }
+}
+
+/// A board together with the side to move and the state needed to generate
+/// legal moves. Castling moves are encoded as the king moving two files.
+#[derive(Debug, Clone, Copy)]
+pub struct Position {
+    pub board: Board,
+    pub turn: Color,
+    pub castling_rights: CastlingRights,
+    pub ep_square: Option<Square>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position::new()
+    }
+}
+
+impl Position {
+    /// The standard starting position.
+    pub fn new() -> Position {
+        let mut board = Board::empty();
+        for (file, role) in BACK_RANK.iter().enumerate() {
+            let file = file as u8;
+            for (color, back, pawns) in [(Color::White, 0, 1), (Color::Black, 7, 6)] {
+                board = board
+                    .put_or_replace(Piece { color, role: *role }, Square::from_coords(file, back).unwrap())
+                    .put_or_replace(Piece { color, role: Role::Pawn }, Square::from_coords(file, pawns).unwrap());
+            }
+        }
+
+        Position {
+            board,
+            turn: Color::White,
+            castling_rights: CastlingRights::ALL,
+            ep_square: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+
+    /// Parses Forsyth-Edwards Notation. The move counters may be omitted and
+    /// default to `0 1`. Returns `None` for malformed input or a position
+    /// without exactly one king per side.
+    pub fn from_fen(fen: &str) -> Option<Position> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if !(4..=6).contains(&fields.len()) {
+            return None;
+        }
+
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return None;
+        }
+        let mut board = Board::empty();
+        for (i, rank_str) in ranks.iter().enumerate() {
+            let rank = 7 - i as u8;
+            let mut file = 0u8;
+            for c in rank_str.chars() {
+                if let Some(empty) = c.to_digit(10).filter(|n| (1..=8).contains(n)) {
+                    file += empty as u8;
+                    continue;
+                }
+                let role = Role::from_char(c)?;
+                let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+                board = board.put_or_replace(Piece { color, role }, Square::from_coords(file, rank)?);
+                file += 1;
+            }
+            if file != 8 {
+                return None;
+            }
+        }
+        for color in [Color::White, Color::Black] {
+            if board.count_of(Piece { color, role: Role::King }) != 1 {
+                return None;
+            }
+        }
+
+        let turn = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return None,
+        };
+
+        let mut castling_rights = CastlingRights::NONE;
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                let (color, side) = match c {
+                    'K' => (Color::White, CastlingSide::KingSide),
+                    'Q' => (Color::White, CastlingSide::QueenSide),
+                    'k' => (Color::Black, CastlingSide::KingSide),
+                    'q' => (Color::Black, CastlingSide::QueenSide),
+                    _ => return None,
+                };
+                castling_rights = CastlingRights(castling_rights.0 | CastlingRights::rook_square(color, side).bitboard());
+            }
+        }
+
+        let ep_square = match fields[3] {
+            "-" => None,
+            name => Some(Square::from_name(name)?),
+        };
+        let halfmove_clock = fields.get(4).map_or(Some(0), |n| n.parse().ok())?;
+        let fullmove_number = fields.get(5).map_or(Some(1), |n| n.parse().ok())?;
+
+        Some(Position {
+            board,
+            turn,
+            castling_rights,
+            ep_square,
+            halfmove_clock,
+            fullmove_number,
+        })
+    }
+
+    /// Returns true if the side to move is in check.
+    pub fn is_check(&self) -> bool {
+        self.board
+            .king_pos_of(self.turn)
+            .is_some_and(|king| self.board.attacks(king, self.turn.opposite(), self.board.occupied))
+    }
+
+    pub fn is_checkmate(&self) -> bool {
+        self.is_check() && self.legal_moves().is_empty()
+    }
+
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_check() && self.legal_moves().is_empty()
+    }
+
+    /// Returns true once a hundred plies have passed without a capture or pawn move.
+    pub fn is_fifty_moves(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    /// The FEN fields that identify a position for repetition, without the move counters.
+    fn repetition_key(&self) -> String {
+        let fen = self.to_fen();
+        fen.rsplitn(3, ' ').nth(2).unwrap_or(&fen).to_string()
+    }
+
+    /// All legal moves for the side to move.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let us = self.turn;
+        let king = self.board.king_of(us);
+
+        // In double check no capture or block can stop both checkers, so only
+        // the king may move (and castling out of check is never allowed)
+        let in_double_check = king.single_square().is_some_and(|sq| {
+            self.board
+                .attackers(sq, us.opposite(), self.board.occupied)
+                .more_than_one()
+        });
+        let (movers, castling) = if in_double_check {
+            (king, Vec::new())
+        } else {
+            (Bitboard::ALL, self.castling_moves())
+        };
+
+        self.pseudo_legal_moves(movers)
+            .into_iter()
+            .filter(|mv| {
+                let after = self.play_unchecked(*mv);
+                after
+                    .board
+                    .king_pos_of(us)
+                    .is_none_or(|king| !after.board.attacks(king, us.opposite(), after.board.occupied))
+            })
+            .chain(castling)
+            .collect()
+    }
+
+    /// Legal moves of the piece on `orig`; empty if it is not ours or cannot move.
+    pub fn legal_moves_from(&self, orig: Square) -> Vec<Move> {
+        self.legal_moves()
+            .into_iter()
+            .filter(|mv| mv.orig == orig)
+            .collect()
+    }
+
+    /// Number of leaf nodes of the legal move tree `depth` plies deep.
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        self.legal_moves()
+            .into_iter()
+            .map(|mv| self.play_unchecked(mv).perft(depth - 1))
+            .sum()
+    }
+
+    /// Each legal root move with the perft count of its subtree, for comparing
+    /// move generation against a reference engine move by move.
+    pub fn perft_divide(&self, depth: u32) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+        self.legal_moves()
+            .into_iter()
+            .map(|mv| (mv, self.play_unchecked(mv).perft(depth - 1)))
+            .collect()
+    }
+
+    fn castling_moves(&self) -> Vec<Move> {
+        let us = self.turn;
+        let king = match self.board.king_pos_of(us) {
+            Some(king) => king,
+            None => return Vec::new(),
+        };
+
+        [CastlingSide::KingSide, CastlingSide::QueenSide]
+            .into_iter()
+            .filter(|side| {
+                self.board
+                    .can_castle(us, *side, self.castling_rights, self.board.occupied)
+            })
+            .filter_map(|side| {
+                Some(Move {
+                    orig: king,
+                    dest: Square::from_coords(side.king_to_file(), king.rank())?,
+                    promotion: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Plays `mv` if it is legal in this position.
+    pub fn play(&self, mv: Move) -> Option<Position> {
+        if self.legal_moves().contains(&mv) {
+            Some(self.play_unchecked(mv))
+        } else {
+            None
+        }
+    }
+
+    /// Standard Algebraic Notation of `mv`, such as `Nbd7`, `exd6`, `O-O` or
+    /// `e8=Q#`. `None` if the move is not legal in this position.
+    pub fn san(&self, mv: Move) -> Option<String> {
+        let after = self.play(mv)?;
+        let role = self.board.role_at(mv.orig)?;
+        let orig = mv.orig.name();
+
+        let mut san = if role == Role::King && mv.orig.file().abs_diff(mv.dest.file()) == 2 {
+            if mv.dest.file() > mv.orig.file() { "O-O" } else { "O-O-O" }.to_string()
+        } else {
+            let is_capture = self.board.is_occupied_square(mv.dest)
+                || (role == Role::Pawn && mv.orig.file() != mv.dest.file());
+            let mut san = String::new();
+            if role == Role::Pawn {
+                if is_capture {
+                    san.push_str(&orig[..1]);
+                }
+            } else {
+                san.push(role.char().to_ascii_uppercase());
+                // Name the origin file, rank or square when another piece of
+                // the same kind could also move there
+                let rivals: Vec<Square> = self
+                    .legal_moves()
+                    .into_iter()
+                    .filter(|other| other.dest == mv.dest && other.orig != mv.orig)
+                    .filter(|other| self.board.role_at(other.orig) == Some(role))
+                    .map(|other| other.orig)
+                    .collect();
+                if !rivals.is_empty() {
+                    if rivals.iter().all(|s| s.file() != mv.orig.file()) {
+                        san.push_str(&orig[..1]);
+                    } else if rivals.iter().all(|s| s.rank() != mv.orig.rank()) {
+                        san.push_str(&orig[1..]);
+                    } else {
+                        san.push_str(&orig);
+                    }
+                }
+            }
+            if is_capture {
+                san.push('x');
+            }
+            san.push_str(&mv.dest.name());
+            if let Some(promotion) = mv.promotion {
+                san.push('=');
+                san.push(promotion.char().to_ascii_uppercase());
+            }
+            san
+        };
+
+        if after.is_checkmate() {
+            san.push('#');
+        } else if after.is_check() {
+            san.push('+');
+        }
+        Some(san)
+    }
+
+    /// Pseudo-legal moves of our pieces on `movers`.
+    fn pseudo_legal_moves(&self, movers: Bitboard) -> Vec<Move> {
+        let us = self.turn;
+        let ours = self.board.color(us);
+        let occupied = self.board.occupied;
+        let mut moves = Vec::new();
+
+        for orig in (ours & movers).to_squares() {
+            let role = match self.board.role_at(orig) {
+                Some(role) => role,
+                None => continue,
+            };
+            let targets = match role {
+                Role::Pawn => {
+                    self.push_pawn_moves(orig, &mut moves);
+                    continue;
+                }
+                Role::Knight => knight_attacks(orig),
+                Role::Bishop => bishop_attacks(orig, occupied),
+                Role::Rook => rook_attacks(orig, occupied),
+                Role::Queen => queen_attacks(orig, occupied),
+                Role::King => king_attacks(orig),
+            } & !ours;
+
+            moves.extend(targets.to_squares().into_iter().map(|dest| Move {
+                orig,
+                dest,
+                promotion: None,
+            }));
+        }
+
+        moves
+    }
+
+    fn push_pawn_moves(&self, orig: Square, moves: &mut Vec<Move>) {
+        let us = self.turn;
+        let (forward, start_rank, last_rank) = match us {
+            Color::White => (1i8, 1, 7),
+            Color::Black => (-1i8, 6, 0),
+        };
+        let mut push = |dest: Square| {
+            if dest.rank() == last_rank {
+                moves.extend(PROMOTION_ROLES.iter().map(|role| Move {
+                    orig,
+                    dest,
+                    promotion: Some(*role),
+                }));
+            } else {
+                moves.push(Move { orig, dest, promotion: None });
+            }
+        };
+
+        let forward_square = |s: Square| Square::from_coords(s.file(), (s.rank() as i8 + forward) as u8);
+        if let Some(one) = forward_square(orig).filter(|s| !self.board.is_occupied_square(*s)) {
+            push(one);
+            if orig.rank() == start_rank {
+                if let Some(two) = forward_square(one).filter(|s| !self.board.is_occupied_square(*s)) {
+                    push(two);
+                }
+            }
+        }
+
+        let ep = self.ep_square.map_or(Bitboard::EMPTY, |s| s.bitboard());
+        let captures = pawn_attacks(us, orig) & (self.board.color(us.opposite()) | ep);
+        for dest in captures.to_squares() {
+            push(dest);
+        }
+    }
+
+    /// Applies a pseudo-legal move without checking that it leaves our king safe.
+    /// Searches use it with moves from [`Position::legal_moves`] to skip validating them twice.
+    pub fn play_unchecked(&self, mv: Move) -> Position {
+        let us = self.turn;
+        let piece = match self.board.piece_at(mv.orig) {
+            Some(piece) => piece,
+            None => return *self,
+        };
+        let is_pawn = piece.role == Role::Pawn;
+        let mut board = self.board;
+        let mut is_capture = board.is_occupied_square(mv.dest);
+
+        // En passant removes the pawn behind the destination square
+        if is_pawn && Some(mv.dest) == self.ep_square && !is_capture {
+            if let Some(victim) = Square::from_coords(mv.dest.file(), mv.orig.rank()) {
+                board = board.discard_by_square(victim);
+                is_capture = true;
+            }
+        }
+
+        let placed = Piece {
+            color: us,
+            role: mv.promotion.unwrap_or(piece.role),
+        };
+        board = board.discard_by_square(mv.orig).put_or_replace(placed, mv.dest);
+
+        // Castling also brings the rook over to the other side of the king
+        if piece.role == Role::King && mv.orig.file().abs_diff(mv.dest.file()) == 2 {
+            let side = if mv.dest.file() > mv.orig.file() {
+                CastlingSide::KingSide
+            } else {
+                CastlingSide::QueenSide
+            };
+            let rook_to = Square::from_coords(side.rook_to_file(), mv.orig.rank()).unwrap();
+            board = board
+                .discard_by_square(CastlingRights::rook_square(us, side))
+                .put_or_replace(Piece { color: us, role: Role::Rook }, rook_to);
+        }
+
+        let mut castling_rights = self
+            .castling_rights
+            .without(mv.orig.bitboard() | mv.dest.bitboard());
+        if piece.role == Role::King {
+            castling_rights = castling_rights.without_color(us);
+        }
+
+        let ep_square = if is_pawn && mv.orig.rank().abs_diff(mv.dest.rank()) == 2 {
+            Square::from_coords(mv.orig.file(), (mv.orig.rank() + mv.dest.rank()) / 2)
+        } else {
+            None
+        };
+
+        Position {
+            board,
+            turn: us.opposite(),
+            castling_rights,
+            ep_square,
+            halfmove_clock: if is_pawn || is_capture { 0 } else { self.halfmove_clock + 1 },
+            fullmove_number: self.fullmove_number + u32::from(us == Color::Black),
+        }
+    }
+
+    /// Forsyth-Edwards Notation for this position.
+    pub fn to_fen(&self) -> String {
+        let castling: String = [
+            ('K', Color::White, CastlingSide::KingSide),
+            ('Q', Color::White, CastlingSide::QueenSide),
+            ('k', Color::Black, CastlingSide::KingSide),
+            ('q', Color::Black, CastlingSide::QueenSide),
+        ]
+        .iter()
+        .filter(|(_, color, side)| self.castling_rights.has(*color, *side))
+        .map(|(c, _, _)| *c)
+        .collect();
+
+        format!(
+            "{} {} {} {} {} {}",
+            board_fen(&self.board),
+            if self.turn == Color::White { "w" } else { "b" },
+            if castling.is_empty() { "-".to_string() } else { castling },
+            self.ep_square.map_or("-".to_string(), |s| s.name()),
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
+
+/// Returns true if the last position of `history` has occurred at least three
+/// times in it. `history` holds every position of the game, oldest first.
+pub fn is_threefold_repetition(history: &[Position]) -> bool {
+    let current = match history.last() {
+        Some(position) => position.repetition_key(),
+        None => return false,
+    };
+    history
+        .iter()
+        .filter(|position| position.repetition_key() == current)
+        .count()
+        >= 3
+}
+
+/// The piece-placement field of a FEN string.
+pub fn board_fen(board: &Board) -> String {
+    let mut fen = String::new();
+    for rank in (0..8).rev() {
+        let mut empty = 0;
+        for file in 0..8 {
+            match board.piece_at(Square::from_coords(file, rank).unwrap()) {
+                Some(piece) => {
+                    if empty > 0 {
+                        fen.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    let c = piece.role.char();
+                    fen.push(if piece.color == Color::White { c.to_ascii_uppercase() } else { c });
+                }
+                None => empty += 1,
+            }
+        }
+        if empty > 0 {
+            fen.push_str(&empty.to_string());
+        }
+        if rank > 0 {
+            fen.push('/');
+        }
+    }
+    fen
+}
@@ -1,2 +1,3 @@
 pub mod bitboard;
-pub mod time_control; // Add this line
\ No newline at end of file
+pub mod time_control; // Add this line
+pub mod outcome;
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+/// How a decided game ended. Every other representation of a result (the
+/// database's `ResultSide`, the API's `GameResult`, the registry contract's
+/// winner address, tournament round results) converts to and from this one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Outcome {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl Outcome {
+    pub const ALL: [Outcome; 3] = [Outcome::WhiteWins, Outcome::BlackWins, Outcome::Draw];
+
+    /// White's score: 1 for a win, 0.5 for a draw, 0 for a loss.
+    pub fn white_score(self) -> f64 {
+        match self {
+            Outcome::WhiteWins => 1.0,
+            Outcome::BlackWins => 0.0,
+            Outcome::Draw => 0.5,
+        }
+    }
+
+    /// Black's score: 1 for a win, 0.5 for a draw, 0 for a loss.
+    pub fn black_score(self) -> f64 {
+        1.0 - self.white_score()
+    }
+
+    /// The same result seen with the colors swapped.
+    pub fn flipped(self) -> Outcome {
+        match self {
+            Outcome::WhiteWins => Outcome::BlackWins,
+            Outcome::BlackWins => Outcome::WhiteWins,
+            Outcome::Draw => Outcome::Draw,
+        }
+    }
+
+    /// Result of a game that names only its winner, as the registry contract
+    /// does. `None` when `winner` played neither side.
+    pub fn from_winner<T: PartialEq>(winner: &T, white: &T, black: &T) -> Option<Outcome> {
+        if winner == white {
+            Some(Outcome::WhiteWins)
+        } else if winner == black {
+            Some(Outcome::BlackWins)
+        } else {
+            None
+        }
+    }
+
+    /// Whichever of `white` and `black` won, or `None` for a draw.
+    pub fn winner<'a, T>(self, white: &'a T, black: &'a T) -> Option<&'a T> {
+        match self {
+            Outcome::WhiteWins => Some(white),
+            Outcome::BlackWins => Some(black),
+            Outcome::Draw => None,
+        }
+    }
+}
+
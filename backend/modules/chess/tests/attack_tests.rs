@@ -0,0 +1,86 @@
+use chess::bitboard::board::{Bitboard, Board, Color, Piece, Role, Square};
+use chess::bitboard::position::Position;
+
+fn square(name: &str) -> Square {
+    Square::from_name(name).unwrap()
+}
+
+#[test]
+fn test_attacked_square_returns_true() {
+    let board = Board::empty()
+        .put_or_replace(Piece { color: Color::Black, role: Role::Rook }, square("a8"))
+        .put_or_replace(Piece { color: Color::White, role: Role::Knight }, square("c3"));
+
+    assert!(board.attacks(square("a1"), Color::Black, board.occupied));
+    assert!(board.attacks(square("e4"), Color::White, board.occupied));
+    assert_eq!(
+        board.attackers(square("a1"), Color::Black, board.occupied),
+        square("a8").bitboard()
+    );
+}
+
+#[test]
+fn test_safe_square_returns_false() {
+    let board = Board::empty()
+        .put_or_replace(Piece { color: Color::Black, role: Role::Rook }, square("a8"))
+        .put_or_replace(Piece { color: Color::White, role: Role::Pawn }, square("a4"));
+
+    // The pawn blocks the rook's file
+    assert!(!board.attacks(square("a1"), Color::Black, board.occupied));
+    assert!(!board.attacks(square("b2"), Color::Black, board.occupied));
+    // Pieces only attack for their own color
+    assert!(!board.attacks(square("a1"), Color::White, board.occupied));
+    // With the blocker removed from the occupancy the file opens up
+    assert!(board.attacks(square("a1"), Color::Black, square("a8").bitboard()));
+}
+
+#[test]
+fn test_pawn_attacks_are_diagonal_and_forward() {
+    let board = Board::empty()
+        .put_or_replace(Piece { color: Color::White, role: Role::Pawn }, square("e4"))
+        .put_or_replace(Piece { color: Color::Black, role: Role::Pawn }, square("e5"));
+
+    assert!(board.attacks(square("d5"), Color::White, board.occupied));
+    assert!(board.attacks(square("f4"), Color::Black, board.occupied));
+    assert!(!board.attacks(square("e5"), Color::White, board.occupied));
+    assert!(!board.attacks(square("d3"), Color::White, board.occupied));
+}
+
+// The squares `by` attacks, asking `attacks` about each square in turn
+fn attacked_one_by_one(board: &Board, by: Color) -> Bitboard {
+    (0..64)
+        .map(|value| Square { value })
+        .filter(|&sq| board.attacks(sq, by, board.occupied))
+        .fold(Bitboard::EMPTY, |acc, sq| acc | sq.bitboard())
+}
+
+#[test]
+fn test_attacked_squares_matches_per_square_attacks() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+        "8/2k5/3p4/p2P1p2/P2P1P2/8/8/4K3 w - - 0 1",
+        "4k3/8/8/3q4/8/8/1B6/R3K2R b KQ - 0 1",
+    ];
+    for fen in fens {
+        let board = Position::from_fen(fen).unwrap().board;
+        for by in [Color::White, Color::Black] {
+            assert_eq!(board.attacked_squares(by), attacked_one_by_one(&board, by), "{} {:?}", fen, by);
+        }
+    }
+}
+
+#[test]
+fn test_attacked_squares_respects_blockers() {
+    let board = Board::empty()
+        .put_or_replace(Piece { color: Color::White, role: Role::Rook }, square("a1"))
+        .put_or_replace(Piece { color: Color::White, role: Role::Pawn }, square("a3"));
+
+    let attacked = board.attacked_squares(Color::White);
+    // The rook sees its own pawn but nothing behind it
+    assert!(attacked.to_squares().contains(&square("a3")));
+    assert!(!attacked.to_squares().contains(&square("a4")));
+    assert!(attacked.to_squares().contains(&square("h1")));
+    assert!(attacked.to_squares().contains(&square("b4")));
+    assert!(board.attacked_squares(Color::Black).is_empty());
+}
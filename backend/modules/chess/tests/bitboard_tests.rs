@@ -0,0 +1,32 @@
+use chess::bitboard::board::{Bitboard, Square};
+
+fn square(name: &str) -> Square {
+    Square::from_name(name).unwrap()
+}
+
+#[test]
+fn test_empty_bitboard_is_neither_single_nor_many() {
+    assert!(!Bitboard::EMPTY.is_single());
+    assert!(!Bitboard::EMPTY.more_than_one());
+    assert_eq!(Bitboard::EMPTY.single_square(), None);
+}
+
+#[test]
+fn test_one_bit_is_single() {
+    for name in ["a1", "e4", "h8"] {
+        let bb = square(name).bitboard();
+        assert!(bb.is_single(), "{}", name);
+        assert!(!bb.more_than_one(), "{}", name);
+        assert_eq!(bb.single_square(), Some(square(name)));
+    }
+}
+
+#[test]
+fn test_many_bits_are_more_than_one() {
+    let two = square("a1").bitboard() | square("h8").bitboard();
+    for bb in [two, Bitboard::CENTER, Bitboard::FIRST_RANK, Bitboard::ALL] {
+        assert!(bb.more_than_one(), "{:?}", bb);
+        assert!(!bb.is_single(), "{:?}", bb);
+        assert_eq!(bb.single_square(), None);
+    }
+}
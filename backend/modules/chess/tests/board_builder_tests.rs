@@ -0,0 +1,46 @@
+use chess::bitboard::board::{BoardBuildError, BoardBuilder, Color, Piece, Role, Square};
+use chess::bitboard::position::Position;
+
+fn sq(name: &str) -> Square {
+    Square::from_name(name).unwrap()
+}
+
+#[test]
+fn test_builder_places_pieces_on_named_squares() {
+    let board = BoardBuilder::new()
+        .piece(Color::White, Role::King, "e1")
+        .piece(Color::White, Role::Rook, "h1")
+        .piece(Color::Black, Role::King, "e8")
+        .piece(Color::Black, Role::Pawn, "d7")
+        .build()
+        .unwrap();
+
+    assert_eq!(board.nb_pieces(), 4);
+    assert_eq!(board.piece_at(sq("h1")), Some(Piece { color: Color::White, role: Role::Rook }));
+    assert_eq!(board.piece_at(sq("d7")), Some(Piece { color: Color::Black, role: Role::Pawn }));
+    assert_eq!(board.king_pos_of(Color::Black), Some(sq("e8")));
+    assert_eq!(board, Position::from_fen("4k3/3p4/8/8/8/8/8/4K2R w - - 0 1").unwrap().board);
+}
+
+#[test]
+fn test_builder_rejects_two_kings_of_a_side() {
+    let built = BoardBuilder::new()
+        .piece(Color::White, Role::King, "e1")
+        .piece(Color::White, Role::King, "d1")
+        .piece(Color::Black, Role::King, "e8")
+        .build();
+    assert_eq!(built, Err(BoardBuildError::TooManyKings(Color::White)));
+
+    // Replacing a king on its own square leaves only one
+    let replaced = BoardBuilder::new()
+        .piece(Color::White, Role::King, "e1")
+        .piece(Color::White, Role::King, "e1")
+        .build();
+    assert!(replaced.is_ok());
+}
+
+#[test]
+fn test_builder_rejects_invalid_squares() {
+    let built = BoardBuilder::new().piece(Color::White, Role::Queen, "i9").build();
+    assert_eq!(built, Err(BoardBuildError::InvalidSquare("i9".to_string())));
+}
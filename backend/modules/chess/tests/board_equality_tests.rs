@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use chess::bitboard::board::{Board, Color, Piece, Role, Square};
+use chess::bitboard::position::{Move, Position};
+
+fn board_after(moves: &[&str]) -> Board {
+    moves
+        .iter()
+        .fold(Position::new(), |position, uci| {
+            position.play(Move::from_uci(uci).unwrap()).unwrap()
+        })
+        .board
+}
+
+#[test]
+fn test_independently_built_boards_are_equal() {
+    let parsed = Position::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    assert_eq!(Position::new().board, parsed.board);
+
+    // Transpositions reach the same board through different move orders
+    assert_eq!(
+        board_after(&["g1f3", "g8f6", "b1c3"]),
+        board_after(&["b1c3", "g8f6", "g1f3"])
+    );
+
+    let placed = Board::empty()
+        .put_or_replace(Piece { color: Color::White, role: Role::King }, Square::from_name("e1").unwrap())
+        .put_or_replace(Piece { color: Color::Black, role: Role::King }, Square::from_name("e8").unwrap());
+    assert_eq!(placed, Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap().board);
+}
+
+#[test]
+fn test_differing_boards_are_not_equal() {
+    assert_ne!(Position::new().board, board_after(&["e2e4"]));
+
+    // Same occupancy, different piece on the square
+    let with_knight = Position::from_fen("4k3/8/8/8/8/8/8/3NK3 w - - 0 1").unwrap().board;
+    let with_bishop = Position::from_fen("4k3/8/8/8/8/8/8/3BK3 w - - 0 1").unwrap().board;
+    assert_eq!(with_knight.occupied, with_bishop.occupied);
+    assert_ne!(with_knight, with_bishop);
+
+    // Same roles, different colors
+    let black_knight = Position::from_fen("4k3/8/8/8/8/8/8/3nK3 w - - 0 1").unwrap().board;
+    assert_ne!(with_knight, black_knight);
+}
+
+#[test]
+fn test_boards_as_map_keys() {
+    let mut seen: HashMap<Board, u32> = HashMap::new();
+    for moves in [
+        &["g1f3", "g8f6", "b1c3"][..],
+        &["b1c3", "g8f6", "g1f3"][..],
+        &["e2e4"][..],
+    ] {
+        *seen.entry(board_after(moves)).or_default() += 1;
+    }
+
+    assert_eq!(seen.len(), 2);
+    assert_eq!(seen[&board_after(&["g1f3", "g8f6", "b1c3"])], 2);
+}
+
+#[test]
+fn test_piece_map_round_trip() {
+    for board in [
+        Position::new().board,
+        board_after(&["e2e4", "d7d5", "e4d5", "d8d5"]),
+        Board::empty(),
+    ] {
+        assert_eq!(Board::from_piece_map(&board.piece_map()), board);
+    }
+}
@@ -0,0 +1,95 @@
+use chess::bitboard::board::{Board, Color, Piece, Role, Square};
+use chess::bitboard::position::{Move, Position};
+
+#[test]
+fn test_pawn_captures_enemy_knight() {
+    let e4 = Square { value: 28 };
+    let d5 = Square { value: 35 };
+    let white_pawn = Piece { color: Color::White, role: Role::Pawn };
+    let black_knight = Piece { color: Color::Black, role: Role::Knight };
+
+    let board = Board::empty()
+        .put_or_replace(white_pawn, e4)
+        .put_or_replace(black_knight, d5);
+
+    let after = board.capture_move(e4, d5).expect("capture should succeed");
+
+    assert_eq!(after.piece_at(d5), Some(white_pawn));
+    assert_eq!(after.piece_at(e4), None);
+    assert_eq!(after.nb_pieces(), 1);
+    assert_eq!(after.knights().0, 0);
+    assert_eq!(after.black().0, 0);
+}
+
+#[test]
+fn test_capture_move_onto_empty_square() {
+    let e2 = Square { value: 12 };
+    let e4 = Square { value: 28 };
+    let white_pawn = Piece { color: Color::White, role: Role::Pawn };
+
+    let board = Board::empty().put_or_replace(white_pawn, e2);
+    let after = board.capture_move(e2, e4).expect("quiet move should succeed");
+
+    assert_eq!(after.piece_at(e4), Some(white_pawn));
+    assert_eq!(after.nb_pieces(), 1);
+}
+
+#[test]
+fn test_capture_move_rejects_friendly_destination() {
+    let e4 = Square { value: 28 };
+    let d5 = Square { value: 35 };
+    let white_pawn = Piece { color: Color::White, role: Role::Pawn };
+    let white_knight = Piece { color: Color::White, role: Role::Knight };
+
+    let board = Board::empty()
+        .put_or_replace(white_pawn, e4)
+        .put_or_replace(white_knight, d5);
+
+    assert!(board.capture_move(e4, d5).is_none());
+}
+
+#[test]
+fn test_capture_move_rejects_empty_origin() {
+    let e4 = Square { value: 28 };
+    let d5 = Square { value: 35 };
+    let black_knight = Piece { color: Color::Black, role: Role::Knight };
+
+    let board = Board::empty().put_or_replace(black_knight, d5);
+
+    assert!(board.capture_move(e4, d5).is_none());
+}
+
+#[test]
+fn test_captured_by_reports_the_piece_on_the_destination() {
+    let position = Position::from_fen("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2").unwrap();
+    let mv = Move::from_uci("e4d5").unwrap();
+
+    assert!(position.legal_moves().contains(&mv));
+    assert_eq!(
+        position.board.captured_by(&mv),
+        Some(Piece { color: Color::Black, role: Role::Pawn })
+    );
+    // Previewing a capture does not play it
+    assert_eq!(position.board.piece_at(mv.orig), Some(Piece { color: Color::White, role: Role::Pawn }));
+}
+
+#[test]
+fn test_captured_by_finds_the_en_passant_victim() {
+    let position = Position::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3").unwrap();
+    let mv = Move::from_uci("e5f6").unwrap();
+
+    assert!(position.legal_moves().contains(&mv));
+    assert_eq!(position.board.piece_at(mv.dest), None);
+    assert_eq!(
+        position.board.captured_by(&mv),
+        Some(Piece { color: Color::Black, role: Role::Pawn })
+    );
+}
+
+#[test]
+fn test_captured_by_is_none_for_quiet_moves() {
+    let position = Position::new();
+
+    assert_eq!(position.board.captured_by(&Move::from_uci("e2e4").unwrap()), None);
+    assert_eq!(position.board.captured_by(&Move::from_uci("g1f3").unwrap()), None);
+}
@@ -0,0 +1,86 @@
+use chess::bitboard::board::{Board, CastlingRights, CastlingSide, Color, Piece, Role, Square};
+use chess::bitboard::position::{Move, Position};
+
+fn square(name: &str) -> Square {
+    Square::from_name(name).unwrap()
+}
+
+fn piece(color: Color, role: Role) -> Piece {
+    Piece { color, role }
+}
+
+/// White king and both rooks on their starting squares, black king on e8.
+fn castling_board() -> Board {
+    Board::empty()
+        .put_or_replace(piece(Color::White, Role::King), square("e1"))
+        .put_or_replace(piece(Color::White, Role::Rook), square("a1"))
+        .put_or_replace(piece(Color::White, Role::Rook), square("h1"))
+        .put_or_replace(piece(Color::Black, Role::King), square("e8"))
+}
+
+#[test]
+fn test_castling_allowed_when_path_is_clear_and_safe() {
+    let board = castling_board();
+    let rights = CastlingRights::ALL;
+
+    assert!(board.can_castle(Color::White, CastlingSide::KingSide, rights, board.occupied));
+    assert!(board.can_castle(Color::White, CastlingSide::QueenSide, rights, board.occupied));
+}
+
+#[test]
+fn test_castling_rejected_through_check() {
+    // Rook on f8 attacks f1, which the king must cross to castle short
+    let board = castling_board().put_or_replace(piece(Color::Black, Role::Rook), square("f8"));
+    let rights = CastlingRights::ALL;
+
+    assert!(!board.can_castle(Color::White, CastlingSide::KingSide, rights, board.occupied));
+    assert!(board.can_castle(Color::White, CastlingSide::QueenSide, rights, board.occupied));
+}
+
+#[test]
+fn test_castling_rejected_out_of_or_into_check() {
+    let rights = CastlingRights::ALL;
+
+    let in_check = castling_board()
+        .discard_by_square(square("e8"))
+        .put_or_replace(piece(Color::Black, Role::King), square("a8"))
+        .put_or_replace(piece(Color::Black, Role::Rook), square("e7"));
+    assert!(!in_check.can_castle(Color::White, CastlingSide::KingSide, rights, in_check.occupied));
+    assert!(!in_check.can_castle(Color::White, CastlingSide::QueenSide, rights, in_check.occupied));
+
+    let into_check = castling_board().put_or_replace(piece(Color::Black, Role::Bishop), square("e3"));
+    assert!(!into_check.can_castle(Color::White, CastlingSide::QueenSide, rights, into_check.occupied));
+}
+
+#[test]
+fn test_castling_needs_empty_squares_and_rights() {
+    let board = castling_board().put_or_replace(piece(Color::White, Role::Knight), square("b1"));
+
+    // b1 is not crossed by the king but still has to be empty
+    assert!(!board.can_castle(Color::White, CastlingSide::QueenSide, CastlingRights::ALL, board.occupied));
+    assert!(!board.can_castle(
+        Color::White,
+        CastlingSide::KingSide,
+        CastlingRights::ALL.without_color(Color::White),
+        board.occupied
+    ));
+    assert!(!board.can_castle(Color::White, CastlingSide::KingSide, CastlingRights::NONE, board.occupied));
+}
+
+#[test]
+fn test_position_generates_and_plays_castling() {
+    let position = ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6"]
+        .iter()
+        .fold(Position::new(), |position, uci| {
+            position.play(Move::from_uci(uci).unwrap()).unwrap()
+        });
+
+    let castle = Move::from_uci("e1g1").unwrap();
+    assert!(position.legal_moves().contains(&castle));
+
+    let after = position.play(castle).unwrap();
+    assert_eq!(
+        after.to_fen(),
+        "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 5 4"
+    );
+}
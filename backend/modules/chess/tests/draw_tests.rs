@@ -0,0 +1,50 @@
+use chess::bitboard::position::{is_threefold_repetition, Move, Position};
+
+fn play_history(moves: &[&str]) -> Vec<Position> {
+    let mut history = vec![Position::new()];
+    for uci in moves {
+        let mv = Move::from_uci(uci).expect("valid UCI");
+        let next = history
+            .last()
+            .unwrap()
+            .play(mv)
+            .unwrap_or_else(|| panic!("{} should be legal", uci));
+        history.push(next);
+    }
+    history
+}
+
+#[test]
+fn test_shortest_stalemate() {
+    // Sam Loyd's ten-move stalemate
+    let history = play_history(&[
+        "e2e3", "a7a5", "d1h5", "a8a6", "h5a5", "h7h5", "h2h4", "a6h6", "a5c7", "f7f6",
+        "c7d7", "e8f7", "d7b7", "d8d3", "b7b8", "d3h7", "b8c8", "f7g6", "c8e6",
+    ]);
+    let position = history.last().unwrap();
+
+    assert!(position.is_stalemate());
+    assert!(!position.is_checkmate());
+}
+
+#[test]
+fn test_threefold_repetition() {
+    let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+    let twice: Vec<&str> = shuffle.iter().chain(shuffle.iter()).copied().collect();
+
+    // The starting position has now appeared three times
+    assert!(is_threefold_repetition(&play_history(&twice)));
+    assert!(!is_threefold_repetition(&play_history(&shuffle)));
+    assert!(!is_threefold_repetition(&play_history(&twice[..7])));
+}
+
+#[test]
+fn test_fifty_move_rule() {
+    let mut position = Position::new();
+    position.halfmove_clock = 99;
+    assert!(!position.is_fifty_moves());
+
+    let position = position.play(Move::from_uci("g1f3").unwrap()).unwrap();
+    assert_eq!(position.halfmove_clock, 100);
+    assert!(position.is_fifty_moves());
+}
@@ -0,0 +1,81 @@
+use chess::bitboard::board::{Board, CastlingRights, Color, Piece, Role, Square};
+use chess::bitboard::history::MoveHistory;
+
+fn assert_same_board(a: &Board, b: &Board) {
+    assert_eq!(a.occupied, b.occupied);
+    assert_eq!(a.white(), b.white());
+    assert_eq!(a.black(), b.black());
+    assert_eq!(a.pawns(), b.pawns());
+    assert_eq!(a.knights(), b.knights());
+    assert_eq!(a.bishops(), b.bishops());
+    assert_eq!(a.rooks(), b.rooks());
+    assert_eq!(a.queens(), b.queens());
+    assert_eq!(a.kings(), b.kings());
+    assert_eq!(a.piece_map(), b.piece_map());
+}
+
+#[test]
+fn test_undo_capture_restores_prior_board() {
+    let e1 = Square { value: 4 };
+    let e4 = Square { value: 28 };
+    let d5 = Square { value: 35 };
+    let e8 = Square { value: 60 };
+    let board = Board::empty()
+        .put_or_replace(Piece { color: Color::White, role: Role::King }, e1)
+        .put_or_replace(Piece { color: Color::White, role: Role::Pawn }, e4)
+        .put_or_replace(Piece { color: Color::Black, role: Role::Knight }, d5)
+        .put_or_replace(Piece { color: Color::Black, role: Role::King }, e8);
+
+    let castling_rights = CastlingRights::ALL;
+    let ep_square = Some(Square { value: 43 });
+
+    let mut history = MoveHistory::new();
+    let after = history
+        .apply(&board, e4, d5, castling_rights, ep_square)
+        .expect("capture should apply");
+    assert_eq!(history.len(), 1);
+    assert_eq!(
+        history.last().unwrap().captured,
+        Some(Piece { color: Color::Black, role: Role::Knight })
+    );
+
+    let (restored, info) = history.undo(&after).expect("move should undo");
+    assert_same_board(&restored, &board);
+    assert_eq!(info.castling_rights, castling_rights);
+    assert_eq!(info.ep_square, ep_square);
+    assert!(history.is_empty());
+}
+
+#[test]
+fn test_undo_quiet_moves_in_reverse_order() {
+    let e2 = Square { value: 12 };
+    let e4 = Square { value: 28 };
+    let g8 = Square { value: 62 };
+    let f6 = Square { value: 45 };
+    let board = Board::empty()
+        .put_or_replace(Piece { color: Color::White, role: Role::Pawn }, e2)
+        .put_or_replace(Piece { color: Color::Black, role: Role::Knight }, g8);
+
+    let mut history = MoveHistory::new();
+    let first = history.apply(&board, e2, e4, CastlingRights::NONE, None).unwrap();
+    let second = history.apply(&first, g8, f6, CastlingRights::NONE, None).unwrap();
+
+    let (undone_once, _) = history.undo(&second).unwrap();
+    assert_same_board(&undone_once, &first);
+    let (undone_twice, _) = history.undo(&undone_once).unwrap();
+    assert_same_board(&undone_twice, &board);
+    assert!(history.undo(&undone_twice).is_none());
+}
+
+#[test]
+fn test_failed_apply_is_not_recorded() {
+    let e2 = Square { value: 12 };
+    let e3 = Square { value: 20 };
+    let board = Board::empty()
+        .put_or_replace(Piece { color: Color::White, role: Role::Pawn }, e2)
+        .put_or_replace(Piece { color: Color::White, role: Role::Knight }, e3);
+
+    let mut history = MoveHistory::new();
+    assert!(history.apply(&board, e2, e3, CastlingRights::NONE, None).is_none());
+    assert!(history.is_empty());
+}
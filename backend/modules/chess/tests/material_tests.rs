@@ -0,0 +1,31 @@
+use chess::bitboard::board::{Board, Color, Piece, Role, RoleCounts};
+use chess::bitboard::position::Position;
+
+#[test]
+fn test_starting_position_material() {
+    let board = Position::new().board;
+    let expected = RoleCounts {
+        pawn: 8,
+        knight: 2,
+        bishop: 2,
+        rook: 2,
+        queen: 1,
+        king: 1,
+    };
+
+    let material = board.material_map();
+    assert_eq!(material.white, expected);
+    assert_eq!(material.get(Color::Black), expected);
+    assert_eq!(board.count_of(Piece { color: Color::White, role: Role::Pawn }), 8);
+    assert_eq!(board.count_of(Piece { color: Color::Black, role: Role::Queen }), 1);
+}
+
+#[test]
+fn test_empty_board_material() {
+    let board = Board::empty();
+
+    let material = board.material_map();
+    assert_eq!(material.white, RoleCounts::default());
+    assert_eq!(material.black, RoleCounts::default());
+    assert_eq!(board.count_of(Piece { color: Color::White, role: Role::King }), 0);
+}
@@ -0,0 +1,148 @@
+use chess::bitboard::board::{Role, Square};
+use chess::bitboard::position::{Move, Position};
+
+fn play_all(moves: &[&str]) -> Position {
+    moves.iter().fold(Position::new(), |position, uci| {
+        position
+            .play(Move::from_uci(uci).unwrap())
+            .unwrap_or_else(|| panic!("{} should be legal", uci))
+    })
+}
+
+#[test]
+fn test_starting_position_perft() {
+    let start = Position::new();
+    assert_eq!(start.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    assert_eq!(start.perft(1), 20);
+    assert_eq!(start.perft(2), 400);
+    assert_eq!(start.perft(3), 8_902);
+}
+
+#[test]
+fn test_uci_round_trip() {
+    let mv = Move::from_uci("e7e8q").unwrap();
+    assert_eq!(mv.orig, Square::from_name("e7").unwrap());
+    assert_eq!(mv.promotion, Some(Role::Queen));
+    assert_eq!(mv.uci(), "e7e8q");
+    assert!(Move::from_uci("e2e9").is_none());
+    assert!(Move::from_uci("e7e8k").is_none());
+}
+
+#[test]
+fn test_illegal_moves_are_rejected() {
+    let start = Position::new();
+    assert!(start.play(Move::from_uci("e2e5").unwrap()).is_none());
+    assert!(start.play(Move::from_uci("e7e5").unwrap()).is_none());
+
+    // The c3 knight is pinned against the king by the bishop on b4
+    let pinned = play_all(&["d2d4", "e7e6", "b1c3", "f8b4"]);
+    assert!(pinned.play(Move::from_uci("c3e4").unwrap()).is_none());
+    assert!(pinned.play(Move::from_uci("a2a3").unwrap()).is_some());
+}
+
+#[test]
+fn test_fools_mate_is_checkmate() {
+    let mated = play_all(&["f2f3", "e7e5", "g2g4", "d8h4"]);
+    assert!(mated.is_check());
+    assert!(mated.is_checkmate());
+    assert!(mated.legal_moves().is_empty());
+}
+
+#[test]
+fn test_en_passant_and_fen() {
+    let position = play_all(&["e2e4", "a7a6", "e4e5", "d7d5"]);
+    assert_eq!(
+        position.to_fen(),
+        "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3"
+    );
+
+    let after = position.play(Move::from_uci("e5d6").unwrap()).unwrap();
+    assert_eq!(
+        after.to_fen(),
+        "rnbqkbnr/1pp1pppp/p2P4/8/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3"
+    );
+}
+
+#[test]
+fn test_king_move_clears_castling_rights() {
+    let position = play_all(&["e2e4", "e7e5", "e1e2", "a7a6", "a2a3", "a8a7"]);
+    assert!(position.to_fen().contains(" w k "));
+}
+
+#[test]
+fn test_fen_round_trip() {
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+        "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 12 40",
+    ] {
+        assert_eq!(Position::from_fen(fen).unwrap().to_fen(), fen);
+    }
+
+    // Move counters are optional
+    assert_eq!(
+        Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - -").unwrap().to_fen(),
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1"
+    );
+}
+
+#[test]
+fn test_invalid_fen_is_rejected() {
+    for fen in [
+        "",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1BNR w kq - 0 1",
+    ] {
+        assert!(Position::from_fen(fen).is_none(), "accepted {:?}", fen);
+    }
+}
+
+#[test]
+fn test_legal_moves_from_square() {
+    let start = Position::new();
+    let mut knight: Vec<String> = start
+        .legal_moves_from(Square::from_name("g1").unwrap())
+        .iter()
+        .map(Move::uci)
+        .collect();
+    knight.sort();
+    assert_eq!(knight, ["g1f3", "g1h3"]);
+    assert!(start.legal_moves_from(Square::from_name("e4").unwrap()).is_empty());
+
+    // The knight on e2 is pinned to its king by the rook on e8
+    let pinned = Position::from_fen("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+    assert!(pinned.legal_moves_from(Square::from_name("e2").unwrap()).is_empty());
+}
+
+#[test]
+fn test_perft_divide_sums_to_perft() {
+    let kiwipete =
+        Position::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    for position in [Position::new(), kiwipete] {
+        let divide = position.perft_divide(2);
+        assert_eq!(divide.len(), position.legal_moves().len());
+        assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), position.perft(2));
+    }
+
+    let g1f3 = Move::from_uci("g1f3").unwrap();
+    let divide = Position::new().perft_divide(2);
+    assert_eq!(divide.iter().find(|(mv, _)| *mv == g1f3).map(|(_, nodes)| *nodes), Some(20));
+}
+
+#[test]
+fn test_only_king_moves_in_double_check() {
+    // Ne4-d6+ uncovers the e1 rook, so the black king is checked twice
+    let position = Position::from_fen("3qk3/8/8/r7/4N3/8/8/4R1K1 w - - 0 1")
+        .unwrap()
+        .play(Move::from_uci("e4d6").unwrap())
+        .unwrap();
+    assert!(position.is_check());
+
+    let mut moves: Vec<String> = position.legal_moves().iter().map(|mv| mv.uci()).collect();
+    moves.sort();
+    // Neither Qxd6 nor the Re5 block answers both checks
+    assert_eq!(moves, ["e8d7", "e8f8"]);
+}
@@ -0,0 +1,78 @@
+use chess::bitboard::board::{Color, ParseColorError, ParseRoleError, Role};
+use chess::bitboard::position::{Move, Position};
+
+#[test]
+fn test_color_round_trips() {
+    for color in [Color::White, Color::Black] {
+        assert_eq!(color.to_string().parse::<Color>(), Ok(color));
+    }
+    assert_eq!(Color::White.to_string(), "white");
+    assert_eq!("BLACK".parse::<Color>(), Ok(Color::Black));
+    assert_eq!("White".parse::<Color>(), Ok(Color::White));
+}
+
+#[test]
+fn test_role_round_trips() {
+    for role in Role::ALL {
+        assert_eq!(role.to_string().parse::<Role>(), Ok(role));
+        assert_eq!(role.to_string().to_uppercase().parse::<Role>(), Ok(role));
+        assert_eq!(role.char().to_string().parse::<Role>(), Ok(role));
+        assert_eq!(role.char().to_ascii_uppercase().to_string().parse::<Role>(), Ok(role));
+    }
+    assert_eq!(Role::Knight.to_string(), "knight");
+    assert_eq!(Role::Knight.char(), 'n');
+}
+
+#[test]
+fn test_garbage_is_rejected() {
+    for input in ["", "w", "whites", "red", " white"] {
+        assert_eq!(input.parse::<Color>(), Err(ParseColorError(input.to_string())));
+    }
+    for input in ["", "x", "kn", "kings", "bishop ", "♞"] {
+        assert_eq!(input.parse::<Role>(), Err(ParseRoleError(input.to_string())));
+    }
+    assert_eq!(
+        "red".parse::<Color>().unwrap_err().to_string(),
+        "invalid color \"red\", expected \"white\" or \"black\""
+    );
+}
+
+fn san(fen: &str, uci: &str) -> Option<String> {
+    Position::from_fen(fen).unwrap().san(Move::from_uci(uci).unwrap())
+}
+
+#[test]
+fn test_san_of_pawn_and_piece_moves() {
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_eq!(san(start, "e2e4").as_deref(), Some("e4"));
+    assert_eq!(san(start, "g1f3").as_deref(), Some("Nf3"));
+    assert_eq!(san(start, "e2e5"), None);
+
+    // Captures, including en passant
+    let open = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+    assert_eq!(san(open, "e5f6").as_deref(), Some("exf6"));
+    assert_eq!(san(open, "f1b5").as_deref(), Some("Bb5+"));
+}
+
+#[test]
+fn test_san_disambiguates_pieces() {
+    // Knights on b1 and f3 can both reach d2; rooks on a1 and a5 share a file
+    let fen = "4k3/8/8/R7/8/5N2/8/RN2K3 w - - 0 1";
+    assert_eq!(san(fen, "b1d2").as_deref(), Some("Nbd2"));
+    assert_eq!(san(fen, "a5a3").as_deref(), Some("R5a3"));
+    assert_eq!(san(fen, "a1a3").as_deref(), Some("R1a3"));
+    assert_eq!(san(fen, "a5e5").as_deref(), Some("Re5+"));
+}
+
+#[test]
+fn test_san_of_castling_promotion_and_mate() {
+    let castles = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    assert_eq!(san(castles, "e1g1").as_deref(), Some("O-O"));
+    assert_eq!(san(castles, "e1c1").as_deref(), Some("O-O-O"));
+
+    assert_eq!(san("7k/P7/8/8/8/8/8/K7 w - - 0 1", "a7a8q").as_deref(), Some("a8=Q+"));
+    assert_eq!(san("7k/P7/8/8/8/8/8/K7 w - - 0 1", "a7a8n").as_deref(), Some("a8=N"));
+
+    // Back rank mate
+    assert_eq!(san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8").as_deref(), Some("Ra8#"));
+}
@@ -0,0 +1,23 @@
+use chess::outcome::Outcome;
+
+#[test]
+fn test_scores_add_up_to_one_game() {
+    for outcome in Outcome::ALL {
+        assert_eq!(outcome.white_score() + outcome.black_score(), 1.0);
+        assert_eq!(outcome.flipped().white_score(), outcome.black_score());
+        assert_eq!(outcome.flipped().flipped(), outcome);
+    }
+    assert_eq!(Outcome::Draw.white_score(), 0.5);
+}
+
+#[test]
+fn test_winner_round_trips_through_contract_addresses() {
+    let (white, black) = ("GWHITE", "GBLACK");
+    for outcome in [Outcome::WhiteWins, Outcome::BlackWins] {
+        let winner = outcome.winner(&white, &black).unwrap();
+        assert_eq!(Outcome::from_winner(winner, &white, &black), Some(outcome));
+    }
+    // The contract has no way to record a draw
+    assert_eq!(Outcome::Draw.winner(&white, &black), None);
+    assert_eq!(Outcome::from_winner(&"GSOMEONE", &white, &black), None);
+}
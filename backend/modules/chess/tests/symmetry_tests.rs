@@ -0,0 +1,43 @@
+use chess::bitboard::board::{Bitboard, Square};
+use chess::bitboard::position::{Move, Position};
+
+fn after(moves: &[&str]) -> Position {
+    moves.iter().fold(Position::new(), |position, uci| {
+        position.play(Move::from_uci(uci).unwrap()).unwrap()
+    })
+}
+
+#[test]
+fn test_flipping_the_starting_position_gives_it_back() {
+    let start = Position::new();
+    let flipped = start.board.flip_vertical();
+    assert_eq!(flipped, start.board);
+    assert_eq!(flipped.flip_vertical(), start.board);
+    // With the side to move swapped too, it is the same position with Black to move
+    let black_to_move = Position::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert_eq!(flipped, black_to_move.board);
+    assert_eq!(start.turn.opposite(), black_to_move.turn);
+}
+
+#[test]
+fn test_flip_mirrors_ranks_and_swaps_colors() {
+    // After 1. e4 it is Black to move; the mirror is White to move facing ...e5
+    let position = after(&["e2e4"]);
+    let mirror = Position::from_fen("rnbqkbnr/pppp1ppp/8/4p3/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    assert_eq!(position.board.flip_vertical(), mirror.board);
+    assert_eq!(position.turn.opposite(), mirror.turn);
+
+    let e4 = Square::from_name("e4").unwrap().bitboard();
+    assert_eq!(e4.flip_vertical(), Square::from_name("e5").unwrap().bitboard());
+    assert_eq!(Bitboard::FIRST_RANK.flip_vertical(), Bitboard::LAST_RANK);
+}
+
+#[test]
+fn test_material_balance_negates_under_flip() {
+    assert_eq!(Position::new().board.material_balance(), 0);
+
+    // White wins a knight: 1. e4 d5 2. exd5 Nf6 3. Nc3 Nxd5 4. Nxd5
+    let board = after(&["e2e4", "d7d5", "e4d5", "g8f6", "b1c3", "f6d5", "c3d5"]).board;
+    assert_eq!(board.material_balance(), 3);
+    assert_eq!(board.flip_vertical().material_balance(), -3);
+}
@@ -12,6 +12,7 @@ pub struct Model {
     pub san: String,            
     pub fen: String,             
     pub timestamp: DateTime<Utc>,
+    pub think_ms: i64,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
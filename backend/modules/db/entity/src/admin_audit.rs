@@ -0,0 +1,23 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+
+/// Audit trail of contract transactions submitted from admin endpoints.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "admin_audit", schema_name = "smdb")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    /// Id of the admin player who triggered the action.
+    pub actor_id: Uuid,
+    /// Contract function invoked, e.g. `set_server`.
+    pub action: String,
+    /// What the action was applied to, e.g. the new server address.
+    pub target: String,
+    pub tx_hash: String,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
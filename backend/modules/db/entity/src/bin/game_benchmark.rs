@@ -106,7 +106,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // === Benchmark: Insertions ===
     println!("Inserting {} games in batches of {}...", NUM_GAMES_TO_INSERT, BATCH_SIZE);
     let mut game_models = Vec::with_capacity(BATCH_SIZE);
-    let variants = [GameVariant::Standard, GameVariant::Chess960, GameVariant::Blitz, GameVariant::Rapid, GameVariant::Classical]; // Update variants list
+    let variants = [GameVariant::Standard, GameVariant::Chess960, GameVariant::Bullet, GameVariant::Blitz, GameVariant::Rapid, GameVariant::Classical]; // Update variants list
     let results = [ResultSide::WhiteWins, ResultSide::BlackWins, ResultSide::Draw]; // Update results list
     let insert_start = Instant::now();
 
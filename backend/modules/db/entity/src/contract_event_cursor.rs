@@ -0,0 +1,18 @@
+use sea_orm::entity::prelude::*;
+
+/// Progress marker for a contract event stream, e.g. `GameFinalized` from the game registry.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "contract_event_cursor")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub source: String,
+    pub last_ledger: i64,
+    /// Soroban RPC cursor to continue paging from, when the RPC reported one
+    pub paging_token: Option<String>,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
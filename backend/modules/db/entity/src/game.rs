@@ -1,5 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.10
 
+use chess::outcome::Outcome;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -19,6 +20,42 @@ pub enum ResultSide {
     Abandoned,
 }
 
+impl ResultSide {
+    /// How the game was decided, or `None` while ongoing or when abandoned.
+    pub fn outcome(&self) -> Option<Outcome> {
+        match self {
+            ResultSide::WhiteWins => Some(Outcome::WhiteWins),
+            ResultSide::BlackWins => Some(Outcome::BlackWins),
+            ResultSide::Draw => Some(Outcome::Draw),
+            ResultSide::Ongoing | ResultSide::Abandoned => None,
+        }
+    }
+}
+
+impl From<Outcome> for ResultSide {
+    fn from(outcome: Outcome) -> Self {
+        match outcome {
+            Outcome::WhiteWins => ResultSide::WhiteWins,
+            Outcome::BlackWins => ResultSide::BlackWins,
+            Outcome::Draw => ResultSide::Draw,
+        }
+    }
+}
+
+/// Why a game ended in a draw.
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "draw_reason")]
+pub enum DrawReason {
+    #[sea_orm(string_value = "agreement")]
+    Agreement,
+    #[sea_orm(string_value = "stalemate")]
+    Stalemate,
+    #[sea_orm(string_value = "threefold_repetition")]
+    ThreefoldRepetition,
+    #[sea_orm(string_value = "fifty_move_rule")]
+    FiftyMoveRule,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, DeriveActiveEnum, EnumIter, Serialize, Deserialize)]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "game_variant")]
 pub enum GameVariant {
@@ -34,6 +71,22 @@ pub enum GameVariant {
     Rapid,
     #[sea_orm(string_value = "classical")]
     Classical,
+    #[sea_orm(string_value = "bullet")]
+    Bullet,
+}
+
+impl GameVariant {
+    /// Speed category of a `base_sec`+`inc_sec` time control, judged by the
+    /// estimated game length of `base_sec + 40 * inc_sec` seconds. `Bullet`
+    /// is never inferred: the shortest controls count as blitz.
+    pub fn from_time_control(base_sec: i32, inc_sec: i32) -> GameVariant {
+        let estimated = base_sec as i64 + 40 * inc_sec as i64;
+        match estimated {
+            ..=179 => GameVariant::Blitz,
+            180..=1499 => GameVariant::Rapid,
+            _ => GameVariant::Classical,
+        }
+    }
 }
 
 
@@ -50,6 +103,7 @@ pub struct Model {
     #[sea_orm(column_type = "JsonBinary")]
     pub pgn: Json,
     pub result: Option<ResultSide>,
+    pub draw_reason: Option<DrawReason>,
     pub variant: GameVariant,
     pub started_at: DateTimeWithTimeZone,
     pub duration_sec: i32,
@@ -84,3 +138,34 @@ impl Related<super::player::Entity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_side_round_trips_every_outcome() {
+        for outcome in Outcome::ALL {
+            assert_eq!(ResultSide::from(outcome).outcome(), Some(outcome));
+        }
+        assert_eq!(ResultSide::Ongoing.outcome(), None);
+        assert_eq!(ResultSide::Abandoned.outcome(), None);
+    }
+
+    #[test]
+    fn test_variant_from_time_control() {
+        assert_eq!(GameVariant::from_time_control(60, 0), GameVariant::Blitz);
+        assert_eq!(GameVariant::from_time_control(600, 0), GameVariant::Rapid);
+        assert_eq!(GameVariant::from_time_control(1800, 0), GameVariant::Classical);
+    }
+
+    #[test]
+    fn test_increment_counts_towards_estimate() {
+        // 2+1 is estimated at 160s, 3+0 at 180s
+        assert_eq!(GameVariant::from_time_control(120, 1), GameVariant::Blitz);
+        assert_eq!(GameVariant::from_time_control(180, 0), GameVariant::Rapid);
+        // 15+10 is estimated at 1300s, 25+0 at 1500s
+        assert_eq!(GameVariant::from_time_control(900, 10), GameVariant::Rapid);
+        assert_eq!(GameVariant::from_time_control(1500, 0), GameVariant::Classical);
+    }
+}
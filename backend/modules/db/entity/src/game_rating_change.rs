@@ -0,0 +1,38 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+
+/// Rating of a player before and after a rated game, written when the game
+/// is finalized.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "game_rating_change", schema_name = "smdb")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub game_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub player_id: Uuid,
+    /// Rating pool the game counted towards, e.g. "blitz".
+    pub pool: String,
+    pub rating_before: i32,
+    pub rating_after: i32,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::game::Entity",
+        from = "Column::GameId",
+        to = "super::game::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Game,
+}
+
+impl Related<super::game::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Game.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
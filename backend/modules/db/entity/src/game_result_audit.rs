@@ -0,0 +1,38 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+
+/// Audit trail of moderator overrides of a game's result.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "game_result_audit", schema_name = "smdb")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub game_id: Uuid,
+    /// Id of the admin player who forced the result.
+    pub admin_id: Uuid,
+    pub previous_result: Option<String>,
+    pub new_result: String,
+    #[sea_orm(column_type = "Text")]
+    pub reason: String,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::game::Entity",
+        from = "Column::GameId",
+        to = "super::game::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Game,
+}
+
+impl Related<super::game::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Game.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
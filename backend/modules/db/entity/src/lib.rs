@@ -1,6 +1,12 @@
 pub mod prelude;
 pub mod game;
 pub mod player;
+pub mod contract_event_cursor;
+pub mod game_result_audit;
+pub mod game_rating_change;
+pub mod email_verification_token;
+pub mod password_reset_token;
+pub mod admin_audit;
 
 #[path = "../user.rs"]
 pub mod user;
\ No newline at end of file
@@ -0,0 +1,35 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+
+/// Single-use password reset token, stored as the SHA-256 of the token mailed to the player.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "password_reset_token")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub token_hash: String,
+    pub player_id: Uuid,
+    pub expires_at: DateTimeWithTimeZone,
+    /// Set when the token is redeemed; a used token cannot reset again.
+    pub used_at: Option<DateTimeWithTimeZone>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::player::Entity",
+        from = "Column::PlayerId",
+        to = "super::player::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Player,
+}
+
+impl Related<super::player::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Player.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
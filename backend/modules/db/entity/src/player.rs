@@ -21,7 +21,12 @@ pub struct Model {
     pub location: Option<String>,
     pub fide_rating: Option<i32>,
     pub social_links: Option<Vec<String>>,
-    pub is_enabled: bool
+    pub is_enabled: bool,
+    pub email_verified: bool,
+    pub blitz_rating: Option<i32>,
+    pub rapid_rating: Option<i32>,
+    pub classical_rating: Option<i32>,
+    pub bullet_rating: Option<i32>,
 }
 
 
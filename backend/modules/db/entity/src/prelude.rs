@@ -2,3 +2,9 @@
 
 pub use super::game::Entity as Game;
 pub use super::player::Entity as Player;
+pub use super::contract_event_cursor::Entity as ContractEventCursor;
+pub use super::game_result_audit::Entity as GameResultAudit;
+pub use super::game_rating_change::Entity as GameRatingChange;
+pub use super::email_verification_token::Entity as EmailVerificationToken;
+pub use super::password_reset_token::Entity as PasswordResetToken;
+pub use super::admin_audit::Entity as AdminAudit;
\ No newline at end of file
@@ -6,6 +6,17 @@ mod m20250429_163843_create_games_table;
 mod m20250429_192832_add_common_indexes;
 mod m20250604_160341_create_games_and_moves;
 mod m20250605_090000_add_game_search_indexes;
+mod m20261016_000001_create_contract_event_cursor;
+mod m20261016_000002_create_game_result_audit;
+mod m20261016_000003_add_game_draw_reason;
+mod m20261016_000004_add_email_verification;
+mod m20261016_000005_create_password_reset_token;
+mod m20261016_000006_add_game_result_index;
+mod m20261016_000007_add_pool_ratings;
+mod m20261016_000008_create_game_rating_change;
+mod m20261016_000009_add_bullet_rating;
+mod m20261016_000010_create_admin_audit;
+mod m20261016_000011_add_contract_event_cursor_paging_token;
 
 
 pub struct Migrator;
@@ -20,6 +31,17 @@ impl MigratorTrait for Migrator {
             Box::new(m20250429_192832_add_common_indexes::Migration),
             Box::new(m20250604_160341_create_games_and_moves::Migration),
             Box::new(m20250605_090000_add_game_search_indexes::Migration), 
+            Box::new(m20261016_000001_create_contract_event_cursor::Migration),
+            Box::new(m20261016_000002_create_game_result_audit::Migration),
+            Box::new(m20261016_000003_add_game_draw_reason::Migration),
+            Box::new(m20261016_000004_add_email_verification::Migration),
+            Box::new(m20261016_000005_create_password_reset_token::Migration),
+            Box::new(m20261016_000006_add_game_result_index::Migration),
+            Box::new(m20261016_000007_add_pool_ratings::Migration),
+            Box::new(m20261016_000008_create_game_rating_change::Migration),
+            Box::new(m20261016_000009_add_bullet_rating::Migration),
+            Box::new(m20261016_000010_create_admin_audit::Migration),
+            Box::new(m20261016_000011_add_contract_event_cursor_paging_token::Migration),
         ]
     }
 }
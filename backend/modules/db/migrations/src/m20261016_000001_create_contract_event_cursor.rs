@@ -0,0 +1,46 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Last ledger processed per contract event stream, so ingestion resumes where it stopped
+        manager
+            .create_table(
+                Table::create()
+                    .table(ContractEventCursor::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ContractEventCursor::Source)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ContractEventCursor::LastLedger).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(ContractEventCursor::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ContractEventCursor::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ContractEventCursor {
+    Table,
+    Source,
+    LastLedger,
+    UpdatedAt,
+}
@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // One row per moderator override of a game result
+        manager
+            .create_table(
+                Table::create()
+                    .table((Smdb, GameResultAudit::Table))
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GameResultAudit::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(GameResultAudit::GameId).uuid().not_null())
+                    .col(ColumnDef::new(GameResultAudit::AdminId).uuid().not_null())
+                    .col(ColumnDef::new(GameResultAudit::PreviousResult).string())
+                    .col(ColumnDef::new(GameResultAudit::NewResult).string().not_null())
+                    .col(ColumnDef::new(GameResultAudit::Reason).text().not_null())
+                    .col(
+                        ColumnDef::new(GameResultAudit::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_game_result_audit_game")
+                            .from((Smdb, GameResultAudit::Table), GameResultAudit::GameId)
+                            .to((Smdb, Game::Table), Game::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_game_result_audit_game_id")
+                    .table((Smdb, GameResultAudit::Table))
+                    .col(GameResultAudit::GameId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table((Smdb, GameResultAudit::Table)).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+struct Smdb;
+
+#[derive(DeriveIden)]
+enum Game {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum GameResultAudit {
+    Table,
+    Id,
+    GameId,
+    AdminId,
+    PreviousResult,
+    NewResult,
+    Reason,
+    CreatedAt,
+}
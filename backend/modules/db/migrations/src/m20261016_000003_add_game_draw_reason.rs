@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Why a drawn game ended; NULL for every other result
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(DrawReason::Enum)
+                    .values([
+                        DrawReason::Agreement,
+                        DrawReason::Stalemate,
+                        DrawReason::ThreefoldRepetition,
+                        DrawReason::FiftyMoveRule,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table((Smdb, Game::Table))
+                    .add_column(
+                        ColumnDef::new(Game::DrawReason)
+                            .custom(DrawReason::Enum)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table((Smdb, Game::Table))
+                    .drop_column(Game::DrawReason)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(DrawReason::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+struct Smdb;
+
+#[derive(DeriveIden)]
+enum Game {
+    Table,
+    DrawReason,
+}
+
+#[derive(DeriveIden)]
+enum DrawReason {
+    #[sea_orm(iden = "draw_reason")]
+    Enum,
+    Agreement,
+    Stalemate,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+}
@@ -0,0 +1,113 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Player::Table)
+                    .add_column(
+                        ColumnDef::new(Player::EmailVerified)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Accounts created before verification existed are grandfathered in,
+        // so enabling the login gate does not lock them out
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Player::Table)
+                    .value(Player::EmailVerified, true)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Single-use tokens mailed to new players; used_at is set once redeemed
+        manager
+            .create_table(
+                Table::create()
+                    .table(EmailVerificationToken::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(EmailVerificationToken::Token)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(EmailVerificationToken::PlayerId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(EmailVerificationToken::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(EmailVerificationToken::UsedAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(EmailVerificationToken::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_email_verification_token_player")
+                            .from(EmailVerificationToken::Table, EmailVerificationToken::PlayerId)
+                            .to(Player::Table, Player::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_email_verification_token_player_id")
+                    .table(EmailVerificationToken::Table)
+                    .col(EmailVerificationToken::PlayerId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EmailVerificationToken::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Player::Table)
+                    .drop_column(Player::EmailVerified)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Player {
+    Table,
+    Id,
+    EmailVerified,
+}
+
+#[derive(DeriveIden)]
+enum EmailVerificationToken {
+    Table,
+    Token,
+    PlayerId,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt,
+}
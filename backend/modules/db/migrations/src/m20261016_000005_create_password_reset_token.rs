@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Only a SHA-256 of each reset token is stored, so a leaked table
+        // cannot be used to reset passwords
+        manager
+            .create_table(
+                Table::create()
+                    .table(PasswordResetToken::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PasswordResetToken::TokenHash)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PasswordResetToken::PlayerId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(PasswordResetToken::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PasswordResetToken::UsedAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(PasswordResetToken::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_password_reset_token_player")
+                            .from(PasswordResetToken::Table, PasswordResetToken::PlayerId)
+                            .to(Player::Table, Player::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_password_reset_token_player_id")
+                    .table(PasswordResetToken::Table)
+                    .col(PasswordResetToken::PlayerId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PasswordResetToken::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Player {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum PasswordResetToken {
+    Table,
+    TokenHash,
+    PlayerId,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt,
+}
@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Serves the lobby's active games query: `result = 'ongoing'` newest first.
+fn result_started_at_index() -> IndexCreateStatement {
+    Index::create()
+        .name("idx_game_result_started_at")
+        .table((Smdb, Game::Table))
+        .col(Game::Result)
+        .col((Game::StartedAt, IndexOrder::Desc))
+        .if_not_exists()
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_index(result_started_at_index()).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_game_result_started_at")
+                    .table((Smdb, Game::Table))
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+struct Smdb;
+
+#[derive(DeriveIden)]
+enum Game {
+    Table,
+    Result,
+    StartedAt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_index_statement() {
+        assert_eq!(
+            result_started_at_index().to_string(PostgresQueryBuilder),
+            r#"CREATE INDEX IF NOT EXISTS "idx_game_result_started_at" ON "smdb"."game" ("result", "started_at" DESC)"#
+        );
+    }
+}
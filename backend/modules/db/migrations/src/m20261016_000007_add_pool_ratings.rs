@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // One rating per speed pool; NULL until the player's first rated game in it
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Player::Table)
+                    .add_column(ColumnDef::new(Player::BlitzRating).integer().null())
+                    .add_column(ColumnDef::new(Player::RapidRating).integer().null())
+                    .add_column(ColumnDef::new(Player::ClassicalRating).integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Player::Table)
+                    .drop_column(Player::BlitzRating)
+                    .drop_column(Player::RapidRating)
+                    .drop_column(Player::ClassicalRating)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Player {
+    Table,
+    BlitzRating,
+    RapidRating,
+    ClassicalRating,
+}
@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // One row per player of a rated game, written when the game finishes
+        manager
+            .create_table(
+                Table::create()
+                    .table((Smdb, GameRatingChange::Table))
+                    .if_not_exists()
+                    .col(ColumnDef::new(GameRatingChange::GameId).uuid().not_null())
+                    .col(ColumnDef::new(GameRatingChange::PlayerId).uuid().not_null())
+                    .col(ColumnDef::new(GameRatingChange::Pool).string().not_null())
+                    .col(ColumnDef::new(GameRatingChange::RatingBefore).integer().not_null())
+                    .col(ColumnDef::new(GameRatingChange::RatingAfter).integer().not_null())
+                    .col(
+                        ColumnDef::new(GameRatingChange::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(GameRatingChange::GameId)
+                            .col(GameRatingChange::PlayerId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_game_rating_change_game")
+                            .from((Smdb, GameRatingChange::Table), GameRatingChange::GameId)
+                            .to((Smdb, Game::Table), Game::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table((Smdb, GameRatingChange::Table)).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+struct Smdb;
+
+#[derive(DeriveIden)]
+enum Game {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum GameRatingChange {
+    Table,
+    GameId,
+    PlayerId,
+    Pool,
+    RatingBefore,
+    RatingAfter,
+    CreatedAt,
+}
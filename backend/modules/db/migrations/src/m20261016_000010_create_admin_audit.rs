@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // One row per contract transaction submitted from an admin endpoint
+        manager
+            .create_table(
+                Table::create()
+                    .table((Smdb, AdminAudit::Table))
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AdminAudit::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AdminAudit::ActorId).uuid().not_null())
+                    .col(ColumnDef::new(AdminAudit::Action).string().not_null())
+                    .col(ColumnDef::new(AdminAudit::Target).string().not_null())
+                    .col(ColumnDef::new(AdminAudit::TxHash).string().not_null())
+                    .col(
+                        ColumnDef::new(AdminAudit::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_admin_audit_created_at")
+                    .table((Smdb, AdminAudit::Table))
+                    .col(AdminAudit::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table((Smdb, AdminAudit::Table)).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+struct Smdb;
+
+#[derive(DeriveIden)]
+enum AdminAudit {
+    Table,
+    Id,
+    ActorId,
+    Action,
+    Target,
+    TxHash,
+    CreatedAt,
+}
@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The RPC's own cursor, so paging can resume part-way through a ledger;
+        // NULL for streams that only ever stored a ledger
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContractEventCursor::Table)
+                    .add_column(ColumnDef::new(ContractEventCursor::PagingToken).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContractEventCursor::Table)
+                    .drop_column(ContractEventCursor::PagingToken)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ContractEventCursor {
+    Table,
+    PagingToken,
+}
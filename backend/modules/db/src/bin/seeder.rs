@@ -8,6 +8,7 @@ use rand::seq::SliceRandom;
 use rand::Rng;
 use chrono::{Utc, Duration};
 use serde_json::json;
+use db::seed::random_game;
 
 const NUM_PLAYERS: usize = 100;
 const NUM_GAMES: usize = 5000;
@@ -15,9 +16,14 @@ const NUM_GAMES: usize = 5000;
 // Basic starting FEN position
 const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
+// Upper bound on plies for games generated with --realistic
+const MAX_REALISTIC_PLIES: usize = 80;
+
 #[tokio::main]
 async fn main() -> Result<(), DbErr> {
     dotenv().ok();
+    // --realistic plays out random legal games instead of placeholder move lists
+    let realistic = env::args().any(|arg| arg == "--realistic");
     let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let db = Database::connect(&db_url).await?;
 
@@ -47,6 +53,7 @@ async fn main() -> Result<(), DbErr> {
             fide_rating: Set(Some(rand::thread_rng().gen_range(800..2800))),
             social_links: Set(Some(vec!["http://twitter.com/player".to_string()])),
             is_enabled: Set(true),
+            email_verified: Set(true),
             ..Default::default()
         }
     }).collect();
@@ -67,6 +74,7 @@ async fn main() -> Result<(), DbErr> {
         GameVariant::Standard, 
         GameVariant::Chess960, 
         GameVariant::ThreeCheck, 
+        GameVariant::Bullet, 
         GameVariant::Blitz, 
         GameVariant::Rapid, 
         GameVariant::Classical
@@ -77,7 +85,11 @@ async fn main() -> Result<(), DbErr> {
         ResultSide::Draw
     ];
 
-    println!("Seeding {} games...", NUM_GAMES);
+    println!(
+        "Seeding {} games{}...",
+        NUM_GAMES,
+        if realistic { " with realistic move lists" } else { "" }
+    );
     for i in 0..NUM_GAMES {
         let white_player_id = *player_ids.choose(&mut rng).unwrap();
         let black_player_id = loop {
@@ -90,13 +102,33 @@ async fn main() -> Result<(), DbErr> {
         let started_at = Utc::now() - Duration::days(rng.gen_range(0..365));
         let duration_sec = rng.gen_range(30..3600); // 30 seconds to 1 hour
 
+        let (fen, pgn, result) = if realistic {
+            let max_plies = rng.gen_range(10..=MAX_REALISTIC_PLIES);
+            let played = random_game(&mut rng, max_plies);
+            let result = played
+                .result
+                .unwrap_or_else(|| results.choose(&mut rng).unwrap().clone());
+            (
+                played.fen,
+                json!({ "moves": played.moves.join(" "), "final_ply": played.moves.len() }),
+                result,
+            )
+        } else {
+            (
+                STARTING_FEN.to_string(), // Simple FEN for now
+                json!({ "moves": "e4 c5 ...", "final_ply": rng.gen_range(10..150) }), // Added final_ply for benchmark
+                results.choose(&mut rng).unwrap().clone(),
+            )
+        };
+
         let game = game::ActiveModel {
             id: Set(Uuid::new_v4()),
             white_player: Set(white_player_id),
             black_player: Set(black_player_id),
-            fen: Set(STARTING_FEN.to_string()), // Simple FEN for now
-            pgn: Set(json!({ "moves": "e4 c5 ...", "final_ply": rng.gen_range(10..150) })), // Added final_ply for benchmark
-            result: Set(Some(results.choose(&mut rng).unwrap().clone())),
+            fen: Set(fen),
+            pgn: Set(pgn),
+            result: Set(Some(result)),
+            draw_reason: Set(None),
             variant: Set(variants.choose(&mut rng).unwrap().clone()),
             started_at: Set(started_at.into()),
             duration_sec: Set(duration_sec),
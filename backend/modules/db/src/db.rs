@@ -1,12 +1,97 @@
 pub mod db {
     use sea_orm::{ConnectOptions, Database, DatabaseConnection};
 
+    pub const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+    pub const DEFAULT_MIN_CONNECTIONS: u32 = 1;
+
+    /// Connection pool sizing read from `DB_MAX_CONNECTIONS` / `DB_MIN_CONNECTIONS`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct PoolConfig {
+        pub max_connections: u32,
+        pub min_connections: u32,
+    }
+
+    impl Default for PoolConfig {
+        fn default() -> Self {
+            Self {
+                max_connections: DEFAULT_MAX_CONNECTIONS,
+                min_connections: DEFAULT_MIN_CONNECTIONS,
+            }
+        }
+    }
+
+    impl PoolConfig {
+        pub fn from_env() -> Self {
+            Self::from_values(
+                std::env::var("DB_MAX_CONNECTIONS").ok().as_deref(),
+                std::env::var("DB_MIN_CONNECTIONS").ok().as_deref(),
+            )
+        }
+
+        /// Parses the raw values like [`PoolConfig::parse`], logging a warning
+        /// for each value that was not used as given.
+        pub fn from_values(max: Option<&str>, min: Option<&str>) -> Self {
+            let (config, warnings) = Self::parse(max, min);
+            for warning in warnings {
+                log::warn!("{}", warning);
+            }
+            config
+        }
+
+        /// Parses the raw values, falling back to the default of each value that
+        /// is missing or not a positive integer, and lowering min to max when it
+        /// exceeds it. Returns a description of each value that was not used as given.
+        pub fn parse(max: Option<&str>, min: Option<&str>) -> (Self, Vec<String>) {
+            let mut warnings = Vec::new();
+            let mut parse = |value: Option<&str>, default: u32, name: &str| match value {
+                None => default,
+                Some(raw) => match raw.trim().parse::<u32>() {
+                    Ok(parsed) if parsed > 0 => parsed,
+                    _ => {
+                        warnings.push(format!("Invalid {} value '{}', using default {}", name, raw, default));
+                        default
+                    }
+                },
+            };
+
+            let max_connections = parse(max, DEFAULT_MAX_CONNECTIONS, "DB_MAX_CONNECTIONS");
+            let mut min_connections = parse(min, DEFAULT_MIN_CONNECTIONS, "DB_MIN_CONNECTIONS");
+
+            if min_connections > max_connections {
+                warnings.push(format!(
+                    "DB_MIN_CONNECTIONS ({}) exceeds DB_MAX_CONNECTIONS ({}), using {}",
+                    min_connections, max_connections, max_connections
+                ));
+                min_connections = max_connections;
+            }
+
+            (
+                Self {
+                    max_connections,
+                    min_connections,
+                },
+                warnings,
+            )
+        }
+
+        pub fn apply(&self, connect_options: &mut ConnectOptions) {
+            connect_options
+                .max_connections(self.max_connections)
+                .min_connections(self.min_connections);
+        }
+    }
+
     pub async fn get_db() -> DatabaseConnection {
         dotenv::dotenv().ok();
-        let connect_options = ConnectOptions::new(
+        let mut connect_options = ConnectOptions::new(
             std::env::var("DATABASE_URL").expect("DATABASE_URL is not defined"),
-        )
-        .to_owned();
+        );
+        let pool = PoolConfig::from_env();
+        log::info!(
+            "Database pool: {} to {} connections",
+            pool.min_connections, pool.max_connections
+        );
+        pool.apply(&mut connect_options);
 
         let db: DatabaseConnection =
             Database::connect(connect_options)
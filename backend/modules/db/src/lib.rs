@@ -1,4 +1,5 @@
 pub mod db;
+pub mod seed;
 
 #[cfg(test)]
 mod tests {
@@ -6,7 +7,9 @@ mod tests {
 
     use sea_orm::{ConnectionTrait, DatabaseBackend, DbConn, DbErr, Statement};
 
-    use crate::db::db::get_db;
+    use crate::db::db::{
+        get_db, PoolConfig, DEFAULT_MAX_CONNECTIONS, DEFAULT_MIN_CONNECTIONS,
+    };
 
     const DATABASE_NAME: &str = "player";
 
@@ -92,6 +95,10 @@ mod tests {
             ("real_name","character varying"),
             ("location","character varying"),
             ("fide_rating","integer"),
+            ("blitz_rating","integer"),
+            ("rapid_rating","integer"),
+            ("classical_rating","integer"),
+            ("bullet_rating","integer"),
             ("social_links","ARRAY")
         ]);
 
@@ -104,4 +111,41 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn pool_config_parses_valid_values() {
+        let config = PoolConfig::from_values(Some("25"), Some("5"));
+        assert_eq!(config.max_connections, 25);
+        assert_eq!(config.min_connections, 5);
+    }
+
+    #[test]
+    fn pool_config_invalid_values_fall_back_to_defaults() {
+        assert_eq!(PoolConfig::from_values(None, None), PoolConfig::default());
+        assert_eq!(
+            PoolConfig::from_values(Some("abc"), Some("-1")),
+            PoolConfig::default()
+        );
+        assert_eq!(
+            PoolConfig::from_values(Some("0"), Some("0")),
+            PoolConfig::default()
+        );
+        assert_eq!(PoolConfig::default().max_connections, DEFAULT_MAX_CONNECTIONS);
+        assert_eq!(PoolConfig::default().min_connections, DEFAULT_MIN_CONNECTIONS);
+    }
+
+    #[test]
+    fn pool_config_lowers_min_to_max() {
+        let (config, warnings) = PoolConfig::parse(Some("4"), Some("8"));
+        assert_eq!(config, PoolConfig { max_connections: 4, min_connections: 4 });
+        assert_eq!(warnings.len(), 1);
+
+        // Only the malformed value falls back, and min still fits under max
+        let (config, warnings) = PoolConfig::parse(Some("abc"), Some("20"));
+        assert_eq!(config.max_connections, DEFAULT_MAX_CONNECTIONS);
+        assert_eq!(config.min_connections, DEFAULT_MAX_CONNECTIONS);
+        assert_eq!(warnings.len(), 2);
+
+        assert!(PoolConfig::parse(Some("25"), Some("5")).1.is_empty());
+    }
 }
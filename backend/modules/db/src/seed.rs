@@ -0,0 +1,76 @@
+use chess::bitboard::board::Color;
+use chess::bitboard::position::Position;
+use db_entity::game::ResultSide;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// A short game of random legal moves, used by the seeder's `--realistic` mode.
+pub struct RandomGame {
+    /// Moves in UCI notation, from the standard starting position.
+    pub moves: Vec<String>,
+    /// FEN of the position after the last move.
+    pub fen: String,
+    /// Set when the game ended on the board (checkmate or stalemate).
+    pub result: Option<ResultSide>,
+}
+
+/// Plays up to `max_plies` random legal moves from the starting position.
+pub fn random_game<R: Rng>(rng: &mut R, max_plies: usize) -> RandomGame {
+    let mut position = Position::new();
+    let mut moves = Vec::new();
+    let mut result = None;
+
+    for _ in 0..max_plies {
+        let legal = position.legal_moves();
+        let mv = match legal.choose(rng) {
+            Some(mv) => *mv,
+            None => {
+                result = Some(match (position.is_check(), position.turn) {
+                    (true, Color::White) => ResultSide::BlackWins,
+                    (true, Color::Black) => ResultSide::WhiteWins,
+                    (false, _) => ResultSide::Draw,
+                });
+                break;
+            }
+        };
+        moves.push(mv.uci());
+        position = position.play(mv).expect("generated moves are legal");
+    }
+
+    RandomGame {
+        moves,
+        fen: position.to_fen(),
+        result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::bitboard::position::Move;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn seeded_game_replays_legally() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..20 {
+            let game = random_game(&mut rng, 80);
+            assert!(!game.moves.is_empty());
+
+            let mut position = Position::new();
+            for uci in &game.moves {
+                let mv = Move::from_uci(uci).expect("valid UCI");
+                position = position.play(mv).expect("move should be legal on replay");
+            }
+            assert_eq!(position.to_fen(), game.fen);
+        }
+    }
+
+    #[test]
+    fn random_game_respects_ply_limit() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let game = random_game(&mut rng, 10);
+        assert!(game.moves.len() <= 10);
+    }
+}
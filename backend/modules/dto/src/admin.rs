@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use db_entity::admin_audit;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::games::GameResult;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct RotateServerRequest {
+    /// New authorized server: an account (`G...`) or contract (`C...`) address
+    #[validate(length(min = 1, message = "New server address is required"))]
+    #[schema(example = "GDQNY3PBOJOKYZSRMK2S7LHHGWZIUISD4QORETLMXEWXBI7KFZZMKTL3")]
+    pub new_server: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RotateServerResponse {
+    #[schema(example = "Registry server rotated")]
+    pub message: String,
+    #[schema(example = "3389e9f0f1a65f19736cacf544c2e825313e8447f569233bb8db39aa607c8889")]
+    pub tx_hash: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ForceResultRequest {
+    /// Result to record for the game
+    pub result: GameResult,
+    /// Why the result was overridden; kept in the audit log
+    #[validate(length(min = 1, max = 500, message = "Reason must be between 1 and 500 characters"))]
+    #[schema(example = "Opponent confirmed the disconnect was caused by a server outage")]
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ForceResultResponse {
+    #[schema(example = "Game result updated")]
+    pub message: String,
+    pub game_id: Uuid,
+    pub result: GameResult,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RecomputeRatingsRequest {
+    /// Games started at or after this instant are replayed
+    pub from: DateTime<Utc>,
+    /// Games started before this instant are replayed; must be after `from`
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RecomputeRatingsResponse {
+    #[schema(example = "Ratings recomputed")]
+    pub message: String,
+    pub games_replayed: usize,
+    /// Player ratings written, counting each rating pool of a player once
+    pub ratings_updated: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AdminAuditQuery {
+    #[schema(default = 20, example = 20)]
+    pub limit: Option<u64>,
+
+    #[schema(default = 0, example = 0)]
+    pub offset: Option<u64>,
+}
+
+/// A contract transaction submitted from an admin endpoint
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AdminAuditEntry {
+    pub id: Uuid,
+    /// Id of the admin player who triggered the action
+    pub actor_id: Uuid,
+    /// Contract function invoked
+    #[schema(example = "set_server")]
+    pub action: String,
+    #[schema(example = "GDQNY3PBOJOKYZSRMK2S7LHHGWZIUISD4QORETLMXEWXBI7KFZZMKTL3")]
+    pub target: String,
+    #[schema(example = "3389e9f0f1a65f19736cacf544c2e825313e8447f569233bb8db39aa607c8889")]
+    pub tx_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<admin_audit::Model> for AdminAuditEntry {
+    fn from(entry: admin_audit::Model) -> Self {
+        AdminAuditEntry {
+            id: entry.id,
+            actor_id: entry.actor_id,
+            action: entry.action,
+            target: entry.target,
+            tx_hash: entry.tx_hash,
+            created_at: entry.created_at.with_timezone(&Utc),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AdminAuditResponse {
+    /// Most recent first
+    pub entries: Vec<AdminAuditEntry>,
+    /// Entries in the whole log, across all pages
+    #[schema(example = 42)]
+    pub total: u64,
+}
@@ -1,13 +1,14 @@
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-// Define a regex for validating FEN chess position notation
+// Define a regex for validating FEN chess position notation.
+// The regex crate has no look-around, so both kings being present is left to the chess parser
 static FEN_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
-      r"^(?=\S*K)(?=\S*k)([rnbqkpRNBQKP1-8]+/){7}[rnbqkpRNBQKP1-8]+\s[bw]\s(-|[KQkq]+)\s(-|[a-h][36])\s\d+\s\d+$"
+      r"^([rnbqkpRNBQKP1-8]+/){7}[rnbqkpRNBQKP1-8]+\s[bw]\s(-|[KQkq]+)\s(-|[a-h][36])\s\d+\s\d+$"
     ).unwrap()
 });
 
@@ -46,7 +47,9 @@ pub struct AiSuggestionResponse {
     pub computation_time_ms: u32,
 }
 
+/// Searches are bounded either by depth or by time, never both
 #[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+#[validate(schema(function = "validate_search_limit"))]
 pub struct PositionAnalysisRequest {
     #[validate(regex(
         path = "FEN_REGEX",
@@ -55,9 +58,39 @@ pub struct PositionAnalysisRequest {
     #[schema(example = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
     pub fen: String,
     
+    #[serde(default)]
     #[validate(range(min = 1, max = 30, message = "Depth must be between 1 and 30"))]
     #[schema(example = 15)]
-    pub depth: u8,
+    pub depth: Option<u8>,
+
+    /// Search for this long instead of to a depth, sent to the engine as
+    /// `go movetime`. The server caps it at its configured maximum
+    #[serde(default)]
+    #[validate(range(min = 1, message = "Move time must be at least 1 ms"))]
+    #[schema(example = 2000)]
+    pub movetime_ms: Option<u32>,
+
+    /// One of the engines configured on the server; the default when omitted
+    #[serde(default)]
+    #[schema(example = "stockfish")]
+    pub engine: Option<String>,
+
+    /// Stops a streamed analysis early once this much time has passed
+    #[serde(default)]
+    #[validate(range(min = 1000, max = 60000, message = "Time limit must be between 1 and 60 seconds"))]
+    #[schema(example = 5000)]
+    pub time_limit_ms: Option<u32>,
+}
+
+fn validate_search_limit(request: &PositionAnalysisRequest) -> Result<(), ValidationError> {
+    let message = match (request.depth, request.movetime_ms) {
+        (Some(_), Some(_)) => "Give either depth or movetime_ms, not both",
+        (None, None) => "Either depth or movetime_ms is required",
+        _ => return Ok(()),
+    };
+    let mut error = ValidationError::new("search_limit");
+    error.message = Some(message.into());
+    Err(error)
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -81,3 +114,21 @@ pub struct AlternativeMove {
     #[schema(example = 0.25)]
     pub evaluation: f32,
 }
+
+/// One completed engine depth, sent as a server-sent event by the streaming analysis.
+#[derive(Debug, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct AnalysisDepthUpdate {
+    #[schema(example = 12)]
+    pub depth: u8,
+
+    /// Evaluation in pawns from White's point of view
+    #[schema(example = 0.35)]
+    pub eval: f32,
+
+    /// Moves until mate, positive when White mates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mate: Option<i32>,
+
+    #[schema(example = "e2e4")]
+    pub best_move: String,
+}
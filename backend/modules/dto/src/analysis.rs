@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LegalMovesRequest {
+    #[schema(example = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+    pub fen: String,
+
+    /// Square of the piece being moved, e.g. `g1`
+    #[schema(example = "g1")]
+    pub square: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct LegalDestination {
+    #[schema(example = "f3")]
+    pub square: String,
+
+    /// True when the move promotes a pawn; the client then picks the piece
+    #[schema(example = false)]
+    pub promotion: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LegalMovesResponse {
+    #[schema(example = "g1")]
+    pub square: String,
+
+    /// Empty when the square is empty, holds an opponent piece, or the piece cannot move
+    pub destinations: Vec<LegalDestination>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct StaticEvalRequest {
+    #[schema(example = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+    pub fen: String,
+}
+
+/// All terms are from the perspective of the side to move
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct StaticEvalResponse {
+    /// Material advantage in pawns, counting minor pieces as 3, rooks as 5 and queens as 9
+    #[schema(example = 0)]
+    pub material_balance: i32,
+
+    /// Legal move count of the side to move minus that of the opponent
+    #[schema(example = 0)]
+    pub mobility: i32,
+
+    /// Combined estimate in centipawns
+    #[schema(example = 0)]
+    pub score_cp: i32,
+}
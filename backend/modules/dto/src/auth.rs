@@ -47,6 +47,44 @@ pub struct AuthResponse {
     pub username: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VerifyEmailQuery {
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VerifyEmailResponse {
+    #[schema(value_type = String, format = "uuid", example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub player_id: Uuid,
+
+    #[schema(example = true)]
+    pub email_verified: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(email(message = "Invalid email format"))]
+    #[schema(example = "user@example.com")]
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ResetPasswordRequest {
+    #[schema(example = "q2l0Cq3sN0oWJzG1m0bB7x6yYtq9sVv8kP3dR5eF1aE")]
+    pub token: String,
+
+    #[validate(length(min = 8, message = "Password must be at least 8 characters"))]
+    #[schema(example = "NewSecurePass123!")]
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MessageResponse {
+    #[schema(example = "Password has been reset")]
+    pub message: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RefreshTokenRequest {
     #[schema(example = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...")]
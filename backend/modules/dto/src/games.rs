@@ -5,13 +5,18 @@ use validator::{Validate, ValidationError};
 use chrono::{DateTime, Utc};
 use once_cell::sync::Lazy;
 use regex::Regex;
+use chess::outcome::Outcome;
+use db_entity::game::{self, ResultSide};
+use db_entity::game_rating_change;
+
+use crate::ids::{GameId, PlayerId};
 
 // Define a regex for validating chess moves in algebraic notation
 static CHESS_MOVE_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^[a-h][1-8][a-h][1-8][qrbnQRBN]?$").unwrap()
 });
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub enum PlayerColor {
     #[serde(rename = "white")]
     White,
@@ -21,8 +26,26 @@ pub enum PlayerColor {
     Random,
 }
 
+impl PlayerColor {
+    /// Resolves a color preference to a concrete side, picking randomly for
+    /// `Random` or when no preference was given.
+    pub fn resolve(preference: Option<PlayerColor>) -> PlayerColor {
+        match preference {
+            Some(PlayerColor::White) => PlayerColor::White,
+            Some(PlayerColor::Black) => PlayerColor::Black,
+            Some(PlayerColor::Random) | None => {
+                if rand::random::<bool>() {
+                    PlayerColor::White
+                } else {
+                    PlayerColor::Black
+                }
+            }
+        }
+    }
+}
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub enum GameStatus {
     #[serde(rename = "waiting")]
     Waiting,
@@ -34,7 +57,7 @@ pub enum GameStatus {
     Aborted,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub enum GameResult {
     #[serde(rename = "white_win")]
     WhiteWin,
@@ -46,6 +69,28 @@ pub enum GameResult {
     InProgress,
 }
 
+impl GameResult {
+    /// How the game was decided, or `None` while it is in progress.
+    pub fn outcome(self) -> Option<Outcome> {
+        match self {
+            GameResult::WhiteWin => Some(Outcome::WhiteWins),
+            GameResult::BlackWin => Some(Outcome::BlackWins),
+            GameResult::Draw => Some(Outcome::Draw),
+            GameResult::InProgress => None,
+        }
+    }
+}
+
+impl From<Outcome> for GameResult {
+    fn from(outcome: Outcome) -> Self {
+        match outcome {
+            Outcome::WhiteWins => GameResult::WhiteWin,
+            Outcome::BlackWins => GameResult::BlackWin,
+            Outcome::Draw => GameResult::Draw,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
 pub struct CreateGameRequest {
     #[validate(range(min = 60, max = 7200, message = "Time control must be between 1 minute and 2 hours"))]
@@ -54,8 +99,15 @@ pub struct CreateGameRequest {
     #[validate(range(min = 0, max = 60, message = "Increment must be between 0 and 60 seconds"))]
     pub increment: i32,
     
+    /// Preferred side for the creator; also accepted as `preferred_color`.
+    #[serde(alias = "preferred_color")]
     pub player_color: Option<PlayerColor>,
     pub opponent_id: Option<Uuid>,
+    /// Stellar addresses (`G...` or `C...`) of both sides. With both, the game
+    /// id is derived from them and the creation time, so it is the same id the
+    /// game is later recorded under on-chain.
+    pub white_address: Option<String>,
+    pub black_address: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -91,6 +143,133 @@ pub struct GameDisplayDTO {
     pub updated_at: DateTime<Utc>,
 }
 
+fn status_and_result(result: &Option<ResultSide>) -> (GameStatus, GameResult) {
+    match result {
+        None | Some(ResultSide::Ongoing) => (GameStatus::InProgress, GameResult::InProgress),
+        Some(ResultSide::WhiteWins) => (GameStatus::Completed, GameResult::WhiteWin),
+        Some(ResultSide::BlackWins) => (GameStatus::Completed, GameResult::BlackWin),
+        Some(ResultSide::Draw) => (GameStatus::Completed, GameResult::Draw),
+        // An abandoned game has no winner to report
+        Some(ResultSide::Abandoned) => (GameStatus::Aborted, GameResult::InProgress),
+    }
+}
+
+fn move_history(pgn: &serde_json::Value) -> Vec<String> {
+    pgn.get("moves")
+        .and_then(|moves| moves.as_array())
+        .map(|moves| moves.iter().filter_map(|mv| mv.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+impl From<game::Model> for GameDisplayDTO {
+    fn from(value: game::Model) -> Self {
+        let (status, result) = status_and_result(&value.result);
+        let move_history = move_history(&value.pgn);
+
+        // Clocks are not stored with the game, so the whole time control is
+        // reported as remaining for both sides
+        Self {
+            id: value.id,
+            white_player_id: value.white_player,
+            black_player_id: Some(value.black_player),
+            status,
+            result,
+            current_fen: value.fen,
+            move_history,
+            time_control: value.duration_sec,
+            increment: 0,
+            white_time_remaining: value.duration_sec,
+            black_time_remaining: value.duration_sec,
+            created_at: value.created_at.with_timezone(&Utc),
+            started_at: Some(value.started_at.with_timezone(&Utc)),
+            updated_at: value.updated_at.with_timezone(&Utc),
+        }
+    }
+}
+
+/// One side of a [`GameSummary`]. The ratings are only known for rated games
+/// that have finished.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SideSummary {
+    #[schema(value_type = String, format = "uuid", example = "123e4567-e89b-12d3-a456-426614174001")]
+    pub player_id: Uuid,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 1500)]
+    pub rating_before: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 1516)]
+    pub rating_after: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 16)]
+    pub rating_delta: Option<i32>,
+}
+
+impl SideSummary {
+    fn new(player_id: Uuid, changes: &[game_rating_change::Model]) -> Self {
+        let change = changes.iter().find(|change| change.player_id == player_id);
+        Self {
+            player_id,
+            rating_before: change.map(|change| change.rating_before),
+            rating_after: change.map(|change| change.rating_after),
+            rating_delta: change.map(|change| change.rating_after - change.rating_before),
+        }
+    }
+}
+
+/// A game together with its move count and, for rated games, the rating
+/// change of each side.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GameSummary {
+    #[schema(value_type = String, format = "uuid", example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub game_id: Uuid,
+
+    pub status: GameStatus,
+    pub result: GameResult,
+
+    #[schema(example = 42)]
+    pub move_count: usize,
+
+    #[schema(example = 600)]
+    pub duration_sec: i32,
+
+    /// Rating pool the game counted towards, absent for unrated games
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "blitz")]
+    pub pool: Option<String>,
+
+    pub white: SideSummary,
+    pub black: SideSummary,
+}
+
+impl GameSummary {
+    /// Summary of `game`, with `changes` being the rating snapshots written
+    /// when it was finalized (none for unrated games).
+    pub fn new(game: game::Model, changes: &[game_rating_change::Model]) -> Self {
+        let (status, result) = status_and_result(&game.result);
+        Self {
+            game_id: game.id,
+            status,
+            result,
+            move_count: move_history(&game.pgn).len(),
+            duration_sec: game.duration_sec,
+            pool: changes.first().map(|change| change.pool.clone()),
+            white: SideSummary::new(game.white_player, changes),
+            black: SideSummary::new(game.black_player, changes),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct BatchGamesRequest {
+    /// Games to fetch, at most 100; unknown ids are skipped
+    #[validate(length(min = 1, max = 100, message = "Between 1 and 100 game ids may be requested at once"))]
+    #[schema(value_type = Vec<String>, example = json!(["123e4567-e89b-12d3-a456-426614174000"]))]
+    pub ids: Vec<GameId>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
 pub struct MakeMoveRequest {
     #[validate(regex(
@@ -103,9 +282,13 @@ pub struct MakeMoveRequest {
 
 #[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
 pub struct JoinGameRequest {
-    #[validate(custom = "validate_uuid")]
+    #[validate(custom = "validate_player_id")]
     #[schema(value_type = String, format = "uuid", example = "123e4567-e89b-12d3-a456-426614174000")]
-    pub player_id: Uuid,
+    pub player_id: PlayerId,
+}
+
+pub fn validate_player_id(id: &PlayerId) -> Result<(), ValidationError> {
+    validate_uuid(&id.0)
 }
 
 // UUID validation function
@@ -133,4 +316,45 @@ pub struct ListGamesQuery {
 
     #[schema(example = "MjAyNS0wNS0zMVQxMDowMDowMC4wMDAwMDBaLDEyM2U0NTY3LWU4OWItMTJkMy1hNDU2LTQyNjYxNDE3NDAwMA==")]
     pub cursor: Option<String>,
+
+    /// Only ongoing games, most recently started first; paginated with `offset`
+    #[schema(example = true)]
+    pub active: Option<bool>,
+
+    #[schema(default = 0, example = 0)]
+    pub offset: Option<u64>,
+}
+
+/// Per-side accuracy of a game: 100 means every move kept the engine's evaluation
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GameAccuracyResponse {
+    #[schema(value_type = String, format = "uuid", example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub game_id: Uuid,
+
+    /// `null` when White has not moved
+    #[schema(example = 87.4)]
+    pub white_accuracy: Option<f64>,
+
+    /// `null` when Black has not moved
+    #[schema(example = 79.2)]
+    pub black_accuracy: Option<f64>,
+
+    #[schema(example = 64)]
+    pub moves_analyzed: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_outcome_round_trips_through_api_and_db_results() {
+        for outcome in Outcome::ALL {
+            let api = GameResult::from(outcome);
+            assert_eq!(api.outcome(), Some(outcome));
+            let db = ResultSide::from(api.outcome().unwrap());
+            assert_eq!(db.outcome().map(GameResult::from), Some(api));
+        }
+        assert_eq!(GameResult::InProgress.outcome(), None);
+    }
 }
@@ -0,0 +1,96 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Ids travel as plain UUID strings on the wire; the newtypes only keep a
+// player id from being passed where a game id is expected.
+macro_rules! uuid_id {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(pub Uuid);
+
+        impl $name {
+            pub fn new_v4() -> Self {
+                Self(Uuid::new_v4())
+            }
+
+            pub fn as_uuid(&self) -> Uuid {
+                self.0
+            }
+        }
+
+        impl From<Uuid> for $name {
+            fn from(id: Uuid) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<$name> for Uuid {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = uuid::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Uuid::parse_str(s).map(Self)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+uuid_id!(
+    /// Primary key of a row in the `player` table
+    PlayerId
+);
+
+uuid_id!(
+    /// Primary key of a row in the `game` table, also used for matchmaking matches
+    GameId
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "123e4567-e89b-12d3-a456-426614174000";
+
+    #[test]
+    fn test_ids_round_trip_through_strings() {
+        let player: PlayerId = ID.parse().unwrap();
+        assert_eq!(player.to_string(), ID);
+        assert_eq!(Uuid::from(player), Uuid::parse_str(ID).unwrap());
+
+        let game: GameId = ID.to_uppercase().parse().unwrap();
+        assert_eq!(game.to_string(), ID);
+    }
+
+    #[test]
+    fn test_ids_serialize_as_plain_strings() {
+        let game = GameId::from(Uuid::parse_str(ID).unwrap());
+        assert_eq!(serde_json::to_string(&game).unwrap(), format!("\"{}\"", ID));
+        assert_eq!(serde_json::from_str::<GameId>(&format!("\"{}\"", ID)).unwrap(), game);
+    }
+
+    #[test]
+    fn test_malformed_ids_are_rejected() {
+        for input in ["", "42", "not-a-uuid", "123e4567-e89b-12d3-a456-42661417400"] {
+            assert!(input.parse::<PlayerId>().is_err(), "{input:?} parsed as a player id");
+            assert!(input.parse::<GameId>().is_err(), "{input:?} parsed as a game id");
+        }
+        assert!(serde_json::from_str::<PlayerId>("\"not-a-uuid\"").is_err());
+        assert!(serde_json::from_str::<PlayerId>("42").is_err());
+    }
+}
@@ -1,5 +1,8 @@
+pub mod ids;
 pub mod players;
 pub mod responses;
 pub mod games;
 pub mod auth;
-pub mod ai;
\ No newline at end of file
+pub mod ai;
+pub mod admin;
+pub mod analysis;
@@ -1,9 +1,12 @@
-use db_entity::player::Model;
+use chrono::{DateTime, Utc};
+use db_entity::{game, player::Model};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::games::PlayerColor;
+
 #[derive(Debug, Deserialize, Serialize, ToSchema, Validate)]
 pub struct NewPlayer {
     #[validate(length(
@@ -106,6 +109,46 @@ pub struct UpdatedPlayer {
     pub social_links: Option<Vec<String>>,
 }
 
+/// A player's finished games against one opponent, from the player's side.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct HeadToHead {
+    pub player_id: Uuid,
+    pub opponent_id: Uuid,
+    pub wins: u64,
+    pub losses: u64,
+    pub draws: u64,
+    pub total: u64,
+}
+
+/// An ongoing game of a player, with the position it is currently in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ActiveGame {
+    pub game_id: Uuid,
+    pub white_player_id: Uuid,
+    pub black_player_id: Uuid,
+    pub current_fen: String,
+    /// Side to move, read from the FEN
+    pub turn: PlayerColor,
+    pub started_at: DateTime<Utc>,
+}
+
+impl From<game::Model> for ActiveGame {
+    fn from(value: game::Model) -> Self {
+        let turn = match value.fen.split_whitespace().nth(1) {
+            Some("b") => PlayerColor::Black,
+            _ => PlayerColor::White,
+        };
+        Self {
+            game_id: value.id,
+            white_player_id: value.white_player,
+            black_player_id: value.black_player,
+            current_fen: value.fen,
+            turn,
+            started_at: value.started_at.with_timezone(&Utc),
+        }
+    }
+}
+
 impl From<Model> for UpdatedPlayer {
     fn from(value: Model) -> Self {
         Self {
@@ -2,8 +2,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
 use uuid::Uuid;
+use dto::ids::GameId;
 use chrono::{DateTime, Utc};
-use std::time::Duration;
+use std::fmt;
 
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -21,9 +22,66 @@ impl MatchType {
             MatchType::Private => "matchmaking:invites".to_string(),
         }
     }
+
+    /// List of the times, in milliseconds, at which recent matches were made
+    /// from this queue, newest first
+    pub fn match_times_key(&self) -> String {
+        match self {
+            MatchType::Rated => "matchmaking:match_times:rated".to_string(),
+            MatchType::Casual => "matchmaking:match_times:casual".to_string(),
+            MatchType::Private => "matchmaking:match_times:private".to_string(),
+        }
+    }
+}
+
+
+/// Every request waiting for an opponent, by queue
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueListing {
+    pub rated: Vec<MatchRequest>,
+    pub casual: Vec<MatchRequest>,
+    /// Private invites still waiting for the invited player
+    pub private: Vec<MatchRequest>,
 }
 
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    pub fn opposite(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+/// Assigns sides honoring both players' preferences when they are compatible,
+/// and picking randomly when they conflict or neither player has one.
+pub fn assign_colors(first: Option<Color>, second: Option<Color>) -> (Color, Color) {
+    assign_colors_with(first, second, rand::random::<bool>)
+}
+
+/// Same as [`assign_colors`], with the coin flip (`true` = first player gets white) supplied by the caller.
+pub fn assign_colors_with<F>(first: Option<Color>, second: Option<Color>, coin_flip: F) -> (Color, Color)
+where
+    F: FnOnce() -> bool,
+{
+    match (first, second) {
+        (Some(a), Some(b)) if a != b => (a, b),
+        (Some(a), None) => (a, a.opposite()),
+        (None, Some(b)) => (b.opposite(), b),
+        _ => {
+            let first_color = if coin_flip() { Color::White } else { Color::Black };
+            (first_color, first_color.opposite())
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub wallet_address: String,
@@ -38,9 +96,65 @@ pub struct MatchRequest {
     pub match_type: MatchType,
     pub invite_address: Option<String>, // For private matches__
     pub max_elo_diff: Option<u32>,      // For rated matches__
+    #[serde(default)]
+    pub preferred_color: Option<Color>, // None = no preference
+}
+
+/// Largest rating gap a rated request may accept.
+pub const MAX_ELO_DIFF: u32 = 2000;
+
+/// Why a `MatchRequest` was refused before reaching the queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidMatchRequest {
+    WalletAddress(String),
+    InviteAddress(String),
+    MaxEloDiff(u32),
+    MissingInviteAddress,
+    UnexpectedInviteAddress,
+}
+
+impl fmt::Display for InvalidMatchRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidMatchRequest::WalletAddress(v) => write!(f, "Invalid wallet address: {:?}", v),
+            InvalidMatchRequest::InviteAddress(v) => write!(f, "Invalid invite address: {:?}", v),
+            InvalidMatchRequest::MaxEloDiff(v) => {
+                write!(f, "max_elo_diff {} is above the limit of {}", v, MAX_ELO_DIFF)
+            }
+            InvalidMatchRequest::MissingInviteAddress => write!(f, "Private matches need an invite_address"),
+            InvalidMatchRequest::UnexpectedInviteAddress => {
+                write!(f, "invite_address is only allowed for private matches")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidMatchRequest {}
+
+/// True for a Stellar account address (`G...` strkey).
+fn is_wallet_address(address: &str) -> bool {
+    stellar_strkey::ed25519::PublicKey::from_string(address).is_ok()
 }
 
 impl MatchRequest {
+    /// Checks the request is one the queue can serve.
+    pub fn validate(&self) -> Result<(), InvalidMatchRequest> {
+        if !is_wallet_address(&self.player.wallet_address) {
+            return Err(InvalidMatchRequest::WalletAddress(self.player.wallet_address.clone()));
+        }
+        if let Some(max_elo_diff) = self.max_elo_diff.filter(|diff| *diff > MAX_ELO_DIFF) {
+            return Err(InvalidMatchRequest::MaxEloDiff(max_elo_diff));
+        }
+        match (&self.match_type, &self.invite_address) {
+            (MatchType::Private, None) => Err(InvalidMatchRequest::MissingInviteAddress),
+            (MatchType::Private, Some(invite)) if !is_wallet_address(invite) => {
+                Err(InvalidMatchRequest::InviteAddress(invite.clone()))
+            }
+            (MatchType::Rated | MatchType::Casual, Some(_)) => Err(InvalidMatchRequest::UnexpectedInviteAddress),
+            _ => Ok(()),
+        }
+    }
+
     pub fn to_redis_value(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
     }
@@ -53,9 +167,11 @@ impl MatchRequest {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Match {
-    pub id: Uuid,
+    pub id: GameId,
     pub player1: Player,
     pub player2: Player,
+    pub player1_color: Color,
+    pub player2_color: Color,
     pub match_type: MatchType,
     pub created_at: DateTime<Utc>, 
 }
@@ -71,6 +187,115 @@ pub struct QueueStatus {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MatchmakingResponse {
     pub status: String,
-    pub match_id: Option<Uuid>,
+    pub match_id: Option<GameId>,
     pub request_id: Uuid,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compatible_preferences_are_honored() {
+        let never_called = || panic!("no coin flip needed");
+        assert_eq!(
+            assign_colors_with(Some(Color::White), Some(Color::Black), never_called),
+            (Color::White, Color::Black)
+        );
+        assert_eq!(
+            assign_colors_with(Some(Color::Black), Some(Color::White), never_called),
+            (Color::Black, Color::White)
+        );
+        assert_eq!(
+            assign_colors_with(None, Some(Color::White), never_called),
+            (Color::Black, Color::White)
+        );
+        assert_eq!(
+            assign_colors_with(Some(Color::Black), None, never_called),
+            (Color::Black, Color::White)
+        );
+    }
+
+    fn wallet(seed: u8) -> String {
+        stellar_strkey::ed25519::PublicKey([seed; 32]).to_string()
+    }
+
+    fn request(match_type: MatchType, invite_address: Option<String>) -> MatchRequest {
+        MatchRequest {
+            id: Uuid::new_v4(),
+            player: Player {
+                wallet_address: wallet(1),
+                elo: 1500,
+                join_time: Utc::now(),
+            },
+            match_type,
+            invite_address,
+            max_elo_diff: Some(200),
+            preferred_color: None,
+        }
+    }
+
+    #[test]
+    fn valid_requests_pass() {
+        assert_eq!(request(MatchType::Rated, None).validate(), Ok(()));
+        assert_eq!(request(MatchType::Casual, None).validate(), Ok(()));
+        assert_eq!(request(MatchType::Private, Some(wallet(2))).validate(), Ok(()));
+
+        let mut widest = request(MatchType::Rated, None);
+        widest.max_elo_diff = Some(MAX_ELO_DIFF);
+        assert_eq!(widest.validate(), Ok(()));
+    }
+
+    #[test]
+    fn invalid_wallet_address_is_rejected() {
+        for address in ["", "GABC", "not a wallet"] {
+            let mut invalid = request(MatchType::Casual, None);
+            invalid.player.wallet_address = address.to_string();
+            assert_eq!(invalid.validate(), Err(InvalidMatchRequest::WalletAddress(address.to_string())));
+        }
+        // A well-formed contract address is not a wallet
+        let mut contract = request(MatchType::Casual, None);
+        contract.player.wallet_address = stellar_strkey::Contract([1; 32]).to_string();
+        assert!(matches!(contract.validate(), Err(InvalidMatchRequest::WalletAddress(_))));
+    }
+
+    #[test]
+    fn excessive_elo_diff_is_rejected() {
+        let mut invalid = request(MatchType::Rated, None);
+        invalid.max_elo_diff = Some(MAX_ELO_DIFF + 1);
+        let error = invalid.validate().unwrap_err();
+        assert_eq!(error, InvalidMatchRequest::MaxEloDiff(MAX_ELO_DIFF + 1));
+        assert_eq!(error.to_string(), "max_elo_diff 2001 is above the limit of 2000");
+    }
+
+    #[test]
+    fn invite_address_must_match_match_type() {
+        assert_eq!(
+            request(MatchType::Private, None).validate(),
+            Err(InvalidMatchRequest::MissingInviteAddress)
+        );
+        assert_eq!(
+            request(MatchType::Private, Some("nobody".to_string())).validate(),
+            Err(InvalidMatchRequest::InviteAddress("nobody".to_string()))
+        );
+        assert_eq!(
+            request(MatchType::Rated, Some(wallet(2))).validate(),
+            Err(InvalidMatchRequest::UnexpectedInviteAddress)
+        );
+        assert_eq!(
+            request(MatchType::Casual, Some(wallet(2))).validate(),
+            Err(InvalidMatchRequest::UnexpectedInviteAddress)
+        );
+    }
+
+    #[test]
+    fn conflicting_preferences_are_randomized() {
+        let white = Some(Color::White);
+        assert_eq!(assign_colors_with(white, white, || true), (Color::White, Color::Black));
+        assert_eq!(assign_colors_with(white, white, || false), (Color::Black, Color::White));
+        assert_eq!(assign_colors_with(None, None, || false), (Color::Black, Color::White));
+
+        let (a, b) = assign_colors(white, white);
+        assert_eq!(a.opposite(), b);
+    }
+}
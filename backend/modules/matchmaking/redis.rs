@@ -1,5 +1,79 @@
 use deadpool_redis::{Config, Pool, Runtime};
 use redis::AsyncCommands;
+use std::env;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+/// Bounds how long matchmaking waits for a pooled Redis connection, so a hung
+/// Redis fails requests instead of stalling them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConnectionRetry {
+    /// Time allowed for each attempt to get a connection
+    pub timeout: Duration,
+    /// Attempts before giving up, including the first
+    pub max_attempts: u32,
+    /// Pause before the first retry; doubled before each further retry
+    pub backoff: Duration,
+}
+
+impl Default for ConnectionRetry {
+    fn default() -> Self {
+        ConnectionRetry {
+            timeout: Duration::from_secs(2),
+            max_attempts: 3,
+            backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+impl ConnectionRetry {
+    /// Reads `REDIS_CONNECT_TIMEOUT_MS`, `REDIS_CONNECT_ATTEMPTS` and
+    /// `REDIS_CONNECT_BACKOFF_MS`, keeping the default for any that are unset.
+    pub fn from_env() -> Self {
+        let default = ConnectionRetry::default();
+        let var = |name: &str| env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        ConnectionRetry {
+            timeout: var("REDIS_CONNECT_TIMEOUT_MS").map(Duration::from_millis).unwrap_or(default.timeout),
+            max_attempts: var("REDIS_CONNECT_ATTEMPTS").map(|n| n as u32).unwrap_or(default.max_attempts),
+            backoff: var("REDIS_CONNECT_BACKOFF_MS").map(Duration::from_millis).unwrap_or(default.backoff),
+        }
+    }
+}
+
+/// Runs `acquire` until it yields a connection, giving each attempt
+/// `retry.timeout` and backing off between attempts.
+pub async fn acquire_with_retry<T, E, F, Fut>(retry: &ConnectionRetry, mut acquire: F) -> Result<T, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    let attempts = retry.max_attempts.max(1);
+    let mut backoff = retry.backoff;
+    let mut last_error = String::new();
+
+    for attempt in 1..=attempts {
+        match tokio::time::timeout(retry.timeout, acquire()).await {
+            Ok(Ok(conn)) => return Ok(conn),
+            Ok(Err(e)) => last_error = e.to_string(),
+            Err(_) => last_error = format!("timed out after {}ms", retry.timeout.as_millis()),
+        }
+        if attempt < attempts {
+            log::warn!(
+                "Redis connection attempt {}/{} failed: {}; retrying in {}ms",
+                attempt,
+                attempts,
+                last_error,
+                backoff.as_millis()
+            );
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+
+    Err(format!("Redis connection failed after {} attempts: {}", attempts, last_error))
+}
 
 /// Creates a Redis connection pool from a Redis URL
 pub fn create_redis_pool(redis_url: &str) -> Result<Pool, Box<dyn std::error::Error>> {
@@ -22,3 +96,48 @@ pub async fn test_redis_connection(pool: &Pool) -> Result<(), String> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Instant;
+
+    fn quick_retry() -> ConnectionRetry {
+        ConnectionRetry {
+            timeout: Duration::from_millis(20),
+            max_attempts: 3,
+            backoff: Duration::from_millis(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hung_pool_fails_promptly() {
+        let started = Instant::now();
+        // A pool that never hands out a connection
+        let result = acquire_with_retry(&quick_retry(), std::future::pending::<Result<(), String>>).await;
+
+        let error = result.unwrap_err();
+        assert!(error.contains("after 3 attempts"), "{}", error);
+        assert!(error.contains("timed out"), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_connection_succeeds() {
+        let attempts = AtomicU32::new(0);
+        let result = acquire_with_retry(&quick_retry(), || {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                if attempt < 3 {
+                    Err("connection refused")
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(3));
+    }
+}
@@ -1,5 +1,7 @@
 use actix_web::{web, HttpResponse, Responder};
 use chrono::Utc;
+use dto::ids::GameId;
+use security::{AdminPlayers, AuthenticatedUser};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -13,13 +15,15 @@ pub struct JoinQueueRequest {
     pub match_type: MatchType,
     pub invite_address: Option<String>,
     pub max_elo_diff: Option<u32>,
+    pub preferred_color: Option<Color>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AcceptInviteRequest {
     pub wallet_address: String,
     pub elo: u32,
-    pub inviter_request_id: Uuid,
+    /// When omitted, the invite addressed to `wallet_address` is accepted.
+    pub inviter_request_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -27,6 +31,17 @@ pub struct CancelRequest {
     pub request_id: Uuid,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CancelAllRequest {
+    pub wallet_address: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PurgeRequest {
+    /// Queue to clear; every queue and private invite when omitted
+    pub queue: Option<MatchType>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct StatusResponse {
     pub status: String,
@@ -45,11 +60,21 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .route("/join", web::post().to(join_queue))
             .route("/status/{request_id}", web::get().to(get_status))
             .route("/cancel", web::post().to(cancel_request))
+            .route("/mine", web::delete().to(cancel_all_requests))
             .route("/accept-invite", web::post().to(accept_invite))
             .route("/match/{match_id}", web::get().to(get_match)),
     );
 }
 
+/// Operator endpoints; requires a `JwtService` and `AdminPlayers` in app data
+pub fn admin_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/v1/admin/matchmaking")
+            .route("/queues", web::get().to(list_queues))
+            .route("/purge", web::post().to(purge_queues)),
+    );
+}
+
 async fn join_queue(
     service: web::Data<MatchmakingService>,
     req: web::Json<JoinQueueRequest>,
@@ -68,8 +93,16 @@ async fn join_queue(
         match_type: req.match_type.clone(),
         invite_address: req.invite_address.clone(),
         max_elo_diff: req.max_elo_diff,
+        preferred_color: req.preferred_color,
     };
 
+    if let Err(e) = match_request.validate() {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            status: "error".to_string(),
+            error: e.to_string(),
+        });
+    }
+
     match service.join_queue(match_request).await {
         Ok(response) => HttpResponse::Ok().json(response),
         Err(e) => {
@@ -128,6 +161,25 @@ async fn cancel_request(
     }
 }
 
+async fn cancel_all_requests(
+    service: web::Data<MatchmakingService>,
+    query: web::Query<CancelAllRequest>,
+) -> impl Responder {
+    match service.cancel_all_for(&query.wallet_address).await {
+        Ok(cancelled) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "Requests cancelled successfully",
+            "cancelled": cancelled
+        })),
+        Err(e) => {
+            log::error!("Failed to cancel requests: {}", e);
+            HttpResponse::ServiceUnavailable().json(ErrorResponse {
+                status: "error".to_string(),
+                error: "Service temporarily unavailable".to_string(),
+            })
+        }
+    }
+}
+
 async fn accept_invite(
     service: web::Data<MatchmakingService>,
     req: web::Json<AcceptInviteRequest>,
@@ -138,7 +190,12 @@ async fn accept_invite(
         join_time: Utc::now(),
     };
 
-    match service.accept_private_invite(req.inviter_request_id, player).await {
+    let result = match req.inviter_request_id {
+        Some(inviter_request_id) => service.accept_private_invite(inviter_request_id, player).await,
+        None => service.accept_invite_for(&req.wallet_address, player).await,
+    };
+
+    match result {
         Ok(Some(response)) => HttpResponse::Ok().json(response),
         Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
             "status": "Invite not found"
@@ -155,7 +212,7 @@ async fn accept_invite(
 
 async fn get_match(
     service: web::Data<MatchmakingService>,
-    path: web::Path<Uuid>,
+    path: web::Path<GameId>,
 ) -> impl Responder {
     let match_id = path.into_inner();
 
@@ -167,3 +224,126 @@ async fn get_match(
         }))
     }
 }
+
+async fn list_queues(
+    service: web::Data<MatchmakingService>,
+    admins: web::Data<AdminPlayers>,
+    user: AuthenticatedUser,
+) -> impl Responder {
+    if let Err(forbidden) = admins.authorize(&user) {
+        return forbidden;
+    }
+
+    match service.list_queues().await {
+        Ok(listing) => HttpResponse::Ok().json(listing),
+        Err(e) => {
+            log::error!("Failed to list queues: {}", e);
+            HttpResponse::ServiceUnavailable().json(ErrorResponse {
+                status: "error".to_string(),
+                error: "Service temporarily unavailable".to_string(),
+            })
+        }
+    }
+}
+
+async fn purge_queues(
+    service: web::Data<MatchmakingService>,
+    admins: web::Data<AdminPlayers>,
+    user: AuthenticatedUser,
+    req: web::Json<PurgeRequest>,
+) -> impl Responder {
+    let admin_id = match admins.authorize(&user) {
+        Ok(admin_id) => admin_id,
+        Err(forbidden) => return forbidden,
+    };
+
+    match service.purge(req.queue.clone()).await {
+        Ok(purged) => {
+            log::info!("Admin {} purged {} requests from {:?}", admin_id, purged, req.queue);
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "Queues purged",
+                "purged": purged
+            }))
+        }
+        Err(e) => {
+            log::error!("Failed to purge queues: {}", e);
+            HttpResponse::ServiceUnavailable().json(ErrorResponse {
+                status: "error".to_string(),
+                error: "Service temporarily unavailable".to_string(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::create_redis_pool;
+    use actix_web::{test, App};
+    use security::JwtService;
+
+    // get_match only reads in-memory state, so the pool never connects
+    fn service() -> web::Data<MatchmakingService> {
+        web::Data::new(MatchmakingService::new(
+            create_redis_pool("redis://127.0.0.1:6379").unwrap(),
+        ))
+    }
+
+    #[actix_web::test]
+    async fn test_match_lookup_rejects_malformed_ids() {
+        let app = test::init_service(App::new().app_data(service()).configure(config)).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/matchmaking/match/{}", GameId::new_v4()))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 404);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["status"], "Match not found");
+
+        // Never reaches the handler, so there is no JSON body
+        let req = test::TestRequest::get()
+            .uri("/matchmaking/match/not-a-uuid")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 404);
+        assert!(serde_json::from_slice::<serde_json::Value>(&test::read_body(resp).await).is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_admin_endpoints_reject_non_admins() {
+        let jwt_service = JwtService::new("test_secret".to_string(), 3600);
+        let player_token = jwt_service.generate_player_token(1, Uuid::new_v4(), "player").unwrap();
+        // Rejected before the service is used, so Redis is never contacted
+        let app = test::init_service(
+            App::new()
+                .app_data(service())
+                .app_data(web::Data::new(jwt_service))
+                .app_data(web::Data::new(AdminPlayers(vec![Uuid::new_v4()])))
+                .configure(admin_config),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/v1/admin/matchmaking/queues")
+            .insert_header(("Authorization", format!("Bearer {}", player_token)))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+        let req = test::TestRequest::post()
+            .uri("/v1/admin/matchmaking/purge")
+            .insert_header(("Authorization", format!("Bearer {}", player_token)))
+            .set_json(serde_json::json!({ "queue": "Casual" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 403);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"], "Admin access required");
+
+        let req = test::TestRequest::post()
+            .uri("/v1/admin/matchmaking/purge")
+            .set_json(serde_json::json!({}))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 401);
+    }
+}
@@ -1,45 +1,95 @@
 use actix_web::web;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use deadpool_redis::Pool;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use redis::AsyncCommands;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use dto::ids::GameId;
 use uuid::Uuid;
 
 use super::models::*;
+use super::redis::{acquire_with_retry, ConnectionRetry};
 
 const ELO_RANGE_INCREMENT_PER_MINUTE: u32 = 50;
 const DEFAULT_MAX_ELO_DIFF: u32 = 200;
 const DEFAULT_ESTIMATED_WAIT_TIME: Duration = Duration::from_secs(60);
+/// Recent matches per queue the wait-time estimate averages over
+const DEFAULT_MATCH_RATE_SAMPLES: usize = 20;
+/// Queued requests expire after an hour, so no estimate goes beyond that
+const MAX_ESTIMATED_WAIT_TIME: Duration = Duration::from_secs(3600);
+
+/// Source of the current time, replaceable so tests can move time forward.
+pub type Clock = Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>;
 
 #[derive(Clone)]
 pub struct MatchmakingService {
     redis_pool: Pool,
-    active_matches: Arc<Mutex<HashMap<Uuid, Match>>>,
+    connection_retry: ConnectionRetry,
+    clock: Clock,
+    // Flips the coin when colors cannot follow the players' preferences
+    rng: Arc<Mutex<StdRng>>,
+    match_rate_samples: usize,
+    active_matches: Arc<Mutex<HashMap<GameId, Match>>>,
 }
 
 impl MatchmakingService {
     pub fn new(redis_pool: Pool) -> Self {
         Self {
             redis_pool,
+            connection_retry: ConnectionRetry::default(),
+            clock: Arc::new(Utc::now),
+            rng: Arc::new(Mutex::new(StdRng::from_entropy())),
+            match_rate_samples: DEFAULT_MATCH_RATE_SAMPLES,
             active_matches: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    pub fn with_connection_retry(mut self, connection_retry: ConnectionRetry) -> Self {
+        self.connection_retry = connection_retry;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Makes color assignment reproducible: services with the same seed
+    /// assign the same colors to the same sequence of matches.
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        self.rng = Arc::new(Mutex::new(StdRng::seed_from_u64(seed)));
+        self
+    }
+
+    /// How many recent matches per queue the wait-time estimate averages over
+    pub fn with_match_rate_samples(mut self, samples: usize) -> Self {
+        self.match_rate_samples = samples;
+        self
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        (self.clock)()
+    }
+
+    fn assign_colors(&self, first: Option<Color>, second: Option<Color>) -> (Color, Color) {
+        let mut rng = self.rng.lock().unwrap();
+        assign_colors_with(first, second, || rng.gen())
+    }
+
     async fn get_redis_connection(
         &self,
     ) -> Result<deadpool_redis::Connection, String> {
-        self.redis_pool
-            .get()
-            .await
-            .map_err(|e| format!("Redis connection failed: {}", e))
+        acquire_with_retry(&self.connection_retry, || self.redis_pool.get()).await
     }
 
     pub async fn join_queue(
         &self,
         request: MatchRequest,
     ) -> Result<MatchmakingResponse, String> {
+        request.validate().map_err(|e| e.to_string())?;
         let request_id = request.id;
 
         match request.match_type {
@@ -56,21 +106,14 @@ impl MatchmakingService {
                 self.add_to_redis_queue(&request).await?;
             }
             MatchType::Private => {
-                if let Some(invite_address) = &request.invite_address {
-                    self.add_private_invite(invite_address, &request).await?;
-                    return Ok(MatchmakingResponse {
-                        status: "Waiting for invited player".to_string(),
-                        match_id: None,
-                        request_id,
-                    });
-                } else {
-                    return Ok(MatchmakingResponse {
-                        status: "Invalid private match request: missing invite address"
-                            .to_string(),
-                        match_id: None,
-                        request_id,
-                    });
-                }
+                // validate() guarantees private requests carry an invite address
+                let invite_address = request.invite_address.as_deref().unwrap_or_default();
+                self.add_private_invite(invite_address, &request).await?;
+                return Ok(MatchmakingResponse {
+                    status: "Waiting for invited player".to_string(),
+                    match_id: None,
+                    request_id,
+                });
             }
         }
 
@@ -84,7 +127,7 @@ impl MatchmakingService {
     async fn add_to_redis_queue(&self, request: &MatchRequest) -> Result<(), String> {
         let mut conn = self.get_redis_connection().await?;
         let key = request.match_type.redis_key();
-        let now = Utc::now();
+        let now = self.now();
         let score = now.timestamp() as f64;
         let value = request
             .to_redis_value()
@@ -184,24 +227,7 @@ impl MatchmakingService {
 
         if let Some(invite_json) = result {
             if let Ok(invite_request) = MatchRequest::from_redis_value(&invite_json) {
-                // Create match
-                let match_id = Uuid::new_v4();
-                let new_match = Match {
-                    id: match_id,
-                    player1: invite_request.player,
-                    player2: accepting_player,
-                    match_type: MatchType::Private,
-                    created_at: Utc::now(),
-                };
-
-                let mut active_matches = self.active_matches.lock().unwrap();
-                active_matches.insert(match_id, new_match);
-
-                return Ok(Some(MatchmakingResponse {
-                    status: "Match created".to_string(),
-                    match_id: Some(match_id),
-                    request_id: inviter_request_id,
-                }));
+                return Ok(Some(self.create_private_match(invite_request, accepting_player)));
             }
         }
 
@@ -209,68 +235,234 @@ impl MatchmakingService {
 
     }
 
+    /// Accepts the invite addressed to `invited_wallet` without needing the
+    /// inviter's request id. Returns `None` when no invite is pending for that wallet.
+    pub async fn accept_invite_for(
+        &self,
+        invited_wallet: &str,
+        accepting_player: Player,
+    ) -> Result<Option<MatchmakingResponse>, String> {
+        let mut conn = self.get_redis_connection().await?;
+        let key = "matchmaking:invites";
+
+        // Lua script for atomic get-and-remove of the invite addressed to this wallet
+        // This prevents the same invite from being accepted twice
+        let lua_script = r#"
+            local key = KEYS[1]
+            local invited_wallet = ARGV[1]
+
+            local invite_json = redis.call('HGET', key, invited_wallet)
+            if invite_json then
+                redis.call('HDEL', key, invited_wallet)
+            end
+
+            return invite_json
+        "#;
+
+        let result: Option<String> = redis::Script::new(lua_script)
+            .key(key)
+            .arg(invited_wallet)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| format!("Redis Lua script failed: {}", e))?;
+
+        match result {
+            Some(invite_json) => {
+                let invite_request = MatchRequest::from_redis_value(&invite_json)
+                    .map_err(|e| format!("Deserialization error: {}", e))?;
+                Ok(Some(self.create_private_match(invite_request, accepting_player)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn create_private_match(
+        &self,
+        invite_request: MatchRequest,
+        accepting_player: Player,
+    ) -> MatchmakingResponse {
+        let request_id = invite_request.id;
+        let match_id = GameId::new_v4();
+        // The accepting player has no stated preference, so the inviter's wish is honored
+        let (player1_color, player2_color) =
+            self.assign_colors(invite_request.preferred_color, None);
+        let new_match = Match {
+            id: match_id,
+            player1: invite_request.player,
+            player2: accepting_player,
+            player1_color,
+            player2_color,
+            match_type: MatchType::Private,
+            created_at: self.now(),
+        };
+
+        let mut active_matches = self.active_matches.lock().unwrap();
+        active_matches.insert(match_id, new_match);
+
+        MatchmakingResponse {
+            status: "Match created".to_string(),
+            match_id: Some(match_id),
+            request_id,
+        }
+    }
+
     pub async fn cancel_request(&self, request_id: Uuid) -> Result<bool, String> {
         let mut conn = self.get_redis_connection().await?;
+        let is_request = |request: &MatchRequest| request.id == request_id;
 
         // Try to remove from rated queue
         if self
-            .remove_from_queue(&mut conn, "matchmaking:queue:rated", request_id)
+            .remove_from_queue(&mut conn, "matchmaking:queue:rated", is_request, Some(1))
             .await?
+            > 0
         {
             return Ok(true);
         }
 
         // Try to remove from casual queue
         if self
-            .remove_from_queue(&mut conn, "matchmaking:queue:casual", request_id)
+            .remove_from_queue(&mut conn, "matchmaking:queue:casual", is_request, Some(1))
             .await?
+            > 0
         {
             return Ok(true);
         }
 
         // Try to remove from private invites
-        let invites: HashMap<String, String> = conn
-            .hgetall("matchmaking:invites")
-            .await
-            .map_err(|e| format!("Redis HGETALL failed: {}", e))?;
+        Ok(self.remove_invites(&mut conn, is_request, Some(1)).await? > 0)
+    }
 
-        for (invite_address, json) in invites {
-            if let Ok(request) = MatchRequest::from_redis_value(&json) {
-                if request.id == request_id {
-                    conn.hdel::<_, _, ()>("matchmaking:invites", &invite_address)
-                        .await
-                        .map_err(|e| format!("Redis HDEL failed: {}", e))?;
-                    return Ok(true);
-                }
-            }
-        }
+    /// Cancels every queued request and private invite made by `wallet_address`,
+    /// returning how many were removed.
+    pub async fn cancel_all_for(&self, wallet_address: &str) -> Result<usize, String> {
+        let mut conn = self.get_redis_connection().await?;
+        let is_mine = |request: &MatchRequest| request.player.wallet_address == wallet_address;
 
-        Ok(false)
+        let mut removed = 0;
+        for key in ["matchmaking:queue:rated", "matchmaking:queue:casual"] {
+            removed += self.remove_from_queue(&mut conn, key, is_mine, None).await?;
+        }
+        removed += self.remove_invites(&mut conn, is_mine, None).await?;
+        Ok(removed)
     }
 
-    async fn remove_from_queue(
+    /// Removes up to `limit` queued requests matching `matches` (all of them
+    /// when `limit` is `None`), returning how many were removed.
+    async fn remove_from_queue<F>(
         &self,
         conn: &mut deadpool_redis::Connection,
         key: &str,
-        request_id: Uuid,
-    ) -> Result<bool, String> {
+        matches: F,
+        limit: Option<usize>,
+    ) -> Result<usize, String>
+    where
+        F: Fn(&MatchRequest) -> bool,
+    {
         let members: Vec<String> = conn
             .zrange(key, 0, -1)
             .await
             .map_err(|e| format!("Redis ZRANGE failed: {}", e))?;
 
+        let mut removed = 0;
         for member in members {
+            if limit.is_some_and(|limit| removed >= limit) {
+                break;
+            }
             if let Ok(request) = MatchRequest::from_redis_value(&member) {
-                if request.id == request_id {
+                if matches(&request) {
                     conn.zrem::<_, _, ()>(key, &member)
                         .await
                         .map_err(|e| format!("Redis ZREM failed: {}", e))?;
-                    return Ok(true);
+                    removed += 1;
                 }
             }
         }
 
-        Ok(false)
+        Ok(removed)
+    }
+
+    /// Removes up to `limit` private invites whose request matches `matches`
+    /// (all of them when `limit` is `None`), returning how many were removed.
+    async fn remove_invites<F>(
+        &self,
+        conn: &mut deadpool_redis::Connection,
+        matches: F,
+        limit: Option<usize>,
+    ) -> Result<usize, String>
+    where
+        F: Fn(&MatchRequest) -> bool,
+    {
+        let invites: HashMap<String, String> = conn
+            .hgetall("matchmaking:invites")
+            .await
+            .map_err(|e| format!("Redis HGETALL failed: {}", e))?;
+
+        let mut removed = 0;
+        for (invite_address, json) in invites {
+            if limit.is_some_and(|limit| removed >= limit) {
+                break;
+            }
+            if let Ok(request) = MatchRequest::from_redis_value(&json) {
+                if matches(&request) {
+                    conn.hdel::<_, _, ()>("matchmaking:invites", &invite_address)
+                        .await
+                        .map_err(|e| format!("Redis HDEL failed: {}", e))?;
+                    removed += 1;
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Every queued request and pending private invite, for operators.
+    pub async fn list_queues(&self) -> Result<QueueListing, String> {
+        let mut conn = self.get_redis_connection().await?;
+        let mut listing = QueueListing::default();
+
+        for (match_type, requests) in [
+            (MatchType::Rated, &mut listing.rated),
+            (MatchType::Casual, &mut listing.casual),
+        ] {
+            let members: Vec<String> = conn
+                .zrange(match_type.redis_key(), 0, -1)
+                .await
+                .map_err(|e| format!("Redis ZRANGE failed: {}", e))?;
+            requests.extend(members.iter().filter_map(|m| MatchRequest::from_redis_value(m).ok()));
+        }
+
+        let invites: HashMap<String, String> = conn
+            .hgetall("matchmaking:invites")
+            .await
+            .map_err(|e| format!("Redis HGETALL failed: {}", e))?;
+        listing.private = invites
+            .values()
+            .filter_map(|json| MatchRequest::from_redis_value(json).ok())
+            .collect();
+
+        Ok(listing)
+    }
+
+    /// Removes every request waiting in `queue`, or in all queues and
+    /// private invites when `queue` is `None`, returning how many were removed.
+    pub async fn purge(&self, queue: Option<MatchType>) -> Result<usize, String> {
+        let mut conn = self.get_redis_connection().await?;
+        let everything = |_: &MatchRequest| true;
+
+        let mut removed = 0;
+        for match_type in [MatchType::Rated, MatchType::Casual, MatchType::Private] {
+            if queue.as_ref().is_some_and(|queue| *queue != match_type) {
+                continue;
+            }
+            removed += match match_type {
+                MatchType::Private => self.remove_invites(&mut conn, everything, None).await?,
+                _ => {
+                    self.remove_from_queue(&mut conn, &match_type.redis_key(), everything, None)
+                        .await?
+                }
+            };
+        }
+        Ok(removed)
     }
 
     pub async fn get_queue_status(
@@ -342,10 +534,14 @@ impl MatchmakingService {
         for (index, member) in members.iter().enumerate() {
             if let Ok(request) = MatchRequest::from_redis_value(member) {
                 if request.id == request_id {
+                    let match_times: Vec<i64> = conn
+                        .lrange(match_type.match_times_key(), 0, -1)
+                        .await
+                        .map_err(|e| format!("Redis LRANGE failed: {}", e))?;
                     return Ok(Some(QueueStatus {
                         request_id,
                         position: index + 1,
-                        estimated_wait_time: self.estimate_wait_time(index, &match_type),
+                        estimated_wait_time: self.estimate_wait_time(index, &match_type, &match_times),
                         match_type,
                     }));
                 }
@@ -397,17 +593,21 @@ impl MatchmakingService {
         if let Some(opponent_json) = result {
             if let Ok(opponent_request) = MatchRequest::from_redis_value(&opponent_json) {
                 // Create match
-                let match_id = Uuid::new_v4();
+                let match_id = GameId::new_v4();
+                let (player1_color, player2_color) =
+                    self.assign_colors(opponent_request.preferred_color, request.preferred_color);
                 let new_match = Match {
                     id: match_id,
                     player1: opponent_request.player,
                     player2: request.player.clone(),
+                    player1_color,
+                    player2_color,
                     match_type: MatchType::Rated,
-                    created_at: Utc::now(),
+                    created_at: self.now(),
                 };
 
-                let mut active_matches = self.active_matches.lock().unwrap();
-                active_matches.insert(match_id, new_match);
+                self.active_matches.lock().unwrap().insert(match_id, new_match);
+                self.record_match_time(&mut conn, &MatchType::Rated).await;
 
                 return Ok(Some(MatchmakingResponse {
                     status: "Match found".to_string(),
@@ -429,7 +629,7 @@ impl MatchmakingService {
 
         // Pop the oldest player from queue (FIFO)
         let result: Option<(String, f64)> = conn
-            .zpopmin(key, 1)
+            .zpopmin::<_, Vec<(String, f64)>>(key, 1)
             .await
             .map_err(|e| format!("Redis ZPOPMIN failed: {}", e))?
             .into_iter()
@@ -437,17 +637,21 @@ impl MatchmakingService {
 
         if let Some((member, _score)) = result {
             if let Ok(opponent_request) = MatchRequest::from_redis_value(&member) {
-                let match_id = Uuid::new_v4();
+                let match_id = GameId::new_v4();
+                let (player1_color, player2_color) =
+                    self.assign_colors(opponent_request.preferred_color, request.preferred_color);
                 let new_match = Match {
                     id: match_id,
                     player1: opponent_request.player,
                     player2: request.player.clone(),
+                    player1_color,
+                    player2_color,
                     match_type: MatchType::Casual,
-                    created_at: Utc::now(),
+                    created_at: self.now(),
                 };
 
-                let mut active_matches = self.active_matches.lock().unwrap();
-                active_matches.insert(match_id, new_match);
+                self.active_matches.lock().unwrap().insert(match_id, new_match);
+                self.record_match_time(&mut conn, &MatchType::Casual).await;
 
                 return Ok(Some(MatchmakingResponse {
                     status: "Match found".to_string(),
@@ -460,7 +664,34 @@ impl MatchmakingService {
         Ok(None)
     }
 
-    fn estimate_wait_time(&self, position: usize, match_type: &MatchType) -> Duration {
+    /// Remembers that a match was just made from `match_type`'s queue. The
+    /// match stands even if this fails; the estimate just has less to go on.
+    async fn record_match_time(&self, conn: &mut deadpool_redis::Connection, match_type: &MatchType) {
+        let key = match_type.match_times_key();
+        let recorded: Result<(), redis::RedisError> = redis::pipe()
+            .lpush(&key, self.now().timestamp_millis())
+            .ignore()
+            .ltrim(&key, 0, self.match_rate_samples as isize - 1)
+            .ignore()
+            .expire(&key, 3600)
+            .ignore()
+            .query_async(conn)
+            .await;
+        if let Err(e) = recorded {
+            log::warn!("Failed to record match time for {}: {}", key, e);
+        }
+    }
+
+    /// Time until the request at zero-based `position` is matched. Each match
+    /// takes one request off the front of the queue, so this is the number of
+    /// matches still needed times the recent interval between matches. Without
+    /// at least two recent matches it falls back to a fixed heuristic.
+    fn estimate_wait_time(&self, position: usize, match_type: &MatchType, match_times: &[i64]) -> Duration {
+        if let Some(interval) = mean_match_interval(match_times, self.match_rate_samples) {
+            return interval
+                .saturating_mul((position as u32).saturating_add(1))
+                .min(MAX_ESTIMATED_WAIT_TIME);
+        }
         match match_type {
             MatchType::Rated => Duration::from_secs((30 + position as u64 * 15).min(300)),
             MatchType::Casual => Duration::from_secs((15 + position as u64 * 10).min(180)),
@@ -471,7 +702,7 @@ impl MatchmakingService {
     pub async fn expand_elo_ranges(&self) -> Result<(), String> {
         let mut conn = self.get_redis_connection().await?;
         let key = "matchmaking:queue:rated";
-        let now = Utc::now();
+        let now = self.now();
 
         let members: Vec<(String, f64)> = conn
             .zrange_withscores(key, 0, -1)
@@ -480,14 +711,8 @@ impl MatchmakingService {
 
         for (member, score) in members {
             if let Ok(mut request) = MatchRequest::from_redis_value(&member) {
-                let wait_time = now.signed_duration_since(request.player.join_time);
-                let minutes_waiting = wait_time.num_minutes();
-
-                if minutes_waiting > 0 {
-                    let additional_range = minutes_waiting as u32 * ELO_RANGE_INCREMENT_PER_MINUTE;
-                    request.max_elo_diff = Some(
-                        request.max_elo_diff.unwrap_or(DEFAULT_MAX_ELO_DIFF) + additional_range,
-                    );
+                if let Some(max_elo_diff) = widened_elo_diff(&request, now) {
+                    request.max_elo_diff = Some(max_elo_diff);
 
                     // Update in Redis
                     let updated_value = request
@@ -509,12 +734,289 @@ impl MatchmakingService {
         Ok(())
     }
 
-    pub fn get_match(&self, match_id: Uuid) -> Option<Match> {
+    pub fn get_match(&self, match_id: GameId) -> Option<Match> {
         let active_matches = self.active_matches.lock().unwrap();
         active_matches.get(&match_id).cloned()
     }
 }
 
+/// The Elo gap `request` accepts at `now`, widened by `ELO_RANGE_INCREMENT_PER_MINUTE`
+/// for each full minute it has waited, or `None` before the first minute is up.
+fn widened_elo_diff(request: &MatchRequest, now: DateTime<Utc>) -> Option<u32> {
+    let minutes_waiting = now.signed_duration_since(request.player.join_time).num_minutes();
+    if minutes_waiting <= 0 {
+        return None;
+    }
+    let additional_range = minutes_waiting as u32 * ELO_RANGE_INCREMENT_PER_MINUTE;
+    Some(request.max_elo_diff.unwrap_or(DEFAULT_MAX_ELO_DIFF) + additional_range)
+}
+
+/// Mean gap between the most recent `samples` match times (milliseconds,
+/// newest first), or None with fewer than two of them
+fn mean_match_interval(match_times: &[i64], samples: usize) -> Option<Duration> {
+    let recent = &match_times[..match_times.len().min(samples)];
+    if recent.len() < 2 {
+        return None;
+    }
+    let span_ms = recent[0].saturating_sub(recent[recent.len() - 1]).max(0) as u64;
+    Some(Duration::from_millis(span_ms / (recent.len() as u64 - 1)))
+}
+
 pub fn get_matchmaking_service(redis_pool: Pool) -> web::Data<MatchmakingService> {
-    web::Data::new(MatchmakingService::new(redis_pool))
+    web::Data::new(MatchmakingService::new(redis_pool).with_connection_retry(ConnectionRetry::from_env()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::create_redis_pool;
+
+    // These tests need a running Redis (REDIS_URL, defaults to localhost):
+    // cargo test -p matchmaking -- --ignored
+    fn test_service() -> MatchmakingService {
+        let redis_url =
+            std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        MatchmakingService::new(create_redis_pool(&redis_url).unwrap())
+    }
+
+    // A fresh Stellar account address, so runs don't see each other's invites
+    fn wallet() -> String {
+        let mut key = [0u8; 32];
+        key[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+        stellar_strkey::ed25519::PublicKey(key).to_string()
+    }
+
+    fn player(wallet_address: &str) -> Player {
+        Player {
+            wallet_address: wallet_address.to_string(),
+            elo: 1500,
+            join_time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_same_seed_assigns_same_colors() {
+        // Private matches are created without touching Redis
+        let colors = |seed: u64| {
+            let service = MatchmakingService::new(create_redis_pool("redis://127.0.0.1:6379").unwrap())
+                .with_rng_seed(seed);
+            (0..32)
+                .map(|_| {
+                    let invite = MatchRequest {
+                        id: Uuid::new_v4(),
+                        player: player(&wallet()),
+                        match_type: MatchType::Private,
+                        invite_address: None,
+                        max_elo_diff: None,
+                        preferred_color: None,
+                    };
+                    let response = service.create_private_match(invite, player(&wallet()));
+                    let created = service.get_match(response.match_id.unwrap()).unwrap();
+                    (created.player1_color, created.player2_color)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let first = colors(7);
+        assert_eq!(first, colors(7));
+        assert!(first.iter().any(|(c, _)| *c == Color::White));
+        assert!(first.iter().any(|(c, _)| *c == Color::Black));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Redis"]
+    async fn test_accept_invite_for_invited_wallet() {
+        let service = test_service();
+        let inviter = wallet();
+        let invited = wallet();
+        let request = MatchRequest {
+            id: Uuid::new_v4(),
+            player: player(&inviter),
+            match_type: MatchType::Private,
+            invite_address: Some(invited.clone()),
+            max_elo_diff: None,
+            preferred_color: Some(Color::Black),
+        };
+        service.join_queue(request.clone()).await.unwrap();
+
+        let response = service
+            .accept_invite_for(&invited, player(&invited))
+            .await
+            .unwrap()
+            .expect("invite should be accepted");
+        assert_eq!(response.request_id, request.id);
+
+        let created = service.get_match(response.match_id.unwrap()).unwrap();
+        assert_eq!(created.player1.wallet_address, inviter);
+        assert_eq!(created.player2.wallet_address, invited);
+        assert_eq!(created.player1_color, Color::Black);
+        assert_eq!(created.player2_color, Color::White);
+
+        // The invite is consumed
+        assert!(service.check_private_invite(&invited).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Redis"]
+    async fn test_cancel_all_for_removes_every_request_of_wallet() {
+        let service = test_service();
+        let wallet_address = wallet();
+        // An Elo nobody else has, so the rated request waits in the queue
+        let rated = MatchRequest {
+            id: Uuid::new_v4(),
+            player: Player { elo: 1_000_000, ..player(&wallet_address) },
+            match_type: MatchType::Rated,
+            invite_address: None,
+            max_elo_diff: Some(0),
+            preferred_color: None,
+        };
+        let invite = MatchRequest {
+            id: Uuid::new_v4(),
+            player: player(&wallet_address),
+            match_type: MatchType::Private,
+            invite_address: Some(wallet()),
+            max_elo_diff: None,
+            preferred_color: None,
+        };
+        service.join_queue(rated.clone()).await.unwrap();
+        service.join_queue(invite.clone()).await.unwrap();
+        assert!(service.get_queue_status(rated.id).await.unwrap().is_some());
+
+        assert_eq!(service.cancel_all_for(&wallet_address).await.unwrap(), 2);
+        assert!(service.get_queue_status(rated.id).await.unwrap().is_none());
+        assert!(!service.cancel_request(invite.id).await.unwrap());
+        assert_eq!(service.cancel_all_for(&wallet_address).await.unwrap(), 0);
+    }
+
+    /// A clock that only moves when the test advances it
+    fn manual_clock(start: DateTime<Utc>) -> (Clock, Arc<Mutex<DateTime<Utc>>>) {
+        let now = Arc::new(Mutex::new(start));
+        let reader = Arc::clone(&now);
+        (Arc::new(move || *reader.lock().unwrap()), now)
+    }
+
+    #[test]
+    fn test_widened_elo_diff_counts_full_minutes() {
+        let (clock, now) = manual_clock(Utc::now());
+        let request = MatchRequest {
+            id: Uuid::new_v4(),
+            player: Player { join_time: clock(), ..player(&wallet()) },
+            match_type: MatchType::Rated,
+            invite_address: None,
+            max_elo_diff: Some(100),
+            preferred_color: None,
+        };
+
+        *now.lock().unwrap() += chrono::Duration::seconds(59);
+        assert_eq!(widened_elo_diff(&request, clock()), None);
+        *now.lock().unwrap() += chrono::Duration::seconds(1);
+        assert_eq!(widened_elo_diff(&request, clock()), Some(100 + ELO_RANGE_INCREMENT_PER_MINUTE));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Redis"]
+    async fn test_expand_elo_ranges_uses_injected_clock() {
+        let (clock, now) = manual_clock(Utc::now());
+        let service = test_service().with_clock(Arc::clone(&clock));
+        // An Elo nobody else has, so the request waits in the queue
+        let request = MatchRequest {
+            id: Uuid::new_v4(),
+            player: Player { elo: 2_000_000, join_time: clock(), ..player(&wallet()) },
+            match_type: MatchType::Rated,
+            invite_address: None,
+            max_elo_diff: Some(0),
+            preferred_color: None,
+        };
+        service.join_queue(request.clone()).await.unwrap();
+
+        *now.lock().unwrap() += chrono::Duration::minutes(3);
+        service.expand_elo_ranges().await.unwrap();
+
+        let mut conn = service.get_redis_connection().await.unwrap();
+        let members: Vec<String> = conn.zrange("matchmaking:queue:rated", 0, -1).await.unwrap();
+        let queued = members
+            .iter()
+            .filter_map(|member| MatchRequest::from_redis_value(member).ok())
+            .find(|queued| queued.id == request.id)
+            .expect("request should still be queued");
+        assert_eq!(queued.max_elo_diff, Some(3 * ELO_RANGE_INCREMENT_PER_MINUTE));
+
+        service.cancel_request(request.id).await.unwrap();
+    }
+
+    #[test]
+    fn test_wait_estimate_follows_recent_match_rate() {
+        let service = MatchmakingService::new(create_redis_pool("redis://127.0.0.1:6379").unwrap())
+            .with_match_rate_samples(5);
+        // A match every 20 seconds, newest first; the sixth entry is older
+        // than the sample window and ignored
+        let match_times = [100_000, 80_000, 60_000, 40_000, 20_000, -1_000_000];
+
+        assert_eq!(
+            service.estimate_wait_time(0, &MatchType::Rated, &match_times),
+            Duration::from_secs(20)
+        );
+        assert_eq!(
+            service.estimate_wait_time(2, &MatchType::Casual, &match_times),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            service.estimate_wait_time(500, &MatchType::Casual, &match_times),
+            MAX_ESTIMATED_WAIT_TIME
+        );
+
+        // Without a rate to go on, the heuristic applies
+        assert_eq!(
+            service.estimate_wait_time(2, &MatchType::Rated, &[100_000]),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            service.estimate_wait_time(2, &MatchType::Casual, &[]),
+            Duration::from_secs(35)
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Redis"]
+    async fn test_purge_empties_the_named_queue_only() {
+        let service = test_service();
+        // Elos nobody else has, so both requests wait in their queues
+        let request = |match_type, elo| MatchRequest {
+            id: Uuid::new_v4(),
+            player: Player { elo, ..player(&wallet()) },
+            match_type,
+            invite_address: None,
+            max_elo_diff: Some(0),
+            preferred_color: None,
+        };
+        let rated = request(MatchType::Rated, 3_000_000);
+        service.join_queue(rated.clone()).await.unwrap();
+        service.purge(Some(MatchType::Casual)).await.unwrap();
+        let casual = request(MatchType::Casual, 1500);
+        service.join_queue(casual.clone()).await.unwrap();
+
+        let listing = service.list_queues().await.unwrap();
+        assert!(listing.rated.iter().any(|queued| queued.id == rated.id));
+        assert!(listing.casual.iter().any(|queued| queued.id == casual.id));
+
+        assert!(service.purge(Some(MatchType::Casual)).await.unwrap() >= 1);
+        let listing = service.list_queues().await.unwrap();
+        assert!(listing.casual.is_empty());
+        assert!(listing.rated.iter().any(|queued| queued.id == rated.id));
+
+        service.cancel_request(rated.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running Redis"]
+    async fn test_accept_invite_for_without_invite_returns_none() {
+        let service = test_service();
+        let invited = wallet();
+
+        let response = service
+            .accept_invite_for(&invited, player(&invited))
+            .await
+            .unwrap();
+        assert!(response.is_none());
+    }
 }
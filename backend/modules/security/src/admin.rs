@@ -0,0 +1,46 @@
+use actix_web::HttpResponse;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::extractor::AuthenticatedUser;
+
+/// Players allowed to use the admin endpoints, matched against the player id
+/// in their login token
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AdminPlayers(pub Vec<Uuid>);
+
+impl AdminPlayers {
+    /// Parse a comma-separated list of player ids, also returning the
+    /// entries that are not UUIDs
+    pub fn parse(list: &str) -> (Self, Vec<String>) {
+        let mut ids = Vec::new();
+        let mut invalid = Vec::new();
+        for entry in list.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            match Uuid::parse_str(entry) {
+                Ok(id) => ids.push(id),
+                Err(_) => invalid.push(entry.to_string()),
+            }
+        }
+        (AdminPlayers(ids), invalid)
+    }
+
+    /// Reads `ADMIN_PLAYER_IDS`, logging entries that are not player ids
+    pub fn from_env() -> Self {
+        let (admins, invalid) = Self::parse(&std::env::var("ADMIN_PLAYER_IDS").unwrap_or_default());
+        for entry in invalid {
+            log::warn!("Ignoring ADMIN_PLAYER_IDS entry '{}'", entry);
+        }
+        admins
+    }
+
+    /// The caller's player id if they are an admin, otherwise a 403 response
+    pub fn authorize(&self, user: &AuthenticatedUser) -> Result<Uuid, HttpResponse> {
+        match user.player_id() {
+            Some(player_id) if self.0.contains(&player_id) => Ok(player_id),
+            _ => Err(HttpResponse::Forbidden().json(json!({
+                "error": "Admin access required",
+                "code": 403
+            }))),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use actix_web::{
+    dev::Payload, error::ErrorUnauthorized, web, Error, FromRequest, HttpMessage, HttpRequest,
+};
+use futures_util::future::{ready, Ready};
+use uuid::Uuid;
+
+use crate::jwt::{Claims, JwtService};
+
+/// Extractor yielding the claims of the authenticated caller.
+///
+/// Claims already stored by `JwtAuthMiddleware` are reused; otherwise the
+/// `Authorization: Bearer` header is validated with the app's `JwtService`.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser(pub Claims);
+
+impl AuthenticatedUser {
+    pub fn user_id(&self) -> i32 {
+        self.0.user_id
+    }
+
+    /// Id of the player who logged in; `None` for tokens that predate it
+    pub fn player_id(&self) -> Option<Uuid> {
+        self.0.player_id
+    }
+
+    pub fn claims(&self) -> &Claims {
+        &self.0
+    }
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if let Some(claims) = req.extensions().get::<Claims>() {
+            return ready(Ok(AuthenticatedUser(claims.clone())));
+        }
+
+        let jwt_service = match req.app_data::<web::Data<JwtService>>() {
+            Some(service) => service,
+            None => return ready(Err(ErrorUnauthorized("Authentication is not configured"))),
+        };
+
+        let token = req
+            .headers()
+            .get("Authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(JwtService::extract_token_from_header);
+
+        let result = match token {
+            Some(token) => jwt_service
+                .validate_token(&token)
+                .map(AuthenticatedUser)
+                .map_err(|_| ErrorUnauthorized("Invalid or expired token")),
+            None => Err(ErrorUnauthorized("Missing authorization header")),
+        };
+
+        ready(result)
+    }
+}
@@ -6,24 +6,45 @@ use actix_web::{
 };
 use futures_util::future::{ok, LocalBoxFuture, Ready};
 use std::task::{Context, Poll};
+use jsonwebtoken::errors::ErrorKind;
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// Default lifetime of refresh tokens, in seconds (7 days)
+pub const DEFAULT_REFRESH_EXPIRATION_SECS: usize = 7 * 24 * 3600;
+
+/// Kind of token, so a refresh token cannot be used to authenticate requests
+/// and an access token cannot be traded for a new one
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenType {
+    #[default]
+    Access,
+    Refresh,
+}
 
 /// JWT Claims structure containing user identification and expiration
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
-    /// Subject (user ID)
+    /// Subject (the player ID when known, otherwise the user ID)
     pub sub: String,
     /// User ID as integer
     pub user_id: i32,
+    /// ID of the player the token was issued to at login
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_id: Option<Uuid>,
     /// Username
     pub username: String,
     /// Expiration time (Unix timestamp)
     pub exp: usize,
     /// Issued at time (Unix timestamp)
     pub iat: usize,
+    /// Token type; tokens issued before types existed are access tokens
+    #[serde(default)]
+    pub token_type: TokenType,
 }
 
 /// JWT Service for token generation and validation
@@ -31,30 +52,89 @@ pub struct Claims {
 pub struct JwtService {
     secret_key: String,
     expiration_time: usize, // in seconds
+    refresh_expiration_time: usize, // in seconds
 }
 
 impl JwtService {
-    /// Create a new JWT service
+    /// Create a new JWT service whose access tokens live `expiration_time`
+    /// seconds and refresh tokens `DEFAULT_REFRESH_EXPIRATION_SECS`
     pub fn new(secret_key: String, expiration_time: usize) -> Self {
         JwtService {
             secret_key,
             expiration_time,
+            refresh_expiration_time: DEFAULT_REFRESH_EXPIRATION_SECS,
         }
     }
 
-    /// Generate a new JWT token for a user
+    /// Set the lifetime of refresh tokens, in seconds
+    pub fn with_refresh_expiration(mut self, refresh_expiration_time: usize) -> Self {
+        self.refresh_expiration_time = refresh_expiration_time;
+        self
+    }
+
+    /// Lifetime of access tokens, in seconds
+    pub fn access_expiration(&self) -> usize {
+        self.expiration_time
+    }
+
+    /// Lifetime of refresh tokens, in seconds
+    pub fn refresh_expiration(&self) -> usize {
+        self.refresh_expiration_time
+    }
+
+    /// Generate a new JWT access token for a user
     pub fn generate_token(&self, user_id: i32, username: &str) -> Result<String, jsonwebtoken::errors::Error> {
-        let now = SystemTime::now()
+        self.issue(user_id, None, username, TokenType::Access, Self::now())
+    }
+
+    /// Generate a new JWT access token for a player who logged in
+    pub fn generate_player_token(
+        &self,
+        user_id: i32,
+        player_id: Uuid,
+        username: &str,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        self.issue(user_id, Some(player_id), username, TokenType::Access, Self::now())
+    }
+
+    /// Generate a new JWT refresh token for a user
+    pub fn generate_refresh_token(&self, user_id: i32, username: &str) -> Result<String, jsonwebtoken::errors::Error> {
+        self.issue(user_id, None, username, TokenType::Refresh, Self::now())
+    }
+
+    /// Issue a new access token for the holder of a valid refresh token
+    pub fn refresh_access_token(&self, refresh_token: &str) -> Result<String, jsonwebtoken::errors::Error> {
+        let claims = self.validate_token_of_type(refresh_token, TokenType::Refresh)?;
+        self.issue(claims.user_id, claims.player_id, &claims.username, TokenType::Access, Self::now())
+    }
+
+    fn now() -> usize {
+        SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
-            .as_secs() as usize;
+            .as_secs() as usize
+    }
 
+    fn issue(
+        &self,
+        user_id: i32,
+        player_id: Option<Uuid>,
+        username: &str,
+        token_type: TokenType,
+        now: usize,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        let lifetime = match token_type {
+            TokenType::Access => self.expiration_time,
+            TokenType::Refresh => self.refresh_expiration_time,
+        };
         let claims = Claims {
-            sub: user_id.to_string(),
+            sub: player_id.map_or_else(|| user_id.to_string(), |id| id.to_string()),
             user_id,
+            player_id,
             username: username.to_string(),
-            exp: now + self.expiration_time,
+            exp: now + lifetime,
             iat: now,
+            token_type,
         };
 
         let token = encode(
@@ -66,14 +146,26 @@ impl JwtService {
         Ok(token)
     }
 
-    /// Validate and decode a JWT token
+    /// Validate and decode a JWT access token
     pub fn validate_token(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+        self.validate_token_of_type(token, TokenType::Access)
+    }
+
+    /// Validate and decode a JWT refresh token
+    pub fn validate_refresh_token(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+        self.validate_token_of_type(token, TokenType::Refresh)
+    }
+
+    fn validate_token_of_type(&self, token: &str, token_type: TokenType) -> Result<Claims, jsonwebtoken::errors::Error> {
         let token_data = decode::<Claims>(
             token,
             &DecodingKey::from_secret(self.secret_key.as_ref()),
             &Validation::new(Algorithm::HS256),
         )?;
 
+        if token_data.claims.token_type != token_type {
+            return Err(ErrorKind::InvalidToken.into());
+        }
         Ok(token_data.claims)
     }
 
@@ -196,3 +288,67 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_HOURS: usize = 2 * 3600;
+
+    fn service() -> JwtService {
+        JwtService::new("test_secret".to_string(), 3600).with_refresh_expiration(7 * 24 * 3600)
+    }
+
+    #[test]
+    fn test_access_token_expires_before_refresh_token() {
+        let jwt_service = service();
+        let issued_at = JwtService::now() - TWO_HOURS;
+        let access = jwt_service.issue(7, None, "alice", TokenType::Access, issued_at).unwrap();
+        let refresh = jwt_service.issue(7, None, "alice", TokenType::Refresh, issued_at).unwrap();
+
+        assert_eq!(
+            jwt_service.validate_token(&access).unwrap_err().kind(),
+            &ErrorKind::ExpiredSignature
+        );
+        assert_eq!(jwt_service.validate_refresh_token(&refresh).unwrap().user_id, 7);
+
+        let renewed = jwt_service.refresh_access_token(&refresh).unwrap();
+        assert_eq!(jwt_service.validate_token(&renewed).unwrap().username, "alice");
+    }
+
+    #[test]
+    fn test_tokens_are_only_accepted_as_their_own_type() {
+        let jwt_service = service();
+        let access = jwt_service.generate_token(7, "alice").unwrap();
+        let refresh = jwt_service.generate_refresh_token(7, "alice").unwrap();
+
+        assert_eq!(jwt_service.validate_token(&refresh).unwrap_err().kind(), &ErrorKind::InvalidToken);
+        assert_eq!(jwt_service.validate_refresh_token(&access).unwrap_err().kind(), &ErrorKind::InvalidToken);
+        assert!(jwt_service.refresh_access_token(&access).is_err());
+    }
+
+    #[test]
+    fn test_untyped_tokens_are_access_tokens() {
+        let claims: Claims = serde_json::from_str(
+            r#"{"sub":"7","user_id":7,"username":"alice","exp":0,"iat":0}"#,
+        )
+        .unwrap();
+        assert_eq!(claims.token_type, TokenType::Access);
+        assert_eq!(claims.player_id, None);
+    }
+
+    #[test]
+    fn test_player_tokens_carry_the_player_id() {
+        let jwt_service = service();
+        let player_id = Uuid::new_v4();
+        let access = jwt_service.generate_player_token(1, player_id, "alice").unwrap();
+
+        let claims = jwt_service.validate_token(&access).unwrap();
+        assert_eq!(claims.player_id, Some(player_id));
+        assert_eq!(claims.sub, player_id.to_string());
+
+        let refresh = jwt_service.issue(1, Some(player_id), "alice", TokenType::Refresh, JwtService::now()).unwrap();
+        let renewed = jwt_service.refresh_access_token(&refresh).unwrap();
+        assert_eq!(jwt_service.validate_token(&renewed).unwrap().player_id, Some(player_id));
+    }
+}
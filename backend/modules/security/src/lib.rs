@@ -1,2 +1,6 @@
 pub mod jwt;
-pub use jwt::{JwtAuthMiddleware, JwtService, Claims};
+pub mod extractor;
+pub mod admin;
+pub use jwt::{JwtAuthMiddleware, JwtService, Claims, TokenType};
+pub use extractor::AuthenticatedUser;
+pub use admin::AdminPlayers;
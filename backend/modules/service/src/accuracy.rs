@@ -0,0 +1,311 @@
+use std::fmt;
+use std::sync::Arc;
+
+use chess::bitboard::board::{Color, RoleCounts};
+use chess::bitboard::position::{Move, Position};
+use db_entity::{game, prelude::Game};
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait};
+use uuid::Uuid;
+
+use crate::engine::{parse_info_line, SearchLimits, UciEngine};
+
+/// Scores positions for accuracy analysis.
+pub trait PositionEvaluator: Send + Sync {
+    /// Evaluation of `position` in centipawns from White's point of view.
+    fn evaluate(&self, position: &Position) -> i32;
+}
+
+/// Score of a position whose side to move is checkmated.
+const MATE_SCORE: i32 = 100_000;
+
+/// Depth the engine searches each position of a game to for accuracy.
+pub const ACCURACY_SEARCH_DEPTH: u8 = 10;
+
+/// Counts material after searching every line `depth` plies deep, so pieces
+/// left hanging are seen. Stands in for the engine when none is configured.
+pub struct MaterialEvaluator {
+    pub depth: u32,
+}
+
+impl Default for MaterialEvaluator {
+    fn default() -> Self {
+        MaterialEvaluator { depth: 2 }
+    }
+}
+
+impl MaterialEvaluator {
+    fn value(counts: RoleCounts) -> i32 {
+        (counts.pawn * 100 + counts.knight * 300 + counts.bishop * 300 + counts.rook * 500 + counts.queen * 900)
+            as i32
+    }
+
+    /// Negamax score of `position` for the side to move.
+    fn search(&self, position: &Position, depth: u32) -> i32 {
+        if depth == 0 {
+            let material = position.board.material_map();
+            let white = Self::value(material.white) - Self::value(material.black);
+            return match position.turn {
+                Color::White => white,
+                Color::Black => -white,
+            };
+        }
+        let moves = position.legal_moves();
+        if moves.is_empty() {
+            return if position.is_check() { -MATE_SCORE } else { 0 };
+        }
+        moves
+            .into_iter()
+            .map(|mv| -self.search(&position.play_unchecked(mv), depth - 1))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl PositionEvaluator for MaterialEvaluator {
+    fn evaluate(&self, position: &Position) -> i32 {
+        let score = self.search(position, self.depth);
+        match position.turn {
+            Color::White => score,
+            Color::Black => -score,
+        }
+    }
+}
+
+/// Scores positions with a UCI engine. Positions the engine cannot score,
+/// including finished ones, are counted with `fallback` instead.
+///
+/// Blocks while the engine searches, so it must run off the async workers,
+/// e.g. in `spawn_blocking`.
+pub struct EngineEvaluator {
+    pub engine: Arc<dyn UciEngine>,
+    pub limits: SearchLimits,
+    pub fallback: MaterialEvaluator,
+}
+
+impl EngineEvaluator {
+    pub fn new(engine: Arc<dyn UciEngine>) -> Self {
+        EngineEvaluator {
+            engine,
+            limits: SearchLimits {
+                depth: Some(ACCURACY_SEARCH_DEPTH),
+                movetime_ms: None,
+            },
+            fallback: MaterialEvaluator::default(),
+        }
+    }
+
+    /// The engine's score of `position` at the deepest depth it finished.
+    fn search(&self, position: &Position) -> Option<i32> {
+        let mut lines = self.engine.go(&position.to_fen(), self.limits).ok()?;
+        let white_to_move = position.turn == Color::White;
+        let mut score = None;
+        while let Some(line) = lines.blocking_recv() {
+            if line.starts_with("bestmove") {
+                break;
+            }
+            if let Some(update) = parse_info_line(&line, white_to_move) {
+                score = Some(match update.mate {
+                    Some(mate) if mate > 0 => MATE_SCORE,
+                    Some(_) => -MATE_SCORE,
+                    None => (update.eval * 100.0).round() as i32,
+                });
+            }
+        }
+        score
+    }
+}
+
+impl PositionEvaluator for EngineEvaluator {
+    fn evaluate(&self, position: &Position) -> i32 {
+        self.search(position).unwrap_or_else(|| self.fallback.evaluate(position))
+    }
+}
+
+/// Per-side accuracy of a game, in percent. A side is `None` when it made no moves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameAccuracy {
+    pub white: Option<f64>,
+    pub black: Option<f64>,
+    pub moves_analyzed: usize,
+}
+
+/// A stored move that is malformed or illegal where it was played.
+#[derive(Debug, PartialEq)]
+pub struct IllegalMove {
+    /// 0-based half-move index
+    pub ply: usize,
+    pub uci: String,
+}
+
+impl fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Illegal move {} at ply {}", self.uci, self.ply)
+    }
+}
+
+/// Chance of winning, in percent, for the side an evaluation favours.
+pub fn win_percent(centipawns: i32) -> f64 {
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.00368208 * centipawns as f64).exp()) - 1.0)
+}
+
+/// Accuracy of a single move from how much it lowered the mover's winning chances.
+pub fn move_accuracy(win_before: f64, win_after: f64) -> f64 {
+    let loss = (win_before - win_after).max(0.0);
+    (103.1668 * (-0.04354 * loss).exp() - 3.1669).clamp(0.0, 100.0)
+}
+
+pub struct AccuracyService;
+
+impl AccuracyService {
+    /// Moves of the game with `game_id`, or `None` if there is no such game.
+    pub async fn game_moves(db: &DatabaseConnection, game_id: Uuid) -> Result<Option<Vec<String>>, DbErr> {
+        Ok(Game::find_by_id(game_id).one(db).await?.map(|game| Self::moves(&game)))
+    }
+
+    /// UCI moves stored under `moves` in the game's PGN document.
+    pub fn moves(game: &game::Model) -> Vec<String> {
+        game.pgn
+            .get("moves")
+            .and_then(|moves| moves.as_array())
+            .map(|moves| moves.iter().filter_map(|mv| mv.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Replays `moves` from the starting position and averages, per side, the
+    /// accuracy of each move against the evaluation of the position before it.
+    pub fn accuracy(evaluator: &dyn PositionEvaluator, moves: &[String]) -> Result<GameAccuracy, IllegalMove> {
+        let mut position = Position::new();
+        let mut white_win_before = win_percent(evaluator.evaluate(&position));
+        let (mut white, mut black) = (Vec::new(), Vec::new());
+
+        for (ply, uci) in moves.iter().enumerate() {
+            position = Move::from_uci(uci)
+                .and_then(|mv| position.play(mv))
+                .ok_or_else(|| IllegalMove { ply, uci: uci.clone() })?;
+            let white_win_after = win_percent(evaluator.evaluate(&position));
+
+            // `position.turn` is now the side that did not make the move
+            match position.turn {
+                Color::Black => white.push(move_accuracy(white_win_before, white_win_after)),
+                Color::White => black.push(move_accuracy(100.0 - white_win_before, 100.0 - white_win_after)),
+            }
+            white_win_before = white_win_after;
+        }
+
+        let mean = |accuracies: Vec<f64>| {
+            (!accuracies.is_empty()).then(|| accuracies.iter().sum::<f64>() / accuracies.len() as f64)
+        };
+        Ok(GameAccuracy {
+            white: mean(white),
+            black: mean(black),
+            moves_analyzed: moves.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOVES: [&str; 6] = ["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6"];
+
+    fn moves(moves: &[&str]) -> Vec<String> {
+        moves.iter().map(|mv| mv.to_string()).collect()
+    }
+
+    /// Engine that thinks every position is equal, so every move keeps the evaluation
+    struct Level;
+
+    impl PositionEvaluator for Level {
+        fn evaluate(&self, _: &Position) -> i32 {
+            0
+        }
+    }
+
+    /// Engine that prints `lines` for every position it is asked about
+    struct ScriptedEngine {
+        lines: Vec<&'static str>,
+    }
+
+    impl UciEngine for ScriptedEngine {
+        fn go(&self, _fen: &str, _limits: SearchLimits) -> Result<tokio::sync::mpsc::Receiver<String>, String> {
+            if self.lines.is_empty() {
+                return Err("engine unavailable".to_string());
+            }
+            let (tx, rx) = tokio::sync::mpsc::channel(self.lines.len());
+            for line in &self.lines {
+                tx.try_send(line.to_string()).unwrap();
+            }
+            Ok(rx)
+        }
+    }
+
+    fn after(moves: &[&str]) -> Position {
+        moves
+            .iter()
+            .fold(Position::new(), |position, uci| position.play(Move::from_uci(uci).unwrap()).unwrap())
+    }
+
+    #[test]
+    fn test_engine_scores_are_from_whites_point_of_view() {
+        let engine = EngineEvaluator::new(Arc::new(ScriptedEngine {
+            lines: vec![
+                "info depth 1 score cp 20 pv e7e5",
+                "info depth 2 score cp 35 pv e7e5 g1f3",
+                "bestmove e7e5",
+            ],
+        }));
+        // Black is to move and a third of a pawn up
+        assert_eq!(engine.evaluate(&after(&["e2e4"])), -35);
+
+        let mating = EngineEvaluator::new(Arc::new(ScriptedEngine {
+            lines: vec!["info depth 5 score mate 2 pv d1h5", "bestmove d1h5"],
+        }));
+        assert_eq!(mating.evaluate(&Position::new()), MATE_SCORE);
+    }
+
+    #[test]
+    fn test_material_stands_in_when_the_engine_cannot_score() {
+        let hanging = after(&["e2e4", "f7f5"]);
+        let material = MaterialEvaluator::default().evaluate(&hanging);
+
+        let unavailable = EngineEvaluator::new(Arc::new(ScriptedEngine { lines: Vec::new() }));
+        assert_eq!(unavailable.evaluate(&hanging), material);
+        // A finished game has no move to search, so there is no score
+        let no_score = EngineEvaluator::new(Arc::new(ScriptedEngine {
+            lines: vec!["info depth 0 score mate 0", "bestmove (none)"],
+        }));
+        assert_eq!(no_score.evaluate(&hanging), material);
+    }
+
+    #[test]
+    fn test_win_percent_mapping() {
+        assert_eq!(win_percent(0), 50.0);
+        assert!((win_percent(300) - 75.1).abs() < 0.1);
+        assert!((win_percent(300) + win_percent(-300) - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_hanging_a_pawn_lowers_accuracy() {
+        // 1... f5 leaves the pawn to 2. exf5 with no recapture
+        let accuracy = AccuracyService::accuracy(&MaterialEvaluator::default(), &moves(&["e2e4", "f7f5"])).unwrap();
+        assert!(accuracy.white.unwrap() > 99.9);
+        assert!(accuracy.black.unwrap() < 70.0);
+    }
+
+    #[test]
+    fn test_illegal_move_is_reported() {
+        assert_eq!(
+            AccuracyService::accuracy(&Level, &moves(&["e2e4", "e2e4"])),
+            Err(IllegalMove { ply: 1, uci: "e2e4".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_best_moves_score_full_accuracy() {
+        let accuracy = AccuracyService::accuracy(&Level, &moves(&MOVES)).unwrap();
+        assert!(accuracy.white.unwrap() > 99.9);
+        assert!(accuracy.black.unwrap() > 99.9);
+        assert_eq!(accuracy.moves_analyzed, 6);
+    }
+}
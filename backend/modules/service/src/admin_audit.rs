@@ -0,0 +1,49 @@
+use chrono::Utc;
+use db_entity::{admin_audit, prelude::AdminAudit};
+use sea_orm::{
+    ActiveValue::Set, DatabaseConnection, DbErr, EntityTrait, Order, PaginatorTrait, QueryOrder,
+    QuerySelect,
+};
+use uuid::Uuid;
+
+pub struct AdminAuditService;
+
+impl AdminAuditService {
+    /// Records that `actor_id` submitted the contract transaction `tx_hash`
+    /// invoking `action` on `target`.
+    pub async fn record(
+        db: &DatabaseConnection,
+        actor_id: Uuid,
+        action: &str,
+        target: &str,
+        tx_hash: &str,
+    ) -> Result<(), DbErr> {
+        let entry = admin_audit::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            actor_id: Set(actor_id),
+            action: Set(action.to_string()),
+            target: Set(target.to_string()),
+            tx_hash: Set(tx_hash.to_string()),
+            created_at: Set(Utc::now().into()),
+        };
+        AdminAudit::insert(entry).exec_without_returning(db).await?;
+        Ok(())
+    }
+
+    /// A page of the audit log, most recent first, with the total number of entries.
+    pub async fn list(
+        db: &DatabaseConnection,
+        limit: u64,
+        offset: u64,
+    ) -> Result<(Vec<admin_audit::Model>, u64), DbErr> {
+        let entries = AdminAudit::find()
+            .order_by(admin_audit::Column::CreatedAt, Order::Desc)
+            .order_by(admin_audit::Column::Id, Order::Desc)
+            .limit(limit)
+            .offset(offset)
+            .all(db)
+            .await?;
+        let total = AdminAudit::find().count(db).await?;
+        Ok((entries, total))
+    }
+}
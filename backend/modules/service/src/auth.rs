@@ -0,0 +1,390 @@
+use crate::helper::password;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{Duration, Utc};
+use db_entity::{
+    email_verification_token, password_reset_token, player,
+    prelude::{EmailVerificationToken, PasswordResetToken, Player},
+};
+use error::error::ApiError;
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+/// How long an email verification token stays valid.
+pub const VERIFICATION_TOKEN_TTL_HOURS: i64 = 24;
+
+/// How long a password reset token stays valid.
+pub const RESET_TOKEN_TTL_MINUTES: i64 = 30;
+
+/// Hex SHA-256 of a reset token, the form it is stored in.
+fn hash_reset_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+pub struct AuthService;
+
+impl AuthService {
+    /// Creates an unverified player and issues the token that verifies their email.
+    ///
+    /// Returns `None` (and creates nothing) when the username or email is taken.
+    pub async fn register(
+        db: &DatabaseConnection,
+        username: &str,
+        email: &str,
+        password: &str,
+    ) -> Result<Option<(player::Model, Uuid)>, ApiError> {
+        let taken = Player::find()
+            .filter(
+                Condition::any()
+                    .add(player::Column::Username.eq(username))
+                    .add(player::Column::Email.eq(email)),
+            )
+            .one(db)
+            .await?;
+        if taken.is_some() {
+            return Ok(None);
+        }
+
+        let password_hash = password::hash_password(password)?;
+        let txn = db.begin().await?;
+
+        let player = player::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            username: Set(username.to_string()),
+            email: Set(email.to_string()),
+            password_hash: Set(password_hash.into_bytes()),
+            biography: Set(String::new()),
+            country: Set(String::new()),
+            flair: Set(String::new()),
+            real_name: Set(String::new()),
+            is_enabled: Set(true),
+            email_verified: Set(false),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+        let token = Self::issue_verification_token(&txn, player.id).await?;
+
+        txn.commit().await?;
+        Ok(Some((player, token)))
+    }
+
+    async fn issue_verification_token<C: sea_orm::ConnectionTrait>(
+        db: &C,
+        player_id: Uuid,
+    ) -> Result<Uuid, DbErr> {
+        let now = Utc::now();
+        let token = email_verification_token::ActiveModel {
+            token: Set(Uuid::new_v4()),
+            player_id: Set(player_id),
+            expires_at: Set((now + Duration::hours(VERIFICATION_TOKEN_TTL_HOURS)).into()),
+            used_at: Set(None),
+            created_at: Set(now.into()),
+        }
+        .insert(db)
+        .await?;
+        Ok(token.token)
+    }
+
+    /// Redeems `token` and marks its player's email as verified.
+    ///
+    /// Returns the player id, or `None` when the token is unknown, expired or
+    /// already used.
+    pub async fn verify_email(db: &DatabaseConnection, token: Uuid) -> Result<Option<Uuid>, DbErr> {
+        let txn = db.begin().await?;
+        let now = Utc::now();
+
+        let record = match EmailVerificationToken::find_by_id(token).one(&txn).await? {
+            Some(record) if record.used_at.is_none() && record.expires_at > now => record,
+            _ => return Ok(None),
+        };
+
+        // Guard again in the UPDATE so two concurrent requests cannot both redeem it
+        let redeemed = EmailVerificationToken::update_many()
+            .set(email_verification_token::ActiveModel {
+                used_at: Set(Some(now.into())),
+                ..Default::default()
+            })
+            .filter(email_verification_token::Column::Token.eq(token))
+            .filter(email_verification_token::Column::UsedAt.is_null())
+            .exec(&txn)
+            .await?;
+        if redeemed.rows_affected == 0 {
+            return Ok(None);
+        }
+
+        Player::update_many()
+            .set(player::ActiveModel {
+                email_verified: Set(true),
+                ..Default::default()
+            })
+            .filter(player::Column::Id.eq(record.player_id))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+        Ok(Some(record.player_id))
+    }
+
+    /// Issues a password reset token for the enabled player with `email`.
+    ///
+    /// Returns the token to mail to the player, or `None` when no such player
+    /// exists. Only its hash is stored.
+    pub async fn request_password_reset(db: &DatabaseConnection, email: &str) -> Result<Option<String>, DbErr> {
+        let player = match Player::find()
+            .filter(player::Column::Email.eq(email))
+            .filter(player::Column::IsEnabled.eq(true))
+            .one(db)
+            .await?
+        {
+            Some(player) => player,
+            None => return Ok(None),
+        };
+
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let token = URL_SAFE_NO_PAD.encode(bytes);
+
+        let now = Utc::now();
+        password_reset_token::ActiveModel {
+            token_hash: Set(hash_reset_token(&token)),
+            player_id: Set(player.id),
+            expires_at: Set((now + Duration::minutes(RESET_TOKEN_TTL_MINUTES)).into()),
+            used_at: Set(None),
+            created_at: Set(now.into()),
+        }
+        .insert(db)
+        .await?;
+
+        Ok(Some(token))
+    }
+
+    /// Redeems a reset `token` and replaces its player's password.
+    ///
+    /// Returns `false` (and changes nothing) when the token is unknown,
+    /// expired or already used.
+    pub async fn reset_password(db: &DatabaseConnection, token: &str, new_password: &str) -> Result<bool, ApiError> {
+        let token_hash = hash_reset_token(token);
+        let password_hash = password::hash_password(new_password)?;
+        let txn = db.begin().await?;
+        let now = Utc::now();
+
+        let record = match PasswordResetToken::find_by_id(token_hash.clone()).one(&txn).await? {
+            Some(record) if record.used_at.is_none() && record.expires_at > now => record,
+            _ => return Ok(false),
+        };
+
+        // Guard again in the UPDATE so two concurrent requests cannot both redeem it
+        let redeemed = PasswordResetToken::update_many()
+            .set(password_reset_token::ActiveModel {
+                used_at: Set(Some(now.into())),
+                ..Default::default()
+            })
+            .filter(password_reset_token::Column::TokenHash.eq(token_hash))
+            .filter(password_reset_token::Column::UsedAt.is_null())
+            .exec(&txn)
+            .await?;
+        if redeemed.rows_affected == 0 {
+            return Ok(false);
+        }
+
+        Player::update_many()
+            .set(player::ActiveModel {
+                password_hash: Set(password_hash.into_bytes()),
+                ..Default::default()
+            })
+            .filter(player::Column::Id.eq(record.player_id))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+        Ok(true)
+    }
+
+    /// The enabled player with `username`, e.g. the owner of a token.
+    pub async fn enabled_player(db: &DatabaseConnection, username: &str) -> Result<Option<player::Model>, DbErr> {
+        Player::find()
+            .filter(player::Column::Username.eq(username))
+            .filter(player::Column::IsEnabled.eq(true))
+            .one(db)
+            .await
+    }
+
+    /// The enabled player with `username` if `password` matches their hash.
+    pub async fn authenticate(
+        db: &DatabaseConnection,
+        username: &str,
+        password: &str,
+    ) -> Result<Option<player::Model>, DbErr> {
+        let player = match Self::enabled_player(db, username).await? {
+            Some(player) => player,
+            None => return Ok(None),
+        };
+
+        let matches = std::str::from_utf8(&player.password_hash)
+            .map(|hash| password::verify_password(password, hash).is_ok())
+            .unwrap_or(false);
+        Ok(matches.then_some(player))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+    use sea_orm::{DbBackend, MockDatabase, MockExecResult};
+
+    fn token_model(
+        token: Uuid,
+        player_id: Uuid,
+        expires_in: Duration,
+        used: bool,
+    ) -> email_verification_token::Model {
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+        email_verification_token::Model {
+            token,
+            player_id,
+            expires_at: now + expires_in,
+            used_at: used.then_some(now),
+            created_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_marks_player_verified() {
+        let (token, player_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![token_model(token, player_id, Duration::hours(1), false)]])
+            .append_exec_results((0..2).map(|_| MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }))
+            .into_connection();
+
+        assert_eq!(AuthService::verify_email(&db, token).await.unwrap(), Some(player_id));
+
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(log.contains(r#"UPDATE \"email_verification_token\" SET \"used_at\""#));
+        assert!(log.contains(r#"UPDATE \"player\" SET \"email_verified\" = $1"#));
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_rejects_used_and_expired_tokens() {
+        for (expires_in, used) in [(Duration::hours(1), true), (Duration::hours(-1), false)] {
+            let token = Uuid::new_v4();
+            let db = MockDatabase::new(DbBackend::Postgres)
+                .append_query_results([vec![token_model(token, Uuid::new_v4(), expires_in, used)]])
+                .into_connection();
+
+            assert_eq!(AuthService::verify_email(&db, token).await.unwrap(), None);
+
+            let log = format!("{:?}", db.into_transaction_log());
+            assert!(!log.contains("UPDATE"));
+        }
+    }
+
+    fn reset_token_model(token: &str, expires_in: Duration, used: bool) -> password_reset_token::Model {
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+        password_reset_token::Model {
+            token_hash: hash_reset_token(token),
+            player_id: Uuid::new_v4(),
+            expires_at: now + expires_in,
+            used_at: used.then_some(now),
+            created_at: now,
+        }
+    }
+
+    #[test]
+    fn test_reset_tokens_are_stored_hashed() {
+        let hash = hash_reset_token("token");
+        assert_eq!(hash.len(), 64);
+        assert_ne!(hash, "token");
+        assert_eq!(hash, hash_reset_token("token"));
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_updates_hash() {
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![reset_token_model("token", Duration::minutes(5), false)]])
+            .append_exec_results((0..2).map(|_| MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }))
+            .into_connection();
+
+        assert!(AuthService::reset_password(&db, "token", "NewSecurePass1!").await.unwrap());
+
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(log.contains(&hash_reset_token("token")));
+        assert!(!log.contains(r#"String(Some("token"))"#));
+        assert!(log.contains(r#"UPDATE \"password_reset_token\" SET \"used_at\""#));
+        assert!(log.contains(r#"UPDATE \"player\" SET \"password_hash\" = $1"#));
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_rejects_used_and_expired_tokens() {
+        for (expires_in, used) in [(Duration::minutes(5), true), (Duration::minutes(-1), false)] {
+            let db = MockDatabase::new(DbBackend::Postgres)
+                .append_query_results([vec![reset_token_model("token", expires_in, used)]])
+                .into_connection();
+
+            assert!(!AuthService::reset_password(&db, "token", "NewSecurePass1!").await.unwrap());
+
+            let log = format!("{:?}", db.into_transaction_log());
+            assert!(!log.contains("UPDATE"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_checks_password() {
+        let hash = password::hash_password("SecurePass123!").unwrap();
+        let player = player::Model {
+            id: Uuid::new_v4(),
+            username: "chess_master".to_string(),
+            email: "user@example.com".to_string(),
+            password_hash: hash.into_bytes(),
+            biography: String::new(),
+            country: String::new(),
+            flair: String::new(),
+            real_name: String::new(),
+            location: None,
+            fide_rating: None,
+            social_links: None,
+            is_enabled: true,
+            email_verified: true,
+            blitz_rating: None,
+            rapid_rating: None,
+            classical_rating: None,
+            bullet_rating: None,
+        };
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![player.clone()], vec![player.clone()], vec![]])
+            .into_connection();
+
+        let found = AuthService::authenticate(&db, "chess_master", "SecurePass123!").await.unwrap();
+        assert_eq!(found.map(|p| p.id), Some(player.id));
+        assert!(AuthService::authenticate(&db, "chess_master", "WrongPass123!").await.unwrap().is_none());
+        assert!(AuthService::authenticate(&db, "nobody", "SecurePass123!").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_loses_race_to_concurrent_redeem() {
+        let token = Uuid::new_v4();
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![token_model(token, Uuid::new_v4(), Duration::hours(1), false)]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 0,
+            }])
+            .into_connection();
+
+        assert_eq!(AuthService::verify_email(&db, token).await.unwrap(), None);
+
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(!log.contains(r#"UPDATE \"player\""#));
+    }
+}
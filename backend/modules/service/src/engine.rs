@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+/// Lines buffered between a running engine and its reader.
+const ENGINE_LINE_BUFFER: usize = 64;
+
+/// How far a search goes: to `depth` plies, for `movetime_ms`, or whichever
+/// of the two ends first when both are set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub depth: Option<u8>,
+    pub movetime_ms: Option<u32>,
+}
+
+impl SearchLimits {
+    /// The UCI `go` command for these limits, e.g. `go depth 12` or
+    /// `go movetime 2000`. Without any limit the engine searches until stopped.
+    pub fn go_command(&self) -> String {
+        let mut go = "go".to_string();
+        if let Some(depth) = self.depth {
+            go.push_str(&format!(" depth {}", depth));
+        }
+        if let Some(movetime) = self.movetime_ms {
+            go.push_str(&format!(" movetime {}", movetime));
+        }
+        if self.depth.is_none() && self.movetime_ms.is_none() {
+            go.push_str(" infinite");
+        }
+        go
+    }
+}
+
+/// A UCI engine that can search a position.
+pub trait UciEngine: Send + Sync {
+    /// Starts searching `fen` within `limits` and returns the engine's output
+    /// lines as they are printed. The search is abandoned when the receiver
+    /// is dropped.
+    fn go(&self, fen: &str, limits: SearchLimits) -> Result<mpsc::Receiver<String>, String>;
+}
+
+/// The engines a server can run, by name. Requests that do not name one get
+/// the default, which is the first engine registered.
+pub struct UciEngines {
+    default: String,
+    engines: HashMap<String, Arc<dyn UciEngine>>,
+}
+
+impl UciEngines {
+    pub fn new(name: impl Into<String>, engine: Arc<dyn UciEngine>) -> Self {
+        let name = name.into();
+        UciEngines {
+            engines: HashMap::from([(name.clone(), engine)]),
+            default: name,
+        }
+    }
+
+    pub fn with_engine(mut self, name: impl Into<String>, engine: Arc<dyn UciEngine>) -> Self {
+        self.engines.insert(name.into(), engine);
+        self
+    }
+
+    /// Parses `name=path` pairs separated by commas into engine binaries, the
+    /// first being the default. Returns `None` when no pair is well formed.
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        let mut engines: Option<UciEngines> = None;
+        for (name, path) in spec.split(',').filter_map(|pair| pair.split_once('=')) {
+            let (name, path) = (name.trim(), path.trim());
+            if name.is_empty() || path.is_empty() {
+                continue;
+            }
+            let engine: Arc<dyn UciEngine> = Arc::new(UciProcess::new(path));
+            engines = Some(match engines {
+                Some(engines) => engines.with_engine(name, engine),
+                None => UciEngines::new(name, engine),
+            });
+        }
+        engines
+    }
+
+    /// The engine called `name`, or the default one when no name is given.
+    pub fn get(&self, name: Option<&str>) -> Option<&Arc<dyn UciEngine>> {
+        self.engines.get(name.unwrap_or(&self.default))
+    }
+
+    pub fn default_name(&self) -> &str {
+        &self.default
+    }
+}
+
+/// Runs a UCI engine binary, one process per search.
+pub struct UciProcess {
+    pub path: PathBuf,
+}
+
+impl UciProcess {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        UciProcess { path: path.into() }
+    }
+}
+
+impl UciEngine for UciProcess {
+    fn go(&self, fen: &str, limits: SearchLimits) -> Result<mpsc::Receiver<String>, String> {
+        // The engine's output is read on the runtime, so there has to be one
+        let runtime = Handle::try_current().map_err(|_| "No runtime to run the engine on".to_string())?;
+        let mut child = Command::new(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start engine {}: {}", self.path.display(), e))?;
+        let mut stdin = child.stdin.take().ok_or("Engine stdin unavailable")?;
+        let stdout = child.stdout.take().ok_or("Engine stdout unavailable")?;
+
+        let go = limits.go_command();
+        // Rejoining the fields keeps a stray newline from reaching the engine as a command
+        let fen = fen.split_whitespace().collect::<Vec<_>>().join(" ");
+        let commands = format!("uci\nisready\nposition fen {}\n{}\n", fen, go);
+
+        let (tx, rx) = mpsc::channel(ENGINE_LINE_BUFFER);
+        runtime.spawn(async move {
+            // Owning the child here kills the engine once reading stops
+            let _child = child;
+            if stdin.write_all(commands.as_bytes()).await.is_err() {
+                return;
+            }
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let finished = line.starts_with("bestmove");
+                if tx.send(line).await.is_err() || finished {
+                    break;
+                }
+            }
+            let _ = stdin.write_all(b"quit\n").await;
+        });
+        Ok(rx)
+    }
+}
+
+/// The engine's verdict once it has finished searching a depth.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthUpdate {
+    pub depth: u8,
+    /// Evaluation in pawns from White's point of view
+    pub eval: f32,
+    /// Moves until mate, positive when White mates
+    pub mate: Option<i32>,
+    /// First move of the principal variation, in UCI notation
+    pub best_move: String,
+}
+
+/// Reads the `depth`, `score` and first `pv` move of a UCI `info` line.
+/// Scores are from the side to move. Lines without all three, bound-only
+/// scores, and secondary `multipv` lines are skipped.
+pub fn parse_info_line(line: &str, white_to_move: bool) -> Option<DepthUpdate> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next()? != "info" {
+        return None;
+    }
+
+    let (mut depth, mut score, mut best_move) = (None, None, None);
+    while let Some(token) = tokens.next() {
+        match token {
+            "depth" => depth = tokens.next()?.parse::<u8>().ok(),
+            "multipv" if tokens.next()? != "1" => return None,
+            "score" => {
+                let kind = tokens.next()?;
+                let value = tokens.next()?.parse::<i32>().ok()?;
+                score = Some((kind, value));
+            }
+            "lowerbound" | "upperbound" => return None,
+            "pv" => {
+                best_move = tokens.next().map(str::to_string);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let sign = if white_to_move { 1 } else { -1 };
+    let (eval, mate) = match score? {
+        ("cp", cp) => ((sign * cp) as f32 / 100.0, None),
+        ("mate", moves) => {
+            let mate = sign * moves;
+            (if mate > 0 { 100.0 } else { -100.0 }, Some(mate))
+        }
+        _ => return None,
+    };
+    Some(DepthUpdate {
+        depth: depth?,
+        eval,
+        mate,
+        best_move: best_move?,
+    })
+}
+
+/// Turns an engine's output into one update per completed depth. A depth
+/// counts as completed once the engine starts the next one or reports its
+/// best move.
+pub struct DepthUpdates {
+    lines: mpsc::Receiver<String>,
+    white_to_move: bool,
+    target_depth: Option<u8>,
+    deadline: Option<Instant>,
+    current: Option<DepthUpdate>,
+    finished: bool,
+}
+
+impl DepthUpdates {
+    /// Starts `engine` on `fen` and follows its search. Updates stop after
+    /// the depth in `limits`, when the engine reports its move, or, when
+    /// given, once `time_limit_ms` has passed.
+    pub fn start(
+        engine: &dyn UciEngine,
+        fen: &str,
+        limits: SearchLimits,
+        time_limit_ms: Option<u32>,
+    ) -> Result<Self, String> {
+        let white_to_move = fen.split_whitespace().nth(1) != Some("b");
+        let lines = engine.go(fen, limits)?;
+        Ok(DepthUpdates {
+            lines,
+            white_to_move,
+            target_depth: limits.depth,
+            deadline: time_limit_ms.map(|ms| Instant::now() + std::time::Duration::from_millis(ms as u64)),
+            current: None,
+            finished: false,
+        })
+    }
+
+    /// The next completed depth, or `None` once the search is over.
+    pub async fn next(&mut self) -> Option<DepthUpdate> {
+        while !self.finished {
+            let line = match self.deadline {
+                Some(deadline) => match tokio::time::timeout_at(deadline, self.lines.recv()).await {
+                    Ok(line) => line,
+                    Err(_) => {
+                        self.finished = true;
+                        return None;
+                    }
+                },
+                None => self.lines.recv().await,
+            };
+
+            let line = match line {
+                Some(line) if !line.starts_with("bestmove") => line,
+                // The engine reported its move or went away, so the last depth is final
+                _ => {
+                    self.finished = true;
+                    return self.current.take();
+                }
+            };
+
+            let Some(update) = parse_info_line(&line, self.white_to_move) else {
+                continue;
+            };
+            match &self.current {
+                Some(current) if update.depth > current.depth => {
+                    let completed = self.current.replace(update);
+                    let reached = |c: &DepthUpdate| self.target_depth.is_some_and(|target| c.depth >= target);
+                    if completed.as_ref().is_some_and(reached) {
+                        self.finished = true;
+                    }
+                    return completed;
+                }
+                Some(current) if update.depth < current.depth => {}
+                _ => self.current = Some(update),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_info_line() {
+        let line = "info depth 12 seldepth 18 multipv 1 score cp 35 nodes 120000 nps 900000 pv e2e4 e7e5 g1f3";
+        assert_eq!(
+            parse_info_line(line, true),
+            Some(DepthUpdate { depth: 12, eval: 0.35, mate: None, best_move: "e2e4".to_string() })
+        );
+        // Black to move: a score good for Black is bad for White
+        assert_eq!(parse_info_line(line, false).unwrap().eval, -0.35);
+    }
+
+    #[test]
+    fn test_go_command_for_each_limit() {
+        let depth = SearchLimits { depth: Some(12), movetime_ms: None };
+        assert_eq!(depth.go_command(), "go depth 12");
+        let movetime = SearchLimits { depth: None, movetime_ms: Some(2000) };
+        assert_eq!(movetime.go_command(), "go movetime 2000");
+        let both = SearchLimits { depth: Some(12), movetime_ms: Some(2000) };
+        assert_eq!(both.go_command(), "go depth 12 movetime 2000");
+        assert_eq!(SearchLimits::default().go_command(), "go infinite");
+    }
+
+    #[test]
+    fn test_engines_from_spec() {
+        let engines = UciEngines::from_spec("stockfish=/usr/bin/stockfish, lc0 = /opt/lc0,broken").unwrap();
+        assert_eq!(engines.default_name(), "stockfish");
+        assert!(engines.get(None).is_some());
+        assert!(engines.get(Some("lc0")).is_some());
+        assert!(engines.get(Some("komodo")).is_none());
+
+        assert!(UciEngines::from_spec("").is_none());
+        assert!(UciEngines::from_spec("=/usr/bin/stockfish").is_none());
+    }
+
+    #[test]
+    fn test_parse_info_line_mate_and_skipped_lines() {
+        let mate = parse_info_line("info depth 5 score mate -2 pv h7h8", false).unwrap();
+        assert_eq!(mate.mate, Some(2));
+        assert_eq!(mate.eval, 100.0);
+
+        assert_eq!(parse_info_line("info depth 5 currmove e2e4 currmovenumber 1", true), None);
+        assert_eq!(parse_info_line("info depth 5 score cp 10 lowerbound pv e2e4", true), None);
+        assert_eq!(parse_info_line("info depth 5 multipv 2 score cp 10 pv d2d4", true), None);
+        assert_eq!(parse_info_line("bestmove e2e4", true), None);
+    }
+}
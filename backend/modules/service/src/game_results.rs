@@ -0,0 +1,860 @@
+use chess::bitboard::position::{is_threefold_repetition, Position};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use db_entity::{
+    contract_event_cursor,
+    game::{self, DrawReason, GameVariant, ResultSide},
+    game_rating_change, game_result_audit, player,
+    prelude::{ContractEventCursor, Game, GameRatingChange, GameResultAudit, Player},
+};
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{
+    ActiveEnum, ActiveValue::{NotSet, Set}, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, QueryTrait, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::games::GameService;
+
+/// K-factor used for rating changes of finalized games in the standard pool.
+pub const RATING_K_FACTOR: f64 = 32.0;
+
+/// K-factor of the bullet pool. Bullet results are the noisiest of all.
+pub const BULLET_K_FACTOR: f64 = 16.0;
+
+/// K-factor of the blitz pool. Blitz games are many and noisy, so each moves
+/// the rating less.
+pub const BLITZ_K_FACTOR: f64 = 20.0;
+
+/// K-factor of the rapid pool.
+pub const RAPID_K_FACTOR: f64 = 24.0;
+
+/// K-factor of the classical pool.
+pub const CLASSICAL_K_FACTOR: f64 = 32.0;
+
+/// Rating assumed for players that have none yet.
+pub const DEFAULT_RATING: i32 = 1200;
+
+/// Rating a player starts from in a speed pool they have not played in.
+pub const POOL_DEFAULT_RATING: i32 = 1500;
+
+/// Rating snapshots written per statement by `recompute_ratings`.
+const SNAPSHOT_BATCH_SIZE: usize = 1000;
+
+/// Rating pool a game's result counts towards, chosen from the game's
+/// variant. Each pool is a separate rating column on the player with its own
+/// K-factor; variants without a speed category use the standard pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RatingPool {
+    Standard,
+    Bullet,
+    Blitz,
+    Rapid,
+    Classical,
+}
+
+impl RatingPool {
+    pub fn for_variant(variant: &GameVariant) -> RatingPool {
+        match variant {
+            GameVariant::Bullet => RatingPool::Bullet,
+            GameVariant::Blitz => RatingPool::Blitz,
+            GameVariant::Rapid => RatingPool::Rapid,
+            GameVariant::Classical => RatingPool::Classical,
+            GameVariant::Standard | GameVariant::Chess960 | GameVariant::ThreeCheck => {
+                RatingPool::Standard
+            }
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RatingPool::Standard => "standard",
+            RatingPool::Bullet => "bullet",
+            RatingPool::Blitz => "blitz",
+            RatingPool::Rapid => "rapid",
+            RatingPool::Classical => "classical",
+        }
+    }
+
+    pub fn k_factor(self) -> f64 {
+        match self {
+            RatingPool::Standard => RATING_K_FACTOR,
+            RatingPool::Bullet => BULLET_K_FACTOR,
+            RatingPool::Blitz => BLITZ_K_FACTOR,
+            RatingPool::Rapid => RAPID_K_FACTOR,
+            RatingPool::Classical => CLASSICAL_K_FACTOR,
+        }
+    }
+
+    /// Player column holding ratings of this pool.
+    pub fn column(self) -> player::Column {
+        match self {
+            RatingPool::Standard => player::Column::FideRating,
+            RatingPool::Bullet => player::Column::BulletRating,
+            RatingPool::Blitz => player::Column::BlitzRating,
+            RatingPool::Rapid => player::Column::RapidRating,
+            RatingPool::Classical => player::Column::ClassicalRating,
+        }
+    }
+
+    /// Rating of a player with no rating in this pool yet.
+    pub fn default_rating(self) -> i32 {
+        match self {
+            RatingPool::Standard => DEFAULT_RATING,
+            _ => POOL_DEFAULT_RATING,
+        }
+    }
+
+    /// `player`'s rating in this pool, or the pool's default if they have
+    /// not played in it.
+    pub fn rating_of(self, player: &player::Model) -> i32 {
+        let pool_rating = match self {
+            RatingPool::Standard => player.fide_rating,
+            RatingPool::Bullet => player.bullet_rating,
+            RatingPool::Blitz => player.blitz_rating,
+            RatingPool::Rapid => player.rapid_rating,
+            RatingPool::Classical => player.classical_rating,
+        };
+        pool_rating.unwrap_or(self.default_rating())
+    }
+}
+
+/// Outcome of [`GameResultService::recompute_ratings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecomputedRatings {
+    pub games_replayed: usize,
+    /// Player ratings written, counting each pool of a player once.
+    pub ratings_updated: usize,
+}
+
+/// Progress of a contract event stream: the last ledger an event was
+/// processed from and, when the RPC reported one, the cursor to continue
+/// paging from, which can point part-way through a ledger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventCursor {
+    pub last_ledger: u32,
+    pub paging_token: Option<String>,
+}
+
+pub struct GameResultService;
+
+impl GameResultService {
+    /// Records `result` on a game that has not finished yet and applies the
+    /// rating change to both players in the same transaction.
+    ///
+    /// Returns `false` (and changes nothing) when the game does not exist or
+    /// already has a final result, so replaying the same outcome is harmless.
+    pub async fn finalize_game(
+        db: &DatabaseConnection,
+        game_id: Uuid,
+        result: ResultSide,
+    ) -> Result<bool, DbErr> {
+        Self::finalize(db, game_id, result, None).await
+    }
+
+    /// Records a draw together with why it happened, as [`Self::finalize_game`] does.
+    pub async fn finalize_draw(
+        db: &DatabaseConnection,
+        game_id: Uuid,
+        reason: DrawReason,
+    ) -> Result<bool, DbErr> {
+        Self::finalize(db, game_id, ResultSide::Draw, Some(reason)).await
+    }
+
+    async fn finalize(
+        db: &DatabaseConnection,
+        game_id: Uuid,
+        result: ResultSide,
+        draw_reason: Option<DrawReason>,
+    ) -> Result<bool, DbErr> {
+        let txn = db.begin().await?;
+
+        let game = match Game::find_by_id(game_id).one(&txn).await? {
+            Some(game) if matches!(game.result, None | Some(ResultSide::Ongoing)) => game,
+            _ => return Ok(false),
+        };
+
+        // Guard again in the UPDATE so a concurrent finalization cannot apply twice
+        let updated = Game::update_many()
+            .set(game::ActiveModel {
+                result: Set(Some(result.clone())),
+                draw_reason: draw_reason.map_or(NotSet, |reason| Set(Some(reason))),
+                updated_at: Set(Utc::now().into()),
+                ..Default::default()
+            })
+            .filter(game::Column::Id.eq(game_id))
+            .filter(GameService::ongoing())
+            .exec(&txn)
+            .await?;
+        if updated.rows_affected == 0 {
+            return Ok(false);
+        }
+
+        if let Some(outcome) = result.outcome() {
+            let players = Player::find()
+                .filter(player::Column::Id.is_in([game.white_player, game.black_player]))
+                .all(&txn)
+                .await?;
+            let pool = RatingPool::for_variant(&game.variant);
+            let rating_of = |id: Uuid| {
+                players
+                    .iter()
+                    .find(|p| p.id == id)
+                    .map_or(pool.default_rating(), |p| pool.rating_of(p))
+            };
+
+            let (white_before, black_before) =
+                (rating_of(game.white_player), rating_of(game.black_player));
+            let (white, black) = rate_game_with_k(
+                white_before,
+                black_before,
+                outcome.white_score(),
+                pool.k_factor(),
+            );
+            let changes = [
+                (game.white_player, white_before, white),
+                (game.black_player, black_before, black),
+            ];
+            for (id, _, rating) in changes {
+                Player::update_many()
+                    .col_expr(pool.column(), Expr::value(rating))
+                    .filter(player::Column::Id.eq(id))
+                    .exec(&txn)
+                    .await?;
+            }
+
+            // Snapshot both sides so the game's rating change can be shown later
+            let now = Utc::now();
+            GameRatingChange::insert_many(changes.map(|(id, before, after)| {
+                game_rating_change::ActiveModel {
+                    game_id: Set(game_id),
+                    player_id: Set(id),
+                    pool: Set(pool.name().to_string()),
+                    rating_before: Set(before),
+                    rating_after: Set(after),
+                    created_at: Set(now.into()),
+                }
+            }))
+            .exec_without_returning(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+        Ok(true)
+    }
+
+    /// Replays the rating changes of every decided game started in
+    /// `[from, to)`, oldest first, and stores the resulting ratings along
+    /// with each replayed game's `game_rating_change` snapshots.
+    ///
+    /// Each player starts every pool the range touches from the rating after
+    /// their last snapshot in that pool for a game started before `from`, or
+    /// the pool's default without one, so running it again over the same
+    /// range gives the same ratings. Pools a player has no games in within
+    /// the range are left alone.
+    pub async fn recompute_ratings(
+        db: &DatabaseConnection,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<RecomputedRatings, DbErr> {
+        let txn = db.begin().await?;
+
+        let games = Game::find()
+            .filter(game::Column::Result.is_in([
+                ResultSide::WhiteWins,
+                ResultSide::BlackWins,
+                ResultSide::Draw,
+            ]))
+            .filter(game::Column::StartedAt.gte(from))
+            .filter(game::Column::StartedAt.lt(to))
+            .order_by_asc(game::Column::StartedAt)
+            .order_by_asc(game::Column::Id)
+            .all(&txn)
+            .await?;
+
+        let seeds = Self::ratings_before(&txn, &games, from).await?;
+
+        // Ordered, so ratings are written in the same order on every run
+        let mut ratings: BTreeMap<(Uuid, RatingPool), i32> = BTreeMap::new();
+        let mut snapshots = Vec::new();
+        for game in &games {
+            // Only decided games were selected
+            let Some(outcome) = game.result.as_ref().and_then(ResultSide::outcome) else {
+                continue;
+            };
+            let pool = RatingPool::for_variant(&game.variant);
+            let rating_before = |ratings: &BTreeMap<(Uuid, RatingPool), i32>, id: Uuid| {
+                ratings
+                    .get(&(id, pool))
+                    .or_else(|| seeds.get(&(id, pool.name().to_string())))
+                    .copied()
+                    .unwrap_or(pool.default_rating())
+            };
+            let (white_before, black_before) =
+                (rating_before(&ratings, game.white_player), rating_before(&ratings, game.black_player));
+            let (white, black) = rate_game_with_k(white_before, black_before, outcome.white_score(), pool.k_factor());
+            ratings.insert((game.white_player, pool), white);
+            ratings.insert((game.black_player, pool), black);
+
+            for (id, before, after) in [
+                (game.white_player, white_before, white),
+                (game.black_player, black_before, black),
+            ] {
+                snapshots.push(game_rating_change::ActiveModel {
+                    game_id: Set(game.id),
+                    player_id: Set(id),
+                    pool: Set(pool.name().to_string()),
+                    rating_before: Set(before),
+                    rating_after: Set(after),
+                    // Only used for snapshots a game is missing; as if written
+                    // when the game was last finalized
+                    created_at: Set(game.updated_at),
+                });
+            }
+        }
+
+        // Existing snapshots keep their creation time, so they stay in the
+        // order the games were first finalized in
+        for chunk in snapshots.chunks(SNAPSHOT_BATCH_SIZE) {
+            GameRatingChange::insert_many(chunk.to_vec())
+                .on_conflict(
+                    OnConflict::columns([
+                        game_rating_change::Column::GameId,
+                        game_rating_change::Column::PlayerId,
+                    ])
+                    .update_columns([
+                        game_rating_change::Column::Pool,
+                        game_rating_change::Column::RatingBefore,
+                        game_rating_change::Column::RatingAfter,
+                    ])
+                    .to_owned(),
+                )
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        for ((id, pool), rating) in &ratings {
+            Player::update_many()
+                .col_expr(pool.column(), Expr::value(*rating))
+                .filter(player::Column::Id.eq(*id))
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+        Ok(RecomputedRatings {
+            games_replayed: games.len(),
+            ratings_updated: ratings.len(),
+        })
+    }
+
+    /// Rating after each player's most recent snapshot, by pool name, among
+    /// games started before `from`. Only players of `games` are looked up.
+    async fn ratings_before<C: ConnectionTrait>(
+        db: &C,
+        games: &[game::Model],
+        from: DateTime<Utc>,
+    ) -> Result<HashMap<(Uuid, String), i32>, DbErr> {
+        let players: BTreeSet<Uuid> = games
+            .iter()
+            .flat_map(|game| [game.white_player, game.black_player])
+            .collect();
+        if players.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let earlier_games = Game::find()
+            .select_only()
+            .column(game::Column::Id)
+            .filter(game::Column::StartedAt.lt(from))
+            .into_query();
+        let snapshots = GameRatingChange::find()
+            .filter(game_rating_change::Column::PlayerId.is_in(players))
+            .filter(game_rating_change::Column::GameId.in_subquery(earlier_games))
+            .order_by_desc(game_rating_change::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        let mut seeds = HashMap::new();
+        for snapshot in snapshots {
+            // Newest first, so the first snapshot of each pool wins
+            seeds
+                .entry((snapshot.player_id, snapshot.pool))
+                .or_insert(snapshot.rating_after);
+        }
+        Ok(seeds)
+    }
+
+    /// Overrides a game's result after a dispute and records who did it and
+    /// why in `game_result_audit`. Ratings are left untouched.
+    ///
+    /// Returns `false` when the game does not exist.
+    pub async fn force_result(
+        db: &DatabaseConnection,
+        game_id: Uuid,
+        result: ResultSide,
+        admin_id: Uuid,
+        reason: &str,
+    ) -> Result<bool, DbErr> {
+        let txn = db.begin().await?;
+
+        let game = match Game::find_by_id(game_id).one(&txn).await? {
+            Some(game) => game,
+            None => return Ok(false),
+        };
+
+        Game::update_many()
+            .set(game::ActiveModel {
+                result: Set(Some(result.clone())),
+                updated_at: Set(Utc::now().into()),
+                ..Default::default()
+            })
+            .filter(game::Column::Id.eq(game_id))
+            .exec(&txn)
+            .await?;
+
+        let audit = game_result_audit::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            game_id: Set(game_id),
+            admin_id: Set(admin_id),
+            previous_result: Set(game.result.map(|r| r.to_value())),
+            new_result: Set(result.to_value()),
+            reason: Set(reason.to_string()),
+            created_at: Set(Utc::now().into()),
+        };
+        GameResultAudit::insert(audit).exec_without_returning(&txn).await?;
+
+        txn.commit().await?;
+        Ok(true)
+    }
+
+    /// Where an event stream's processing stopped, if it has started.
+    pub async fn event_cursor(db: &DatabaseConnection, source: &str) -> Result<Option<EventCursor>, DbErr> {
+        Ok(ContractEventCursor::find_by_id(source.to_string())
+            .one(db)
+            .await?
+            .map(|cursor| EventCursor {
+                last_ledger: cursor.last_ledger as u32,
+                paging_token: cursor.paging_token,
+            }))
+    }
+
+    pub async fn set_event_cursor(db: &DatabaseConnection, source: &str, cursor: &EventCursor) -> Result<(), DbErr> {
+        let cursor = contract_event_cursor::ActiveModel {
+            source: Set(source.to_string()),
+            last_ledger: Set(cursor.last_ledger as i64),
+            paging_token: Set(cursor.paging_token.clone()),
+            updated_at: Set(Utc::now().into()),
+        };
+
+        ContractEventCursor::insert(cursor)
+            .on_conflict(
+                OnConflict::column(contract_event_cursor::Column::Source)
+                    .update_columns([
+                        contract_event_cursor::Column::LastLedger,
+                        contract_event_cursor::Column::PagingToken,
+                        contract_event_cursor::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+        Ok(())
+    }
+}
+
+/// The draw the rules of chess impose on the last position of `history`, if
+/// any. `history` holds every position of the game, oldest first.
+pub fn engine_draw_reason(history: &[Position]) -> Option<DrawReason> {
+    let position = history.last()?;
+    if position.is_stalemate() {
+        Some(DrawReason::Stalemate)
+    } else if is_threefold_repetition(history) {
+        Some(DrawReason::ThreefoldRepetition)
+    } else if position.is_fifty_moves() {
+        Some(DrawReason::FiftyMoveRule)
+    } else {
+        None
+    }
+}
+
+/// Elo update given white's score (1.0 win, 0.5 draw, 0.0 loss).
+/// Returns the new `(white, black)` ratings.
+pub fn rate_game(white: i32, black: i32, white_score: f64) -> (i32, i32) {
+    rate_game_with_k(white, black, white_score, RATING_K_FACTOR)
+}
+
+/// Same as [`rate_game`], with the K-factor `k` of the game's rating pool.
+pub fn rate_game_with_k(white: i32, black: i32, white_score: f64, k: f64) -> (i32, i32) {
+    let expected_white = 1.0 / (1.0 + 10_f64.powf((black - white) as f64 / 400.0));
+    let delta = (k * (white_score - expected_white)).round() as i32;
+
+    ((white + delta).max(0), (black - delta).max(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+    use sea_orm::{DbBackend, MockDatabase, MockExecResult};
+
+    fn game_model(id: Uuid, white: Uuid, black: Uuid, result: Option<ResultSide>) -> game::Model {
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+        game::Model {
+            id,
+            white_player: white,
+            black_player: black,
+            fen: "fen".to_string(),
+            pgn: serde_json::json!({}),
+            result,
+            draw_reason: None,
+            variant: game::GameVariant::Standard,
+            started_at: now,
+            duration_sec: 600,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn player_model(id: Uuid, rating: Option<i32>) -> player::Model {
+        player::Model {
+            id,
+            username: id.to_string(),
+            email: format!("{}@example.com", id),
+            password_hash: Vec::new(),
+            biography: String::new(),
+            country: "NG".to_string(),
+            flair: String::new(),
+            real_name: String::new(),
+            location: None,
+            fide_rating: rating,
+            social_links: None,
+            is_enabled: true,
+            email_verified: true,
+            blitz_rating: None,
+            rapid_rating: None,
+            classical_rating: None,
+            bullet_rating: None,
+        }
+    }
+
+    #[test]
+    fn test_rate_game() {
+        assert_eq!(rate_game(1500, 1500, 1.0), (1516, 1484));
+        assert_eq!(rate_game(1500, 1500, 0.5), (1500, 1500));
+        // Upsets move ratings further than expected wins
+        let (white, _) = rate_game(1200, 1800, 1.0);
+        assert!(white - 1200 > 16);
+    }
+
+    #[tokio::test]
+    async fn test_finalize_game_sets_result_and_ratings() {
+        let (game_id, white, black) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![game_model(game_id, white, black, None)]])
+            .append_query_results([vec![
+                player_model(white, Some(1500)),
+                player_model(black, None),
+            ]])
+            .append_exec_results((0..4).map(|_| MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }))
+            .into_connection();
+
+        let applied = GameResultService::finalize_game(&db, game_id, ResultSide::BlackWins)
+            .await
+            .unwrap();
+        assert!(applied);
+
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(log.contains(r#"UPDATE \"smdb\".\"game\" SET \"result\""#));
+        // White (1500) loses to an unrated black player (1200)
+        let (white_after, black_after) = rate_game(1500, DEFAULT_RATING, 0.0);
+        assert!(log.contains(&format!("Int(Some({}))", white_after)));
+        assert!(log.contains(&format!("Int(Some({}))", black_after)));
+        // Both sides' before and after ratings are snapshotted
+        assert!(log.contains(r#"INSERT INTO \"smdb\".\"game_rating_change\""#));
+        assert!(log.contains("Int(Some(1500))"));
+    }
+
+    #[test]
+    fn test_rating_pools_by_variant() {
+        assert_eq!(RatingPool::for_variant(&GameVariant::Bullet), RatingPool::Bullet);
+        assert_eq!(RatingPool::for_variant(&GameVariant::Blitz), RatingPool::Blitz);
+        assert_eq!(RatingPool::for_variant(&GameVariant::Rapid), RatingPool::Rapid);
+        assert_eq!(RatingPool::for_variant(&GameVariant::Chess960), RatingPool::Standard);
+        assert_eq!(RatingPool::Blitz.name(), "blitz");
+        assert_eq!(rate_game_with_k(1500, 1500, 1.0, BLITZ_K_FACTOR), (1510, 1490));
+    }
+
+    #[tokio::test]
+    async fn test_blitz_game_updates_only_the_blitz_pool() {
+        let (game_id, white, black) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut game = game_model(game_id, white, black, None);
+        game.variant = GameVariant::Blitz;
+        let mut white_player = player_model(white, Some(1800));
+        white_player.blitz_rating = Some(1500);
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![game]])
+            .append_query_results([vec![white_player, player_model(black, Some(1900))]])
+            .append_exec_results((0..4).map(|_| MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }))
+            .into_connection();
+
+        let applied = GameResultService::finalize_game(&db, game_id, ResultSide::WhiteWins)
+            .await
+            .unwrap();
+        assert!(applied);
+
+        let log = format!("{:?}", db.into_transaction_log());
+        // Black has no blitz rating yet, so starts from the pool's 1500
+        // rather than its standard rating
+        assert!(log.contains(r#"UPDATE \"player\" SET \"blitz_rating\""#));
+        assert!(!log.contains(r#"SET \"fide_rating\""#));
+        assert!(!log.contains(r#"SET \"bullet_rating\""#));
+        assert!(log.contains("Int(Some(1510))"));
+        assert!(log.contains("Int(Some(1490))"));
+    }
+
+    #[tokio::test]
+    async fn test_bullet_game_updates_only_the_bullet_pool() {
+        let (game_id, white, black) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut game = game_model(game_id, white, black, None);
+        game.variant = GameVariant::Bullet;
+        let mut white_player = player_model(white, Some(1800));
+        white_player.bullet_rating = Some(1500);
+        white_player.blitz_rating = Some(1700);
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![game]])
+            .append_query_results([vec![white_player, player_model(black, Some(1500))]])
+            .append_exec_results((0..4).map(|_| MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }))
+            .into_connection();
+
+        let applied = GameResultService::finalize_game(&db, game_id, ResultSide::WhiteWins)
+            .await
+            .unwrap();
+        assert!(applied);
+
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(log.contains(r#"UPDATE \"player\" SET \"bullet_rating\""#));
+        assert!(!log.contains(r#"SET \"blitz_rating\""#));
+        assert!(!log.contains(r#"SET \"fide_rating\""#));
+        // Even players at 1500 move by half the bullet K-factor
+        assert!(log.contains("Int(Some(1508))"));
+        assert!(log.contains("Int(Some(1492))"));
+        assert!(log.contains(r#"String(Some("bullet"))"#));
+    }
+
+    async fn recompute_log(
+        games: Vec<game::Model>,
+        earlier_snapshots: Vec<game_rating_change::Model>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> (RecomputedRatings, String) {
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([games])
+            .append_query_results([earlier_snapshots])
+            .append_exec_results((0..4).map(|_| MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }))
+            .into_connection();
+        let summary = GameResultService::recompute_ratings(&db, from, to)
+            .await
+            .unwrap();
+        (summary, format!("{:?}", db.into_transaction_log()))
+    }
+
+    #[tokio::test]
+    async fn test_recompute_ratings_is_stable_across_runs() {
+        let (alice, bob, carol) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let games = vec![
+            game_model(Uuid::new_v4(), alice, bob, Some(ResultSide::WhiteWins)),
+            game_model(Uuid::new_v4(), bob, carol, Some(ResultSide::Draw)),
+            game_model(Uuid::new_v4(), carol, alice, Some(ResultSide::BlackWins)),
+        ];
+
+        let (from, to) = (Utc::now() - chrono::Duration::days(7), Utc::now());
+        let (first, first_log) = recompute_log(games.clone(), Vec::new(), from, to).await;
+        let (second, second_log) = recompute_log(games, Vec::new(), from, to).await;
+
+        assert_eq!(first, RecomputedRatings { games_replayed: 3, ratings_updated: 3 });
+        assert_eq!(first, second);
+        assert_eq!(first_log, second_log);
+
+        // Replayed from the baseline: alice 1200 -> 1216 -> 1231,
+        // bob 1200 -> 1184 -> 1185, carol 1200 -> 1199 -> 1184
+        for rating in [1231, 1185, 1184] {
+            assert!(first_log.contains(&format!("Int(Some({}))", rating)), "{}", rating);
+        }
+        assert!(first_log.contains(r#"ORDER BY \"game\".\"started_at\" ASC, \"game\".\"id\" ASC"#));
+    }
+
+    #[tokio::test]
+    async fn test_recompute_ratings_continues_from_earlier_snapshots() {
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let game = game_model(Uuid::new_v4(), alice, bob, Some(ResultSide::WhiteWins));
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+        let earlier = game_rating_change::Model {
+            game_id: Uuid::new_v4(),
+            player_id: alice,
+            pool: "standard".to_string(),
+            rating_before: 1390,
+            rating_after: 1400,
+            created_at: now,
+        };
+
+        let (from, to) = (Utc::now() - chrono::Duration::days(7), Utc::now());
+        let (summary, log) = recompute_log(vec![game.clone()], vec![earlier], from, to).await;
+        assert_eq!(summary, RecomputedRatings { games_replayed: 1, ratings_updated: 2 });
+
+        // Alice carries on from 1400 while bob, without snapshots, starts at the default
+        assert!(log.contains(r#"\"game_rating_change\".\"game_id\" IN (SELECT \"game\".\"id\" FROM \"smdb\".\"game\" WHERE \"game\".\"started_at\" < $"#));
+        assert!(log.contains(r#"INSERT INTO \"smdb\".\"game_rating_change\""#));
+        assert!(log.contains(r#"ON CONFLICT (\"game_id\", \"player_id\") DO UPDATE SET \"pool\" = \"excluded\".\"pool\""#));
+        assert!(log.contains(&format!("Uuid(Some({}))", game.id)));
+        for rating in [1400, 1408, 1200, 1192] {
+            assert!(log.contains(&format!("Int(Some({}))", rating)), "{}", rating);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_force_result_overrides_and_audits() {
+        let (game_id, white, black) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![game_model(
+                game_id,
+                white,
+                black,
+                Some(ResultSide::WhiteWins),
+            )]])
+            .append_exec_results((0..2).map(|_| MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }))
+            .into_connection();
+
+        let applied = GameResultService::force_result(
+            &db,
+            game_id,
+            ResultSide::Draw,
+            Uuid::new_v4(),
+            "Disconnect caused by server outage",
+        )
+        .await
+        .unwrap();
+        assert!(applied);
+
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(log.contains(r#"UPDATE \"smdb\".\"game\" SET \"result\""#));
+        assert!(log.contains(r#"INSERT INTO \"smdb\".\"game_result_audit\""#));
+        assert!(log.contains("white_wins"));
+        assert!(log.contains("Disconnect caused by server outage"));
+        // Forcing a result does not touch ratings
+        assert!(!log.contains("fide_rating"));
+    }
+
+    #[tokio::test]
+    async fn test_finalize_game_skips_finished_games() {
+        let (game_id, white, black) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![game_model(
+                game_id,
+                white,
+                black,
+                Some(ResultSide::WhiteWins),
+            )]])
+            .into_connection();
+
+        let applied = GameResultService::finalize_game(&db, game_id, ResultSide::BlackWins)
+            .await
+            .unwrap();
+        assert!(!applied);
+
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(!log.contains("UPDATE"));
+    }
+
+    async fn finalize_draw_log(reason: DrawReason) -> String {
+        let (game_id, white, black) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![game_model(game_id, white, black, None)]])
+            .append_query_results([vec![
+                player_model(white, Some(1500)),
+                player_model(black, Some(1500)),
+            ]])
+            .append_exec_results((0..4).map(|_| MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }))
+            .into_connection();
+
+        let applied = GameResultService::finalize_draw(&db, game_id, reason)
+            .await
+            .unwrap();
+        assert!(applied);
+        format!("{:?}", db.into_transaction_log())
+    }
+
+    // Every position of Sam Loyd's ten-move stalemate
+    fn loyd_stalemate() -> Vec<Position> {
+        let mut history = vec![Position::new()];
+        for uci in [
+            "e2e3", "a7a5", "d1h5", "a8a6", "h5a5", "h7h5", "h2h4", "a6h6", "a5c7", "f7f6",
+            "c7d7", "e8f7", "d7b7", "d8d3", "b7b8", "d3h7", "b8c8", "f7g6", "c8e6",
+        ] {
+            let mv = chess::bitboard::position::Move::from_uci(uci).unwrap();
+            let next = history.last().unwrap().play(mv).unwrap();
+            history.push(next);
+        }
+        history
+    }
+
+    #[tokio::test]
+    async fn test_agreed_draw_and_stalemate_record_different_reasons() {
+        let agreed = finalize_draw_log(DrawReason::Agreement).await;
+        let stalemate = finalize_draw_log(engine_draw_reason(&loyd_stalemate()).unwrap()).await;
+
+        assert!(agreed.contains(r#"\"draw_reason\" = CAST($2 AS \"draw_reason\")"#));
+        assert!(agreed.contains(r#"String(Some("agreement"))"#));
+        assert!(!agreed.contains(r#"String(Some("stalemate"))"#));
+        assert!(stalemate.contains(r#"String(Some("stalemate"))"#));
+        assert!(!stalemate.contains(r#"String(Some("agreement"))"#));
+    }
+
+    #[tokio::test]
+    async fn test_finalize_game_leaves_draw_reason_untouched() {
+        let (game_id, white, black) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![game_model(game_id, white, black, None)]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            .into_connection();
+
+        GameResultService::finalize_game(&db, game_id, ResultSide::Abandoned)
+            .await
+            .unwrap();
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(log.contains(r#"SET \"result\" = CAST($1 AS \"result_side\"), \"updated_at\""#));
+    }
+
+    #[test]
+    fn test_engine_draw_reason() {
+        let history = loyd_stalemate();
+        assert_eq!(engine_draw_reason(&history), Some(DrawReason::Stalemate));
+        assert_eq!(engine_draw_reason(&history[..3]), None);
+
+        let mut fifty = Position::new();
+        fifty.halfmove_clock = 100;
+        assert_eq!(engine_draw_reason(&[fifty]), Some(DrawReason::FiftyMoveRule));
+    }
+}
@@ -1,13 +1,18 @@
-use db_entity::{game, prelude::Game};
+use db_entity::{
+    game::{self, ResultSide}, game_rating_change, player,
+    prelude::{Game, GameRatingChange, Player},
+};
 use sea_orm::{
-    ColumnTrait, DbErr, EntityTrait, Order, QueryFilter,
+    ColumnTrait, DbErr, EntityTrait, Order, PaginatorTrait, QueryFilter,
     QueryOrder, QuerySelect,
 };
 use sea_orm::{Condition, DatabaseConnection};
+use std::collections::HashMap;
 use uuid::Uuid;
 use chrono::{DateTime, Utc, TimeZone};
 use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
-use dto::games::GameStatus;
+use dto::games::{GameStatus, GameSummary};
+use dto::players::{ActiveGame, HeadToHead};
 
 pub struct GameService;
 
@@ -30,34 +35,8 @@ impl GameService {
         player_id: Option<Uuid>,
         status: Option<GameStatus>,
     ) -> Result<(Vec<game::Model>, Option<String>), DbErr> {
-        let mut query = Game::find();
-
         // 1. Apply Filtering
-        if let Some(pid) = player_id {
-            // Filter by player (white OR black)
-            // effective union of indexes logic would be nice, but OR is simpler to write here.
-            // "idx_games_white_player_created_at_id" and "idx_games_black_player_created_at_id"
-            // Postgres creates a BitmapOr for these two indexes usually.
-            let condition = Condition::any()
-                .add(game::Column::WhitePlayer.eq(pid))
-                .add(game::Column::BlackPlayer.eq(pid));
-            query = query.filter(condition);
-        }
-
-        if let Some(s) = status {
-            match s {
-                GameStatus::Waiting | GameStatus::InProgress => {
-                     // Active games: result is NULL
-                     query = query.filter(game::Column::Result.is_null());
-                },
-                GameStatus::Completed | GameStatus::Aborted => {
-                    // Finished games: result is NOT NULL
-                    // Note: "Aborted" vs "Completed" might need distinguishing via ResultSide if we had it, 
-                    // but for now we just check if it has a result.
-                    query = query.filter(game::Column::Result.is_not_null());
-                }
-            }
-        }
+        let mut query = Game::find().filter(Self::list_filter(player_id, status));
 
         // 2. Apply Cursor (Keyset Pagination)
         // Sort by created_at DESC, id DESC
@@ -119,6 +98,222 @@ impl GameService {
         Ok((games, next_cursor))
     }
 
+    /// Number of games `list_games` pages through for the same filters.
+    pub async fn count_games(
+        db: &DatabaseConnection,
+        player_id: Option<Uuid>,
+        status: Option<GameStatus>,
+    ) -> Result<u64, DbErr> {
+        Game::find()
+            .filter(Self::list_filter(player_id, status))
+            .count(db)
+            .await
+    }
+
+    /// Filters shared by `list_games` and `count_games`, so a page and the
+    /// total always describe the same games.
+    fn list_filter(player_id: Option<Uuid>, status: Option<GameStatus>) -> Condition {
+        let mut condition = Condition::all();
+
+        if let Some(pid) = player_id {
+            // Filter by player (white OR black)
+            // effective union of indexes logic would be nice, but OR is simpler to write here.
+            // "idx_games_white_player_created_at_id" and "idx_games_black_player_created_at_id"
+            // Postgres creates a BitmapOr for these two indexes usually.
+            condition = condition.add(
+                Condition::any()
+                    .add(game::Column::WhitePlayer.eq(pid))
+                    .add(game::Column::BlackPlayer.eq(pid)),
+            );
+        }
+
+        if let Some(s) = status {
+            match s {
+                GameStatus::Waiting | GameStatus::InProgress => {
+                     condition = condition.add(Self::ongoing());
+                },
+                GameStatus::Completed | GameStatus::Aborted => {
+                    // Note: "Aborted" vs "Completed" might need distinguishing via ResultSide if we had it, 
+                    // but for now we just check if it has a result.
+                    condition = condition.add(Self::ongoing().not());
+                }
+            }
+        }
+
+        condition
+    }
+
+    /// Games still being played, most recently started first, for the lobby.
+    /// Served by the `(result, started_at DESC)` index.
+    pub async fn list_active_games(
+        db: &DatabaseConnection,
+        limit: u64,
+        offset: u64,
+    ) -> Result<Vec<game::Model>, DbErr> {
+        Game::find()
+            .filter(Self::ongoing())
+            .order_by(game::Column::StartedAt, Order::Desc)
+            .order_by(game::Column::Id, Order::Desc)
+            .limit(limit)
+            .offset(offset)
+            .all(db)
+            .await
+    }
+
+    /// Number of games `list_active_games` pages through.
+    pub async fn count_active_games(db: &DatabaseConnection) -> Result<u64, DbErr> {
+        Game::find().filter(Self::ongoing()).count(db).await
+    }
+
+    /// Games without a final result. New games may have no result yet or
+    /// `ongoing`, so both count.
+    pub fn ongoing() -> Condition {
+        Condition::any()
+            .add(game::Column::Result.is_null())
+            .add(game::Column::Result.eq(ResultSide::Ongoing))
+    }
+
+    /// The game with `id`, if there is one.
+    pub async fn find_game(db: &DatabaseConnection, id: Uuid) -> Result<Option<game::Model>, DbErr> {
+        Game::find_by_id(id).one(db).await
+    }
+
+    /// Summary of the game with `id`, with the rating snapshots written when
+    /// it was finalized. Unrated games get a summary without ratings.
+    pub async fn game_summary(db: &DatabaseConnection, id: Uuid) -> Result<Option<GameSummary>, DbErr> {
+        let Some(game) = Game::find_by_id(id).one(db).await? else {
+            return Ok(None);
+        };
+        let changes = GameRatingChange::find()
+            .filter(game_rating_change::Column::GameId.eq(id))
+            .all(db)
+            .await?;
+        Ok(Some(GameSummary::new(game, &changes)))
+    }
+
+    /// Games with the given ids in the order asked for, fetched with a single
+    /// query. Unknown ids, and repeats of an id, are skipped.
+    pub async fn games_by_ids(
+        db: &DatabaseConnection,
+        ids: &[Uuid],
+    ) -> Result<Vec<game::Model>, DbErr> {
+        let mut found: HashMap<Uuid, game::Model> = Game::find()
+            .filter(game::Column::Id.is_in(ids.iter().copied()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|g| (g.id, g))
+            .collect();
+
+        Ok(ids.iter().filter_map(|id| found.remove(id)).collect())
+    }
+
+    /// Ongoing games where `player_id` plays either color, most recently
+    /// started first.
+    pub async fn active_games_for_player(
+        db: &DatabaseConnection,
+        player_id: Uuid,
+    ) -> Result<Vec<ActiveGame>, DbErr> {
+        let games = Game::find()
+            .filter(Self::plays_either_color(player_id))
+            .filter(Self::ongoing())
+            .order_by(game::Column::StartedAt, Order::Desc)
+            .order_by(game::Column::Id, Order::Desc)
+            .all(db)
+            .await?;
+
+        Ok(games
+            .into_iter()
+            .filter(|g| matches!(g.result, None | Some(ResultSide::Ongoing)))
+            .map(ActiveGame::from)
+            .collect())
+    }
+
+    /// Number of ongoing games where `player_id` plays either color.
+    pub async fn count_active_games_for_player(
+        db: &DatabaseConnection,
+        player_id: Uuid,
+    ) -> Result<u64, DbErr> {
+        Game::find()
+            .filter(Self::plays_either_color(player_id))
+            .filter(Self::ongoing())
+            .count(db)
+            .await
+    }
+
+    /// Same as [`Self::count_active_games_for_player`], for the player with
+    /// `username`. An unknown username has no games.
+    pub async fn count_active_games_for_username(
+        db: &DatabaseConnection,
+        username: &str,
+    ) -> Result<u64, DbErr> {
+        let player = Player::find()
+            .filter(player::Column::Username.eq(username))
+            .one(db)
+            .await?;
+        match player {
+            Some(player) => Self::count_active_games_for_player(db, player.id).await,
+            None => Ok(0),
+        }
+    }
+
+    fn plays_either_color(player_id: Uuid) -> Condition {
+        Condition::any()
+            .add(game::Column::WhitePlayer.eq(player_id))
+            .add(game::Column::BlackPlayer.eq(player_id))
+    }
+
+    /// Wins, losses and draws of `player_id` against `opponent_id` over the
+    /// games they played each other with either color. Games without a
+    /// decisive result or draw (ongoing, abandoned) are not counted.
+    pub async fn head_to_head(
+        db: &DatabaseConnection,
+        player_id: Uuid,
+        opponent_id: Uuid,
+    ) -> Result<HeadToHead, DbErr> {
+        // Each branch is served by the white/black player indexes
+        let pairing = Condition::any()
+            .add(
+                Condition::all()
+                    .add(game::Column::WhitePlayer.eq(player_id))
+                    .add(game::Column::BlackPlayer.eq(opponent_id)),
+            )
+            .add(
+                Condition::all()
+                    .add(game::Column::WhitePlayer.eq(opponent_id))
+                    .add(game::Column::BlackPlayer.eq(player_id)),
+            );
+
+        let games = Game::find()
+            .filter(pairing)
+            .filter(game::Column::Result.is_in([
+                ResultSide::WhiteWins,
+                ResultSide::BlackWins,
+                ResultSide::Draw,
+            ]))
+            .all(db)
+            .await?;
+
+        let mut record = HeadToHead {
+            player_id,
+            opponent_id,
+            ..Default::default()
+        };
+        for g in games {
+            let played_white = g.white_player == player_id;
+            match g.result {
+                Some(ResultSide::Draw) => record.draws += 1,
+                Some(ResultSide::WhiteWins) if played_white => record.wins += 1,
+                Some(ResultSide::BlackWins) if !played_white => record.wins += 1,
+                Some(ResultSide::WhiteWins) | Some(ResultSide::BlackWins) => record.losses += 1,
+                _ => continue,
+            }
+            record.total += 1;
+        }
+
+        Ok(record)
+    }
+
     fn encode_cursor(timestamp: DateTime<Utc>, id: Uuid) -> String {
         // Format: "timestamp_micros,uuid"
         // timestamp: use timestamp_micros for precision
@@ -185,6 +380,7 @@ mod tests {
                     fen: "fen".to_string(),
                     pgn: serde_json::json!({}),
                     result: None,
+                    draw_reason: None,
                     variant: db_entity::game::GameVariant::Standard,
                     started_at: Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap()),
                     duration_sec: 600,
@@ -238,6 +434,7 @@ mod tests {
                     fen: "fen".to_string(),
                     pgn: serde_json::json!({}),
                     result: None,
+                    draw_reason: None,
                     variant: db_entity::game::GameVariant::Standard,
                     started_at: Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap()),
                     duration_sec: 600,
@@ -264,4 +461,135 @@ mod tests {
         assert!(log_str.contains(r#"\"game\".\"created_at\" = $2"#));
         assert!(log_str.contains(r#"\"game\".\"id\" < $3"#));
     }
+
+    #[tokio::test]
+    async fn test_list_active_games_filters_ongoing() {
+        let (white, black) = (Uuid::new_v4(), Uuid::new_v4());
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![finished_game(white, black, ResultSide::Ongoing)]])
+            .into_connection();
+
+        let games = GameService::list_active_games(&db, 20, 40).await.unwrap();
+        assert!(games.iter().all(|g| g.result == Some(ResultSide::Ongoing)));
+
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(log.contains(r#"WHERE \"game\".\"result\" IS NULL OR \"game\".\"result\" = (CAST($1 AS \"result_side\"))"#));
+        assert!(log.contains(r#"String(Some("ongoing"))"#));
+        assert!(log.contains(r#"ORDER BY \"game\".\"started_at\" DESC, \"game\".\"id\" DESC"#));
+        assert!(log.contains("LIMIT $2 OFFSET $3"));
+    }
+
+    fn finished_game(white: Uuid, black: Uuid, result: ResultSide) -> game::Model {
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+        game::Model {
+            id: Uuid::new_v4(),
+            white_player: white,
+            black_player: black,
+            fen: "fen".to_string(),
+            pgn: serde_json::json!({}),
+            result: Some(result),
+            draw_reason: None,
+            variant: db_entity::game::GameVariant::Standard,
+            started_at: now,
+            duration_sec: 600,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_active_games_for_player_skips_finished() {
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut ongoing = finished_game(bob, alice, ResultSide::Ongoing);
+        ongoing.fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".to_string();
+        let finished = finished_game(alice, bob, ResultSide::WhiteWins);
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![ongoing.clone(), finished]])
+            .into_connection();
+
+        let games = GameService::active_games_for_player(&db, alice).await.unwrap();
+
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].game_id, ongoing.id);
+        assert_eq!(games[0].current_fen, ongoing.fen);
+        assert_eq!(games[0].turn, dto::games::PlayerColor::Black);
+
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(log.contains(r#"\"game\".\"white_player\" = $1 OR \"game\".\"black_player\" = $2"#));
+        assert!(log.contains(r#"(\"game\".\"result\" IS NULL OR \"game\".\"result\" = (CAST($3 AS \"result_side\")))"#));
+        assert!(log.contains(r#"ORDER BY \"game\".\"started_at\" DESC"#));
+    }
+
+    #[tokio::test]
+    async fn test_games_by_ids_keeps_request_order_in_one_query() {
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let first = finished_game(alice, bob, ResultSide::Draw);
+        let second = finished_game(bob, alice, ResultSide::WhiteWins);
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![second.clone(), first.clone()]])
+            .into_connection();
+
+        let games = GameService::games_by_ids(&db, &[first.id, Uuid::new_v4(), second.id, first.id])
+            .await
+            .unwrap();
+
+        let ids: Vec<Uuid> = games.iter().map(|g| g.id).collect();
+        assert_eq!(ids, vec![first.id, second.id]);
+        let log = db.into_transaction_log();
+        assert_eq!(log.len(), 1);
+        assert!(format!("{:?}", log).contains(r#"\"game\".\"id\" IN ($1, $2, $3, $4)"#));
+    }
+
+    #[tokio::test]
+    async fn test_count_active_games_counts_both_colors() {
+        let player = Uuid::new_v4();
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results([vec![std::collections::BTreeMap::from([(
+                "num_items",
+                sea_orm::Value::BigInt(Some(3)),
+            )])]])
+            .into_connection();
+
+        let count = GameService::count_active_games_for_player(&db, player).await.unwrap();
+        assert_eq!(count, 3);
+
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(log.contains(r#"\"game\".\"white_player\" = $1 OR \"game\".\"black_player\" = $2"#));
+        assert!(log.contains(r#"(\"game\".\"result\" IS NULL OR \"game\".\"result\" = (CAST($3 AS \"result_side\")))"#));
+    }
+
+    #[tokio::test]
+    async fn test_head_to_head_counts_both_colors() {
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+        let db = MockDatabase::new(DbBackend::Postgres)
+            .append_query_results(vec![vec![
+                finished_game(alice, bob, ResultSide::WhiteWins),
+                finished_game(bob, alice, ResultSide::BlackWins),
+                finished_game(bob, alice, ResultSide::WhiteWins),
+                finished_game(alice, bob, ResultSide::Draw),
+                finished_game(bob, alice, ResultSide::Draw),
+                finished_game(alice, bob, ResultSide::BlackWins),
+            ]])
+            .into_connection();
+
+        let record = GameService::head_to_head(&db, alice, bob).await.unwrap();
+
+        assert_eq!(
+            record,
+            HeadToHead {
+                player_id: alice,
+                opponent_id: bob,
+                wins: 2,
+                losses: 2,
+                draws: 2,
+                total: 6,
+            }
+        );
+
+        let log_str = format!("{:?}", db.into_transaction_log());
+        assert!(log_str.contains(r#"\"game\".\"white_player\" = $1 AND \"game\".\"black_player\" = $2"#));
+        assert!(log_str.contains(r#"\"game\".\"white_player\" = $3 AND \"game\".\"black_player\" = $4"#));
+        assert!(log_str.contains(r#"\"game\".\"result\" IN"#));
+    }
 }
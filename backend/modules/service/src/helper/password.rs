@@ -1,11 +1,62 @@
-use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier, password_hash::SaltString};
+use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version, password_hash::SaltString};
+use std::env;
+use std::sync::OnceLock;
 
 use rand::rngs::OsRng;
 
+/// How passwords are hashed: the Argon2 cost and an optional server-side pepper.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordConfig {
+    /// Argon2 passes over memory; higher is slower to hash and to crack
+    pub cost: u32,
+    /// Secret mixed into every hash, kept out of the database
+    pub pepper: Option<String>,
+}
+
+impl Default for PasswordConfig {
+    fn default() -> Self {
+        PasswordConfig {
+            cost: Params::DEFAULT_T_COST,
+            pepper: None,
+        }
+    }
+}
+
+impl PasswordConfig {
+    /// Reads `PASSWORD_HASH_COST` and `PASSWORD_PEPPER`, keeping the defaults when unset.
+    pub fn from_env() -> Self {
+        let default = PasswordConfig::default();
+        PasswordConfig {
+            cost: env::var("PASSWORD_HASH_COST")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.cost),
+            pepper: env::var("PASSWORD_PEPPER").ok().filter(|p| !p.is_empty()),
+        }
+    }
+
+    fn argon2(&self) -> Result<Argon2<'_>, argon2::password_hash::Error> {
+        let params = Params::new(Params::DEFAULT_M_COST, self.cost, Params::DEFAULT_P_COST, None)?;
+        Ok(match &self.pepper {
+            Some(pepper) => Argon2::new_with_secret(pepper.as_bytes(), Algorithm::Argon2id, Version::V0x13, params)?,
+            None => Argon2::new(Algorithm::Argon2id, Version::V0x13, params),
+        })
+    }
+}
+
+// Read once so every hash and verification in the process uses the same settings
+fn config() -> &'static PasswordConfig {
+    static CONFIG: OnceLock<PasswordConfig> = OnceLock::new();
+    CONFIG.get_or_init(PasswordConfig::from_env)
+}
+
 pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    hash_password_with(config(), password)
+}
+
+pub fn hash_password_with(config: &PasswordConfig, password: &str) -> Result<String, argon2::password_hash::Error> {
     let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    let hash = argon2.hash_password(password.as_bytes(), &salt)?; // ? propagates errors
+    let hash = config.argon2()?.hash_password(password.as_bytes(), &salt)?; // ? propagates errors
     Ok(hash.to_string())
 }
 
@@ -13,12 +64,82 @@ pub fn verify_password<'a>(
     password: &'a str,
     hashed_password: &'a str,
 ) -> Result<(), argon2::password_hash::Error> {
-    let password_hash = PasswordHash::new(&hashed_password)?;
-    // Trait objects for algorithms to support
-    let algs: &[&dyn PasswordVerifier] = &[&Argon2::default()];
+    verify_password_with(config(), password, hashed_password)
+}
+
+/// Whether `hashed_password` is a bcrypt hash from before hashing moved to
+/// Argon2. Such hashes still verify, but should be replaced on the next login.
+pub fn needs_rehash(hashed_password: &str) -> bool {
+    ["$2a$", "$2b$", "$2y$"].iter().any(|prefix| hashed_password.starts_with(prefix))
+}
+
+/// Checks `password` against a stored hash. The cost is read from the hash
+/// itself, so hashes made before a cost change still verify. Legacy bcrypt
+/// hashes were made without the pepper and are checked without it.
+pub fn verify_password_with(
+    config: &PasswordConfig,
+    password: &str,
+    hashed_password: &str,
+) -> Result<(), argon2::password_hash::Error> {
+    if needs_rehash(hashed_password) {
+        return match bcrypt::verify(password, hashed_password) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(argon2::password_hash::Error::Password),
+            Err(_) => Err(argon2::password_hash::Error::PhcStringField),
+        };
+    }
+    let password_hash = PasswordHash::new(hashed_password)?;
+    config.argon2()?.verify_password(password.as_bytes(), &password_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peppered() -> PasswordConfig {
+        PasswordConfig {
+            cost: 1,
+            pepper: Some("server-secret".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_pepper_is_required_to_verify() {
+        let hash = hash_password_with(&peppered(), "SecurePass123!").unwrap();
+
+        assert!(verify_password_with(&peppered(), "SecurePass123!", &hash).is_ok());
+        assert!(verify_password_with(&peppered(), "WrongPass123!", &hash).is_err());
+
+        let unpeppered = PasswordConfig { pepper: None, ..peppered() };
+        assert!(verify_password_with(&unpeppered, "SecurePass123!", &hash).is_err());
+        let other_pepper = PasswordConfig { pepper: Some("other-secret".to_string()), ..peppered() };
+        assert!(verify_password_with(&other_pepper, "SecurePass123!", &hash).is_err());
+    }
+
+    #[test]
+    fn test_cost_is_recorded_in_hash() {
+        let config = PasswordConfig { cost: 3, pepper: None };
+        let hash = hash_password_with(&config, "SecurePass123!").unwrap();
+        assert!(hash.contains("t=3"), "{}", hash);
+
+        // A later cost change does not lock out existing passwords
+        let raised = PasswordConfig { cost: 4, pepper: None };
+        assert!(verify_password_with(&raised, "SecurePass123!", &hash).is_ok());
+    }
+
+    #[test]
+    fn test_legacy_bcrypt_hashes_still_verify() {
+        let hash = bcrypt::hash("SecurePass123!", 4).unwrap();
+        assert!(needs_rehash(&hash));
+
+        // The pepper postdates these hashes, so it does not get in the way
+        assert!(verify_password_with(&peppered(), "SecurePass123!", &hash).is_ok());
+        assert_eq!(
+            verify_password_with(&peppered(), "WrongPass123!", &hash),
+            Err(argon2::password_hash::Error::Password)
+        );
 
-    match password_hash.verify_password(algs, password) {
-        Ok(_) => Ok(()),
-        Err(_err) => Err(_err),
+        let rehashed = hash_password_with(&peppered(), "SecurePass123!").unwrap();
+        assert!(!needs_rehash(&rehashed));
     }
 }
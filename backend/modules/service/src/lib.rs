@@ -4,5 +4,10 @@ pub mod players;
 // pub mod user; // Temporarily disabled due to compilation issues
 
 pub mod games;
+pub mod game_results;
+pub mod auth;
+pub mod accuracy;
+pub mod engine;
+pub mod admin_audit;
 
 // pub use user::UserService;
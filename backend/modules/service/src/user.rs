@@ -3,7 +3,7 @@ use sea_orm::{
 };
 use db_entity::user::{self, Entity as UserEntity};
 use chrono::Utc;
-use bcrypt::{hash, verify, DEFAULT_COST};
+use crate::helper::password;
 
 
 /// User service for authentication and user management
@@ -38,7 +38,7 @@ impl UserService {
         }
 
         // Hash password
-        let password_hash = hash(password, DEFAULT_COST)
+        let password_hash = password::hash_password(password)
             .map_err(|_| DbErr::Custom("Failed to hash password".to_string()))?;
 
         let now = Utc::now();
@@ -71,13 +71,13 @@ impl UserService {
         match user {
             Some(user_model) => {
                 // Verify password
-                match verify(password, &user_model.password_hash) {
-                    Ok(is_valid) => {
-                        if is_valid {
-                            Ok(user_model)
-                        } else {
-                            Err(DbErr::Custom("Invalid password".to_string()))
-                        }
+                match password::verify_password(password, &user_model.password_hash) {
+                    Ok(()) if password::needs_rehash(&user_model.password_hash) => {
+                        Ok(Self::rehash(db, user_model, password).await)
+                    }
+                    Ok(()) => Ok(user_model),
+                    Err(argon2::password_hash::Error::Password) => {
+                        Err(DbErr::Custom("Invalid password".to_string()))
                     }
                     Err(_) => Err(DbErr::Custom("Authentication failed".to_string())),
                 }
@@ -86,6 +86,20 @@ impl UserService {
         }
     }
 
+    /// Replace a legacy bcrypt hash with the current scheme after a successful
+    /// login. Best effort: if it fails, the old hash keeps working and the next
+    /// login tries again.
+    async fn rehash(db: &DatabaseConnection, user_model: user::Model, password: &str) -> user::Model {
+        let password_hash = match password::hash_password(password) {
+            Ok(password_hash) => password_hash,
+            Err(_) => return user_model,
+        };
+        let mut active: user::ActiveModel = user_model.clone().into();
+        active.password_hash = ActiveValue::Set(password_hash);
+        active.updated_at = ActiveValue::Set(Utc::now());
+        active.update(db).await.unwrap_or(user_model)
+    }
+
     /// Get user by ID
     pub async fn get_by_id(db: &DatabaseConnection, user_id: i32) -> Result<Option<user::Model>, DbErr> {
         user::Entity::find_by_id(user_id).one(db).await
@@ -1,6 +1,7 @@
 use actix_web::{App, HttpServer};
 use dotenv::dotenv;
 use modules::matchmaking;
+use security::JwtService;
 use std::env;
 
 #[actix_web::main]
@@ -29,12 +30,21 @@ async fn main() -> std::io::Result<()> {
         }
     }
 
+    // Admin endpoints authenticate with the same tokens as the API server
+    let jwt_secret = env::var("JWT_SECRET_KEY")
+        .unwrap_or_else(|_| "development_secret_key".to_string());
+    let jwt_service = actix_web::web::Data::new(JwtService::new(jwt_secret, 3600));
+    let admins = actix_web::web::Data::new(security::AdminPlayers::from_env());
+
     println!("Server starting on http://127.0.0.1:8080");
 
     HttpServer::new(move || {
         App::new()
             .app_data(matchmaking::service::get_matchmaking_service(redis_pool.clone()))
+            .app_data(jwt_service.clone())
+            .app_data(admins.clone())
             .configure(matchmaking::routes::config)
+            .configure(matchmaking::routes::admin_config)
     })
     .bind("127.0.0.1:8080")?
     .run()
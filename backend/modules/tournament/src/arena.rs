@@ -1,22 +1,81 @@
-use crate::pairing::{Pairing, PairingStrategy, TournamentPlayer};
-use std::collections::HashSet;
+use crate::pairing::{Pairing, PairingStrategy, PairingStrategyInfo, TournamentPlayer};
+use std::collections::{BTreeMap, HashSet};
 
-pub struct ArenaPairingStrategy;
+/// Opponents avoided by default: only the one played last.
+pub const DEFAULT_AVOID_WINDOW: usize = 1;
+
+/// What players are ordered by before the closest ones are paired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArenaRanking {
+    Elo,
+    /// Live arena score (streak bonuses included), ties broken by ELO
+    Score,
+}
+
+impl ArenaRanking {
+    pub fn name(self) -> &'static str {
+        match self {
+            ArenaRanking::Elo => "elo",
+            ArenaRanking::Score => "score",
+        }
+    }
+}
+
+pub struct ArenaPairingStrategy {
+    /// How many of a player's most recent opponents to avoid when another
+    /// opponent is available.
+    avoid_window: usize,
+    ranking: ArenaRanking,
+}
 
 impl ArenaPairingStrategy {
     pub fn new() -> Self {
-        Self
+        Self {
+            avoid_window: DEFAULT_AVOID_WINDOW,
+            ranking: ArenaRanking::Elo,
+        }
+    }
+
+    pub fn with_avoid_window(mut self, avoid_window: usize) -> Self {
+        self.avoid_window = avoid_window;
+        self
+    }
+
+    pub fn with_ranking(mut self, ranking: ArenaRanking) -> Self {
+        self.ranking = ranking;
+        self
+    }
+
+    fn played_recently(&self, player: &TournamentPlayer, opponent: &TournamentPlayer) -> bool {
+        player
+            .recent_opponents
+            .iter()
+            .rev()
+            .take(self.avoid_window)
+            .any(|id| *id == opponent.id)
+    }
+}
+
+impl Default for ArenaPairingStrategy {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl PairingStrategy for ArenaPairingStrategy {
     fn pair(&self, mut players: Vec<TournamentPlayer>) -> (Vec<Pairing>, Vec<TournamentPlayer>) {
+        players.retain(|p| !p.withdrawn);
         if players.is_empty() {
             return (vec![], vec![]);
         }
 
-        // 1. Sort by ELO (descending or ascending doesn't matter for closeness, let's use descending)
-        players.sort_by(|a, b| b.elo.cmp(&a.elo));
+        // 1. Sort by ELO or score (descending or ascending doesn't matter for closeness, let's use descending)
+        match self.ranking {
+            ArenaRanking::Elo => players.sort_by(|a, b| b.elo.cmp(&a.elo)),
+            ArenaRanking::Score => {
+                players.sort_by(|a, b| b.score.cmp(&a.score).then(b.elo.cmp(&a.elo)))
+            }
+        }
 
         let mut pairings = Vec::new();
         let mut paired_indices = HashSet::new();
@@ -46,9 +105,9 @@ impl PairingStrategy for ArenaPairingStrategy {
 
                 // Check soft constraint: avoid pairing if played recently
                 // Assuming recent_opponents contains IDs of players played against.
-                // We check if the LAST opponent is player_b.
-                let played_recently = player_a.recent_opponents.last().map_or(false, |id| *id == player_b.id)
-                    || player_b.recent_opponents.last().map_or(false, |id| *id == player_a.id);
+                // We check the last `avoid_window` opponents of either player.
+                let played_recently = self.played_recently(player_a, player_b)
+                    || self.played_recently(player_b, player_a);
 
                 if !played_recently {
                     best_match_idx = Some(j);
@@ -80,6 +139,16 @@ impl PairingStrategy for ArenaPairingStrategy {
 
         (pairings, remaining_players)
     }
+
+    fn describe(&self) -> PairingStrategyInfo {
+        PairingStrategyInfo {
+            name: "arena".to_string(),
+            params: BTreeMap::from([
+                ("avoid_window".to_string(), self.avoid_window.to_string()),
+                ("ranking".to_string(), self.ranking.name().to_string()),
+            ]),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -94,6 +163,9 @@ mod tests {
             elo,
             joined_at: Utc::now(),
             recent_opponents,
+            withdrawn: false,
+            score: 0,
+            win_streak: 0,
         }
     }
 
@@ -160,6 +232,107 @@ mod tests {
         assert_eq!(pairs.len(), 1);
     }
 
+    #[test]
+    fn test_pair_skips_withdrawn_players() {
+        let p1 = create_player(2000, vec![]);
+        let mut p2 = create_player(1990, vec![]);
+        p2.withdrawn = true;
+        let p3 = create_player(1900, vec![]);
+
+        let strat = ArenaPairingStrategy::new();
+        let (pairs, left) = strat.pair(vec![p1.clone(), p2.clone(), p3.clone()]);
+
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].player1.id, pairs[0].player2.id), (p1.id, p3.id));
+        // A withdrawn player is not left over for a bye either
+        assert!(left.is_empty());
+    }
+
+    #[test]
+    fn test_avoid_window_reaches_further_back() {
+        // p_a played p_b, then p_c; only a window of two still avoids p_b
+        let (id_b, id_c) = (Uuid::new_v4(), Uuid::new_v4());
+        let p_a = create_player(2000, vec![id_b, id_c]);
+        let mut p_b = create_player(1990, vec![]);
+        p_b.id = id_b;
+        let p_d = create_player(1900, vec![]);
+        let players = vec![p_a, p_b.clone(), p_d.clone()];
+
+        let (pairs, _) = ArenaPairingStrategy::new().pair(players.clone());
+        assert_eq!(pairs[0].player2.id, p_b.id);
+        let (pairs, _) = ArenaPairingStrategy::new().with_avoid_window(2).pair(players);
+        assert_eq!(pairs[0].player2.id, p_d.id);
+    }
+
+    #[test]
+    fn test_describe_reports_name_and_params() {
+        let info = ArenaPairingStrategy::new().describe();
+        assert_eq!(info.name, "arena");
+        assert_eq!(
+            info.params,
+            BTreeMap::from([
+                ("avoid_window".to_string(), "1".to_string()),
+                ("ranking".to_string(), "elo".to_string()),
+            ])
+        );
+
+        let info = ArenaPairingStrategy::new()
+            .with_avoid_window(3)
+            .with_ranking(ArenaRanking::Score)
+            .describe();
+        assert_eq!(info.params["avoid_window"], "3");
+        assert_eq!(info.params["ranking"], "score");
+    }
+
+    #[test]
+    fn test_streaking_players_pair_at_the_top_by_score() {
+        use crate::pairing::ArenaResult;
+
+        // Two lower rated players on a winning streak
+        let mut hot_a = create_player(1500, vec![]);
+        let mut hot_b = create_player(1450, vec![]);
+        for player in [&mut hot_a, &mut hot_b] {
+            for _ in 0..3 {
+                player.record_arena_result(ArenaResult::Win);
+            }
+        }
+        // Stronger players with a win, a draw and a loss between them
+        let mut strong_a = create_player(2400, vec![]);
+        strong_a.record_arena_result(ArenaResult::Win);
+        let mut strong_b = create_player(2350, vec![]);
+        strong_b.record_arena_result(ArenaResult::Draw);
+        let players = vec![strong_a.clone(), hot_a.clone(), strong_b.clone(), hot_b.clone()];
+
+        // 2 + 2 + 4: the third win is doubled
+        assert_eq!(hot_a.score, 8);
+        assert!(hot_a.on_streak());
+
+        let by_score = ArenaPairingStrategy::new().with_ranking(ArenaRanking::Score);
+        let (pairs, left) = by_score.pair(players.clone());
+        assert!(left.is_empty());
+        assert_eq!((pairs[0].player1.id, pairs[0].player2.id), (hot_a.id, hot_b.id));
+        assert_eq!((pairs[1].player1.id, pairs[1].player2.id), (strong_a.id, strong_b.id));
+
+        // Ranked by ELO the strong players would meet first
+        let (pairs, _) = ArenaPairingStrategy::new().pair(players);
+        assert_eq!((pairs[0].player1.id, pairs[0].player2.id), (strong_a.id, strong_b.id));
+    }
+
+    #[test]
+    fn test_streak_ends_on_a_draw_or_loss() {
+        use crate::pairing::ArenaResult;
+
+        let mut player = create_player(1500, vec![]);
+        player.record_arena_result(ArenaResult::Win);
+        player.record_arena_result(ArenaResult::Win);
+        // On a streak a draw is worth double but ends the streak
+        player.record_arena_result(ArenaResult::Draw);
+        assert_eq!((player.score, player.win_streak), (6, 0));
+        player.record_arena_result(ArenaResult::Win);
+        player.record_arena_result(ArenaResult::Loss);
+        assert_eq!((player.score, player.win_streak), (8, 0));
+    }
+
     #[test]
     #[ignore]
     fn test_pair_performance_1000_players() {
@@ -1,2 +1,3 @@
 pub mod pairing;
 pub mod arena;
+pub mod tournament;
@@ -1,6 +1,7 @@
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TournamentPlayer {
@@ -9,6 +10,50 @@ pub struct TournamentPlayer {
     pub joined_at: DateTime<Utc>,
     // Track previous opponents to avoid repeats if possible
     pub recent_opponents: Vec<Uuid>,
+    // Withdrawn players keep their results but are never paired again
+    #[serde(default)]
+    pub withdrawn: bool,
+    // Live arena score, see `record_arena_result`
+    #[serde(default)]
+    pub score: u32,
+    // Games won in a row, reset by a draw or a loss
+    #[serde(default)]
+    pub win_streak: u32,
+}
+
+/// Arena points for a win and a draw; both are doubled while on a streak.
+pub const ARENA_WIN_POINTS: u32 = 2;
+pub const ARENA_DRAW_POINTS: u32 = 1;
+/// Wins in a row after which a player is on a streak.
+pub const ARENA_STREAK_WINS: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArenaResult {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl TournamentPlayer {
+    pub fn on_streak(&self) -> bool {
+        self.win_streak >= ARENA_STREAK_WINS
+    }
+
+    /// Adds a finished game to the live arena score.
+    pub fn record_arena_result(&mut self, result: ArenaResult) {
+        let multiplier = if self.on_streak() { 2 } else { 1 };
+        match result {
+            ArenaResult::Win => {
+                self.score += ARENA_WIN_POINTS * multiplier;
+                self.win_streak += 1;
+            }
+            ArenaResult::Draw => {
+                self.score += ARENA_DRAW_POINTS * multiplier;
+                self.win_streak = 0;
+            }
+            ArenaResult::Loss => self.win_streak = 0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,8 +62,19 @@ pub struct Pairing {
     pub player2: TournamentPlayer,
 }
 
+/// Which strategy produced a set of pairings and how it was configured.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PairingStrategyInfo {
+    pub name: String,
+    pub params: BTreeMap<String, String>,
+}
+
 pub trait PairingStrategy {
     /// Find pairings within a pool of available players.
     /// Returns a list of pairings and the remaining players who couldn't be paired.
+    /// Withdrawn players are skipped entirely: they are neither paired nor remaining.
     fn pair(&self, players: Vec<TournamentPlayer>) -> (Vec<Pairing>, Vec<TournamentPlayer>);
+
+    /// Name and parameters of the strategy, recorded for auditing.
+    fn describe(&self) -> PairingStrategyInfo;
 }
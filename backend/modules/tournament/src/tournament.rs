@@ -0,0 +1,420 @@
+use crate::pairing::{ArenaResult, Pairing, PairingStrategy, PairingStrategyInfo, TournamentPlayer};
+use chess::outcome::Outcome;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+
+/// Points awarded for a win, a draw and a bye.
+pub const WIN_POINTS: f64 = 1.0;
+pub const DRAW_POINTS: f64 = 0.5;
+pub const BYE_POINTS: f64 = 1.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentConfig {
+    /// The tournament finishes after this many rounds.
+    pub total_rounds: u32,
+    /// Time players have to finish their games once a round starts.
+    pub round_duration: Duration,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TournamentStatus {
+    /// Waiting for the next round to start.
+    Pending,
+    RoundInProgress,
+    Finished,
+}
+
+/// Outcome of a single pairing, identified by the two player ids.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundResult {
+    /// Played White; `outcome` is read from this player's side
+    pub player1: Uuid,
+    pub player2: Uuid,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Standing {
+    pub player_id: Uuid,
+    pub score: f64,
+    /// Sum of the scores of every opponent faced (Buchholz).
+    pub tiebreak: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TournamentError {
+    Finished,
+    RoundInProgress,
+    NoRoundInProgress,
+    UnknownPlayer(Uuid),
+    /// A result was given for players who were not paired this round.
+    UnknownPairing(Uuid, Uuid),
+    /// A pairing of the current round has no result.
+    MissingResult(Uuid, Uuid),
+}
+
+impl fmt::Display for TournamentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TournamentError::Finished => write!(f, "tournament has already finished"),
+            TournamentError::RoundInProgress => write!(f, "a round is already in progress"),
+            TournamentError::NoRoundInProgress => write!(f, "no round is in progress"),
+            TournamentError::UnknownPlayer(id) => write!(f, "player {} is not in this tournament", id),
+            TournamentError::UnknownPairing(a, b) => {
+                write!(f, "{} and {} were not paired this round", a, b)
+            }
+            TournamentError::MissingResult(a, b) => {
+                write!(f, "missing result for {} vs {}", a, b)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TournamentError {}
+
+/// Runs a tournament round by round using a pluggable pairing strategy.
+pub struct Tournament {
+    pub id: Uuid,
+    pub players: Vec<TournamentPlayer>,
+    pub config: TournamentConfig,
+    pub status: TournamentStatus,
+    /// Number of rounds completed so far.
+    pub current_round: u32,
+    /// Deadline of the round in progress, if any.
+    pub round_deadline: Option<DateTime<Utc>>,
+    pub pairings: Vec<Pairing>,
+    /// Strategy and parameters the pairings are made with.
+    pub pairing_strategy: PairingStrategyInfo,
+    /// Players left unpaired in the round in progress.
+    pub byes: Vec<Uuid>,
+    scores: HashMap<Uuid, f64>,
+    strategy: Box<dyn PairingStrategy + Send + Sync>,
+}
+
+impl Tournament {
+    pub fn new(
+        players: Vec<TournamentPlayer>,
+        strategy: Box<dyn PairingStrategy + Send + Sync>,
+        config: TournamentConfig,
+    ) -> Self {
+        let scores = players.iter().map(|p| (p.id, 0.0)).collect();
+        let pairing_strategy = strategy.describe();
+        Self {
+            id: Uuid::new_v4(),
+            players,
+            config,
+            status: TournamentStatus::Pending,
+            current_round: 0,
+            round_deadline: None,
+            pairings: Vec::new(),
+            pairing_strategy,
+            byes: Vec::new(),
+            scores,
+            strategy,
+        }
+    }
+
+    /// Pairs the players for the next round and starts its clock at `now`.
+    pub fn start_round(&mut self, now: DateTime<Utc>) -> Result<&[Pairing], TournamentError> {
+        match self.status {
+            TournamentStatus::Finished => return Err(TournamentError::Finished),
+            TournamentStatus::RoundInProgress => return Err(TournamentError::RoundInProgress),
+            TournamentStatus::Pending => {}
+        }
+
+        let (pairings, unpaired) = self.strategy.pair(self.players.clone());
+        self.pairings = pairings;
+        self.byes = unpaired.into_iter().map(|p| p.id).collect();
+        self.round_deadline = Some(now + self.config.round_duration);
+        self.status = TournamentStatus::RoundInProgress;
+
+        Ok(&self.pairings)
+    }
+
+    /// Withdraws a player from all later rounds. A game already paired in the
+    /// round in progress still needs a result; a pending bye is dropped.
+    pub fn withdraw(&mut self, player_id: Uuid) -> Result<(), TournamentError> {
+        let player = self
+            .players
+            .iter_mut()
+            .find(|p| p.id == player_id)
+            .ok_or(TournamentError::UnknownPlayer(player_id))?;
+        player.withdrawn = true;
+        self.byes.retain(|id| *id != player_id);
+        Ok(())
+    }
+
+    /// Returns true if the round in progress has run past its deadline.
+    pub fn is_round_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.status == TournamentStatus::RoundInProgress
+            && self.round_deadline.is_some_and(|deadline| now > deadline)
+    }
+
+    /// Records the results of the round in progress and advances the tournament.
+    /// Every pairing must have exactly one result; nothing changes on error.
+    pub fn complete_round(&mut self, results: &[RoundResult]) -> Result<(), TournamentError> {
+        if self.status != TournamentStatus::RoundInProgress {
+            return Err(TournamentError::NoRoundInProgress);
+        }
+
+        let mut outcomes = Vec::with_capacity(self.pairings.len());
+        for pairing in &self.pairings {
+            let (a, b) = (pairing.player1.id, pairing.player2.id);
+            let outcome = results
+                .iter()
+                .find_map(|r| {
+                    if (r.player1, r.player2) == (a, b) {
+                        Some(r.outcome)
+                    } else if (r.player1, r.player2) == (b, a) {
+                        // Reported the other way round
+                        Some(r.outcome.flipped())
+                    } else {
+                        None
+                    }
+                })
+                .ok_or(TournamentError::MissingResult(a, b))?;
+            outcomes.push((a, b, outcome));
+        }
+        if let Some(r) = results.iter().find(|r| {
+            !outcomes
+                .iter()
+                .any(|(a, b, _)| (r.player1, r.player2) == (*a, *b) || (r.player1, r.player2) == (*b, *a))
+        }) {
+            return Err(TournamentError::UnknownPairing(r.player1, r.player2));
+        }
+
+        for (a, b, outcome) in outcomes {
+            let (points_a, points_b) = match outcome {
+                Outcome::WhiteWins => (WIN_POINTS, 0.0),
+                Outcome::BlackWins => (0.0, WIN_POINTS),
+                Outcome::Draw => (DRAW_POINTS, DRAW_POINTS),
+            };
+            *self.scores.entry(a).or_default() += points_a;
+            *self.scores.entry(b).or_default() += points_b;
+
+            let (result_a, result_b) = match outcome {
+                Outcome::WhiteWins => (ArenaResult::Win, ArenaResult::Loss),
+                Outcome::BlackWins => (ArenaResult::Loss, ArenaResult::Win),
+                Outcome::Draw => (ArenaResult::Draw, ArenaResult::Draw),
+            };
+            for player in self.players.iter_mut() {
+                if player.id == a {
+                    player.recent_opponents.push(b);
+                    player.record_arena_result(result_a);
+                } else if player.id == b {
+                    player.recent_opponents.push(a);
+                    player.record_arena_result(result_b);
+                }
+            }
+        }
+        for id in &self.byes {
+            *self.scores.entry(*id).or_default() += BYE_POINTS;
+        }
+
+        self.pairings.clear();
+        self.byes.clear();
+        self.round_deadline = None;
+        self.current_round += 1;
+        self.status = if self.current_round >= self.config.total_rounds {
+            TournamentStatus::Finished
+        } else {
+            TournamentStatus::Pending
+        };
+
+        Ok(())
+    }
+
+    pub fn score(&self, player_id: Uuid) -> f64 {
+        self.scores.get(&player_id).copied().unwrap_or(0.0)
+    }
+
+    /// Sum of the scores of `player_id`'s opponents, withdrawn ones included.
+    pub fn buchholz(&self, player_id: Uuid) -> f64 {
+        self.players
+            .iter()
+            .find(|p| p.id == player_id)
+            .map_or(0.0, |p| p.recent_opponents.iter().map(|id| self.score(*id)).sum())
+    }
+
+    /// Players ordered by score, highest first. Ties are broken by Buchholz,
+    /// then by ELO.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<(Standing, u32)> = self
+            .players
+            .iter()
+            .map(|p| {
+                let standing = Standing {
+                    player_id: p.id,
+                    score: self.score(p.id),
+                    tiebreak: self.buchholz(p.id),
+                };
+                (standing, p.elo)
+            })
+            .collect();
+        standings.sort_by(|(a, a_elo), (b, b_elo)| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.tiebreak.total_cmp(&a.tiebreak))
+                .then(b_elo.cmp(a_elo))
+        });
+
+        standings.into_iter().map(|(standing, _)| standing).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::ArenaPairingStrategy;
+
+    fn create_player(elo: u32) -> TournamentPlayer {
+        TournamentPlayer {
+            id: Uuid::new_v4(),
+            elo,
+            joined_at: Utc::now(),
+            recent_opponents: vec![],
+            withdrawn: false,
+            score: 0,
+            win_streak: 0,
+        }
+    }
+
+    fn arena(players: Vec<TournamentPlayer>, total_rounds: u32) -> Tournament {
+        Tournament::new(
+            players,
+            Box::new(ArenaPairingStrategy::new()),
+            TournamentConfig {
+                total_rounds,
+                round_duration: Duration::minutes(10),
+            },
+        )
+    }
+
+    /// The higher rated player wins every game.
+    fn higher_elo_wins(pairings: &[Pairing]) -> Vec<RoundResult> {
+        pairings
+            .iter()
+            .map(|p| RoundResult {
+                player1: p.player1.id,
+                player2: p.player2.id,
+                outcome: if p.player1.elo > p.player2.elo {
+                    Outcome::WhiteWins
+                } else {
+                    Outcome::BlackWins
+                },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_four_player_arena_runs_to_completion() {
+        let players: Vec<TournamentPlayer> =
+            [2000, 1900, 1800, 1700].into_iter().map(create_player).collect();
+        let ids: Vec<Uuid> = players.iter().map(|p| p.id).collect();
+        let mut tournament = arena(players, 3);
+
+        for round in 0..3 {
+            let now = Utc::now();
+            let results = higher_elo_wins(tournament.start_round(now).unwrap());
+            assert_eq!(results.len(), 2);
+            assert_eq!(tournament.round_deadline, Some(now + Duration::minutes(10)));
+            tournament.complete_round(&results).unwrap();
+            assert_eq!(tournament.current_round, round + 1);
+        }
+
+        assert_eq!(tournament.status, TournamentStatus::Finished);
+        assert_eq!(
+            tournament.start_round(Utc::now()).unwrap_err(),
+            TournamentError::Finished
+        );
+
+        // Rounds: 2000-1900 & 1800-1700, then 2000-1800 & 1900-1700, then repeats of round one
+        let standings = tournament.standings();
+        let order: Vec<Uuid> = standings.iter().map(|s| s.player_id).collect();
+        assert_eq!(order, vec![ids[0], ids[2], ids[1], ids[3]]);
+        let scores: Vec<f64> = standings.iter().map(|s| s.score).collect();
+        assert_eq!(scores, vec![3.0, 2.0, 1.0, 0.0]);
+        assert_eq!(tournament.players[0].recent_opponents.len(), 3);
+        // Three straight wins in arena points: the third is doubled
+        assert_eq!(tournament.players[0].score, 8);
+        assert_eq!(tournament.players[3].score, 0);
+    }
+
+    #[test]
+    fn test_round_state_transitions() {
+        let players: Vec<TournamentPlayer> =
+            [1500, 1400, 1300].into_iter().map(create_player).collect();
+        let mut tournament = arena(players, 2);
+
+        assert_eq!(
+            tournament.complete_round(&[]).unwrap_err(),
+            TournamentError::NoRoundInProgress
+        );
+
+        let now = Utc::now();
+        let pairings = tournament.start_round(now).unwrap().to_vec();
+        assert_eq!(tournament.byes.len(), 1);
+        let bye = tournament.byes[0];
+        assert_eq!(
+            tournament.start_round(now).unwrap_err(),
+            TournamentError::RoundInProgress
+        );
+        assert!(!tournament.is_round_overdue(now));
+        assert!(tournament.is_round_overdue(now + Duration::minutes(11)));
+
+        let (a, b) = (pairings[0].player1.id, pairings[0].player2.id);
+        assert_eq!(
+            tournament.complete_round(&[]).unwrap_err(),
+            TournamentError::MissingResult(a, b)
+        );
+
+        // Results may be reported with the players swapped
+        tournament
+            .complete_round(&[RoundResult {
+                player1: b,
+                player2: a,
+                outcome: Outcome::Draw,
+            }])
+            .unwrap();
+        assert_eq!(tournament.status, TournamentStatus::Pending);
+        assert_eq!(tournament.pairing_strategy, ArenaPairingStrategy::new().describe());
+        assert_eq!(tournament.score(a), DRAW_POINTS);
+        assert_eq!(tournament.score(bye), BYE_POINTS);
+    }
+
+    #[test]
+    fn test_withdrawn_player_is_never_paired_again() {
+        let players: Vec<TournamentPlayer> =
+            [2000, 1900, 1800, 1700, 1600].into_iter().map(create_player).collect();
+        let ids: Vec<Uuid> = players.iter().map(|p| p.id).collect();
+        let mut tournament = arena(players, 4);
+
+        // Round one: 2000 beats 1900, 1800 beats 1700, 1600 gets a bye
+        let results = higher_elo_wins(tournament.start_round(Utc::now()).unwrap());
+        tournament.complete_round(&results).unwrap();
+        let withdrawn = ids[0];
+        tournament.withdraw(withdrawn).unwrap();
+        let stranger = Uuid::new_v4();
+        assert_eq!(
+            tournament.withdraw(stranger).unwrap_err(),
+            TournamentError::UnknownPlayer(stranger)
+        );
+
+        for _ in 1..4 {
+            let pairings = tournament.start_round(Utc::now()).unwrap().to_vec();
+            assert!(pairings
+                .iter()
+                .all(|p| p.player1.id != withdrawn && p.player2.id != withdrawn));
+            assert!(!tournament.byes.contains(&withdrawn));
+            tournament.complete_round(&higher_elo_wins(&pairings)).unwrap();
+        }
+
+        // The withdrawn player's win still counts towards the opponent's tiebreak
+        assert_eq!(tournament.score(withdrawn), WIN_POINTS);
+        assert!(tournament.buchholz(ids[1]) >= WIN_POINTS);
+        assert!(tournament.standings().iter().any(|s| s.player_id == withdrawn));
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use chess::bitboard::board::{Board, Color, Piece, Role, Square};
+use chess::bitboard::position::Position;
+
+use crate::models::{ChessPiece, GameState, PieceColor, PieceType};
+
+// Conversions between the string-keyed board the socket layer keeps in
+// `GameState` and the bitboard `Board` of the chess engine
+
+impl From<PieceColor> for Color {
+    fn from(color: PieceColor) -> Self {
+        match color {
+            PieceColor::White => Color::White,
+            PieceColor::Black => Color::Black,
+        }
+    }
+}
+
+impl From<Color> for PieceColor {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::White => PieceColor::White,
+            Color::Black => PieceColor::Black,
+        }
+    }
+}
+
+impl From<PieceType> for Role {
+    fn from(piece_type: PieceType) -> Self {
+        match piece_type {
+            PieceType::Pawn => Role::Pawn,
+            PieceType::Knight => Role::Knight,
+            PieceType::Bishop => Role::Bishop,
+            PieceType::Rook => Role::Rook,
+            PieceType::Queen => Role::Queen,
+            PieceType::King => Role::King,
+        }
+    }
+}
+
+impl From<Role> for PieceType {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Pawn => PieceType::Pawn,
+            Role::Knight => PieceType::Knight,
+            Role::Bishop => PieceType::Bishop,
+            Role::Rook => PieceType::Rook,
+            Role::Queen => PieceType::Queen,
+            Role::King => PieceType::King,
+        }
+    }
+}
+
+impl From<ChessPiece> for Piece {
+    fn from(piece: ChessPiece) -> Self {
+        Piece { color: piece.color.into(), role: piece.piece_type.into() }
+    }
+}
+
+impl From<Piece> for ChessPiece {
+    fn from(piece: Piece) -> Self {
+        ChessPiece { piece_type: piece.role.into(), color: piece.color.into() }
+    }
+}
+
+// Build the engine board for a socket board, failing on the first key that
+// is not an algebraic square such as "e4"
+pub fn to_engine_board(board: &HashMap<String, ChessPiece>) -> Result<Board, String> {
+    let pieces = board
+        .iter()
+        .map(|(name, piece)| {
+            Square::from_name(name)
+                .map(|square| (square, Piece::from(piece.clone())))
+                .ok_or_else(|| format!("Invalid square: {}", name))
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    Ok(Board::from_piece_map(&pieces))
+}
+
+// The socket board holding the pieces of an engine board
+pub fn from_engine_board(board: &Board) -> HashMap<String, ChessPiece> {
+    board
+        .piece_map()
+        .into_iter()
+        .map(|(square, piece)| (square.name(), ChessPiece::from(piece)))
+        .collect()
+}
+
+// The engine position of a socket game, with castling rights and the en
+// passant square taken from its FEN, for generating legal moves
+pub fn to_engine_position(game_state: &GameState) -> Result<Position, String> {
+    let fen = game_state.to_fen();
+    Position::from_fen(&fen).ok_or_else(|| format!("Invalid position: {}", fen))
+}
+
+// Every position of a socket game, oldest first, ending with the current one
+pub fn engine_positions(game_state: &GameState) -> Result<Vec<Position>, String> {
+    let current = to_engine_position(game_state)?;
+    Ok(game_state
+        .history
+        .iter()
+        .map(|undo| undo.previous_position)
+        .chain(std::iter::once(current))
+        .collect())
+}
@@ -1,40 +1,178 @@
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use db_entity::game::DrawReason as DbDrawReason;
+use sea_orm::DatabaseConnection;
+use service::game_results::{engine_draw_reason, GameResultService};
 use tokio::sync::broadcast;
 use uuid::Uuid;
 
-use crate::models::{GameState, MoveRecord, Player, Room, ServerMessage};
+use crate::engine::engine_positions;
+use crate::models::{
+    DrawReason, GameClock, GameStatus, PieceColor, PieceType, Player, Room, RoomOptions, ServerMessage,
+    TimeControl, MIN_TAKEBACK_MOVES, ROOM_CAPACITY,
+};
+use crate::metrics::{export_rejected_move, MoveRejection};
+use crate::variants::GameVariant;
 
 // Type alias for the broadcast sender
 type MessageSender = broadcast::Sender<ServerMessage>;
 
+// Messages buffered per room before slow receivers start lagging
+pub const DEFAULT_BROADCAST_BUFFER: usize = 100;
+
+// How long a disconnected player has to rejoin before forfeiting the game
+pub const DEFAULT_DISCONNECT_GRACE: Duration = Duration::from_secs(60);
+
+// Disconnects within `DEFAULT_DISCONNECT_WINDOW` that still get a grace
+// period; the next one resigns the game
+pub const DEFAULT_MAX_DISCONNECTS: usize = 3;
+
+// How long a disconnect counts towards `DEFAULT_MAX_DISCONNECTS`
+pub const DEFAULT_DISCONNECT_WINDOW: Duration = Duration::from_secs(600);
+
+// How long a room that requires ready players waits before starting anyway
+pub const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(30);
+
+// How long a takeback offer waits for an answer before it lapses
+pub const DEFAULT_TAKEBACK_EXPIRY: Duration = Duration::from_secs(30);
+
+// Registry of rooms and their broadcast channels
+struct ServerState {
+    rooms: HashMap<String, Room>,
+    message_senders: HashMap<String, MessageSender>,
+    broadcast_buffer: usize,
+    disconnect_grace: Duration,
+    // Identifies each forfeit timer so a stale one can tell it was superseded
+    next_disconnect_token: u64,
+    max_disconnects: usize,
+    disconnect_window: Duration,
+    ready_timeout: Duration,
+    // Identifies each auto-start timer, like `next_disconnect_token`
+    next_auto_start_token: u64,
+    takeback_expiry: Duration,
+    // Identifies each takeback offer, so its expiry timer leaves later offers alone
+    next_takeback_token: u64,
+    // Where rejected moves are counted for the API's /metrics; None keeps
+    // the counts in the rooms only
+    metrics_redis: Option<redis::Client>,
+    // Where finished games are recorded; results are not stored without one
+    db: Option<Arc<DatabaseConnection>>,
+}
+
+impl ServerState {
+    fn new_room_channel(&self) -> MessageSender {
+        broadcast::channel(self.broadcast_buffer).0
+    }
+}
+
 // Global game state
 lazy_static::lazy_static! {
-    static ref GAME_STATE: Arc<Mutex<GameState>> = Arc::new(Mutex::new(GameState {
+    static ref GAME_STATE: Arc<Mutex<ServerState>> = Arc::new(Mutex::new(ServerState {
         rooms: HashMap::new(),
         message_senders: HashMap::new(),
+        broadcast_buffer: DEFAULT_BROADCAST_BUFFER,
+        disconnect_grace: DEFAULT_DISCONNECT_GRACE,
+        next_disconnect_token: 0,
+        max_disconnects: DEFAULT_MAX_DISCONNECTS,
+        disconnect_window: DEFAULT_DISCONNECT_WINDOW,
+        ready_timeout: DEFAULT_READY_TIMEOUT,
+        next_auto_start_token: 0,
+        takeback_expiry: DEFAULT_TAKEBACK_EXPIRY,
+        next_takeback_token: 0,
+        metrics_redis: None,
+        db: None,
     }));
 }
 
+// Lock a mutex, taking the data back if a thread panicked while holding it.
+// A panic in one game must not poison the lock for every other room.
+pub fn lock_or_recover<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        tracing::error!(event = "error", "recovering lock poisoned by a panicked thread");
+        mutex.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+fn game_state() -> MutexGuard<'static, ServerState> {
+    lock_or_recover(&GAME_STATE)
+}
+
+// Poison the global game state lock the way a panicking handler would.
+// Only meant for tests exercising lock recovery.
+#[doc(hidden)]
+pub fn poison_game_state() {
+    let _ = std::thread::spawn(|| {
+        let _guard = game_state();
+        panic!("poisoning game state");
+    })
+    .join();
+}
+
 // Initialize the game state
 pub fn init_game_state() {
     // This function is called at startup to ensure the lazy_static is initialized
-    let _guard = GAME_STATE.lock().unwrap();
-    log::info!("Game state initialized");
+    let _guard = game_state();
+    tracing::info!("game state initialized");
+}
+
+// Set the broadcast buffer size used for rooms created from now on
+pub fn set_broadcast_buffer(size: usize) {
+    let mut state = game_state();
+    state.broadcast_buffer = size.max(1);
+}
+
+// Set how long disconnected players have to rejoin their game
+pub fn set_disconnect_grace(grace: Duration) {
+    let mut state = game_state();
+    state.disconnect_grace = grace;
+}
+
+// Set how many disconnects within `window` get a grace period before the
+// next one counts as resigning
+pub fn set_disconnect_limit(max_disconnects: usize, window: Duration) {
+    let mut state = game_state();
+    state.max_disconnects = max_disconnects;
+    state.disconnect_window = window;
+}
+
+// Set how long rooms that require ready players wait before starting anyway
+pub fn set_ready_timeout(timeout: Duration) {
+    let mut state = game_state();
+    state.ready_timeout = timeout;
+}
+
+// Set how long takeback offers wait for an answer before lapsing
+pub fn set_takeback_expiry(expiry: Duration) {
+    let mut state = game_state();
+    state.takeback_expiry = expiry;
+}
+
+// Set the database finished games are recorded in; None stops recording them
+pub fn set_database(db: Option<Arc<DatabaseConnection>>) {
+    let mut state = game_state();
+    state.db = db;
+}
+
+// Set the Redis instance rejected moves are counted in for the API's /metrics
+pub fn set_metrics_redis(client: Option<redis::Client>) {
+    let mut state = game_state();
+    state.metrics_redis = client;
 }
 
 // Get a clone of the message sender for a room
 pub fn get_room_sender(room_id: &str) -> Option<MessageSender> {
-    let state = GAME_STATE.lock().unwrap();
+    let state = game_state();
     state.message_senders.get(room_id).cloned()
 }
 
 // Create a new room
 pub fn create_room() -> String {
     let room_id = Uuid::new_v4().to_string();
-    let (tx, _) = broadcast::channel(100); // Buffer size of 100 messages
     
-    let mut state = GAME_STATE.lock().unwrap();
+    let mut state = game_state();
+    let tx = state.new_room_channel();
     state.rooms.insert(room_id.clone(), Room::new(room_id.clone()));
     state.message_senders.insert(room_id.clone(), tx);
     
@@ -43,30 +181,92 @@ pub fn create_room() -> String {
 
 // Join a room
 pub fn join_room(room_id: &str, player_id: &str, player_name: Option<String>) -> Result<ServerMessage, String> {
-    let mut state = GAME_STATE.lock().unwrap();
-    
-    // Check if room exists, create if not
+    join_room_with_variant(room_id, player_id, player_name, GameVariant::Standard)
+}
+
+// Join a room, creating it with the given variant if it doesn't exist yet
+pub fn join_room_with_variant(
+    room_id: &str,
+    player_id: &str,
+    player_name: Option<String>,
+    variant: GameVariant,
+) -> Result<ServerMessage, String> {
+    join_room_with_time_control(room_id, player_id, player_name, variant, None)
+}
+
+// Join a room, creating it with the given variant and clocks if it doesn't
+// exist yet. Without a time control the new game is untimed
+pub fn join_room_with_time_control(
+    room_id: &str,
+    player_id: &str,
+    player_name: Option<String>,
+    variant: GameVariant,
+    time_control: Option<TimeControl>,
+) -> Result<ServerMessage, String> {
+    let options = RoomOptions {
+        variant,
+        time_control,
+        require_ready: false,
+        takebacks: None,
+    };
+    join_room_with_options(room_id, player_id, player_name, options)
+}
+
+// Join a room, creating it with `options` if it doesn't exist yet. When the
+// room requires ready players, filling it starts the auto-start timer
+pub fn join_room_with_options(
+    room_id: &str,
+    player_id: &str,
+    player_name: Option<String>,
+    options: RoomOptions,
+) -> Result<ServerMessage, String> {
+    join_room_as(room_id, player_id, player_name, options, None)
+}
+
+// Join a room like `join_room_with_options`, asking to play `color`. The
+// join fails if that side is already taken
+pub fn join_room_as(
+    room_id: &str,
+    player_id: &str,
+    player_name: Option<String>,
+    options: RoomOptions,
+    color: Option<PieceColor>,
+) -> Result<ServerMessage, String> {
+    // Creation, the capacity check and the join all happen under this one lock,
+    // so concurrent joins to a new room can neither replace it nor overfill it
+    let mut state = game_state();
+
     if !state.rooms.contains_key(room_id) {
-                drop(state); // Release the lock before calling create_room
-                let _ = create_room(); // This creates a new room with a UUID
-                state = GAME_STATE.lock().unwrap();
-                // Now create the room with the requested ID
-                let (tx, _) = broadcast::channel(100);
-                state.rooms.insert(room_id.to_string(), Room::new(room_id.to_string()));
-                state.message_senders.insert(room_id.to_string(), tx);
+        let tx = state.new_room_channel();
+        let mut room = Room::with_variant(room_id.to_string(), options.variant);
+        room.clock = options.time_control.map(GameClock::new);
+        room.require_ready = options.require_ready;
+        if let Some(takebacks) = options.takebacks {
+            room.takebacks = takebacks;
+        }
+        state.rooms.insert(room_id.to_string(), room);
+        state.message_senders.insert(room_id.to_string(), tx);
     }
-    
+
     let room = state.rooms.get_mut(room_id).unwrap();
-    
-    // Create player
-    let player = Player {
-        id: player_id.to_string(),
-        name: player_name.unwrap_or_else(|| format!("Player {}", player_id)),
-        color: None,
-    };
-    
-    // Add player to room
-    room.add_player(player)?;
+
+    // A disconnected player rejoining takes their seat back, cancelling the forfeit
+    if room.disconnected.remove(player_id).is_none() {
+        // Create player
+        let player = Player {
+            id: player_id.to_string(),
+            name: player_name.unwrap_or_else(|| format!("Player {}", player_id)),
+            color,
+            ready: false,
+        };
+
+        // Add player to room
+        room.add_player(player)?;
+    }
+    let awaiting_ready = room.require_ready
+        && room.game_state.is_none()
+        && room.auto_start_token.is_none()
+        && room.players.len() == ROOM_CAPACITY;
     
     // Create response message
     let response = ServerMessage::RoomJoined {
@@ -78,31 +278,201 @@ pub fn join_room(room_id: &str, player_id: &str, player_name: Option<String>) ->
     
         // Broadcast to other players in the room
        if let Some(sender) = state.message_senders.get(room_id) {
-            if let Err(e) = sender.send(response.clone()) {
-                log::warn!("Failed to broadcast RoomJoined message: {:?}", e);
+            // The send error carries the whole message, so it is not logged
+            if sender.send(response.clone()).is_err() {
+                tracing::warn!(
+                    event = "error",
+                    game_id = %room_id,
+                    player_id = %player_id,
+                    "failed to broadcast RoomJoined message"
+                );
             }
         }
+
+    // Start the game anyway if the players are not both ready in time. Rooms
+    // filled outside a tokio runtime have no timer and wait for both players
+    if let (true, Ok(runtime)) = (awaiting_ready, tokio::runtime::Handle::try_current()) {
+        state.next_auto_start_token += 1;
+        let token = state.next_auto_start_token;
+        let timeout = state.ready_timeout;
+        if let Some(room) = state.rooms.get_mut(room_id) {
+            room.auto_start_token = Some(token);
+        }
+
+        let room_id = room_id.to_string();
+        runtime.spawn(async move {
+            tokio::time::sleep(timeout).await;
+            auto_start(&room_id, token);
+        });
+    }
     
     Ok(response)
 }
 
+// Mark a player ready. In a room that requires it, the game starts once
+// both players are ready
+pub fn mark_ready(room_id: &str, player_id: &str) -> Result<ServerMessage, String> {
+    let mut state = game_state();
+
+    let room = state
+        .rooms
+        .get_mut(room_id)
+        .ok_or_else(|| "Room not found".to_string())?;
+
+    if room.game_state.is_some() {
+        return Err("Game already started".to_string());
+    }
+    let player = room
+        .players
+        .iter_mut()
+        .find(|p| p.id == player_id)
+        .ok_or_else(|| "Player not in room".to_string())?;
+    player.ready = true;
+
+    let mut responses = vec![ServerMessage::PlayerReady {
+        room_id: room_id.to_string(),
+        player_id: player_id.to_string(),
+    }];
+    if room.require_ready && room.all_ready() {
+        room.auto_start_token = None;
+        room.start_game();
+        responses.push(ServerMessage::GameStarted {
+            room_id: room_id.to_string(),
+            game_state: room.game_state.clone().unwrap(),
+        });
+    }
+
+    if let Some(sender) = state.message_senders.get(room_id) {
+        for response in &responses {
+            let _ = sender.send(response.clone());
+        }
+    }
+
+    Ok(responses.pop().unwrap())
+}
+
+// Start a game whose players did not both get ready before the timeout,
+// unless it started (or a newer timer took over) in the meantime
+fn auto_start(room_id: &str, token: u64) -> Option<ServerMessage> {
+    let mut state = game_state();
+    let room = state.rooms.get_mut(room_id)?;
+    if room.auto_start_token != Some(token) {
+        return None;
+    }
+    room.auto_start_token = None;
+    if room.game_state.is_some() || room.players.len() < ROOM_CAPACITY {
+        return None;
+    }
+    room.start_game();
+
+    let response = ServerMessage::GameStarted {
+        room_id: room_id.to_string(),
+        game_state: room.game_state.clone().unwrap(),
+    };
+
+    if let Some(sender) = state.message_senders.get(room_id) {
+        let _ = sender.send(response.clone());
+    }
+
+    Some(response)
+}
+
 // Send a move
 pub fn send_move(room_id: &str, player_id: &str, move_notation: &str) -> Result<ServerMessage, String> {
-    let mut state = GAME_STATE.lock().unwrap();
+    send_move_with_promotion(room_id, player_id, move_notation, None)
+}
+
+// Send a move with an optional promotion choice ("q", "r", "b" or "n").
+// Refused moves are counted by reason in their room and in the shared metrics
+pub fn send_move_with_promotion(
+    room_id: &str,
+    player_id: &str,
+    move_notation: &str,
+    promotion: Option<&str>,
+) -> Result<ServerMessage, String> {
+    let mut state = game_state();
+    play_move(&mut state, room_id, player_id, move_notation, promotion).map_err(|(reason, e)| {
+        if let Some(room) = state.rooms.get_mut(room_id) {
+            *room.rejected_moves.entry(reason).or_default() += 1;
+        }
+        if let (Some(client), Ok(runtime)) = (&state.metrics_redis, tokio::runtime::Handle::try_current()) {
+            runtime.spawn(export_rejected_move(client.clone(), reason));
+        }
+        e
+    })
+}
+
+fn play_move(
+    state: &mut ServerState,
+    room_id: &str,
+    player_id: &str,
+    move_notation: &str,
+    promotion: Option<&str>,
+) -> Result<ServerMessage, (MoveRejection, String)> {
+    let reject = |reason: MoveRejection| move |e: String| (reason, e);
+    let promotion = promotion
+        .map(PieceType::from_promotion)
+        .transpose()
+        .map_err(reject(MoveRejection::Illegal))?;
     
     // Check if room exists
-    let room = state.rooms.get_mut(room_id).ok_or_else(|| "Room not found".to_string())?;
+    let room = state
+        .rooms
+        .get_mut(room_id)
+        .ok_or_else(|| (MoveRejection::RoomNotFound, "Room not found".to_string()))?;
     
     // Check if player is in the room
     if !room.players.iter().any(|p| p.id == player_id) {
-        return Err("Player not in room".to_string());
+        return Err((MoveRejection::NotInRoom, "Player not in room".to_string()));
     }
     
     // Check if game has started
-    let game_state = room.game_state.as_mut().ok_or_else(|| "Game not started".to_string())?;
+    let rules = room.variant.rules();
+    let duplicate = room.is_duplicate_move(player_id, move_notation);
+    let color = room.player_color(player_id);
+    let game_state = room
+        .game_state
+        .as_mut()
+        .ok_or_else(|| (MoveRejection::NotStarted, "Game not started".to_string()))?;
+
+    // A resend of the move just played is acknowledged again, not replayed
+    if duplicate {
+        return Ok(ServerMessage::MoveMade {
+            room_id: room_id.to_string(),
+            player_id: player_id.to_string(),
+            move_notation: move_notation.to_string(),
+            game_state: game_state.clone(),
+        });
+    }
+    if game_state.status != GameStatus::InProgress {
+        return Err((MoveRejection::GameOver, "Game is over".to_string()));
+    }
+    if color.as_ref() != Some(&game_state.current_turn) {
+        return Err((MoveRejection::OutOfTurn, "Not your turn".to_string()));
+    }
     
-    // Apply the move
-    game_state.apply_move(move_notation)?;
+    // Apply the move, then any variant-specific win conditions
+    let mover = game_state.current_turn.clone();
+    game_state
+        .apply_move_with_promotion(move_notation, promotion)
+        .map_err(reject(MoveRejection::Illegal))?;
+    rules.after_move(game_state, &mover);
+
+    // End the game on a draw the rules of chess impose
+    let engine_draw = match game_state.status {
+        GameStatus::InProgress => engine_positions(game_state)
+            .ok()
+            .and_then(|positions| engine_draw_reason(&positions)),
+        _ => None,
+    };
+    if let Some(reason) = &engine_draw {
+        game_state.status = match reason {
+            DbDrawReason::Stalemate => GameStatus::Stalemate,
+            _ => GameStatus::Draw,
+        };
+        game_state.draw_reason = Some(reason.clone().into());
+    }
+    let game_state = game_state.clone();
     
     // Record the move
     room.add_move(player_id.to_string(), move_notation.to_string());
@@ -112,20 +482,32 @@ pub fn send_move(room_id: &str, player_id: &str, move_notation: &str) -> Result<
         room_id: room_id.to_string(),
         player_id: player_id.to_string(),
         move_notation: move_notation.to_string(),
-        game_state: game_state.clone(),
+        game_state,
     };
     
     // Broadcast to all players in the room
     if let Some(sender) = state.message_senders.get(room_id) {
         let _ = sender.send(response.clone());
     }
+    if let Some(reason) = engine_draw {
+        record_draw(state, room_id, reason);
+    }
     
     Ok(response)
 }
 
+// How many moves in `room_id` were refused for `reason`
+pub fn rejected_move_count(room_id: &str, reason: MoveRejection) -> u64 {
+    game_state()
+        .rooms
+        .get(room_id)
+        .and_then(|room| room.rejected_moves.get(&reason).copied())
+        .unwrap_or(0)
+}
+
 // Leave a room
 pub fn leave_room(room_id: &str, player_id: &str) -> Result<ServerMessage, String> {
-    let mut state = GAME_STATE.lock().unwrap();
+    let mut state = game_state();
     
     // Check if room exists
     let room = state.rooms.get_mut(room_id).ok_or_else(|| "Room not found".to_string())?;
@@ -135,6 +517,8 @@ pub fn leave_room(room_id: &str, player_id: &str) -> Result<ServerMessage, Strin
         return Err("Player not in room".to_string());
     }
     
+    let room_is_empty = room.players.is_empty();
+    
     // Create response message
     let response = ServerMessage::PlayerLeft {
         room_id: room_id.to_string(),
@@ -147,7 +531,7 @@ pub fn leave_room(room_id: &str, player_id: &str) -> Result<ServerMessage, Strin
     }
     
     // Clean up empty rooms
-    if room.players.is_empty() {
+    if room_is_empty {
         state.rooms.remove(room_id);
         state.message_senders.remove(room_id);
     }
@@ -155,26 +539,57 @@ pub fn leave_room(room_id: &str, player_id: &str) -> Result<ServerMessage, Strin
     Ok(response)
 }
 
-// Get game log
+// Get the full game log
 pub fn get_game_log(room_id: &str) -> Result<ServerMessage, String> {
-    let state = GAME_STATE.lock().unwrap();
+    get_game_log_page(room_id, None, None)
+}
+
+// Get up to `limit` moves of the game log starting at ply `from_ply`
+pub fn get_game_log_page(room_id: &str, from_ply: Option<usize>, limit: Option<usize>) -> Result<ServerMessage, String> {
+    let state = game_state();
     
     // Check if room exists
     let room = state.rooms.get(room_id).ok_or_else(|| "Room not found".to_string())?;
     
+    let total_moves = room.moves.len();
+    let from_ply = from_ply.unwrap_or(0).min(total_moves);
+    let end = limit.map_or(total_moves, |limit| from_ply.saturating_add(limit).min(total_moves));
+    
     // Create response message
     let response = ServerMessage::GameLog {
         room_id: room_id.to_string(),
-        moves: room.moves.clone(),
+        moves: room.moves[from_ply..end].to_vec(),
+        from_ply,
+        total_moves,
     };
     
     Ok(response)
 }
 
+// Get the current position of a room's game as FEN
+pub fn get_fen(room_id: &str) -> Result<ServerMessage, String> {
+    let state = game_state();
+    let room = state.rooms.get(room_id).ok_or_else(|| "Room not found".to_string())?;
+    let game_state = room.game_state.as_ref().ok_or_else(|| "Game not started".to_string())?;
+
+    Ok(ServerMessage::FenState {
+        room_id: room_id.to_string(),
+        fen: game_state.to_fen(),
+        turn: game_state.current_turn.clone(),
+    })
+}
+
+// Get both clocks of a room as of now; None if the game is untimed
+pub fn get_clock_sync(room_id: &str) -> Result<Option<ServerMessage>, String> {
+    let state = game_state();
+    let room = state.rooms.get(room_id).ok_or_else(|| "Room not found".to_string())?;
+    Ok(room.clock_sync())
+}
+
 // Handle a takeback offer from a player.
 // Current behavior: only board state and move history are affected; clocks/time controls are not modified.
 pub fn offer_takeback(room_id: &str, player_id: &str) -> Result<ServerMessage, String> {
-    let mut state = GAME_STATE.lock().unwrap();
+    let mut state = game_state();
 
     let room = state
         .rooms
@@ -186,8 +601,19 @@ pub fn offer_takeback(room_id: &str, player_id: &str) -> Result<ServerMessage, S
         return Err("Player not in room".to_string());
     }
 
-    // Require at least one full move (two half-moves) to be able to take back
-    if room.moves.len() < 2 {
+    // A finished game has already been recorded, so it cannot be rolled back
+    match &room.game_state {
+        Some(game_state) if game_state.status == GameStatus::InProgress => {}
+        Some(_) => return Err("Game is over".to_string()),
+        None => return Err("Game not started".to_string()),
+    }
+
+    if !room.takebacks.allowed {
+        return Err("Takebacks are disabled in this room".to_string());
+    }
+
+    // A takeback rolls back one full move, so never allow fewer than two half-moves
+    if room.moves.len() < room.takebacks.min_moves.max(MIN_TAKEBACK_MOVES) {
         return Err("Not enough moves to take back".to_string());
     }
 
@@ -197,6 +623,12 @@ pub fn offer_takeback(room_id: &str, player_id: &str) -> Result<ServerMessage, S
     }
 
     room.pending_takeback = Some(player_id.to_string());
+    state.next_takeback_token += 1;
+    let token = state.next_takeback_token;
+    let expiry = state.takeback_expiry;
+    if let Some(room) = state.rooms.get_mut(room_id) {
+        room.takeback_token = Some(token);
+    }
 
     let response = ServerMessage::TakebackOffered {
         room_id: room_id.to_string(),
@@ -206,13 +638,47 @@ pub fn offer_takeback(room_id: &str, player_id: &str) -> Result<ServerMessage, S
     if let Some(sender) = state.message_senders.get(room_id) {
         let _ = sender.send(response.clone());
     }
+    drop(state);
+
+    // Withdraw the offer if the opponent ignores it. Offers made outside a
+    // tokio runtime have no timer and stay open until answered
+    if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+        let room_id = room_id.to_string();
+        runtime.spawn(async move {
+            tokio::time::sleep(expiry).await;
+            expire_takeback(&room_id, token);
+        });
+    }
 
     Ok(response)
 }
 
+// Clear the takeback offer identified by `token` if it is still unanswered,
+// so the players can make new offers
+fn expire_takeback(room_id: &str, token: u64) -> Option<ServerMessage> {
+    let mut state = game_state();
+    let room = state.rooms.get_mut(room_id)?;
+    if room.pending_takeback.is_none() || room.takeback_token != Some(token) {
+        return None;
+    }
+    let requester_id = room.pending_takeback.take()?;
+    room.takeback_token = None;
+
+    let response = ServerMessage::TakebackExpired {
+        room_id: room_id.to_string(),
+        requester_id,
+    };
+
+    if let Some(sender) = state.message_senders.get(room_id) {
+        let _ = sender.send(response.clone());
+    }
+
+    Some(response)
+}
+
 // Accept a pending takeback request and roll back one full move (two half-moves).
 pub fn accept_takeback(room_id: &str, player_id: &str) -> Result<ServerMessage, String> {
-    let mut state = GAME_STATE.lock().unwrap();
+    let mut state = game_state();
 
     let room = state
         .rooms
@@ -224,6 +690,13 @@ pub fn accept_takeback(room_id: &str, player_id: &str) -> Result<ServerMessage,
         return Err("Player not in room".to_string());
     }
 
+    // A finished game has already been recorded, so it cannot be rolled back
+    match &room.game_state {
+        Some(game_state) if game_state.status == GameStatus::InProgress => {}
+        Some(_) => return Err("Game is over".to_string()),
+        None => return Err("Game not started".to_string()),
+    }
+
     // There must be a pending takeback request
     let requester_id = match &room.pending_takeback {
         Some(id) => id.clone(),
@@ -240,17 +713,23 @@ pub fn accept_takeback(room_id: &str, player_id: &str) -> Result<ServerMessage,
         return Err("Not enough moves to take back".to_string());
     }
 
-    // Truncate last two half-moves
+    // Undo the last two half-moves from the recorded history instead of replaying the game
+    let game_state = room
+        .game_state
+        .as_mut()
+        .ok_or_else(|| "Game not started".to_string())?;
+    if game_state.history.len() < 2 {
+        return Err("Not enough moves to take back".to_string());
+    }
+    game_state.undo_move()?;
+    game_state.undo_move()?;
+    let game_state = game_state.clone();
+
     let new_len = room.moves.len() - 2;
     room.moves.truncate(new_len);
+    // The replayed turn is timed from this broadcast
+    room.turn_started_at = Some(tokio::time::Instant::now());
 
-    // Rebuild game state from initial position and remaining moves
-    let mut game_state = GameState::new_game();
-    for mv in &room.moves {
-        game_state.apply_move(&mv.move_notation)?;
-    }
-
-    room.game_state = Some(game_state.clone());
     room.pending_takeback = None;
 
     let response = ServerMessage::TakebackAccepted {
@@ -268,7 +747,7 @@ pub fn accept_takeback(room_id: &str, player_id: &str) -> Result<ServerMessage,
 
 // Reject a pending takeback request.
 pub fn reject_takeback(room_id: &str, player_id: &str) -> Result<ServerMessage, String> {
-    let mut state = GAME_STATE.lock().unwrap();
+    let mut state = game_state();
 
     let room = state
         .rooms
@@ -299,8 +778,225 @@ pub fn reject_takeback(room_id: &str, player_id: &str) -> Result<ServerMessage,
     Ok(response)
 }
 
+// Offer the opponent a draw.
+pub fn offer_draw(room_id: &str, player_id: &str) -> Result<ServerMessage, String> {
+    let mut state = game_state();
+
+    let room = state
+        .rooms
+        .get_mut(room_id)
+        .ok_or_else(|| "Room not found".to_string())?;
+
+    // Ensure player is in the room
+    if !room.players.iter().any(|p| p.id == player_id) {
+        return Err("Player not in room".to_string());
+    }
+
+    match &room.game_state {
+        Some(game_state) if game_state.status == GameStatus::InProgress => {}
+        Some(_) => return Err("Game is over".to_string()),
+        None => return Err("Game not started".to_string()),
+    }
+
+    // Only one pending draw offer at a time
+    if room.pending_draw.is_some() {
+        return Err("A draw offer is already pending".to_string());
+    }
+
+    room.pending_draw = Some(player_id.to_string());
+
+    let response = ServerMessage::DrawOffered {
+        room_id: room_id.to_string(),
+        requester_id: player_id.to_string(),
+    };
+
+    if let Some(sender) = state.message_senders.get(room_id) {
+        let _ = sender.send(response.clone());
+    }
+
+    Ok(response)
+}
+
+// Accept a pending draw offer, ending the game as a draw by agreement.
+pub fn accept_draw(room_id: &str, player_id: &str) -> Result<ServerMessage, String> {
+    let mut state = game_state();
+
+    let room = state
+        .rooms
+        .get_mut(room_id)
+        .ok_or_else(|| "Room not found".to_string())?;
+
+    // Ensure player is in the room
+    if !room.players.iter().any(|p| p.id == player_id) {
+        return Err("Player not in room".to_string());
+    }
+
+    // There must be a pending draw offer, made by the other player
+    match &room.pending_draw {
+        Some(requester_id) if requester_id == player_id => {
+            return Err("Requester cannot accept their own draw offer".to_string())
+        }
+        Some(_) => {}
+        None => return Err("No pending draw offer".to_string()),
+    }
+
+    let game_state = room
+        .game_state
+        .as_mut()
+        .ok_or_else(|| "Game not started".to_string())?;
+    game_state.status = GameStatus::Draw;
+    game_state.draw_reason = Some(DrawReason::Agreement);
+    let game_state = game_state.clone();
+
+    room.pending_draw = None;
+
+    let response = ServerMessage::DrawAgreed {
+        room_id: room_id.to_string(),
+        game_state,
+    };
+
+    if let Some(sender) = state.message_senders.get(room_id) {
+        let _ = sender.send(response.clone());
+    }
+    record_draw(&state, room_id, DbDrawReason::Agreement);
+
+    Ok(response)
+}
+
+// Start the reconnect grace period for a player whose connection dropped.
+// If they have not rejoined the room when it elapses, they lose a running
+// game; otherwise their seat is freed. A player who already dropped
+// `max_disconnects` times within the disconnect window gets no grace period
+// and loses straight away.
+pub fn player_disconnected(room_id: &str, player_id: &str) {
+    let mut state = game_state();
+    state.next_disconnect_token += 1;
+    let token = state.next_disconnect_token;
+    let grace = state.disconnect_grace;
+    let (max_disconnects, window) = (state.max_disconnects, state.disconnect_window);
+
+    let room = match state.rooms.get_mut(room_id) {
+        Some(room) if room.players.iter().any(|p| p.id == player_id) => room,
+        _ => return,
+    };
+    room.disconnected.insert(player_id.to_string(), token);
+
+    // Older disconnects no longer count, so staying connected for a whole
+    // window resets the count
+    let now = tokio::time::Instant::now();
+    let recent = room.recent_disconnects.entry(player_id.to_string()).or_default();
+    recent.retain(|at| now.duration_since(*at) < window);
+    if recent.len() >= max_disconnects {
+        recent.clear();
+        drop(state);
+        tracing::info!(
+            event = "resign",
+            game_id = %room_id,
+            player_id = %player_id,
+            "player disconnected too often and resigned"
+        );
+        forfeit_if_absent(room_id, player_id, token);
+        return;
+    }
+    recent.push(now);
+    drop(state);
+
+    // Disconnects outside a tokio runtime have no grace timer, so the seat
+    // stays held until the player comes back
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let (room_id, player_id) = (room_id.to_string(), player_id.to_string());
+    runtime.spawn(async move {
+        tokio::time::sleep(grace).await;
+        forfeit_if_absent(&room_id, &player_id, token);
+    });
+}
+
+// End the grace period started by `player_disconnected`, unless the player
+// came back (or disconnected again, starting a newer timer) in the meantime
+fn forfeit_if_absent(room_id: &str, player_id: &str, token: u64) -> Option<ServerMessage> {
+    let mut state = game_state();
+    let room = state.rooms.get_mut(room_id)?;
+    if room.disconnected.get(player_id) != Some(&token) {
+        return None;
+    }
+    room.disconnected.remove(player_id);
+
+    let absent_color = room
+        .players
+        .iter()
+        .find(|p| p.id == player_id)
+        .and_then(|p| p.color.clone());
+    let game_state = match room.game_state.as_mut() {
+        Some(game_state) if game_state.status == GameStatus::InProgress => game_state,
+        _ => {
+            // Nothing to forfeit; just give up the seat
+            drop(state);
+            return leave_room(room_id, player_id).ok();
+        }
+    };
+    game_state.status = GameStatus::Abandoned;
+    game_state.winner = match absent_color {
+        Some(PieceColor::White) => Some(PieceColor::Black),
+        Some(PieceColor::Black) => Some(PieceColor::White),
+        None => None,
+    };
+    let game_state = game_state.clone();
+
+    room.pending_takeback = None;
+    room.pending_draw = None;
+
+    tracing::info!(
+        event = "abandoned",
+        game_id = %room_id,
+        player_id = %player_id,
+        "player did not reconnect in time and lost the game"
+    );
+
+    let response = ServerMessage::GameAbandoned {
+        room_id: room_id.to_string(),
+        player_id: player_id.to_string(),
+        game_state,
+    };
+
+    if let Some(sender) = state.message_senders.get(room_id) {
+        let _ = sender.send(response.clone());
+    }
+
+    Some(response)
+}
+
 // Database integration functions
-// These are placeholders for future implementation
+
+impl From<DbDrawReason> for DrawReason {
+    fn from(reason: DbDrawReason) -> Self {
+        match reason {
+            DbDrawReason::Agreement => DrawReason::Agreement,
+            DbDrawReason::Stalemate => DrawReason::Stalemate,
+            DbDrawReason::ThreefoldRepetition => DrawReason::ThreefoldRepetition,
+            DbDrawReason::FiftyMoveRule => DrawReason::FiftyMoveRule,
+        }
+    }
+}
+
+// Store a drawn game's result and why it was drawn. Only rooms named after a
+// game id, as `create_room` names them, are stored; recording happens in the
+// background, so it needs a running tokio runtime
+fn record_draw(state: &ServerState, room_id: &str, reason: DbDrawReason) {
+    let (Some(db), Ok(game_id)) = (state.db.clone(), Uuid::parse_str(room_id)) else {
+        return;
+    };
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        tracing::warn!(event = "error", game_id = %game_id, "no runtime to record the draw");
+        return;
+    };
+    runtime.spawn(async move {
+        if let Err(e) = GameResultService::finalize_draw(&db, game_id, reason).await {
+            tracing::error!(event = "error", game_id = %game_id, error = %e, "failed to record the draw");
+        }
+    });
+}
 
 pub fn save_game_to_db(_room_id: &str) -> Result<(), String> {
     // In a real implementation, this would save the game state to a database
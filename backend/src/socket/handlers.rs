@@ -4,16 +4,62 @@ use tokio::sync::broadcast;
 use tokio_tungstenite::tungstenite::Message;
 
 use crate::game::{
+    accept_draw,
     accept_takeback,
-    get_game_log,
+    get_clock_sync,
+    get_fen,
+    get_game_log_page,
     get_room_sender,
-    join_room,
+    join_room_as,
     leave_room,
+    mark_ready,
+    offer_draw,
     offer_takeback,
     reject_takeback,
-    send_move,
+    send_move_with_promotion,
 };
-use crate::models::{ClientMessage, ServerMessage};
+use crate::models::{ClientMessage, ErrorCode, RoomOptions, ServerMessage, CLIENT_MESSAGE_TYPES};
+
+// A room this connection joined, and the seat it holds there
+pub struct RoomSubscription {
+    pub room_id: String,
+    pub player_id: String,
+    pub sender: broadcast::Sender<ServerMessage>,
+}
+
+// Explain why `message` is not a valid client message. Only the kind of
+// problem and names from our own schema are reported, never the input itself
+fn describe_parse_error(message: &str) -> String {
+    let value: serde_json::Value = match from_str(message) {
+        Ok(value) => value,
+        Err(_) => return "Failed to parse message: invalid JSON".to_string(),
+    };
+
+    let accepted = CLIENT_MESSAGE_TYPES.join(", ");
+    match value.get("type").and_then(|t| t.as_str()) {
+        None => {
+            return format!(
+                "Failed to parse message: missing field `type`; expected one of {}",
+                accepted
+            )
+        }
+        Some(t) if !CLIENT_MESSAGE_TYPES.contains(&t) => {
+            return format!(
+                "Failed to parse message: unknown message type; expected one of {}",
+                accepted
+            )
+        }
+        Some(_) => {}
+    }
+
+    match serde_json::from_value::<ClientMessage>(value) {
+        // These only name one of our fields, e.g. "missing field `room_id`"
+        Err(e) if e.to_string().starts_with("missing field") => {
+            format!("Failed to parse message: {}", e)
+        }
+        _ => "Failed to parse message: invalid payload".to_string(),
+    }
+}
 
 // Handle a client message
 pub async fn handle_client_message(
@@ -22,16 +68,23 @@ pub async fn handle_client_message(
         tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>,
         Message,
     >,
-    room_senders: &mut Vec<(String, broadcast::Sender<ServerMessage>)>,
+    room_senders: &mut Vec<RoomSubscription>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Parse the message
     let client_message: ClientMessage = match from_str(message) {
         Ok(msg) => msg,
         Err(e) => {
-            log::error!("Failed to parse client message: {}", e);
+            // serde errors can quote the input, so only its position is logged
+            tracing::warn!(
+                event = "error",
+                category = ?e.classify(),
+                line = e.line(),
+                column = e.column(),
+                "failed to parse client message"
+            );
             let error_msg = ServerMessage::Error {
-                code: "PARSE_ERROR".to_string(),
-                message: "Failed to parse message".to_string(),
+                code: ErrorCode::ParseError,
+                message: describe_parse_error(message),
             };
             sender
                 .send(Message::Text(to_string(&error_msg)?))
@@ -43,25 +96,54 @@ pub async fn handle_client_message(
     // Handle the message based on its type
     match client_message {
         ClientMessage::JoinRoom(payload) => {
-            log::info!(
-                "Player {} joining room {}",
-                payload.player_id,
-                payload.room_id
+            tracing::info!(
+                event = "join",
+                game_id = %payload.room_id,
+                player_id = %payload.player_id,
+                "player joining room"
             );
 
-            match join_room(&payload.room_id, &payload.player_id, payload.player_name) {
+            let options = RoomOptions {
+                variant: payload.variant.unwrap_or_default(),
+                time_control: payload.time_control,
+                require_ready: payload.require_ready,
+                takebacks: payload.takebacks,
+            };
+            match join_room_as(
+                &payload.room_id,
+                &payload.player_id,
+                payload.player_name,
+                options,
+                payload.color,
+            ) {
                 Ok(response) => {
                     // Send response to client
                     sender.send(Message::Text(to_string(&response)?)).await?;
 
+                    // Timed games also get the clocks, so a reconnecting client can resume them
+                    if let Ok(Some(clock_sync)) = get_clock_sync(&payload.room_id) {
+                        sender.send(Message::Text(to_string(&clock_sync)?)).await?;
+                    }
+
                     // Subscribe to room messages
                     if let Some(room_sender) = get_room_sender(&payload.room_id) {
-                        room_senders.push((payload.room_id, room_sender));
+                        room_senders.push(RoomSubscription {
+                            room_id: payload.room_id,
+                            player_id: payload.player_id,
+                            sender: room_sender,
+                        });
                     }
                 }
                 Err(e) => {
+                    tracing::warn!(
+                        event = "error",
+                        game_id = %payload.room_id,
+                        player_id = %payload.player_id,
+                        reason = %e,
+                        "join rejected"
+                    );
                     let error_msg = ServerMessage::Error {
-                        code: "JOIN_ERROR".to_string(),
+                        code: ErrorCode::JoinError,
                         message: e,
                     };
                     sender.send(Message::Text(to_string(&error_msg)?)).await?;
@@ -69,20 +151,32 @@ pub async fn handle_client_message(
             }
         }
         ClientMessage::SendMove(payload) => {
-            log::info!(
-                "Player {} making move {} in room {}",
-                payload.player_id,
-                payload.move_notation,
-                payload.room_id
+            tracing::info!(
+                event = "move",
+                game_id = %payload.room_id,
+                player_id = %payload.player_id,
+                "player making move"
             );
 
-            match send_move(&payload.room_id, &payload.player_id, &payload.move_notation) {
+            match send_move_with_promotion(
+                &payload.room_id,
+                &payload.player_id,
+                &payload.move_notation,
+                payload.promotion.as_deref(),
+            ) {
                 Ok(response) => {
                     sender.send(Message::Text(to_string(&response)?)).await?;
                 }
                 Err(e) => {
+                    tracing::warn!(
+                        event = "error",
+                        game_id = %payload.room_id,
+                        player_id = %payload.player_id,
+                        reason = %e,
+                        "move rejected"
+                    );
                     let error_msg = ServerMessage::Error {
-                        code: "MOVE_ERROR".to_string(),
+                        code: ErrorCode::MoveError,
                         message: e,
                     };
                     sender.send(Message::Text(to_string(&error_msg)?)).await?;
@@ -90,10 +184,11 @@ pub async fn handle_client_message(
             }
         }
         ClientMessage::LeaveRoom(payload) => {
-            log::info!(
-                "Player {} leaving room {}",
-                payload.player_id,
-                payload.room_id
+            tracing::info!(
+                event = "leave",
+                game_id = %payload.room_id,
+                player_id = %payload.player_id,
+                "player leaving room"
             );
 
             match leave_room(&payload.room_id, &payload.player_id) {
@@ -101,11 +196,11 @@ pub async fn handle_client_message(
                     sender.send(Message::Text(to_string(&response)?)).await?;
 
                     // Unsubscribe from room messages
-                    room_senders.retain(|(id, _)| id != &payload.room_id);
+                    room_senders.retain(|subscription| subscription.room_id != payload.room_id);
                 }
                 Err(e) => {
                     let error_msg = ServerMessage::Error {
-                        code: "LEAVE_ERROR".to_string(),
+                        code: ErrorCode::LeaveError,
                         message: e,
                     };
                     sender.send(Message::Text(to_string(&error_msg)?)).await?;
@@ -113,15 +208,15 @@ pub async fn handle_client_message(
             }
         }
         ClientMessage::RequestGameLog(payload) => {
-            log::info!("Game log requested for room {}", payload.room_id);
+            tracing::info!(event = "game_log", game_id = %payload.room_id, "game log requested");
 
-            match get_game_log(&payload.room_id) {
+            match get_game_log_page(&payload.room_id, payload.from_ply, payload.limit) {
                 Ok(response) => {
                     sender.send(Message::Text(to_string(&response)?)).await?;
                 }
                 Err(e) => {
                     let error_msg = ServerMessage::Error {
-                        code: "LOG_ERROR".to_string(),
+                        code: ErrorCode::LogError,
                         message: e,
                     };
                     sender.send(Message::Text(to_string(&error_msg)?)).await?;
@@ -129,10 +224,11 @@ pub async fn handle_client_message(
             }
         }
         ClientMessage::OfferTakeback(payload) => {
-            log::info!(
-                "Player {} offering takeback in room {}",
-                payload.player_id,
-                payload.room_id
+            tracing::info!(
+                event = "takeback_offer",
+                game_id = %payload.room_id,
+                player_id = %payload.player_id,
+                "player offering takeback"
             );
 
             match offer_takeback(&payload.room_id, &payload.player_id) {
@@ -141,7 +237,7 @@ pub async fn handle_client_message(
                 }
                 Err(e) => {
                     let error_msg = ServerMessage::Error {
-                        code: "TAKEBACK_OFFER_ERROR".to_string(),
+                        code: ErrorCode::TakebackOfferError,
                         message: e,
                     };
                     sender.send(Message::Text(to_string(&error_msg)?)).await?;
@@ -149,10 +245,11 @@ pub async fn handle_client_message(
             }
         }
         ClientMessage::AcceptTakeback(payload) => {
-            log::info!(
-                "Player {} accepting takeback in room {}",
-                payload.player_id,
-                payload.room_id
+            tracing::info!(
+                event = "takeback_accept",
+                game_id = %payload.room_id,
+                player_id = %payload.player_id,
+                "player accepting takeback"
             );
 
             match accept_takeback(&payload.room_id, &payload.player_id) {
@@ -161,7 +258,7 @@ pub async fn handle_client_message(
                 }
                 Err(e) => {
                     let error_msg = ServerMessage::Error {
-                        code: "TAKEBACK_ACCEPT_ERROR".to_string(),
+                        code: ErrorCode::TakebackAcceptError,
                         message: e,
                     };
                     sender.send(Message::Text(to_string(&error_msg)?)).await?;
@@ -169,10 +266,11 @@ pub async fn handle_client_message(
             }
         }
         ClientMessage::RejectTakeback(payload) => {
-            log::info!(
-                "Player {} rejecting takeback in room {}",
-                payload.player_id,
-                payload.room_id
+            tracing::info!(
+                event = "takeback_reject",
+                game_id = %payload.room_id,
+                player_id = %payload.player_id,
+                "player rejecting takeback"
             );
 
             match reject_takeback(&payload.room_id, &payload.player_id) {
@@ -181,7 +279,86 @@ pub async fn handle_client_message(
                 }
                 Err(e) => {
                     let error_msg = ServerMessage::Error {
-                        code: "TAKEBACK_REJECT_ERROR".to_string(),
+                        code: ErrorCode::TakebackRejectError,
+                        message: e,
+                    };
+                    sender.send(Message::Text(to_string(&error_msg)?)).await?;
+                }
+            }
+        }
+        ClientMessage::OfferDraw(payload) => {
+            tracing::info!(
+                event = "draw_offer",
+                game_id = %payload.room_id,
+                player_id = %payload.player_id,
+                "player offering a draw"
+            );
+
+            match offer_draw(&payload.room_id, &payload.player_id) {
+                Ok(response) => {
+                    sender.send(Message::Text(to_string(&response)?)).await?;
+                }
+                Err(e) => {
+                    let error_msg = ServerMessage::Error {
+                        code: ErrorCode::DrawOfferError,
+                        message: e,
+                    };
+                    sender.send(Message::Text(to_string(&error_msg)?)).await?;
+                }
+            }
+        }
+        ClientMessage::AcceptDraw(payload) => {
+            tracing::info!(
+                event = "draw_accept",
+                game_id = %payload.room_id,
+                player_id = %payload.player_id,
+                "player accepting a draw"
+            );
+
+            match accept_draw(&payload.room_id, &payload.player_id) {
+                Ok(response) => {
+                    sender.send(Message::Text(to_string(&response)?)).await?;
+                }
+                Err(e) => {
+                    let error_msg = ServerMessage::Error {
+                        code: ErrorCode::DrawAcceptError,
+                        message: e,
+                    };
+                    sender.send(Message::Text(to_string(&error_msg)?)).await?;
+                }
+            }
+        }
+        ClientMessage::MarkReady(payload) => {
+            tracing::info!(
+                event = "ready",
+                game_id = %payload.room_id,
+                player_id = %payload.player_id,
+                "player ready"
+            );
+
+            match mark_ready(&payload.room_id, &payload.player_id) {
+                Ok(response) => {
+                    sender.send(Message::Text(to_string(&response)?)).await?;
+                }
+                Err(e) => {
+                    let error_msg = ServerMessage::Error {
+                        code: ErrorCode::ReadyError,
+                        message: e,
+                    };
+                    sender.send(Message::Text(to_string(&error_msg)?)).await?;
+                }
+            }
+        }
+        ClientMessage::RequestFen(payload) => {
+            tracing::info!(event = "fen", game_id = %payload.room_id, "position requested");
+
+            match get_fen(&payload.room_id) {
+                Ok(response) => {
+                    sender.send(Message::Text(to_string(&response)?)).await?;
+                }
+                Err(e) => {
+                    let error_msg = ServerMessage::Error {
+                        code: ErrorCode::FenError,
                         message: e,
                     };
                     sender.send(Message::Text(to_string(&error_msg)?)).await?;
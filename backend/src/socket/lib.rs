@@ -1,5 +1,8 @@
 // Re-export modules for testing
+pub mod engine;
 pub mod game;
 pub mod handlers;
+pub mod metrics;
 pub mod models;
+pub mod variants;
 pub mod websocket;
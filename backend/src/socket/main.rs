@@ -1,6 +1,9 @@
+mod engine;
 mod game;
 mod handlers;
+mod metrics;
 mod models;
+mod variants;
 mod websocket;
 
 use std::env;
@@ -9,36 +12,61 @@ use websocket::handle_connection;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize the logger
+    // Initialize the logger; tracing events are forwarded to it
     env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
     
     // Get the address from environment or use default
     let addr = env::var("BIND_ADDRESS").unwrap_or_else(|_| "127.0.0.1:8080".to_string());
     
-    log::info!("Starting WebSocket server on {}", addr);
+    tracing::info!(addr = %addr, "starting websocket server");
     
     // Initialize the game state
     game::init_game_state();
+    if let Some(size) = env::var("ROOM_BROADCAST_BUFFER").ok().and_then(|v| v.parse().ok()) {
+        game::set_broadcast_buffer(size);
+    }
+    if let Some(secs) = env::var("DISCONNECT_GRACE_SECS").ok().and_then(|v| v.parse().ok()) {
+        game::set_disconnect_grace(std::time::Duration::from_secs(secs));
+    }
+    if let (Some(max), Some(secs)) = (
+        env::var("MAX_DISCONNECTS").ok().and_then(|v| v.parse().ok()),
+        env::var("DISCONNECT_WINDOW_SECS").ok().and_then(|v| v.parse().ok()),
+    ) {
+        game::set_disconnect_limit(max, std::time::Duration::from_secs(secs));
+    }
+    if let Some(secs) = env::var("TAKEBACK_EXPIRY_SECS").ok().and_then(|v| v.parse().ok()) {
+        game::set_takeback_expiry(std::time::Duration::from_secs(secs));
+    }
+    // Finished games are recorded when a database is configured
+    if let Ok(url) = env::var("DATABASE_URL") {
+        let db = sea_orm::Database::connect(&url).await?;
+        game::set_database(Some(std::sync::Arc::new(db)));
+    }
     
+    // Rejected move counts are shared through Redis and served by the API's /metrics
+    if let Ok(url) = env::var("REDIS_URL") {
+        game::set_metrics_redis(Some(redis::Client::open(url)?));
+    }
+
     // Create the TCP listener
     let listener = TcpListener::bind(&addr).await?;
-    log::info!("WebSocket server listening on: {}", addr);
+    tracing::info!(addr = %addr, "websocket server listening");
     
        // Accept connections
 loop {
        match listener.accept().await {
                 Ok((stream, addr)) => {
-                    log::info!("New connection from: {}", addr);
+                    tracing::info!(event = "connect", peer = %addr, "new connection");
                     
                     // Spawn a new task for each connection
                     tokio::spawn(async move {
                         if let Err(e) = handle_connection(stream, addr).await {
-                            log::error!("Error handling connection: {}", e);
+                            tracing::error!(event = "error", peer = %addr, error = %e, "failed to handle connection");
                         }
                     });
                 }
                 Err(e) => {
-                    log::error!("Failed to accept connection: {}", e);
+                    tracing::error!(event = "error", error = %e, "failed to accept connection");
                     // Continue accepting connections despite errors
                 }
             }
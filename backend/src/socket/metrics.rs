@@ -0,0 +1,41 @@
+use redis::AsyncCommands;
+
+// Redis hash the API server reads to serve the gateway's rejected move counts
+// on its /metrics endpoint; one field per reason
+pub const REJECTED_MOVES_KEY: &str = "metrics:gateway:rejected_moves";
+
+// Why a move was refused, used as the `reason` label of the rejected move counter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MoveRejection {
+    RoomNotFound,
+    NotInRoom,
+    NotStarted,
+    GameOver,
+    OutOfTurn,
+    Illegal,
+}
+
+impl MoveRejection {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MoveRejection::RoomNotFound => "room_not_found",
+            MoveRejection::NotInRoom => "not_in_room",
+            MoveRejection::NotStarted => "not_started",
+            MoveRejection::GameOver => "game_over",
+            MoveRejection::OutOfTurn => "out_of_turn",
+            MoveRejection::Illegal => "illegal",
+        }
+    }
+}
+
+// Add a move refused for `reason` to the count shared with the API server
+pub async fn export_rejected_move(client: redis::Client, reason: MoveRejection) {
+    let result = async {
+        let mut conn = client.get_multiplexed_tokio_connection().await?;
+        conn.hincr::<_, _, _, ()>(REJECTED_MOVES_KEY, reason.as_str(), 1).await
+    }
+    .await;
+    if let Err(e) = result {
+        tracing::warn!(event = "error", reason = reason.as_str(), error = %e, "failed to export rejected move");
+    }
+}
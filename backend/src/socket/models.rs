@@ -1,9 +1,20 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::SystemTime;
+use tokio::time::Instant;
+
+use chess::bitboard::board::{CastlingRights, CastlingSide, Color, Role, Square};
+use chess::bitboard::position::{Move, Position};
+
+use crate::engine::to_engine_position;
+use crate::metrics::MoveRejection;
+use crate::variants::GameVariant;
 
 // Client message types
-#[derive(Debug, Deserialize)]
+//
+// Wire format: `{"type": "<VariantName>", "payload": {...}}` with the variant
+// name in PascalCase and payload fields in snake_case, in declaration order.
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", content = "payload")]
 pub enum ClientMessage {
     JoinRoom(JoinRoomPayload),
@@ -13,54 +24,149 @@ pub enum ClientMessage {
     OfferTakeback(OfferTakebackPayload),
     AcceptTakeback(AcceptTakebackPayload),
     RejectTakeback(RejectTakebackPayload),
+    OfferDraw(OfferDrawPayload),
+    AcceptDraw(AcceptDrawPayload),
+    MarkReady(MarkReadyPayload),
+    RequestFen(RequestFenPayload),
 }
 
-#[derive(Debug, Deserialize)]
+// Every `type` a client message can have, listed back to clients that send
+// an unknown one. Keep in sync with ClientMessage
+pub const CLIENT_MESSAGE_TYPES: &[&str] = &[
+    "JoinRoom",
+    "SendMove",
+    "LeaveRoom",
+    "RequestGameLog",
+    "OfferTakeback",
+    "AcceptTakeback",
+    "RejectTakeback",
+    "OfferDraw",
+    "AcceptDraw",
+    "MarkReady",
+    "RequestFen",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub struct JoinRoomPayload {
     pub room_id: String,
     pub player_id: String,
     pub player_name: Option<String>,
+    // Variant used when this join creates the room; ignored for existing rooms
+    #[serde(default)]
+    pub variant: Option<GameVariant>,
+    // Clock settings used when this join creates the room; without them the
+    // game is untimed
+    #[serde(default)]
+    pub time_control: Option<TimeControl>,
+    // When this join creates the room, hold the game until both players
+    // send MarkReady (or the ready timeout passes)
+    #[serde(default)]
+    pub require_ready: bool,
+    // Side the player wants; a returning player keeps their previous side
+    #[serde(default)]
+    pub color: Option<PieceColor>,
+    // Takeback rules used when this join creates the room; without them the
+    // variant's defaults apply
+    #[serde(default)]
+    pub takebacks: Option<TakebackPolicy>,
+}
+
+// Starting time for each side and the time added after every move
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TimeControl {
+    pub base_ms: u64,
+    #[serde(default)]
+    pub increment_ms: u64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub struct SendMovePayload {
     pub room_id: String,
     pub player_id: String,
     pub move_notation: String,
+    // Piece a pawn reaching the last rank becomes: "q", "r", "b" or "n".
+    // Defaults to a queen when omitted
+    #[serde(default)]
+    pub promotion: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub struct LeaveRoomPayload {
     pub room_id: String,
     pub player_id: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub struct RequestGameLogPayload {
     pub room_id: String,
+    // Index of the first move to return; defaults to the start of the game
+    #[serde(default)]
+    pub from_ply: Option<usize>,
+    // Most moves to return; defaults to every move from `from_ply` on
+    #[serde(default)]
+    pub limit: Option<usize>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub struct OfferTakebackPayload {
     pub room_id: String,
     pub player_id: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub struct AcceptTakebackPayload {
     pub room_id: String,
     pub player_id: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub struct RejectTakebackPayload {
     pub room_id: String,
     pub player_id: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct OfferDrawPayload {
+    pub room_id: String,
+    pub player_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AcceptDrawPayload {
+    pub room_id: String,
+    pub player_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct MarkReadyPayload {
+    pub room_id: String,
+    pub player_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RequestFenPayload {
+    pub room_id: String,
+}
+
 // Server message types
-#[derive(Debug, Clone, Serialize)]
-#[serde(tag = "type")]
+//
+// Wire format: `{"type": "<VariantName>", ...fields}` with the variant name in
+// PascalCase and fields inlined in snake_case, in declaration order. The
+// exact shape of every variant is pinned by the model tests.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all_fields = "snake_case")]
 pub enum ServerMessage {
     RoomJoined {
         room_id: String,
@@ -78,9 +184,13 @@ pub enum ServerMessage {
         room_id: String,
         player_id: String,
     },
+    // A page of the move history: `moves` starts at ply `from_ply`,
+    // and `total_moves` is the length of the whole history
     GameLog {
         room_id: String,
         moves: Vec<MoveRecord>,
+        from_ply: usize,
+        total_moves: usize,
     },
     TakebackOffered {
         room_id: String,
@@ -95,21 +205,99 @@ pub enum ServerMessage {
         room_id: String,
         by_player_id: String,
     },
+    // The opponent did not answer `requester_id`'s takeback offer in time
+    TakebackExpired {
+        room_id: String,
+        requester_id: String,
+    },
+    DrawOffered {
+        room_id: String,
+        requester_id: String,
+    },
+    DrawAgreed {
+        room_id: String,
+        game_state: GameState,
+    },
+    // `player_id` disconnected and did not come back within the reconnect
+    // grace period, so the game was awarded to their opponent
+    GameAbandoned {
+        room_id: String,
+        player_id: String,
+        game_state: GameState,
+    },
+    // Sent when this connection fell behind the room broadcast and dropped
+    // `missed` messages; the client should resync with RequestGameLog
+    ResyncRequired {
+        room_id: String,
+        missed: u64,
+    },
+    PlayerReady {
+        room_id: String,
+        player_id: String,
+    },
+    // Both players are seated and the game (and its clocks) is under way
+    GameStarted {
+        room_id: String,
+        game_state: GameState,
+    },
+    // Time left on each clock as of `server_time_ms` (Unix epoch), sent to a
+    // connection when it joins or rejoins a timed game. `running_side` is the
+    // side whose clock is ticking, or None once the game is over
+    ClockSync {
+        room_id: String,
+        white_ms: u64,
+        black_ms: u64,
+        running_side: Option<PieceColor>,
+        server_time_ms: u64,
+    },
+    // The room's current position, sent in reply to RequestFen
+    FenState {
+        room_id: String,
+        fen: String,
+        turn: PieceColor,
+    },
     Error {
-        code: String,
+        code: ErrorCode,
         message: String,
     },
 }
 
+// Codes carried by `ServerMessage::Error`, one per client request that can
+// fail. Serialized as SCREAMING_SNAKE_CASE strings ("JOIN_ERROR"), which are
+// part of the wire format and must not change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[allow(clippy::enum_variant_names)]
+pub enum ErrorCode {
+    // The client message was not valid JSON or not a known message type
+    ParseError,
+    JoinError,
+    MoveError,
+    LeaveError,
+    // RequestGameLog failed
+    LogError,
+    TakebackOfferError,
+    TakebackAcceptError,
+    TakebackRejectError,
+    DrawOfferError,
+    DrawAcceptError,
+    // MarkReady failed
+    ReadyError,
+    // RequestFen failed
+    FenError,
+}
+
 // Game state models
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub id: String,
     pub name: String,
     pub color: Option<PieceColor>,
+    #[serde(default)]
+    pub ready: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PieceColor {
     White,
     Black,
@@ -120,15 +308,58 @@ pub struct GameState {
     pub board: HashMap<String, ChessPiece>,
     pub current_turn: PieceColor,
     pub status: GameStatus,
+    #[serde(default)]
+    pub check_counts: CheckCounts,
+    #[serde(default)]
+    pub winner: Option<PieceColor>,
+    // Set when the game ended in a draw
+    #[serde(default)]
+    pub draw_reason: Option<DrawReason>,
+    // Undo records for applied moves, most recent last; kept server-side only
+    #[serde(skip)]
+    pub history: Vec<MoveUndo>,
+    // Castling and en passant rights for the side to move, as the engine
+    // tracks them; kept server-side only
+    #[serde(skip, default = "initial_castling_rights")]
+    pub castling_rights: CastlingRights,
+    #[serde(skip)]
+    pub ep_square: Option<Square>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn initial_castling_rights() -> CastlingRights {
+    CastlingRights::ALL
+}
+
+// What a single applied move changed, so it can be taken back without replaying the game
+#[derive(Debug, Clone)]
+pub struct MoveUndo {
+    pub previous_turn: PieceColor,
+    pub previous_status: GameStatus,
+    pub previous_check_counts: CheckCounts,
+    pub previous_winner: Option<PieceColor>,
+    pub previous_draw_reason: Option<DrawReason>,
+    pub previous_castling_rights: CastlingRights,
+    pub previous_ep_square: Option<Square>,
+    // Prior contents of every square the move touched (None = was empty)
+    pub replaced_squares: Vec<(String, Option<ChessPiece>)>,
+    // Engine position the move was played from, for repetition checks
+    pub previous_position: Position,
+}
+
+// Checks given by each side, used by the Three-Check variant
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CheckCounts {
+    pub white: u8,
+    pub black: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChessPiece {
     pub piece_type: PieceType,
     pub color: PieceColor,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PieceType {
     Pawn,
     Rook,
@@ -138,13 +369,55 @@ pub enum PieceType {
     King,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ChessPiece {
+    // Letter of the piece in FEN: upper case for White, lower case for Black
+    pub fn fen_char(&self) -> char {
+        let letter = match self.piece_type {
+            PieceType::Pawn => 'p',
+            PieceType::Rook => 'r',
+            PieceType::Knight => 'n',
+            PieceType::Bishop => 'b',
+            PieceType::Queen => 'q',
+            PieceType::King => 'k',
+        };
+        match self.color {
+            PieceColor::White => letter.to_ascii_uppercase(),
+            PieceColor::Black => letter,
+        }
+    }
+}
+
+impl PieceType {
+    // Parse a promotion choice; only Q, R, B and N (either case) are allowed
+    pub fn from_promotion(choice: &str) -> Result<PieceType, String> {
+        match choice.to_ascii_lowercase().as_str() {
+            "q" => Ok(PieceType::Queen),
+            "r" => Ok(PieceType::Rook),
+            "b" => Ok(PieceType::Bishop),
+            "n" => Ok(PieceType::Knight),
+            _ => Err(format!("Invalid promotion piece: {}", choice)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GameStatus {
     Waiting,
     InProgress,
     Checkmate,
     Stalemate,
     Draw,
+    ThreeCheck,
+    Abandoned,
+}
+
+// Why a game ended in a draw
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DrawReason {
+    Agreement,
+    Stalemate,
+    ThreefoldRepetition,
+    FiftyMoveRule,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -152,10 +425,14 @@ pub struct MoveRecord {
     pub player_id: String,
     pub move_notation: String,
     pub timestamp: u64,
+    // Milliseconds the player took, measured on the server from the
+    // broadcast that handed them the turn
+    #[serde(default)]
+    pub think_ms: u64,
 }
 
 impl MoveRecord {
-    pub fn new(player_id: String, move_notation: String) -> Self {
+    pub fn new(player_id: String, move_notation: String, think_ms: u64) -> Self {
         let timestamp = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap_or_default()
@@ -165,10 +442,85 @@ impl MoveRecord {
             player_id,
             move_notation,
             timestamp,
+            think_ms,
         }
     }
 }
 
+// Remaining time on both clocks of a timed game. The side to move's clock
+// has also been running since `Room::turn_started_at`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GameClock {
+    pub white_ms: u64,
+    pub black_ms: u64,
+    pub increment_ms: u64,
+}
+
+impl GameClock {
+    pub fn new(time_control: TimeControl) -> Self {
+        Self {
+            white_ms: time_control.base_ms,
+            black_ms: time_control.base_ms,
+            increment_ms: time_control.increment_ms,
+        }
+    }
+
+    // Charge `color` for `think_ms` and credit the increment
+    pub fn record_move(&mut self, color: &PieceColor, think_ms: u64) {
+        let remaining = match color {
+            PieceColor::White => &mut self.white_ms,
+            PieceColor::Black => &mut self.black_ms,
+        };
+        *remaining = remaining.saturating_sub(think_ms).saturating_add(self.increment_ms);
+    }
+}
+
+// Fewest half-moves a takeback can roll back: one full move
+pub const MIN_TAKEBACK_MOVES: usize = 2;
+
+// Whether a room allows takebacks, and how many half-moves have to be played
+// before one may be offered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TakebackPolicy {
+    pub allowed: bool,
+    #[serde(default = "default_min_takeback_moves")]
+    pub min_moves: usize,
+}
+
+fn default_min_takeback_moves() -> usize {
+    MIN_TAKEBACK_MOVES
+}
+
+impl TakebackPolicy {
+    pub fn disabled() -> Self {
+        Self { allowed: false, min_moves: MIN_TAKEBACK_MOVES }
+    }
+}
+
+impl Default for TakebackPolicy {
+    fn default() -> Self {
+        Self { allowed: true, min_moves: MIN_TAKEBACK_MOVES }
+    }
+}
+
+// Players a room seats
+pub const ROOM_CAPACITY: usize = 2;
+
+// A player sending their last move again within this many seconds is
+// retrying, not moving twice
+pub const DUPLICATE_MOVE_WINDOW_SECS: u64 = 5;
+
+// How a room is set up by the join that creates it
+#[derive(Debug, Clone, Default)]
+pub struct RoomOptions {
+    pub variant: GameVariant,
+    pub time_control: Option<TimeControl>,
+    pub require_ready: bool,
+    // None keeps the variant's takeback rules
+    pub takebacks: Option<TakebackPolicy>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Room {
     pub id: String,
@@ -176,21 +528,76 @@ pub struct Room {
     pub game_state: Option<GameState>,
     pub moves: Vec<MoveRecord>,
     pub pending_takeback: Option<String>,
+    // Token of the expiry timer of the pending takeback offer
+    #[serde(skip)]
+    pub takeback_token: Option<u64>,
+    #[serde(default)]
+    pub pending_draw: Option<String>,
+    #[serde(default)]
+    pub variant: GameVariant,
+    // Players whose connection dropped, with the token of their pending
+    // forfeit timer; rejoining before it fires takes the seat back
+    #[serde(skip)]
+    pub disconnected: HashMap<String, u64>,
+    // When each player's connection dropped recently, oldest first; used to
+    // tell a flaky connection from a player stalling the game
+    #[serde(skip)]
+    pub recent_disconnects: HashMap<String, Vec<Instant>>,
+    // Side each player was seated on, so one who leaves and comes back
+    // keeps their color
+    #[serde(skip)]
+    pub seat_colors: HashMap<String, PieceColor>,
+    // When the side to move got the turn; None until the game starts
+    #[serde(skip)]
+    pub turn_started_at: Option<Instant>,
+    // None for untimed games
+    #[serde(default)]
+    pub clock: Option<GameClock>,
+    // Whether the game waits for both players to be ready before starting
+    #[serde(default)]
+    pub require_ready: bool,
+    // Token of the pending auto-start timer while waiting for players to be ready
+    #[serde(skip)]
+    pub auto_start_token: Option<u64>,
+    #[serde(default)]
+    pub takebacks: TakebackPolicy,
+    // Moves refused in this room, by reason; dropped with the room
+    #[serde(skip)]
+    pub rejected_moves: BTreeMap<MoveRejection, u64>,
 }
 
 impl Room {
     pub fn new(id: String) -> Self {
+        Self::with_variant(id, GameVariant::Standard)
+    }
+
+    pub fn with_variant(id: String, variant: GameVariant) -> Self {
         Self {
             id,
             players: Vec::new(),
             game_state: None,
             moves: Vec::new(),
             pending_takeback: None,
+            takeback_token: None,
+            pending_draw: None,
+            variant,
+            disconnected: HashMap::new(),
+            recent_disconnects: HashMap::new(),
+            seat_colors: HashMap::new(),
+            turn_started_at: None,
+            clock: None,
+            require_ready: false,
+            auto_start_token: None,
+            takebacks: variant.takeback_policy(),
+            rejected_moves: BTreeMap::new(),
         }
     }
     
+    // Seat a player. A returning player gets their previous side back; anyone
+    // else gets the `color` set on the player if they asked for one, or the
+    // free side, White first. Asking for a taken side is rejected
     pub fn add_player(&mut self, player: Player) -> Result<(), String> {
-        if self.players.len() >= 2 {
+        if self.players.len() >= ROOM_CAPACITY {
             return Err("Room is full".to_string());
         }
         
@@ -199,31 +606,124 @@ impl Room {
             return Err("Player is already in the room".to_string());
         }
         
-        // Assign color if this is the first or second player
+        let taken = |color: &PieceColor| self.players.iter().any(|p| p.color.as_ref() == Some(color));
+        let color = match self.seat_colors.get(&player.id).cloned().or_else(|| player.color.clone()) {
+            Some(color) if taken(&color) => return Err("Color is already taken".to_string()),
+            Some(color) => color,
+            None => [PieceColor::White, PieceColor::Black]
+                .into_iter()
+                .find(|color| !taken(color))
+                .ok_or_else(|| "Room is full".to_string())?,
+        };
+        self.seat_colors.insert(player.id.clone(), color.clone());
+
         let mut player = player;
-        if self.players.is_empty() {
-            player.color = Some(PieceColor::White);
-        } else if self.players.len() == 1 {
-            player.color = Some(PieceColor::Black);
-            
-            // Initialize game state when second player joins
-            self.game_state = Some(GameState::new_game());
+        player.color = Some(color);
+        // Initialize game state when second player joins, unless it has to
+        // wait for both players to be ready. A player rejoining a running
+        // game carries on with it
+        let in_progress = self
+            .game_state
+            .as_ref()
+            .is_some_and(|game_state| game_state.status == GameStatus::InProgress);
+        if self.players.len() == 1 && !self.require_ready && !in_progress {
+            self.start_game();
         }
         
         self.players.push(player);
         Ok(())
     }
     
+    // Set up the board and start the side to move's clock
+    pub fn start_game(&mut self) {
+        self.game_state = Some(GameState::new_game());
+        self.turn_started_at = Some(Instant::now());
+    }
+
+    // Both seats are filled and both players said they are ready
+    pub fn all_ready(&self) -> bool {
+        self.players.len() == ROOM_CAPACITY && self.players.iter().all(|p| p.ready)
+    }
+
     pub fn remove_player(&mut self, player_id: &str) -> bool {
         let initial_len = self.players.len();
         self.players.retain(|p| p.id != player_id);
         initial_len != self.players.len()
     }
     
+    // Whether `move_notation` by `player_id` repeats the last recorded move
+    // within `DUPLICATE_MOVE_WINDOW_SECS`, i.e. is a resend of it
+    pub fn is_duplicate_move(&self, player_id: &str, move_notation: &str) -> bool {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.moves.last().is_some_and(|last| {
+            last.player_id == player_id
+                && last.move_notation == move_notation
+                && now.saturating_sub(last.timestamp) <= DUPLICATE_MOVE_WINDOW_SECS
+        })
+    }
+
+    // Color `player_id` plays, if they are seated
+    pub fn player_color(&self, player_id: &str) -> Option<PieceColor> {
+        self.players
+            .iter()
+            .find(|p| p.id == player_id)
+            .and_then(|p| p.color.clone())
+    }
+
     pub fn add_move(&mut self, player_id: String, move_notation: String) {
-        let move_record = MoveRecord::new(player_id, move_notation);
+        let now = Instant::now();
+        let think_ms = self
+            .turn_started_at
+            .map_or(0, |started| now.duration_since(started).as_millis() as u64);
+        self.turn_started_at = Some(now);
+
+        // The clock that was running is the side to move before this move was applied
+        let mover = self
+            .game_state
+            .as_ref()
+            .and_then(|game_state| game_state.history.last())
+            .map(|undo| undo.previous_turn.clone());
+        if let (Some(clock), Some(mover)) = (self.clock.as_mut(), mover) {
+            clock.record_move(&mover, think_ms);
+        }
+
+        let move_record = MoveRecord::new(player_id, move_notation, think_ms);
         self.moves.push(move_record);
     }
+
+    // Both clocks as of now, or None for an untimed game
+    pub fn clock_sync(&self) -> Option<ServerMessage> {
+        let clock = self.clock?;
+        let running_side = self
+            .game_state
+            .as_ref()
+            .filter(|game_state| game_state.status == GameStatus::InProgress)
+            .map(|game_state| game_state.current_turn.clone());
+        let elapsed_ms = self
+            .turn_started_at
+            .map_or(0, |started| started.elapsed().as_millis() as u64);
+
+        let (mut white_ms, mut black_ms) = (clock.white_ms, clock.black_ms);
+        match running_side {
+            Some(PieceColor::White) => white_ms = white_ms.saturating_sub(elapsed_ms),
+            Some(PieceColor::Black) => black_ms = black_ms.saturating_sub(elapsed_ms),
+            None => {}
+        }
+
+        Some(ServerMessage::ClockSync {
+            room_id: self.id.clone(),
+            white_ms,
+            black_ms,
+            running_side,
+            server_time_ms: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        })
+    }
 }
 
 impl GameState {
@@ -256,14 +756,61 @@ impl GameState {
             board,
             current_turn: PieceColor::White,
             status: GameStatus::InProgress,
+            check_counts: CheckCounts::default(),
+            winner: None,
+            draw_reason: None,
+            history: Vec::new(),
+            castling_rights: CastlingRights::ALL,
+            ep_square: None,
         }
     }
     
     // Apply a move to the game state
-    // This is a simplified implementation that doesn't validate chess rules
     pub fn apply_move(&mut self, move_notation: &str) -> Result<(), String> {
-        // In a real implementation, this would parse the move notation and update the board
-        // For now, we'll just toggle the current turn
+        self.apply_move_with_promotion(move_notation, None)
+    }
+
+    // Apply a move, promoting the pawn if it reaches the last rank. The
+    // promotion piece defaults to a queen and is rejected on any other move
+    pub fn apply_move_with_promotion(
+        &mut self,
+        move_notation: &str,
+        promotion: Option<PieceType>,
+    ) -> Result<(), String> {
+        let promotion_squares = self.promotion_squares(move_notation);
+        if promotion_squares.is_none() && promotion.is_some() {
+            return Err("Promotion is only allowed for a pawn reaching the last rank".to_string());
+        }
+
+        // Only moves the engine generates for this position are played
+        let promotion = promotion_squares.as_ref().map(|_| promotion.unwrap_or(PieceType::Queen));
+        let engine_move = move_notation.get(0..4).and_then(Move::from_uci).map(|mv| Move {
+            promotion: promotion.clone().map(Role::from),
+            ..mv
+        });
+        let position = to_engine_position(self)?;
+        let after = engine_move
+            .and_then(|mv| position.play(mv))
+            .ok_or_else(|| format!("Illegal move: {}", move_notation))?;
+
+        let replaced_squares = match (promotion_squares, promotion) {
+            (Some((orig, dest)), Some(piece_type)) => self.promote(&orig, &dest, piece_type)?,
+            _ => self.move_piece(move_notation),
+        };
+
+        self.history.push(MoveUndo {
+            previous_turn: self.current_turn.clone(),
+            previous_status: self.status.clone(),
+            previous_check_counts: self.check_counts,
+            previous_winner: self.winner.clone(),
+            previous_draw_reason: self.draw_reason.clone(),
+            previous_castling_rights: self.castling_rights,
+            previous_ep_square: self.ep_square,
+            replaced_squares,
+            previous_position: position,
+        });
+        self.castling_rights = after.castling_rights;
+        self.ep_square = after.ep_square;
         
         self.current_turn = match self.current_turn {
             PieceColor::White => PieceColor::Black,
@@ -272,4 +819,198 @@ impl GameState {
         
         Ok(())
     }
+
+    // Origin and destination of `move_notation` (e.g. "e7e8") if it moves a
+    // pawn of the side to move onto its last rank
+    fn promotion_squares(&self, move_notation: &str) -> Option<(String, String)> {
+        let orig = move_notation.get(0..2)?;
+        let dest = move_notation.get(2..4)?;
+        let last_rank = match self.current_turn {
+            PieceColor::White => '8',
+            PieceColor::Black => '1',
+        };
+        let piece = self.board.get(orig)?;
+        let on_board = dest.starts_with(|c: char| ('a'..='h').contains(&c));
+        if piece.piece_type == PieceType::Pawn
+            && piece.color == self.current_turn
+            && on_board
+            && dest.ends_with(last_rank)
+        {
+            Some((orig.to_string(), dest.to_string()))
+        } else {
+            None
+        }
+    }
+
+    // Move the piece of the side to move from the origin to the destination
+    // of `move_notation` (e.g. "e2e4"), taking whatever stood there. Castling
+    // brings the rook along and en passant removes the passed pawn. Returns
+    // the prior contents of the touched squares, origin first then
+    // destination; notation the board cannot follow leaves it unchanged and
+    // returns nothing
+    fn move_piece(&mut self, move_notation: &str) -> Vec<(String, Option<ChessPiece>)> {
+        let (Some(orig), Some(dest)) = (move_notation.get(0..2), move_notation.get(2..4)) else {
+            return Vec::new();
+        };
+        if !is_square(orig) || !is_square(dest) || orig == dest {
+            return Vec::new();
+        }
+        let piece = match self.board.get(orig) {
+            Some(piece) if piece.color == self.current_turn => piece.clone(),
+            _ => return Vec::new(),
+        };
+        let (orig_file, orig_rank) = (orig.as_bytes()[0], orig.as_bytes()[1]);
+        let dest_file = dest.as_bytes()[0];
+
+        // A pawn moving diagonally onto an empty square captures en passant
+        let passed_pawn = (piece.piece_type == PieceType::Pawn
+            && orig_file != dest_file
+            && !self.board.contains_key(dest))
+        .then(|| format!("{}{}", dest_file as char, orig_rank as char));
+        // A king moving two files castles with the rook on that side
+        let castling_rook = (piece.piece_type == PieceType::King && orig_file.abs_diff(dest_file) == 2)
+            .then(|| {
+                let (rook_file, rook_dest_file) = if dest_file > orig_file { ('h', 'f') } else { ('a', 'd') };
+                (
+                    format!("{}{}", rook_file, orig_rank as char),
+                    format!("{}{}", rook_dest_file, orig_rank as char),
+                )
+            });
+
+        let mut replaced = vec![(orig.to_string(), self.board.remove(orig))];
+        replaced.push((dest.to_string(), self.board.insert(dest.to_string(), piece)));
+        if let Some(square) = passed_pawn {
+            let taken = self.board.remove(&square);
+            replaced.push((square, taken));
+        }
+        if let Some((rook_orig, rook_dest)) = castling_rook {
+            if let Some(rook) = self.board.remove(&rook_orig) {
+                replaced.push((rook_orig, Some(rook.clone())));
+                replaced.push((rook_dest.clone(), self.board.insert(rook_dest, rook)));
+            }
+        }
+
+        replaced
+    }
+
+    // Forsyth-Edwards Notation of the position. Castling rights, the en
+    // passant square and the move counters are worked out from the history,
+    // which starts at the initial position with White to move
+    pub fn to_fen(&self) -> String {
+        let mut placement = String::new();
+        for rank in (1..=8).rev() {
+            let mut empty = 0;
+            for file in 'a'..='h' {
+                match self.board.get(&format!("{}{}", file, rank)) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push(piece.fen_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank > 1 {
+                placement.push('/');
+            }
+        }
+
+        let turn = match self.current_turn {
+            PieceColor::White => "w",
+            PieceColor::Black => "b",
+        };
+
+        let castling: String = [
+            ('K', Color::White, CastlingSide::KingSide),
+            ('Q', Color::White, CastlingSide::QueenSide),
+            ('k', Color::Black, CastlingSide::KingSide),
+            ('q', Color::Black, CastlingSide::QueenSide),
+        ]
+        .into_iter()
+        .filter(|(_, color, side)| self.castling_rights.has(*color, *side))
+        .map(|(right, _, _)| right)
+        .collect();
+        let castling = if castling.is_empty() { "-".to_string() } else { castling };
+        let en_passant = self.ep_square.map_or("-".to_string(), |square| square.name());
+
+        // Moves since the last capture or pawn move
+        let halfmove_clock = self
+            .history
+            .iter()
+            .rev()
+            .take_while(|undo| match undo.replaced_squares.split_first() {
+                Some(((_, Some(mover)), rest)) => {
+                    mover.piece_type != PieceType::Pawn
+                        && !rest
+                            .iter()
+                            .any(|(_, piece)| piece.as_ref().is_some_and(|p| p.color != mover.color))
+                }
+                _ => true,
+            })
+            .count();
+        let fullmove_number = 1 + self.history.len() / 2;
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement, turn, castling, en_passant, halfmove_clock, fullmove_number
+        )
+    }
+
+    // Replace the pawn on `orig` with `piece_type` on `dest`, returning the
+    // prior contents of both squares for undo
+    pub fn promote(
+        &mut self,
+        orig: &str,
+        dest: &str,
+        piece_type: PieceType,
+    ) -> Result<Vec<(String, Option<ChessPiece>)>, String> {
+        if matches!(piece_type, PieceType::Pawn | PieceType::King) {
+            return Err(format!("Cannot promote to {:?}", piece_type));
+        }
+        let pawn = self
+            .board
+            .remove(orig)
+            .ok_or_else(|| format!("No piece on {}", orig))?;
+        let replaced = self.board.insert(
+            dest.to_string(),
+            ChessPiece { piece_type, color: pawn.color.clone() },
+        );
+
+        Ok(vec![(orig.to_string(), Some(pawn)), (dest.to_string(), replaced)])
+    }
+
+    // Undo the most recently applied move in O(1) using its recorded undo information
+    pub fn undo_move(&mut self) -> Result<(), String> {
+        let undo = self.history.pop().ok_or_else(|| "No moves to undo".to_string())?;
+
+        for (square, piece) in undo.replaced_squares {
+            match piece {
+                Some(piece) => {
+                    self.board.insert(square, piece);
+                }
+                None => {
+                    self.board.remove(&square);
+                }
+            }
+        }
+        self.current_turn = undo.previous_turn;
+        self.status = undo.previous_status;
+        self.check_counts = undo.previous_check_counts;
+        self.winner = undo.previous_winner;
+        self.draw_reason = undo.previous_draw_reason;
+        self.castling_rights = undo.previous_castling_rights;
+        self.ep_square = undo.previous_ep_square;
+
+        Ok(())
+    }
+}
+
+// Whether `square` names a square of the board, e.g. "e4"
+fn is_square(square: &str) -> bool {
+    matches!(square.as_bytes(), [b'a'..=b'h', b'1'..=b'8'])
 }
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+use crate::engine::to_engine_position;
+use crate::models::{GameState, GameStatus, PieceColor, TakebackPolicy};
+
+// Number of checks that wins a Three-Check game
+pub const THREE_CHECK_LIMIT: u8 = 3;
+
+// Game variants, mirroring the `GameVariant` enum stored in the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GameVariant {
+    #[default]
+    Standard,
+    Chess960,
+    ThreeCheck,
+    Blitz,
+    Rapid,
+    Classical,
+    Bullet,
+}
+
+impl GameVariant {
+    // Rules applied to moves in a room of this variant. Time-based variants
+    // (Bullet/Blitz/Rapid/Classical) and Chess960 play with standard move rules.
+    pub fn rules(&self) -> &'static dyn VariantRules {
+        match self {
+            GameVariant::ThreeCheck => &ThreeCheckRules,
+            GameVariant::Standard
+            | GameVariant::Chess960
+            | GameVariant::Bullet
+            | GameVariant::Blitz
+            | GameVariant::Rapid
+            | GameVariant::Classical => &StandardRules,
+        }
+    }
+
+    // Takeback rules a room of this variant starts with. Bullet and blitz are
+    // too fast for takebacks to be fair, so they are off unless the room
+    // enables them
+    pub fn takeback_policy(&self) -> TakebackPolicy {
+        match self {
+            GameVariant::Bullet | GameVariant::Blitz => TakebackPolicy::disabled(),
+            GameVariant::Standard
+            | GameVariant::Chess960
+            | GameVariant::ThreeCheck
+            | GameVariant::Rapid
+            | GameVariant::Classical => TakebackPolicy::default(),
+        }
+    }
+}
+
+// Variant-specific rules evaluated after a move has been applied to the game state
+pub trait VariantRules: Send + Sync {
+    // Update variant state for a move by `mover` and end the game if the variant's win condition is met
+    fn after_move(&self, state: &mut GameState, mover: &PieceColor);
+}
+
+// Standard chess: no extra win conditions beyond the normal rules
+pub struct StandardRules;
+
+impl VariantRules for StandardRules {
+    fn after_move(&self, _state: &mut GameState, _mover: &PieceColor) {}
+}
+
+// Three-Check: a player also wins by giving check three times
+pub struct ThreeCheckRules;
+
+impl VariantRules for ThreeCheckRules {
+    fn after_move(&self, state: &mut GameState, mover: &PieceColor) {
+        if !gives_check(state) {
+            return;
+        }
+
+        let checks = match mover {
+            PieceColor::White => &mut state.check_counts.white,
+            PieceColor::Black => &mut state.check_counts.black,
+        };
+        *checks += 1;
+
+        if *checks >= THREE_CHECK_LIMIT {
+            state.status = GameStatus::ThreeCheck;
+            state.winner = Some(mover.clone());
+        }
+    }
+}
+
+// Whether the move just applied to `state` left the side to move in check.
+// Worked out from the server's board; a "+" or "#" sent by the client is ignored
+pub fn gives_check(state: &GameState) -> bool {
+    to_engine_position(state).is_ok_and(|position| position.is_check())
+}
@@ -2,9 +2,11 @@ use futures_util::{SinkExt, StreamExt};
 use std::net::SocketAddr;
 use tokio::net::TcpStream;
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::TryRecvError;
 use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
 
-use crate::handlers::handle_client_message;
+use crate::game::player_disconnected;
+use crate::handlers::{handle_client_message, RoomSubscription};
 use crate::models::ServerMessage;
 
 // Handle a WebSocket connection
@@ -14,14 +16,14 @@ pub async fn handle_connection(
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Accept the WebSocket connection
     let ws_stream = accept_async(stream).await?;
-    log::info!("WebSocket connection established with: {}", addr);
+    tracing::info!(event = "connect", peer = %addr, "websocket connection established");
 
     // Split the WebSocket stream
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
 
     // Keep track of room subscriptions
-    let mut room_senders: Vec<(String, broadcast::Sender<ServerMessage>)> = Vec::new();
-    let mut room_receivers = Vec::new();
+    let mut room_senders: Vec<RoomSubscription> = Vec::new();
+    let mut room_receivers: Vec<(String, broadcast::Receiver<ServerMessage>)> = Vec::new();
 
     // Main connection loop
     loop {
@@ -33,17 +35,17 @@ pub async fn handle_connection(
                         match msg {
                             Message::Text(text) => {
                                 if let Err(e) = handle_client_message(&text, &mut ws_sender, &mut room_senders).await {
-                                    log::error!("Error handling client message: {}", e);
+                                    tracing::error!(event = "error", peer = %addr, error = %e, "failed to handle client message");
                                     break;
                                 }
                             }
                             Message::Close(_) => {
-                                log::info!("Client {} disconnected", addr);
+                                tracing::info!(event = "disconnect", peer = %addr, "client disconnected");
                                 break;
                             }
                             Message::Ping(data) => {
                                 if let Err(e) = ws_sender.send(Message::Pong(data)).await {
-                                    log::error!("Error sending pong: {}", e);
+                                    tracing::error!(event = "error", peer = %addr, error = %e, "failed to send pong");
                                     break;
                                 }
                             }
@@ -51,11 +53,11 @@ pub async fn handle_connection(
                         }
                     }
                     Some(Err(e)) => {
-                        log::error!("WebSocket error: {}", e);
+                        tracing::error!(event = "error", peer = %addr, error = %e, "websocket error");
                         break;
                     }
                     None => {
-                        log::info!("Client {} disconnected", addr);
+                        tracing::info!(event = "disconnect", peer = %addr, "client disconnected");
                         break;
                     }
                 }
@@ -66,17 +68,17 @@ pub async fn handle_connection(
                // Rebuild receivers when room_senders changes
 if room_receivers.len() != room_senders.len() {
         room_receivers.clear();
-        for (_, sender) in &room_senders {
-            room_receivers.push(sender.subscribe());
+        for subscription in &room_senders {
+            room_receivers.push((subscription.room_id.clone(), subscription.sender.subscribe()));
         }
     }
 
                 // Check for messages from each room
-                for (i, receiver) in room_receivers.iter_mut().enumerate() {
-                    if let Ok(msg) = receiver.try_recv() {
+                for (room_id, receiver) in room_receivers.iter_mut() {
+                    if let Some(msg) = next_room_message(room_id, receiver) {
                         if let Ok(json) = serde_json::to_string(&msg) {
                             if let Err(e) = ws_sender.send(Message::Text(json)).await {
-                                log::error!("Error forwarding room message: {}", e);
+                                tracing::error!(event = "error", peer = %addr, game_id = %room_id, error = %e, "failed to forward room message");
                                 return;
                             }
                         }
@@ -92,5 +94,29 @@ if room_receivers.len() != room_senders.len() {
         }
     }
 
+    // Hold the seats this connection had until the reconnect grace runs out
+    for subscription in &room_senders {
+        player_disconnected(&subscription.room_id, &subscription.player_id);
+    }
+
     Ok(())
 }
+
+// Take the next pending broadcast for a room. If the receiver lagged behind
+// the channel buffer, the dropped messages are replaced by a resync prompt.
+pub fn next_room_message(
+    room_id: &str,
+    receiver: &mut broadcast::Receiver<ServerMessage>,
+) -> Option<ServerMessage> {
+    match receiver.try_recv() {
+        Ok(msg) => Some(msg),
+        Err(TryRecvError::Lagged(missed)) => {
+            tracing::warn!(event = "lagged", game_id = %room_id, missed, "connection fell behind room broadcasts");
+            Some(ServerMessage::ResyncRequired {
+                room_id: room_id.to_string(),
+                missed,
+            })
+        }
+        Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => None,
+    }
+}
@@ -38,21 +38,26 @@ assert!(duration.as_millis() < 1000, "Room creation took {}ms", duration.as_mill
     fn benchmark_move_processing() {
         setup();
         
-        // Set up a room with 2 players
-        let _ = join_room("bench-moves", "player-1", Some("Alice".to_string()));
-        let _ = join_room("bench-moves", "player-2", Some("Bob".to_string()));
+        // A short opening replayed in 100 rooms, as one game cannot run for
+        // 1000 moves without being drawn by repetition
+        let opening = ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "d2d3", "f8c5", "b1c3", "d7d6"];
+        let rooms: Vec<String> = (0..1000 / opening.len())
+            .map(|i| format!("bench-moves-{}", i))
+            .collect();
+        for room_id in &rooms {
+            let _ = join_room(room_id, "player-1", Some("Alice".to_string()));
+            let _ = join_room(room_id, "player-2", Some("Bob".to_string()));
+        }
         
         let start = Instant::now();
         
-       // Example valid chess moves
-let valid_moves = ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6"];
-
-for i in 0..1000 {
-    let player_id = if i % 2 == 0 { "player-1" } else { "player-2" };
-    let move_notation = valid_moves[i % valid_moves.len()];
-    send_move("bench-moves", player_id, move_notation)
-        .expect("Move should be processed successfully");
-}
+        for room_id in &rooms {
+            for (i, move_notation) in opening.iter().enumerate() {
+                let player_id = if i % 2 == 0 { "player-1" } else { "player-2" };
+                send_move(room_id, player_id, move_notation)
+                    .expect("Move should be processed successfully");
+            }
+        }
         
         let duration = start.elapsed();
         println!("Processed 1000 moves in {:?}", duration);
@@ -74,10 +79,10 @@ for i in 0..1000 {
                     let _ = join_room(&room_id, "player-1", Some("Alice".to_string()));
                     let _ = join_room(&room_id, "player-2", Some("Bob".to_string()));
                     
+                    let knight_moves = ["g1f3", "g8f6", "f3g1", "f6g8"];
                     for j in 0..10 {
                         let player_id = if j % 2 == 0 { "player-1" } else { "player-2" };
-                        let move_notation = format!("move-{}", j);
-                        let _ = send_move(&room_id, player_id, &move_notation);
+                        let _ = send_move(&room_id, player_id, knight_moves[j % knight_moves.len()]);
                     }
                 })
             })
@@ -1,8 +1,13 @@
-use chess_websocket_gateway::game::{get_game_log, join_room, leave_room, send_move, init_game_state};
+use chess_websocket_gateway::game::{
+    accept_draw, accept_takeback, get_fen, get_game_log, get_game_log_page, join_room, leave_room, offer_draw,
+    offer_takeback, send_move, send_move_with_promotion, init_game_state, join_room_with_options,
+};
 use chess_websocket_gateway::models::{
     ClientMessage, ServerMessage, JoinRoomPayload, SendMovePayload, 
-    LeaveRoomPayload, RequestGameLogPayload, GameStatus, PieceColor
+    LeaveRoomPayload, RequestGameLogPayload, GameStatus, PieceColor, DrawReason, RoomOptions,
+    TakebackPolicy,
 };
+use chess_websocket_gateway::variants::GameVariant;
 use serde_json::{from_str, to_string};
 use std::sync::Once;
 
@@ -17,6 +22,9 @@ fn setup() {
 #[cfg(test)]
 mod game_tests {
     use super::*;
+    use chess_websocket_gateway::game::{get_room_sender, DEFAULT_TAKEBACK_EXPIRY};
+    use std::time::Duration;
+    use tokio::sync::broadcast::error::TryRecvError;
 
     #[test]
     fn test_join_room_success() {
@@ -146,6 +154,131 @@ mod game_tests {
         assert_eq!(result.unwrap_err(), "Game not started");
     }
 
+    #[test]
+    fn test_send_move_rejects_out_of_turn_and_illegal_moves() {
+        setup();
+
+        let _ = join_room("test-room-illegal", "player-1", Some("Alice".to_string()));
+        let _ = join_room("test-room-illegal", "player-2", Some("Bob".to_string()));
+
+        assert_eq!(send_move("test-room-illegal", "player-2", "e7e5").unwrap_err(), "Not your turn");
+        // Nothing stands on e3, and "e4" is not a coordinate move
+        assert_eq!(send_move("test-room-illegal", "player-1", "e3e4").unwrap_err(), "Illegal move: e3e4");
+        assert_eq!(send_move("test-room-illegal", "player-1", "e4").unwrap_err(), "Illegal move: e4");
+
+        match get_game_log("test-room-illegal") {
+            Ok(ServerMessage::GameLog { moves, .. }) => assert!(moves.is_empty()),
+            other => panic!("Expected GameLog, got {:?}", other),
+        }
+        assert!(send_move("test-room-illegal", "player-1", "e2e4").is_ok());
+    }
+
+    #[test]
+    fn test_send_move_rejects_move_the_piece_cannot_make() {
+        setup();
+
+        let _ = join_room("test-room-impossible", "player-1", Some("Alice".to_string()));
+        let _ = join_room("test-room-impossible", "player-2", Some("Bob".to_string()));
+
+        // A pawn cannot advance three squares
+        assert_eq!(
+            send_move("test-room-impossible", "player-1", "e2e5").unwrap_err(),
+            "Illegal move: e2e5"
+        );
+
+        match get_game_log("test-room-impossible") {
+            Ok(ServerMessage::GameLog { moves, .. }) => assert!(moves.is_empty()),
+            other => panic!("Expected GameLog, got {:?}", other),
+        }
+        // The board is untouched and White is still to move
+        match get_fen("test-room-impossible").unwrap() {
+            ServerMessage::FenState { fen, .. } => {
+                assert_eq!(fen, "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+            }
+            other => panic!("Expected FenState, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rejected_moves_are_counted_by_reason() {
+        use chess_websocket_gateway::game::rejected_move_count;
+        use chess_websocket_gateway::metrics::MoveRejection;
+
+        setup();
+
+        let _ = join_room("test-room-metrics", "player-1", Some("Alice".to_string()));
+        let _ = join_room("test-room-metrics", "player-2", Some("Bob".to_string()));
+
+        assert!(send_move("test-room-metrics", "player-2", "e7e5").is_err());
+        assert_eq!(rejected_move_count("test-room-metrics", MoveRejection::OutOfTurn), 1);
+        assert_eq!(rejected_move_count("test-room-metrics", MoveRejection::Illegal), 0);
+
+        assert!(send_move("test-room-metrics", "player-1", "e3e4").is_err());
+        assert_eq!(rejected_move_count("test-room-metrics", MoveRejection::Illegal), 1);
+
+        // Accepted moves are not counted
+        assert!(send_move("test-room-metrics", "player-1", "e2e4").is_ok());
+        assert_eq!(rejected_move_count("test-room-metrics", MoveRejection::OutOfTurn), 1);
+        assert_eq!(rejected_move_count("test-room-metrics", MoveRejection::Illegal), 1);
+    }
+
+    #[test]
+    fn test_rejected_move_counts_go_away_with_the_room() {
+        use chess_websocket_gateway::game::rejected_move_count;
+        use chess_websocket_gateway::metrics::MoveRejection;
+
+        setup();
+
+        let _ = join_room("test-room-metrics-cleanup", "player-1", Some("Alice".to_string()));
+        assert!(send_move("test-room-metrics-cleanup", "player-2", "e2e4").is_err());
+        assert_eq!(rejected_move_count("test-room-metrics-cleanup", MoveRejection::NotInRoom), 1);
+
+        // Moves sent to rooms that do not exist leave nothing behind
+        assert!(send_move("test-room-metrics-missing", "player-1", "e2e4").is_err());
+        assert_eq!(rejected_move_count("test-room-metrics-missing", MoveRejection::RoomNotFound), 0);
+
+        assert!(leave_room("test-room-metrics-cleanup", "player-1").is_ok());
+        assert_eq!(rejected_move_count("test-room-metrics-cleanup", MoveRejection::NotInRoom), 0);
+    }
+
+    #[test]
+    fn test_resent_move_is_not_recorded_twice() {
+        setup();
+
+        let _ = join_room("test-room-retry", "player-1", Some("Alice".to_string()));
+        let _ = join_room("test-room-retry", "player-2", Some("Bob".to_string()));
+
+        let first = send_move("test-room-retry", "player-1", "e2e4").unwrap();
+        let retry = send_move("test-room-retry", "player-1", "e2e4").unwrap();
+        match (first, retry) {
+            (
+                ServerMessage::MoveMade { game_state: first, .. },
+                ServerMessage::MoveMade { game_state: retry, .. },
+            ) => {
+                assert_eq!(retry.board, first.board);
+                assert_eq!(retry.current_turn, PieceColor::Black);
+            }
+            other => panic!("Expected two MoveMade messages, got {:?}", other),
+        }
+
+        match get_game_log("test-room-retry") {
+            Ok(ServerMessage::GameLog { moves, .. }) => assert_eq!(moves.len(), 1),
+            other => panic!("Expected GameLog, got {:?}", other),
+        }
+        assert!(send_move("test-room-retry", "player-2", "e7e5").is_ok());
+    }
+
+    #[test]
+    fn test_send_move_rejects_king_promotion() {
+        setup();
+
+        let _ = join_room("test-room-promotion", "player-1", Some("Alice".to_string()));
+        let _ = join_room("test-room-promotion", "player-2", Some("Bob".to_string()));
+
+        let result = send_move_with_promotion("test-room-promotion", "player-1", "e7e8", Some("k"));
+        assert_eq!(result.unwrap_err(), "Invalid promotion piece: k");
+    }
+
     #[test]
     fn test_leave_room_success() {
         setup();
@@ -201,7 +334,8 @@ mod game_tests {
         let result = get_game_log("test-room-10");
         assert!(result.is_ok());
         
-        if let Ok(ServerMessage::GameLog { room_id, moves }) = result {
+        if let Ok(ServerMessage::GameLog { room_id, moves, from_ply, total_moves }) = result {
+            assert_eq!((from_ply, total_moves), (0, 2));
             assert_eq!(room_id, "test-room-10");
             assert_eq!(moves.len(), 2);
             assert_eq!(moves[0].player_id, "player-1");
@@ -221,11 +355,250 @@ mod game_tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), "Room not found");
     }
+
+    #[test]
+    fn test_request_fen_after_moves() {
+        setup();
+
+        join_room("test-room-fen", "player-1", Some("Alice".to_string())).unwrap();
+        assert_eq!(get_fen("test-room-fen").unwrap_err(), "Game not started");
+        assert_eq!(get_fen("missing-fen-room").unwrap_err(), "Room not found");
+        join_room("test-room-fen", "player-2", Some("Bob".to_string())).unwrap();
+        send_move("test-room-fen", "player-1", "e2e4").unwrap();
+        send_move("test-room-fen", "player-2", "e7e5").unwrap();
+        send_move("test-room-fen", "player-1", "g1f3").unwrap();
+
+        match get_fen("test-room-fen").unwrap() {
+            ServerMessage::FenState { room_id, fen, turn } => {
+                assert_eq!(room_id, "test-room-fen");
+                assert_eq!(fen, "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+                assert_eq!(turn, PieceColor::Black);
+            }
+            other => panic!("Expected FenState, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_game_log_pages_through_moves() {
+        setup();
+
+        let _ = join_room("test-room-paging", "player-1", Some("Alice".to_string()));
+        let _ = join_room("test-room-paging", "player-2", Some("Bob".to_string()));
+        let plies = [
+            ("player-1", "g1f3"), ("player-2", "g8f6"),
+            ("player-1", "f3g1"), ("player-2", "f6g8"),
+            ("player-1", "b1c3"), ("player-2", "b8c6"),
+            ("player-1", "e2e4"), ("player-2", "e7e5"),
+            ("player-1", "g1f3"), ("player-2", "g8f6"),
+        ];
+        for (player, notation) in plies {
+            send_move("test-room-paging", player, notation).unwrap();
+        }
+
+        let mut paged = Vec::new();
+        for (start, expected_len) in [(0, 4), (4, 4), (8, 2), (12, 0)] {
+            match get_game_log_page("test-room-paging", Some(start), Some(4)) {
+                Ok(ServerMessage::GameLog { moves, from_ply, total_moves, .. }) => {
+                    assert_eq!(moves.len(), expected_len);
+                    assert_eq!(from_ply, start.min(10));
+                    assert_eq!(total_moves, 10);
+                    paged.extend(moves.into_iter().map(|m| m.move_notation));
+                }
+                other => panic!("Expected GameLog message, got {:?}", other),
+            }
+        }
+        let expected: Vec<_> = plies.iter().map(|(_, notation)| notation.to_string()).collect();
+        assert_eq!(paged, expected);
+
+        // Without paging parameters the whole log comes back
+        match get_game_log("test-room-paging") {
+            Ok(ServerMessage::GameLog { moves, from_ply, total_moves, .. }) => {
+                assert_eq!((moves.len(), from_ply, total_moves), (10, 0, 10));
+            }
+            other => panic!("Expected GameLog message, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_game_log_records_think_times() {
+        setup();
+
+        let _ = join_room("test-room-think", "player-1", Some("Alice".to_string()));
+        let _ = join_room("test-room-think", "player-2", Some("Bob".to_string()));
+        tokio::time::advance(std::time::Duration::from_millis(1200)).await;
+        send_move("test-room-think", "player-1", "e2e4").unwrap();
+        tokio::time::advance(std::time::Duration::from_millis(4500)).await;
+        send_move("test-room-think", "player-2", "e7e5").unwrap();
+
+        if let Ok(ServerMessage::GameLog { moves, .. }) = get_game_log("test-room-think") {
+            assert_eq!(moves[0].think_ms, 1200);
+            assert_eq!(moves[1].think_ms, 4500);
+        } else {
+            panic!("Expected GameLog message");
+        }
+    }
+
+    #[test]
+    fn test_accept_takeback_rolls_back_one_full_move() {
+        setup();
+
+        let _ = join_room("test-room-takeback", "player-1", None);
+        let _ = join_room("test-room-takeback", "player-2", None);
+        let _ = send_move("test-room-takeback", "player-1", "e2e4");
+        let _ = send_move("test-room-takeback", "player-2", "e7e5");
+        let _ = send_move("test-room-takeback", "player-1", "g1f3");
+        let _ = send_move("test-room-takeback", "player-2", "b8c6");
+
+        assert!(offer_takeback("test-room-takeback", "player-1").is_ok());
+        let result = accept_takeback("test-room-takeback", "player-2");
+
+        if let Ok(ServerMessage::TakebackAccepted { moves, game_state, .. }) = result {
+            assert_eq!(moves.len(), 2);
+            assert_eq!(moves[1].move_notation, "e7e5");
+            assert_eq!(game_state.current_turn, PieceColor::White);
+        } else {
+            panic!("Expected TakebackAccepted message");
+        }
+    }
+
+    fn play_opening(room_id: &str, options: RoomOptions) {
+        join_room_with_options(room_id, "player-1", None, options.clone()).unwrap();
+        join_room_with_options(room_id, "player-2", None, options).unwrap();
+        send_move(room_id, "player-1", "e2e4").unwrap();
+        send_move(room_id, "player-2", "e7e5").unwrap();
+    }
+
+    #[test]
+    fn test_takeback_rejected_when_disabled() {
+        setup();
+
+        let disabled = RoomOptions {
+            takebacks: Some(TakebackPolicy::disabled()),
+            ..RoomOptions::default()
+        };
+        play_opening("test-room-no-takeback", disabled);
+        assert_eq!(
+            offer_takeback("test-room-no-takeback", "player-1").unwrap_err(),
+            "Takebacks are disabled in this room"
+        );
+
+        // Blitz rooms start with takebacks off, but a room can turn them back on
+        let blitz = RoomOptions { variant: GameVariant::Blitz, ..RoomOptions::default() };
+        play_opening("test-room-blitz-takeback", blitz);
+        assert_eq!(
+            offer_takeback("test-room-blitz-takeback", "player-1").unwrap_err(),
+            "Takebacks are disabled in this room"
+        );
+        let blitz_with_takebacks = RoomOptions {
+            variant: GameVariant::Blitz,
+            takebacks: Some(TakebackPolicy::default()),
+            ..RoomOptions::default()
+        };
+        play_opening("test-room-blitz-takeback-on", blitz_with_takebacks);
+        assert!(offer_takeback("test-room-blitz-takeback-on", "player-1").is_ok());
+    }
+
+    #[test]
+    fn test_takeback_waits_for_min_moves() {
+        setup();
+
+        let options = RoomOptions {
+            takebacks: Some(TakebackPolicy { allowed: true, min_moves: 4 }),
+            ..RoomOptions::default()
+        };
+        play_opening("test-room-takeback-min", options);
+        assert_eq!(
+            offer_takeback("test-room-takeback-min", "player-1").unwrap_err(),
+            "Not enough moves to take back"
+        );
+
+        send_move("test-room-takeback-min", "player-1", "g1f3").unwrap();
+        send_move("test-room-takeback-min", "player-2", "b8c6").unwrap();
+        assert!(offer_takeback("test-room-takeback-min", "player-1").is_ok());
+        assert!(accept_takeback("test-room-takeback-min", "player-2").is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unanswered_takeback_offer_expires() {
+        setup();
+        play_opening("test-room-takeback-expiry", RoomOptions::default());
+        let mut room = get_room_sender("test-room-takeback-expiry").unwrap().subscribe();
+
+        assert!(offer_takeback("test-room-takeback-expiry", "player-1").is_ok());
+        assert!(matches!(room.try_recv(), Ok(ServerMessage::TakebackOffered { .. })));
+        assert_eq!(
+            offer_takeback("test-room-takeback-expiry", "player-2").unwrap_err(),
+            "A takeback request is already pending"
+        );
+
+        tokio::time::advance(DEFAULT_TAKEBACK_EXPIRY - Duration::from_secs(1)).await;
+        tokio::task::yield_now().await;
+        assert!(matches!(room.try_recv(), Err(TryRecvError::Empty)));
+
+        match room.recv().await.unwrap() {
+            ServerMessage::TakebackExpired { room_id, requester_id } => {
+                assert_eq!(room_id, "test-room-takeback-expiry");
+                assert_eq!(requester_id, "player-1");
+            }
+            other => panic!("Expected TakebackExpired, got {:?}", other),
+        }
+        assert_eq!(
+            accept_takeback("test-room-takeback-expiry", "player-2").unwrap_err(),
+            "No pending takeback request"
+        );
+
+        // The lapsed offer no longer blocks a new one
+        assert!(offer_takeback("test-room-takeback-expiry", "player-2").is_ok());
+        assert!(accept_takeback("test-room-takeback-expiry", "player-1").is_ok());
+    }
+
+    #[test]
+    fn test_accept_draw_records_agreement() {
+        setup();
+
+        let _ = join_room("test-room-draw", "player-1", None);
+        let _ = join_room("test-room-draw", "player-2", None);
+        let _ = send_move("test-room-draw", "player-1", "e2e4");
+
+        assert!(offer_draw("test-room-draw", "player-1").is_ok());
+        assert_eq!(
+            accept_draw("test-room-draw", "player-1").unwrap_err(),
+            "Requester cannot accept their own draw offer"
+        );
+
+        match accept_draw("test-room-draw", "player-2") {
+            Ok(ServerMessage::DrawAgreed { game_state, .. }) => {
+                assert_eq!(game_state.status, GameStatus::Draw);
+                assert_eq!(game_state.draw_reason, Some(DrawReason::Agreement));
+                assert_ne!(game_state.draw_reason, Some(DrawReason::Stalemate));
+            }
+            other => panic!("Expected DrawAgreed message, got {:?}", other),
+        }
+
+        // The game is over, so no more moves or offers
+        assert_eq!(send_move("test-room-draw", "player-2", "e7e5").unwrap_err(), "Game is over");
+        assert_eq!(offer_draw("test-room-draw", "player-2").unwrap_err(), "Game is over");
+    }
+
+    #[test]
+    fn test_finished_game_cannot_be_taken_back() {
+        setup();
+
+        play_opening("test-room-takeback-over", RoomOptions::default());
+        assert!(offer_takeback("test-room-takeback-over", "player-1").is_ok());
+        assert!(offer_draw("test-room-takeback-over", "player-1").is_ok());
+        assert!(accept_draw("test-room-takeback-over", "player-2").is_ok());
+
+        // The offer made during the game can no longer be accepted, and no new one made
+        assert_eq!(accept_takeback("test-room-takeback-over", "player-2").unwrap_err(), "Game is over");
+        assert_eq!(offer_takeback("test-room-takeback-over", "player-2").unwrap_err(), "Game is over");
+    }
 }
 
 #[cfg(test)]
 mod model_tests {
     use super::*;
+    use chess_websocket_gateway::models::{ErrorCode, GameState, MoveRecord, Player};
 
     #[test]
     fn test_client_message_serialization() {
@@ -233,6 +606,11 @@ mod model_tests {
             room_id: "test-room".to_string(),
             player_id: "player-1".to_string(),
             player_name: Some("Alice".to_string()),
+            variant: None,
+            time_control: None,
+            require_ready: false,
+            color: None,
+            takebacks: None,
         });
         
         let json = to_string(&join_message).unwrap();
@@ -254,6 +632,7 @@ mod model_tests {
             room_id: "test-room".to_string(),
             player_id: "player-1".to_string(),
             move_notation: "e2e4".to_string(),
+            promotion: None,
         });
         
         let json = to_string(&move_message).unwrap();
@@ -292,6 +671,8 @@ mod model_tests {
     fn test_request_game_log_message_serialization() {
         let log_message = ClientMessage::RequestGameLog(RequestGameLogPayload {
             room_id: "test-room".to_string(),
+            from_ply: Some(4),
+            limit: Some(4),
         });
         
         let json = to_string(&log_message).unwrap();
@@ -300,6 +681,16 @@ mod model_tests {
         match deserialized {
             ClientMessage::RequestGameLog(payload) => {
                 assert_eq!(payload.room_id, "test-room");
+                assert_eq!((payload.from_ply, payload.limit), (Some(4), Some(4)));
+            }
+            _ => panic!("Expected RequestGameLog message"),
+        }
+
+        // Paging fields are optional for older clients
+        let legacy: ClientMessage = from_str(r#"{"type":"RequestGameLog","payload":{"room_id":"test-room"}}"#).unwrap();
+        match legacy {
+            ClientMessage::RequestGameLog(payload) => {
+                assert_eq!((payload.from_ply, payload.limit), (None, None));
             }
             _ => panic!("Expected RequestGameLog message"),
         }
@@ -308,7 +699,7 @@ mod model_tests {
     #[test]
     fn test_server_message_serialization() {
         let server_message = ServerMessage::Error {
-            code: "TEST_ERROR".to_string(),
+            code: ErrorCode::MoveError,
             message: "This is a test error".to_string(),
         };
         
@@ -317,13 +708,218 @@ mod model_tests {
         
         match deserialized {
             ServerMessage::Error { code, message } => {
-                assert_eq!(code, "TEST_ERROR");
+                assert_eq!(code, ErrorCode::MoveError);
                 assert_eq!(message, "This is a test error");
             }
             _ => panic!("Expected Error message"),
         }
     }
 
+    // Serializes `message`, checks it against the exact expected JSON and
+    // that the expected JSON parses back to the same shape
+    fn assert_server_json(message: ServerMessage, expected: &str) {
+        assert_eq!(to_string(&message).unwrap(), expected);
+        let parsed: ServerMessage = from_str(expected).unwrap();
+        assert_eq!(to_string(&parsed).unwrap(), expected);
+    }
+
+    fn small_game_state() -> GameState {
+        let mut game_state = GameState::new_game();
+        game_state.board.retain(|square, _| square == "e1");
+        game_state
+    }
+
+    const STATE_JSON: &str = r#"{"board":{"e1":{"piece_type":"King","color":"White"}},"current_turn":"White","status":"InProgress","check_counts":{"white":0,"black":0},"winner":null,"draw_reason":null}"#;
+
+    #[test]
+    fn test_server_message_json_shape() {
+        let moves = || vec![MoveRecord {
+            player_id: "p1".to_string(),
+            move_notation: "e2e4".to_string(),
+            timestamp: 7,
+            think_ms: 1500,
+        }];
+        let moves_json = r#"[{"player_id":"p1","move_notation":"e2e4","timestamp":7,"think_ms":1500}]"#;
+
+        assert_server_json(
+            ServerMessage::RoomJoined {
+                room_id: "r1".to_string(),
+                player_id: "p1".to_string(),
+                players: vec![Player {
+                    id: "p1".to_string(),
+                    name: "Alice".to_string(),
+                    color: Some(PieceColor::White),
+                    ready: false,
+                }],
+                game_state: None,
+            },
+            r#"{"type":"RoomJoined","room_id":"r1","player_id":"p1","players":[{"id":"p1","name":"Alice","color":"White","ready":false}],"game_state":null}"#,
+        );
+        assert_server_json(
+            ServerMessage::MoveMade {
+                room_id: "r1".to_string(),
+                player_id: "p1".to_string(),
+                move_notation: "e2e4".to_string(),
+                game_state: small_game_state(),
+            },
+            &format!(
+                r#"{{"type":"MoveMade","room_id":"r1","player_id":"p1","move_notation":"e2e4","game_state":{}}}"#,
+                STATE_JSON
+            ),
+        );
+        assert_server_json(
+            ServerMessage::PlayerLeft {
+                room_id: "r1".to_string(),
+                player_id: "p1".to_string(),
+            },
+            r#"{"type":"PlayerLeft","room_id":"r1","player_id":"p1"}"#,
+        );
+        assert_server_json(
+            ServerMessage::GameLog {
+                room_id: "r1".to_string(),
+                moves: moves(),
+                from_ply: 0,
+                total_moves: 1,
+            },
+            &format!(
+                r#"{{"type":"GameLog","room_id":"r1","moves":{},"from_ply":0,"total_moves":1}}"#,
+                moves_json
+            ),
+        );
+        assert_server_json(
+            ServerMessage::TakebackOffered {
+                room_id: "r1".to_string(),
+                requester_id: "p1".to_string(),
+            },
+            r#"{"type":"TakebackOffered","room_id":"r1","requester_id":"p1"}"#,
+        );
+        assert_server_json(
+            ServerMessage::TakebackAccepted {
+                room_id: "r1".to_string(),
+                game_state: small_game_state(),
+                moves: moves(),
+            },
+            &format!(
+                r#"{{"type":"TakebackAccepted","room_id":"r1","game_state":{},"moves":{}}}"#,
+                STATE_JSON, moves_json
+            ),
+        );
+        assert_server_json(
+            ServerMessage::TakebackRejected {
+                room_id: "r1".to_string(),
+                by_player_id: "p2".to_string(),
+            },
+            r#"{"type":"TakebackRejected","room_id":"r1","by_player_id":"p2"}"#,
+        );
+        assert_server_json(
+            ServerMessage::TakebackExpired {
+                room_id: "r1".to_string(),
+                requester_id: "p1".to_string(),
+            },
+            r#"{"type":"TakebackExpired","room_id":"r1","requester_id":"p1"}"#,
+        );
+        assert_server_json(
+            ServerMessage::DrawOffered {
+                room_id: "r1".to_string(),
+                requester_id: "p1".to_string(),
+            },
+            r#"{"type":"DrawOffered","room_id":"r1","requester_id":"p1"}"#,
+        );
+        assert_server_json(
+            ServerMessage::DrawAgreed {
+                room_id: "r1".to_string(),
+                game_state: small_game_state(),
+            },
+            &format!(r#"{{"type":"DrawAgreed","room_id":"r1","game_state":{}}}"#, STATE_JSON),
+        );
+        assert_server_json(
+            ServerMessage::GameAbandoned {
+                room_id: "r1".to_string(),
+                player_id: "p2".to_string(),
+                game_state: small_game_state(),
+            },
+            &format!(
+                r#"{{"type":"GameAbandoned","room_id":"r1","player_id":"p2","game_state":{}}}"#,
+                STATE_JSON
+            ),
+        );
+        assert_server_json(
+            ServerMessage::ResyncRequired {
+                room_id: "r1".to_string(),
+                missed: 3,
+            },
+            r#"{"type":"ResyncRequired","room_id":"r1","missed":3}"#,
+        );
+        assert_server_json(
+            ServerMessage::PlayerReady {
+                room_id: "r1".to_string(),
+                player_id: "p1".to_string(),
+            },
+            r#"{"type":"PlayerReady","room_id":"r1","player_id":"p1"}"#,
+        );
+        assert_server_json(
+            ServerMessage::GameStarted {
+                room_id: "r1".to_string(),
+                game_state: small_game_state(),
+            },
+            &format!(r#"{{"type":"GameStarted","room_id":"r1","game_state":{}}}"#, STATE_JSON),
+        );
+        assert_server_json(
+            ServerMessage::ClockSync {
+                room_id: "r1".to_string(),
+                white_ms: 59000,
+                black_ms: 55000,
+                running_side: Some(PieceColor::Black),
+                server_time_ms: 1_700_000_000_000,
+            },
+            r#"{"type":"ClockSync","room_id":"r1","white_ms":59000,"black_ms":55000,"running_side":"Black","server_time_ms":1700000000000}"#,
+        );
+        assert_server_json(
+            ServerMessage::FenState {
+                room_id: "r1".to_string(),
+                fen: "8/8/8/8/8/8/8/4K3 w - - 0 1".to_string(),
+                turn: PieceColor::White,
+            },
+            r#"{"type":"FenState","room_id":"r1","fen":"8/8/8/8/8/8/8/4K3 w - - 0 1","turn":"White"}"#,
+        );
+        assert_server_json(
+            ServerMessage::Error {
+                code: ErrorCode::JoinError,
+                message: "Room is full".to_string(),
+            },
+            r#"{"type":"Error","code":"JOIN_ERROR","message":"Room is full"}"#,
+        );
+    }
+
+    #[test]
+    fn test_client_message_json_shape() {
+        let message = ClientMessage::SendMove(SendMovePayload {
+            room_id: "r1".to_string(),
+            player_id: "p1".to_string(),
+            move_notation: "e2e4".to_string(),
+            promotion: None,
+        });
+        assert_eq!(
+            to_string(&message).unwrap(),
+            r#"{"type":"SendMove","payload":{"room_id":"r1","player_id":"p1","move_notation":"e2e4","promotion":null}}"#
+        );
+
+        let message = ClientMessage::JoinRoom(JoinRoomPayload {
+            room_id: "r1".to_string(),
+            player_id: "p1".to_string(),
+            player_name: None,
+            variant: None,
+            time_control: None,
+            require_ready: false,
+            color: None,
+            takebacks: None,
+        });
+        assert_eq!(
+            to_string(&message).unwrap(),
+            r#"{"type":"JoinRoom","payload":{"room_id":"r1","player_id":"p1","player_name":null,"variant":null,"time_control":null,"require_ready":false,"color":null,"takebacks":null}}"#
+        );
+    }
+
     #[test]
     fn test_invalid_message_format() {
         let invalid_json = r#"{"type": "InvalidType", "payload": {}}"#;
@@ -339,10 +935,567 @@ mod model_tests {
     }
 }
 
+#[cfg(test)]
+mod broadcast_tests {
+    use super::*;
+    use chess_websocket_gateway::websocket::next_room_message;
+    use tokio::sync::broadcast;
+
+    fn player_left(player_id: &str) -> ServerMessage {
+        ServerMessage::PlayerLeft {
+            room_id: "lag-room".to_string(),
+            player_id: player_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_lagging_receiver_gets_resync_prompt() {
+        let (tx, mut slow_receiver) = broadcast::channel(2);
+        for i in 0..5 {
+            tx.send(player_left(&format!("player-{}", i))).unwrap();
+        }
+
+        match next_room_message("lag-room", &mut slow_receiver) {
+            Some(ServerMessage::ResyncRequired { room_id, missed }) => {
+                assert_eq!(room_id, "lag-room");
+                assert_eq!(missed, 3);
+            }
+            other => panic!("Expected ResyncRequired, got {:?}", other),
+        }
+
+        // Delivery continues with the messages still in the buffer
+        match next_room_message("lag-room", &mut slow_receiver) {
+            Some(ServerMessage::PlayerLeft { player_id, .. }) => assert_eq!(player_id, "player-3"),
+            other => panic!("Expected PlayerLeft, got {:?}", other),
+        }
+        assert!(next_room_message("lag-room", &mut slow_receiver).is_some());
+        assert!(next_room_message("lag-room", &mut slow_receiver).is_none());
+    }
+}
+
+#[cfg(test)]
+mod logging_tests {
+    use super::*;
+    use chess_websocket_gateway::websocket::handle_connection;
+    use futures_util::{SinkExt, StreamExt};
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::{connect_async, tungstenite::Message};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    type Fields = HashMap<String, String>;
+
+    /// Subscriber that keeps the fields of every event it sees
+    #[derive(Clone, Default)]
+    struct CaptureSubscriber {
+        events: Arc<Mutex<Vec<Fields>>>,
+    }
+
+    struct FieldRecorder<'a>(&'a mut Fields);
+
+    impl Visit for FieldRecorder<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl Subscriber for CaptureSubscriber {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::new();
+            event.record(&mut FieldRecorder(&mut fields));
+            self.events.lock().unwrap().push(fields);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[tokio::test]
+    async fn test_join_event_logs_ids_without_message_body() {
+        setup();
+        let subscriber = CaptureSubscriber::default();
+        // The default runtime is single threaded, so the server task sees this dispatcher too
+        let _guard = tracing::subscriber::set_default(subscriber.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            let _ = handle_connection(stream, peer).await;
+        });
+
+        let (mut client, _) = connect_async(format!("ws://{}", addr)).await.unwrap();
+        let join = ClientMessage::JoinRoom(JoinRoomPayload {
+            room_id: "log-room".to_string(),
+            player_id: "log-player".to_string(),
+            player_name: Some("Private Name".to_string()),
+            variant: None,
+            time_control: None,
+            require_ready: false,
+            color: None,
+            takebacks: None,
+        });
+        client.send(Message::Text(to_string(&join).unwrap())).await.unwrap();
+        // The reply arrives after the join has been logged
+        client.next().await.unwrap().unwrap();
+
+        let events = subscriber.events.lock().unwrap();
+        let join_event = events
+            .iter()
+            .find(|fields| fields.get("event").map(String::as_str) == Some("join"))
+            .expect("join event was not logged");
+        assert_eq!(join_event["game_id"], "log-room");
+        assert_eq!(join_event["player_id"], "log-player");
+        assert!(events.iter().any(|fields| fields.get("event").map(String::as_str) == Some("connect")));
+        assert!(events
+            .iter()
+            .flat_map(|fields| fields.values())
+            .all(|value| !value.contains("Private Name")));
+    }
+}
+
+#[cfg(test)]
+mod disconnect_tests {
+    use super::*;
+    use chess_websocket_gateway::game::{
+        get_room_sender, player_disconnected, DEFAULT_DISCONNECT_GRACE, DEFAULT_DISCONNECT_WINDOW,
+        DEFAULT_MAX_DISCONNECTS,
+    };
+    use std::time::Duration;
+    use tokio::sync::broadcast::error::TryRecvError;
+
+    fn start_game(room_id: &str) {
+        join_room(room_id, "white-player", None).unwrap();
+        join_room(room_id, "black-player", None).unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_player_who_does_not_reconnect_loses() {
+        setup();
+        start_game("grace-room-1");
+        let mut room = get_room_sender("grace-room-1").unwrap().subscribe();
+
+        player_disconnected("grace-room-1", "black-player");
+        tokio::time::advance(DEFAULT_DISCONNECT_GRACE - Duration::from_secs(1)).await;
+        tokio::task::yield_now().await;
+        assert!(matches!(room.try_recv(), Err(TryRecvError::Empty)));
+
+        match room.recv().await.unwrap() {
+            ServerMessage::GameAbandoned { player_id, game_state, .. } => {
+                assert_eq!(player_id, "black-player");
+                assert_eq!(game_state.status, GameStatus::Abandoned);
+                assert_eq!(game_state.winner, Some(PieceColor::White));
+            }
+            other => panic!("Expected GameAbandoned, got {:?}", other),
+        }
+        assert!(send_move("grace-room-1", "white-player", "e2e4").is_err());
+    }
+
+    #[test]
+    fn test_disconnect_outside_a_runtime_holds_the_seat() {
+        setup();
+        start_game("grace-room-5");
+
+        player_disconnected("grace-room-5", "black-player");
+        assert!(join_room("grace-room-5", "black-player", None).is_ok());
+        assert!(send_move("grace-room-5", "white-player", "e2e4").is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reconnecting_cancels_forfeit() {
+        setup();
+        start_game("grace-room-2");
+        let mut room = get_room_sender("grace-room-2").unwrap().subscribe();
+
+        player_disconnected("grace-room-2", "black-player");
+        tokio::time::advance(DEFAULT_DISCONNECT_GRACE / 2).await;
+        assert!(join_room("grace-room-2", "black-player", None).is_ok());
+
+        tokio::time::advance(DEFAULT_DISCONNECT_GRACE).await;
+        tokio::task::yield_now().await;
+        assert!(matches!(room.try_recv(), Ok(ServerMessage::RoomJoined { .. })));
+        assert!(matches!(room.try_recv(), Err(TryRecvError::Empty)));
+        assert!(send_move("grace-room-2", "white-player", "e2e4").is_ok());
+    }
+
+    // Drop and come back `times` times, each well within the grace period
+    async fn flap(room_id: &str, player_id: &str, times: usize) {
+        for _ in 0..times {
+            player_disconnected(room_id, player_id);
+            tokio::time::advance(Duration::from_secs(5)).await;
+            join_room(room_id, player_id, None).unwrap();
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_repeated_disconnects_resign_the_game() {
+        setup();
+        start_game("grace-room-3");
+        flap("grace-room-3", "black-player", DEFAULT_MAX_DISCONNECTS).await;
+        let mut room = get_room_sender("grace-room-3").unwrap().subscribe();
+
+        // No grace period this time: the game is lost straight away
+        player_disconnected("grace-room-3", "black-player");
+        match room.try_recv() {
+            Ok(ServerMessage::GameAbandoned { player_id, game_state, .. }) => {
+                assert_eq!(player_id, "black-player");
+                assert_eq!(game_state.winner, Some(PieceColor::White));
+            }
+            other => panic!("Expected GameAbandoned, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_staying_connected_resets_the_disconnect_count() {
+        setup();
+        start_game("grace-room-4");
+        flap("grace-room-4", "black-player", DEFAULT_MAX_DISCONNECTS).await;
+        tokio::time::advance(DEFAULT_DISCONNECT_WINDOW).await;
+        let mut room = get_room_sender("grace-room-4").unwrap().subscribe();
+
+        // The earlier disconnects have aged out, so this one gets a grace period
+        player_disconnected("grace-room-4", "black-player");
+        tokio::task::yield_now().await;
+        assert!(matches!(room.try_recv(), Err(TryRecvError::Empty)));
+        assert!(join_room("grace-room-4", "black-player", None).is_ok());
+        assert!(send_move("grace-room-4", "white-player", "e2e4").is_ok());
+    }
+}
+
+#[cfg(test)]
+mod clock_tests {
+    use super::*;
+    use chess_websocket_gateway::game::{
+        get_clock_sync, join_room_with_time_control, player_disconnected,
+    };
+    use chess_websocket_gateway::models::TimeControl;
+    use chess_websocket_gateway::variants::GameVariant;
+    use chess_websocket_gateway::websocket::handle_connection;
+    use futures_util::{SinkExt, StreamExt};
+    use std::time::{Duration, SystemTime};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+    const ONE_MINUTE_PLUS_ONE: TimeControl = TimeControl { base_ms: 60_000, increment_ms: 1_000 };
+
+    fn start_timed_game(room_id: &str) {
+        for player_id in ["white-player", "black-player"] {
+            join_room_with_time_control(
+                room_id,
+                player_id,
+                None,
+                GameVariant::Standard,
+                Some(ONE_MINUTE_PLUS_ONE),
+            )
+            .unwrap();
+        }
+    }
+
+    fn now_ms() -> u64 {
+        SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_clocks_charge_the_mover_and_add_increment() {
+        setup();
+        start_timed_game("clock-room-1");
+
+        tokio::time::advance(Duration::from_millis(2_000)).await;
+        send_move("clock-room-1", "white-player", "e2e4").unwrap();
+        tokio::time::advance(Duration::from_millis(5_000)).await;
+
+        match get_clock_sync("clock-room-1").unwrap() {
+            Some(ServerMessage::ClockSync { white_ms, black_ms, running_side, .. }) => {
+                assert_eq!(white_ms, 59_000);
+                assert_eq!(black_ms, 55_000);
+                assert_eq!(running_side, Some(PieceColor::Black));
+            }
+            other => panic!("Expected ClockSync, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_untimed_game_has_no_clock_sync() {
+        setup();
+        join_room("clock-room-2", "white-player", None).unwrap();
+        join_room("clock-room-2", "black-player", None).unwrap();
+        assert!(matches!(get_clock_sync("clock-room-2"), Ok(None)));
+        assert!(get_clock_sync("no-such-clock-room").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reconnecting_to_running_game_receives_clock_sync() {
+        setup();
+        start_timed_game("clock-room-3");
+        send_move("clock-room-3", "white-player", "e2e4").unwrap();
+        player_disconnected("clock-room-3", "black-player");
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            let _ = handle_connection(stream, peer).await;
+        });
+
+        let (mut client, _) = connect_async(format!("ws://{}", addr)).await.unwrap();
+        let join = ClientMessage::JoinRoom(JoinRoomPayload {
+            room_id: "clock-room-3".to_string(),
+            player_id: "black-player".to_string(),
+            player_name: None,
+            variant: None,
+            time_control: None,
+            require_ready: false,
+            color: None,
+            takebacks: None,
+        });
+        let before_ms = now_ms();
+        client.send(Message::Text(to_string(&join).unwrap())).await.unwrap();
+
+        let mut replies = Vec::new();
+        for _ in 0..2 {
+            let reply = client.next().await.unwrap().unwrap();
+            replies.push(from_str::<ServerMessage>(reply.to_text().unwrap()).unwrap());
+        }
+        assert!(matches!(replies[0], ServerMessage::RoomJoined { .. }));
+        match &replies[1] {
+            ServerMessage::ClockSync { room_id, white_ms, black_ms, running_side, server_time_ms } => {
+                assert_eq!(room_id, "clock-room-3");
+                // White moved almost instantly, and Black's clock has only just started
+                assert!(*white_ms > 60_000 && *white_ms <= 61_000, "white_ms = {}", white_ms);
+                assert!(*black_ms > 50_000 && *black_ms <= 60_000, "black_ms = {}", black_ms);
+                assert_eq!(*running_side, Some(PieceColor::Black));
+                assert!(*server_time_ms >= before_ms && *server_time_ms <= now_ms());
+            }
+            other => panic!("Expected ClockSync, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod ready_tests {
+    use super::*;
+    use chess_websocket_gateway::game::{
+        get_clock_sync, get_room_sender, join_room_with_options, mark_ready, DEFAULT_READY_TIMEOUT,
+    };
+    use chess_websocket_gateway::models::{RoomOptions, TimeControl};
+    use std::time::Duration;
+    use tokio::sync::broadcast::error::TryRecvError;
+
+    fn seat_players(room_id: &str, time_control: Option<TimeControl>) {
+        for player_id in ["white-player", "black-player"] {
+            let options = RoomOptions {
+                time_control,
+                require_ready: true,
+                ..RoomOptions::default()
+            };
+            join_room_with_options(room_id, player_id, None, options).unwrap();
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_game_waits_until_both_players_are_ready() {
+        setup();
+        let time_control = TimeControl { base_ms: 60_000, increment_ms: 0 };
+        seat_players("ready-room-1", Some(time_control));
+        assert_eq!(send_move("ready-room-1", "white-player", "e2e4").unwrap_err(), "Game not started");
+
+        assert!(matches!(
+            mark_ready("ready-room-1", "white-player"),
+            Ok(ServerMessage::PlayerReady { .. })
+        ));
+        assert_eq!(send_move("ready-room-1", "white-player", "e2e4").unwrap_err(), "Game not started");
+
+        // Waiting to get ready does not cost clock time
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert!(matches!(
+            mark_ready("ready-room-1", "black-player"),
+            Ok(ServerMessage::GameStarted { .. })
+        ));
+        match get_clock_sync("ready-room-1").unwrap() {
+            Some(ServerMessage::ClockSync { white_ms, .. }) => assert_eq!(white_ms, 60_000),
+            other => panic!("Expected ClockSync, got {:?}", other),
+        }
+        assert!(send_move("ready-room-1", "white-player", "e2e4").is_ok());
+        assert_eq!(mark_ready("ready-room-1", "white-player").unwrap_err(), "Game already started");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_ready_timeout_auto_starts_game() {
+        setup();
+        seat_players("ready-room-2", None);
+        let mut room = get_room_sender("ready-room-2").unwrap().subscribe();
+        mark_ready("ready-room-2", "white-player").unwrap();
+        assert!(matches!(room.try_recv(), Ok(ServerMessage::PlayerReady { .. })));
+
+        tokio::time::advance(DEFAULT_READY_TIMEOUT - Duration::from_secs(1)).await;
+        tokio::task::yield_now().await;
+        assert!(matches!(room.try_recv(), Err(TryRecvError::Empty)));
+
+        match room.recv().await.unwrap() {
+            ServerMessage::GameStarted { room_id, game_state } => {
+                assert_eq!(room_id, "ready-room-2");
+                assert_eq!(game_state.status, GameStatus::InProgress);
+            }
+            other => panic!("Expected GameStarted, got {:?}", other),
+        }
+        assert!(send_move("ready-room-2", "white-player", "e2e4").is_ok());
+    }
+
+    #[test]
+    fn test_ready_rooms_filled_outside_a_runtime_wait_for_both_players() {
+        setup();
+        seat_players("ready-room-4", None);
+        mark_ready("ready-room-4", "white-player").unwrap();
+        assert!(matches!(
+            mark_ready("ready-room-4", "black-player"),
+            Ok(ServerMessage::GameStarted { .. })
+        ));
+    }
+
+    #[test]
+    fn test_rooms_without_ready_check_start_on_second_join() {
+        setup();
+        join_room("ready-room-3", "white-player", None).unwrap();
+        join_room("ready-room-3", "black-player", None).unwrap();
+        assert!(send_move("ready-room-3", "white-player", "e2e4").is_ok());
+    }
+}
+
+#[cfg(test)]
+mod error_code_tests {
+    use super::*;
+    use chess_websocket_gateway::models::{ErrorCode, CLIENT_MESSAGE_TYPES};
+    use chess_websocket_gateway::websocket::handle_connection;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+    #[tokio::test]
+    async fn test_each_failing_request_reports_its_error_code() {
+        setup();
+        // A full room, so the websocket join below is refused
+        join_room("error-code-room", "white-player", None).unwrap();
+        join_room("error-code-room", "black-player", None).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            let _ = handle_connection(stream, peer).await;
+        });
+        let (mut client, _) = connect_async(format!("ws://{}", addr)).await.unwrap();
+
+        let seat = r#"{"room_id":"missing-room","player_id":"p1"}"#;
+        let cases = [
+            ("not json".to_string(), ErrorCode::ParseError),
+            (
+                r#"{"type":"JoinRoom","payload":{"room_id":"error-code-room","player_id":"third-player"}}"#.to_string(),
+                ErrorCode::JoinError,
+            ),
+            (
+                r#"{"type":"SendMove","payload":{"room_id":"missing-room","player_id":"p1","move_notation":"e2e4"}}"#.to_string(),
+                ErrorCode::MoveError,
+            ),
+            (format!(r#"{{"type":"LeaveRoom","payload":{}}}"#, seat), ErrorCode::LeaveError),
+            (
+                r#"{"type":"RequestGameLog","payload":{"room_id":"missing-room"}}"#.to_string(),
+                ErrorCode::LogError,
+            ),
+            (format!(r#"{{"type":"OfferTakeback","payload":{}}}"#, seat), ErrorCode::TakebackOfferError),
+            (format!(r#"{{"type":"AcceptTakeback","payload":{}}}"#, seat), ErrorCode::TakebackAcceptError),
+            (format!(r#"{{"type":"RejectTakeback","payload":{}}}"#, seat), ErrorCode::TakebackRejectError),
+            (format!(r#"{{"type":"OfferDraw","payload":{}}}"#, seat), ErrorCode::DrawOfferError),
+            (format!(r#"{{"type":"AcceptDraw","payload":{}}}"#, seat), ErrorCode::DrawAcceptError),
+            (format!(r#"{{"type":"MarkReady","payload":{}}}"#, seat), ErrorCode::ReadyError),
+            (
+                r#"{"type":"RequestFen","payload":{"room_id":"missing-room"}}"#.to_string(),
+                ErrorCode::FenError,
+            ),
+        ];
+
+        for (request, expected) in cases {
+            client.send(Message::Text(request.clone())).await.unwrap();
+            let reply = client.next().await.unwrap().unwrap();
+            match from_str::<ServerMessage>(reply.to_text().unwrap()).unwrap() {
+                ServerMessage::Error { code, .. } => assert_eq!(code, expected, "for {}", request),
+                other => panic!("Expected Error for {}, got {:?}", request, other),
+            }
+        }
+    }
+
+    // Sends `request` and returns the message of the parse error it gets back
+    async fn parse_error(
+        client: &mut WebSocketStream<MaybeTlsStream<TcpStream>>,
+        request: &str,
+    ) -> String {
+        client.send(Message::Text(request.to_string())).await.unwrap();
+        let reply = client.next().await.unwrap().unwrap();
+        match from_str::<ServerMessage>(reply.to_text().unwrap()).unwrap() {
+            ServerMessage::Error { code: ErrorCode::ParseError, message } => message,
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_errors_explain_the_problem() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            let _ = handle_connection(stream, peer).await;
+        });
+        let (mut client, _) = connect_async(format!("ws://{}", addr)).await.unwrap();
+
+        let message = parse_error(&mut client, r#"{"type":"SecretResign","payload":{}}"#).await;
+        assert!(message.contains("unknown message type"), "{}", message);
+        for accepted in CLIENT_MESSAGE_TYPES {
+            assert!(message.contains(accepted), "{} missing from {}", accepted, message);
+        }
+        assert!(!message.contains("SecretResign"));
+
+        let message = parse_error(&mut client, r#"{"type":"SendMove","payload":{"room_id":"secret-room"}}"#).await;
+        assert!(message.contains("missing field `player_id`"), "{}", message);
+        assert!(!message.contains("secret-room"));
+
+        let message = parse_error(&mut client, "{not json").await;
+        assert!(message.contains("invalid JSON"), "{}", message);
+    }
+
+    #[test]
+    fn test_client_message_types_match_client_message() {
+        for message_type in CLIENT_MESSAGE_TYPES {
+            let json = format!(r#"{{"type":"{}","payload":{{}}}}"#, message_type);
+            let err = from_str::<ClientMessage>(&json).unwrap_err().to_string();
+            assert!(err.starts_with("missing field"), "{}: {}", message_type, err);
+        }
+    }
+}
+
 #[cfg(test)]
 mod game_state_tests {
     use super::*;
-    use chess_websocket_gateway::models::{GameState, PieceColor, GameStatus};
+    use chess_websocket_gateway::models::{ChessPiece, DrawReason, GameState, PieceColor, PieceType, GameStatus};
 
     #[test]
     fn test_new_game_initialization() {
@@ -379,6 +1532,147 @@ mod game_state_tests {
         assert!(result.is_ok());
         assert_eq!(game_state.current_turn, PieceColor::White);
     }
+
+    #[test]
+    fn test_undo_move_restores_previous_state() {
+        let mut game_state = GameState::new_game();
+        let initial_board = game_state.board.clone();
+
+        game_state.apply_move("e2e4").unwrap();
+        game_state.apply_move("e7e5").unwrap();
+        assert_eq!(game_state.history.len(), 2);
+
+        game_state.undo_move().unwrap();
+        assert_eq!(game_state.current_turn, PieceColor::Black);
+        game_state.undo_move().unwrap();
+        assert_eq!(game_state.current_turn, PieceColor::White);
+        assert_eq!(game_state.board, initial_board);
+        assert!(game_state.undo_move().is_err());
+    }
+
+    #[test]
+    fn test_undo_move_clears_the_result_the_move_produced() {
+        let mut game_state = GameState::new_game();
+        game_state.apply_move("e2e4").unwrap();
+        // As the game layer marks a draw after the move that caused it
+        game_state.status = GameStatus::Draw;
+        game_state.draw_reason = Some(DrawReason::ThreefoldRepetition);
+
+        game_state.undo_move().unwrap();
+        assert_eq!(game_state.status, GameStatus::InProgress);
+        assert_eq!(game_state.draw_reason, None);
+    }
+
+    #[test]
+    fn test_fen_of_initial_position_and_double_pawn_push() {
+        let mut game_state = GameState::new_game();
+        assert_eq!(
+            game_state.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+
+        game_state.apply_move("e2e4").unwrap();
+        assert_eq!(
+            game_state.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+    }
+
+    #[test]
+    fn test_castling_and_en_passant_move_the_other_piece_and_undo() {
+        let mut game_state = GameState::new_game();
+        let before = game_state.board.clone();
+        for notation in ["e2e4", "d7d5", "e4e5", "f7f5", "e5f6", "g8f6", "g1f3", "b8c6", "f1c4", "c8g4"] {
+            game_state.apply_move(notation).unwrap();
+        }
+        // The f5 pawn was taken en passant
+        assert!(!game_state.board.contains_key("f5"));
+
+        game_state.apply_move("e1g1").unwrap();
+        assert_eq!(
+            game_state.to_fen(),
+            "r2qkb1r/ppp1p1pp/2n2n2/3p4/2B3b1/5N2/PPPP1PPP/RNBQ1RK1 b kq - 5 6"
+        );
+
+        while game_state.undo_move().is_ok() {}
+        assert_eq!(game_state.board, before);
+    }
+
+    #[test]
+    fn test_undo_restores_castling_and_en_passant_rights() {
+        let mut game_state = GameState::new_game();
+        for notation in ["e2e4", "e7e5", "g1f3", "b8c6"] {
+            game_state.apply_move(notation).unwrap();
+        }
+        // Walking the king forward and back leaves it on e1 without its rights
+        game_state.apply_move("e1e2").unwrap();
+        game_state.apply_move("d7d5").unwrap();
+        assert!(game_state.to_fen().ends_with(" w kq d6 0 4"), "{}", game_state.to_fen());
+        game_state.apply_move("e2e1").unwrap();
+        assert!(game_state.to_fen().ends_with(" b kq - 1 4"), "{}", game_state.to_fen());
+
+        game_state.undo_move().unwrap();
+        assert!(game_state.to_fen().ends_with(" w kq d6 0 4"), "{}", game_state.to_fen());
+        game_state.undo_move().unwrap();
+        game_state.undo_move().unwrap();
+        assert!(game_state.to_fen().ends_with(" w KQkq - 2 3"), "{}", game_state.to_fen());
+    }
+
+    // White pawn on e7 with e8 cleared and the black king on g8, white to move
+    fn pawn_about_to_promote() -> GameState {
+        let mut game_state = GameState::new_game();
+        let king = game_state.board.remove("e8").unwrap();
+        game_state.board.insert("g8".to_string(), king);
+        game_state.board.insert(
+            "e7".to_string(),
+            ChessPiece { piece_type: PieceType::Pawn, color: PieceColor::White },
+        );
+        game_state
+    }
+
+    #[test]
+    fn test_promotion_defaults_to_queen() {
+        let mut game_state = pawn_about_to_promote();
+
+        game_state.apply_move_with_promotion("e7e8", None).unwrap();
+
+        assert!(!game_state.board.contains_key("e7"));
+        assert_eq!(
+            game_state.board.get("e8"),
+            Some(&ChessPiece { piece_type: PieceType::Queen, color: PieceColor::White })
+        );
+        assert_eq!(game_state.current_turn, PieceColor::Black);
+    }
+
+    #[test]
+    fn test_promotion_to_knight_and_undo() {
+        let mut game_state = pawn_about_to_promote();
+        let before = game_state.board.clone();
+
+        game_state
+            .apply_move_with_promotion("e7e8", Some(PieceType::from_promotion("N").unwrap()))
+            .unwrap();
+        assert_eq!(
+            game_state.board.get("e8"),
+            Some(&ChessPiece { piece_type: PieceType::Knight, color: PieceColor::White })
+        );
+
+        game_state.undo_move().unwrap();
+        assert_eq!(game_state.board, before);
+    }
+
+    #[test]
+    fn test_promotion_rejects_king_and_non_promoting_moves() {
+        assert!(PieceType::from_promotion("k").is_err());
+        assert!(PieceType::from_promotion("p").is_err());
+
+        let mut game_state = pawn_about_to_promote();
+        assert!(game_state.promote("e7", "e8", PieceType::King).is_err());
+        assert!(game_state
+            .apply_move_with_promotion("e2e4", Some(PieceType::Queen))
+            .is_err());
+        assert!(game_state.history.is_empty());
+    }
 }
 
 #[cfg(test)]
@@ -403,6 +1697,7 @@ mod room_tests {
             id: "player-1".to_string(),
             name: "Alice".to_string(),
             color: None,
+            ready: false,
         };
         
         let result = room.add_player(player);
@@ -421,6 +1716,7 @@ mod room_tests {
             id: "player-1".to_string(),
             name: "Alice".to_string(),
             color: None,
+            ready: false,
         };
         room.add_player(player1).unwrap();
         
@@ -429,6 +1725,7 @@ mod room_tests {
             id: "player-2".to_string(),
             name: "Bob".to_string(),
             color: None,
+            ready: false,
         };
         let result = room.add_player(player2);
         
@@ -443,13 +1740,13 @@ mod room_tests {
         let mut room = Room::new("test-room".to_string());
         
         // Add two players
-        let player1 = Player { id: "player-1".to_string(), name: "Alice".to_string(), color: None };
-        let player2 = Player { id: "player-2".to_string(), name: "Bob".to_string(), color: None };
+        let player1 = Player { id: "player-1".to_string(), name: "Alice".to_string(), color: None, ready: false };
+        let player2 = Player { id: "player-2".to_string(), name: "Bob".to_string(), color: None, ready: false };
         room.add_player(player1).unwrap();
         room.add_player(player2).unwrap();
         
         // Try to add third player
-        let player3 = Player { id: "player-3".to_string(), name: "Charlie".to_string(), color: None };
+        let player3 = Player { id: "player-3".to_string(), name: "Charlie".to_string(), color: None, ready: false };
         let result = room.add_player(player3);
         
         assert!(result.is_err());
@@ -461,23 +1758,77 @@ mod room_tests {
         let mut room = Room::new("test-room".to_string());
         
         // Add player
-        let player1 = Player { id: "player-1".to_string(), name: "Alice".to_string(), color: None };
+        let player1 = Player { id: "player-1".to_string(), name: "Alice".to_string(), color: None, ready: false };
         room.add_player(player1).unwrap();
         
         // Try to add same player again
-        let player1_duplicate = Player { id: "player-1".to_string(), name: "Alice".to_string(), color: None };
+        let player1_duplicate = Player { id: "player-1".to_string(), name: "Alice".to_string(), color: None, ready: false };
         let result = room.add_player(player1_duplicate);
         
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), "Player is already in the room");
     }
 
+    #[test]
+    fn test_host_picks_black() {
+        let mut room = Room::new("test-room".to_string());
+
+        let host = Player { id: "player-1".to_string(), name: "Alice".to_string(), color: Some(PieceColor::Black), ready: false };
+        room.add_player(host).unwrap();
+        let guest = Player { id: "player-2".to_string(), name: "Bob".to_string(), color: None, ready: false };
+        room.add_player(guest).unwrap();
+
+        assert_eq!(room.players[0].color, Some(PieceColor::Black));
+        assert_eq!(room.players[1].color, Some(PieceColor::White));
+    }
+
+    #[test]
+    fn test_taken_color_is_rejected() {
+        let mut room = Room::new("test-room".to_string());
+
+        let host = Player { id: "player-1".to_string(), name: "Alice".to_string(), color: Some(PieceColor::White), ready: false };
+        room.add_player(host).unwrap();
+        let guest = Player { id: "player-2".to_string(), name: "Bob".to_string(), color: Some(PieceColor::White), ready: false };
+
+        assert_eq!(room.add_player(guest).unwrap_err(), "Color is already taken");
+        assert_eq!(room.players.len(), 1);
+    }
+
+    #[test]
+    fn test_reconnect_preserves_color() {
+        let mut room = Room::new("test-room".to_string());
+
+        let player1 = Player { id: "player-1".to_string(), name: "Alice".to_string(), color: None, ready: false };
+        let player2 = Player { id: "player-2".to_string(), name: "Bob".to_string(), color: None, ready: false };
+        room.add_player(player1.clone()).unwrap();
+        room.add_player(player2).unwrap();
+        room.game_state.as_mut().unwrap().apply_move("e2e4").unwrap();
+        room.add_move("player-1".to_string(), "e2e4".to_string());
+
+        // White leaves and comes back while Black is still seated
+        room.remove_player("player-1");
+        room.add_player(player1).unwrap();
+
+        let colors: Vec<_> = room.players.iter().map(|p| (p.id.as_str(), p.color.clone())).collect();
+        assert_eq!(
+            colors,
+            vec![("player-2", Some(PieceColor::Black)), ("player-1", Some(PieceColor::White))]
+        );
+
+        // The game carries on where it was left
+        let notations: Vec<_> = room.moves.iter().map(|m| m.move_notation.as_str()).collect();
+        assert_eq!(notations, vec!["e2e4"]);
+        let game_state = room.game_state.as_ref().unwrap();
+        assert_eq!(game_state.current_turn, PieceColor::Black);
+        assert!(game_state.board.contains_key("e4"));
+    }
+
     #[test]
     fn test_remove_player() {
         let mut room = Room::new("test-room".to_string());
         
         // Add player
-        let player = Player { id: "player-1".to_string(), name: "Alice".to_string(), color: None };
+        let player = Player { id: "player-1".to_string(), name: "Alice".to_string(), color: None, ready: false };
         room.add_player(player).unwrap();
         
         // Remove player
@@ -529,6 +1880,42 @@ mod concurrent_tests {
         }
     }
 
+    #[test]
+    fn test_concurrent_joins_to_new_room_respect_capacity() {
+        use chess_websocket_gateway::models::ROOM_CAPACITY;
+        use std::sync::Barrier;
+
+        setup();
+
+        let joiners = 8;
+        let barrier = Arc::new(Barrier::new(joiners));
+        let handles: Vec<_> = (0..joiners)
+            .map(|i| {
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    barrier.wait();
+                    join_room("race-new-room", &format!("racer-{}", i), None)
+                })
+            })
+            .collect();
+
+        let joined: Vec<_> = handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap().ok())
+            .collect();
+        assert_eq!(joined.len(), ROOM_CAPACITY);
+
+        // Nobody's seat was lost to a second copy of the room being created
+        let last = joined
+            .iter()
+            .filter_map(|msg| match msg {
+                ServerMessage::RoomJoined { players, .. } => Some(players.len()),
+                _ => None,
+            })
+            .max();
+        assert_eq!(last, Some(ROOM_CAPACITY));
+    }
+
     #[test]
     fn test_concurrent_moves_same_room() {
         setup();
@@ -537,12 +1924,13 @@ mod concurrent_tests {
         let _ = join_room("concurrent-moves", "player-1", Some("Alice".to_string()));
         let _ = join_room("concurrent-moves", "player-2", Some("Bob".to_string()));
         
-        let handles: Vec<_> = (0..5)
-            .map(|i| {
+        let handles: Vec<_> = ["e2e4", "e7e5", "d2d4", "d7d5", "c2c4"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, move_notation)| {
                 let player_id = if i % 2 == 0 { "player-1" } else { "player-2" };
-                let move_notation = format!("move-{}", i);
                 thread::spawn(move || {
-                    send_move("concurrent-moves", player_id, &move_notation)
+                    send_move("concurrent-moves", player_id, move_notation)
                 })
             })
             .collect();
@@ -558,4 +1946,247 @@ mod concurrent_tests {
         // At least some moves should succeed
         assert!(success_count > 0);
     }
+
+    #[test]
+    fn test_operations_survive_poisoned_game_state() {
+        setup();
+
+        let _ = join_room("poisoned-room", "player-1", Some("Alice".to_string()));
+        chess_websocket_gateway::game::poison_game_state();
+
+        // The room created before the panic is still there and playable
+        let result = join_room("poisoned-room", "player-2", Some("Bob".to_string()));
+        assert!(matches!(result, Ok(ServerMessage::RoomJoined { ref players, .. }) if players.len() == 2));
+        assert!(send_move("poisoned-room", "player-1", "e2e4").is_ok());
+        assert!(get_game_log("poisoned-room").is_ok());
+    }
+
+    #[test]
+    fn test_lock_or_recover_clears_poison() {
+        use chess_websocket_gateway::game::lock_or_recover;
+        use std::sync::Mutex;
+
+        let counter = Arc::new(Mutex::new(0));
+        let poisoner = Arc::clone(&counter);
+        let _ = thread::spawn(move || {
+            let mut guard = poisoner.lock().unwrap();
+            *guard += 1;
+            panic!("poisoning counter");
+        })
+        .join();
+        assert!(counter.is_poisoned());
+
+        *lock_or_recover(&counter) += 1;
+        assert!(!counter.is_poisoned());
+        assert_eq!(*counter.lock().unwrap(), 2);
+    }
+}
+
+#[cfg(test)]
+mod variant_tests {
+    use super::*;
+    use chess_websocket_gateway::game::join_room_with_variant;
+    use chess_websocket_gateway::variants::GameVariant;
+
+    #[test]
+    fn test_third_check_wins_three_check_game() {
+        setup();
+
+        let room = "test-room-three-check";
+        let _ = join_room_with_variant(room, "player-1", None, GameVariant::ThreeCheck);
+        let _ = join_room(room, "player-2", None);
+
+        let moves = [
+            ("player-1", "e2e4"), ("player-2", "e7e5"),
+            ("player-1", "f1c4"), ("player-2", "b8c6"),
+            ("player-1", "c4f7+"), ("player-2", "e8f7"),
+            ("player-1", "d1h5+"), ("player-2", "f7e7"),
+            ("player-1", "h5e5+"),
+        ];
+        let mut last = None;
+        for (player, notation) in moves {
+            last = Some(send_move(room, player, notation).expect("move should be accepted"));
+        }
+
+        if let Some(ServerMessage::MoveMade { game_state, .. }) = last {
+            assert_eq!(game_state.check_counts.white, 3);
+            assert_eq!(game_state.status, GameStatus::ThreeCheck);
+            assert_eq!(game_state.winner, Some(PieceColor::White));
+        } else {
+            panic!("Expected MoveMade message");
+        }
+
+        // The game is over, so no further moves are accepted
+        assert!(send_move(room, "player-2", "e7d6").is_err());
+    }
+
+    #[test]
+    fn test_check_suffix_on_quiet_move_is_not_counted() {
+        setup();
+
+        let room = "test-room-three-check-suffix";
+        let _ = join_room_with_variant(room, "player-1", None, GameVariant::ThreeCheck);
+        let _ = join_room(room, "player-2", None);
+
+        // Neither move gives check, whatever the notation claims
+        send_move(room, "player-1", "e2e4+").unwrap();
+        let result = send_move(room, "player-2", "e7e5#").unwrap();
+        if let ServerMessage::MoveMade { game_state, .. } = result {
+            assert_eq!(game_state.check_counts.white, 0);
+            assert_eq!(game_state.check_counts.black, 0);
+        } else {
+            panic!("Expected MoveMade message");
+        }
+    }
+
+    #[test]
+    fn test_checks_do_not_end_standard_game() {
+        setup();
+
+        let room = "test-room-standard-checks";
+        let _ = join_room(room, "player-1", None);
+        let _ = join_room(room, "player-2", None);
+
+        send_move(room, "player-1", "e2e4").unwrap();
+        send_move(room, "player-2", "e7e5").unwrap();
+        for (check, reply) in [("d1h5+", "e8e7"), ("h5g5+", "e7e8"), ("g5h5+", "e8e7")] {
+            send_move(room, "player-1", check).unwrap();
+            send_move(room, "player-2", reply).unwrap();
+        }
+
+        let result = send_move(room, "player-1", "h5e5+").unwrap();
+        if let ServerMessage::MoveMade { game_state, .. } = result {
+            assert_eq!(game_state.status, GameStatus::InProgress);
+            assert_eq!(game_state.winner, None);
+        } else {
+            panic!("Expected MoveMade message");
+        }
+    }
+}
+
+#[cfg(test)]
+mod engine_tests {
+    use super::*;
+    use chess::bitboard::position::Position;
+    use chess_websocket_gateway::engine::{from_engine_board, to_engine_board};
+    use chess_websocket_gateway::models::{ChessPiece, GameState, PieceType};
+
+    #[test]
+    fn test_starting_position_converts_both_ways() {
+        let state = GameState::new_game();
+        let engine_board = to_engine_board(&state.board).unwrap();
+        assert_eq!(engine_board, Position::new().board);
+        assert_eq!(from_engine_board(&engine_board), state.board);
+    }
+
+    #[test]
+    fn test_invalid_squares_are_rejected() {
+        let mut state = GameState::new_game();
+        state.board.insert(
+            "z9".to_string(),
+            ChessPiece { piece_type: PieceType::Queen, color: PieceColor::White },
+        );
+        assert_eq!(to_engine_board(&state.board), Err("Invalid square: z9".to_string()));
+    }
+}
+
+#[cfg(test)]
+mod draw_tests {
+    use super::*;
+    use chess_websocket_gateway::game::set_database;
+    use db_entity::game;
+    use sea_orm::{DatabaseConnection, DbBackend, MockDatabase, MockExecResult};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    // Sam Loyd's ten-move stalemate, White stalemating Black
+    const STALEMATE: [&str; 19] = [
+        "e2e3", "a7a5", "d1h5", "a8a6", "h5a5", "h7h5", "h2h4", "a6h6", "a5c7", "f7f6",
+        "c7d7", "e8f7", "d7b7", "d8d3", "b7b8", "d3h7", "b8c8", "f7g6", "c8e6",
+    ];
+
+    fn play_stalemate(room: &str) -> Result<ServerMessage, String> {
+        let _ = join_room(room, "player-1", Some("Alice".to_string()));
+        let _ = join_room(room, "player-2", Some("Bob".to_string()));
+        let mut last = Err("No moves played".to_string());
+        for (ply, notation) in STALEMATE.iter().enumerate() {
+            let player = if ply % 2 == 0 { "player-1" } else { "player-2" };
+            last = send_move(room, player, notation);
+        }
+        last
+    }
+
+    #[test]
+    fn test_stalemate_ends_the_game_as_a_draw() {
+        setup();
+
+        match play_stalemate("test-room-stalemate") {
+            Ok(ServerMessage::MoveMade { game_state, .. }) => {
+                assert_eq!(game_state.status, GameStatus::Stalemate);
+                assert_eq!(game_state.draw_reason, Some(DrawReason::Stalemate));
+                assert_eq!(game_state.winner, None);
+            }
+            other => panic!("Expected MoveMade, got {:?}", other),
+        }
+        assert_eq!(send_move("test-room-stalemate", "player-2", "g6h5").unwrap_err(), "Game is over");
+    }
+
+    // The statements recorded while `draw` draws the game in a room named
+    // after a game id. The mock refuses the result update, so nothing past it runs
+    async fn recorded_draw(draw: impl FnOnce(&str)) -> String {
+        let game_id = Uuid::new_v4();
+        let row = game::Model {
+            id: game_id,
+            white_player: Uuid::new_v4(),
+            black_player: Uuid::new_v4(),
+            fen: "fen".to_string(),
+            pgn: serde_json::json!({}),
+            result: None,
+            draw_reason: None,
+            variant: game::GameVariant::Standard,
+            started_at: Default::default(),
+            duration_sec: 600,
+            created_at: Default::default(),
+            updated_at: Default::default(),
+        };
+        let db: Arc<DatabaseConnection> = Arc::new(
+            MockDatabase::new(DbBackend::Postgres)
+                .append_query_results([vec![row]])
+                .append_exec_results([MockExecResult { last_insert_id: 0, rows_affected: 0 }])
+                .into_connection(),
+        );
+
+        set_database(Some(db.clone()));
+        draw(&game_id.to_string());
+        // The draw is stored in the background; wait until that lets go of the connection
+        while Arc::strong_count(&db) > 2 {
+            tokio::task::yield_now().await;
+        }
+        set_database(None);
+
+        let db = Arc::try_unwrap(db).expect("connection still shared");
+        format!("{:?}", db.into_transaction_log())
+    }
+
+    #[tokio::test]
+    async fn test_agreed_draw_and_stalemate_are_recorded_with_different_reasons() {
+        setup();
+
+        let stalemate = recorded_draw(|room| {
+            play_stalemate(room).unwrap();
+        })
+        .await;
+        let agreed = recorded_draw(|room| {
+            let _ = join_room(room, "player-1", Some("Alice".to_string()));
+            let _ = join_room(room, "player-2", Some("Bob".to_string()));
+            offer_draw(room, "player-1").unwrap();
+            accept_draw(room, "player-2").unwrap();
+        })
+        .await;
+
+        assert!(stalemate.contains(r#"String(Some("stalemate"))"#));
+        assert!(!stalemate.contains(r#"String(Some("agreement"))"#));
+        assert!(agreed.contains(r#"String(Some("agreement"))"#));
+        assert!(!agreed.contains(r#"String(Some("stalemate"))"#));
+    }
 }
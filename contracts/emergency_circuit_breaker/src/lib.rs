@@ -9,6 +9,25 @@ pub enum DataKey {
     Admin,
     Balance(Address),
     TotalSupply,
+    WithdrawLimit,
+    Withdrawn(Address),
+    Locked,
+}
+
+/// Most an address may claim within a window of ledgers
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawLimit {
+    pub cap: i128,
+    pub window_ledgers: u32,
+}
+
+/// What an address has claimed in its current window
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawWindow {
+    pub start_ledger: u32,
+    pub amount: i128,
 }
 
 #[contract]
@@ -19,8 +38,8 @@ impl PausableContract {
     /// Initialize the contract with an admin address
     pub fn initialize(env: Env, admin: Address) {
         // Ensure contract hasn't been initialized
-        if env.storage().instance().has(&DataKey::Admin) {
-            panic!("Contract already initialized");
+        if Self::is_initialized(env.clone()) {
+            panic!("Already initialized");
         }
 
         // Set admin
@@ -100,6 +119,7 @@ impl PausableContract {
     /// Deposit tokens (guarded by pause check)
     pub fn deposit(env: Env, from: Address, amount: i128) {
         from.require_auth();
+        Self::enter(&env);
         
         // CHECK PAUSED - This is the circuit breaker in action!
         Self::check_not_paused(&env);
@@ -116,7 +136,9 @@ impl PausableContract {
             .unwrap_or(0);
         
         // Update balance
-        let new_balance = current_balance + amount;
+        let new_balance = current_balance
+            .checked_add(amount)
+            .expect("balance overflow");
         env.storage().instance().set(&balance_key, &new_balance);
         
         // Update total supply
@@ -126,18 +148,21 @@ impl PausableContract {
             .unwrap_or(0);
         env.storage()
             .instance()
-            .set(&DataKey::TotalSupply, &(total_supply + amount));
+            .set(&DataKey::TotalSupply, &total_supply.checked_add(amount).expect("supply overflow"));
         
         // Emit event
         env.events().publish(
             (soroban_sdk::symbol_short!("deposit"), from.clone()),
             amount,
         );
+        
+        Self::exit(&env);
     }
 
     /// Claim/withdraw tokens (guarded by pause check)
     pub fn claim(env: Env, to: Address, amount: i128) {
         to.require_auth();
+        Self::enter(&env);
         
         // CHECK PAUSED - Circuit breaker!
         Self::check_not_paused(&env);
@@ -158,8 +183,13 @@ impl PausableContract {
             panic!("Insufficient balance");
         }
         
+        // Enforce the per-address withdrawal cap
+        Self::record_withdrawal(&env, &to, amount);
+        
         // Update balance
-        let new_balance = current_balance - amount;
+        let new_balance = current_balance
+            .checked_sub(amount)
+            .expect("balance underflow");
         env.storage().instance().set(&balance_key, &new_balance);
         
         // Update total supply
@@ -169,18 +199,21 @@ impl PausableContract {
             .unwrap_or(0);
         env.storage()
             .instance()
-            .set(&DataKey::TotalSupply, &(total_supply - amount));
+            .set(&DataKey::TotalSupply, &total_supply.checked_sub(amount).expect("supply underflow"));
         
         // Emit event
         env.events().publish(
             (soroban_sdk::symbol_short!("claim"), to.clone()),
             amount,
         );
+        
+        Self::exit(&env);
     }
 
     /// Mint new tokens (guarded by pause check)
     pub fn mint(env: Env, caller: Address, to: Address, amount: i128) {
         caller.require_auth();
+        Self::enter(&env);
         
         // Only admin can mint
         Self::check_admin(&env, &caller);
@@ -200,7 +233,9 @@ impl PausableContract {
             .unwrap_or(0);
         
         // Update balance
-        let new_balance = current_balance + amount;
+        let new_balance = current_balance
+            .checked_add(amount)
+            .expect("balance overflow");
         env.storage().instance().set(&balance_key, &new_balance);
         
         // Update total supply
@@ -210,13 +245,15 @@ impl PausableContract {
             .unwrap_or(0);
         env.storage()
             .instance()
-            .set(&DataKey::TotalSupply, &(total_supply + amount));
+            .set(&DataKey::TotalSupply, &total_supply.checked_add(amount).expect("supply overflow"));
         
         // Emit event
         env.events().publish(
             (soroban_sdk::symbol_short!("mint"), to.clone()),
             amount,
         );
+        
+        Self::exit(&env);
     }
 
     // ============================================
@@ -245,7 +282,9 @@ impl PausableContract {
             .unwrap_or(0);
         
         // Update balance
-        let new_balance = current_balance + amount;
+        let new_balance = current_balance
+            .checked_add(amount)
+            .expect("balance overflow");
         env.storage().instance().set(&balance_key, &new_balance);
         
         // Emit event
@@ -274,6 +313,42 @@ impl PausableContract {
         );
     }
 
+    /// Cap how much each address can claim within `window_ledgers` ledgers
+    /// Only callable by admin; emergency_rescue is not limited
+    pub fn set_withdraw_limit(env: Env, admin: Address, cap: i128, window_ledgers: u32) {
+        admin.require_auth();
+        
+        // Check if caller is admin
+        Self::check_admin(&env, &admin);
+        
+        if cap <= 0 {
+            panic!("Cap must be positive");
+        }
+        if window_ledgers == 0 {
+            panic!("Window must be at least one ledger");
+        }
+        
+        let limit = WithdrawLimit { cap, window_ledgers };
+        env.storage().instance().set(&DataKey::WithdrawLimit, &limit);
+        
+        // Emit event
+        env.events().publish(
+            (soroban_sdk::symbol_short!("wdlimit"),),
+            (cap, window_ledgers),
+        );
+    }
+
+    /// Remove the withdrawal cap
+    /// Only callable by admin
+    pub fn clear_withdraw_limit(env: Env, admin: Address) {
+        admin.require_auth();
+        
+        // Check if caller is admin
+        Self::check_admin(&env, &admin);
+        
+        env.storage().instance().remove(&DataKey::WithdrawLimit);
+    }
+
     // ============================================
     // VIEW FUNCTIONS (READ-ONLY, ALWAYS AVAILABLE)
     // ============================================
@@ -308,6 +383,24 @@ impl PausableContract {
         Self::is_paused(&env)
     }
 
+    /// Get the withdrawal cap, if one is set
+    pub fn withdraw_limit(env: Env) -> Option<WithdrawLimit> {
+        env.storage().instance().get(&DataKey::WithdrawLimit)
+    }
+
+    /// Get how much more an address can claim in its current window
+    /// Returns None when no cap is set
+    pub fn withdraw_allowance(env: Env, address: Address) -> Option<i128> {
+        let limit = Self::withdraw_limit(env.clone())?;
+        let window = Self::current_window(&env, &address, &limit);
+        Some(limit.cap - window.amount)
+    }
+
+    /// Check whether `initialize` has already been called
+    pub fn is_initialized(env: Env) -> bool {
+        env.storage().instance().has(&DataKey::Admin)
+    }
+
     // ============================================
     // HELPER FUNCTIONS (INTERNAL)
     // ============================================
@@ -320,6 +413,50 @@ impl PausableContract {
         }
     }
 
+    /// The address's withdrawal window, starting a fresh one if the last has ended
+    fn current_window(env: &Env, address: &Address, limit: &WithdrawLimit) -> WithdrawWindow {
+        let ledger = env.ledger().sequence();
+        let window: Option<WithdrawWindow> = env.storage()
+            .instance()
+            .get(&DataKey::Withdrawn(address.clone()));
+        
+        match window {
+            Some(window) if ledger < window.start_ledger.saturating_add(limit.window_ledgers) => window,
+            _ => WithdrawWindow { start_ledger: ledger, amount: 0 },
+        }
+    }
+
+    /// Count a claim against the address's cap, panic if it would exceed it
+    fn record_withdrawal(env: &Env, address: &Address, amount: i128) {
+        let limit: WithdrawLimit = match env.storage().instance().get(&DataKey::WithdrawLimit) {
+            Some(limit) => limit,
+            None => return,
+        };
+        
+        let mut window = Self::current_window(env, address, &limit);
+        let withdrawn = window.amount.checked_add(amount).expect("withdrawal overflow");
+        if withdrawn > limit.cap {
+            panic!("Withdrawal limit exceeded");
+        }
+        
+        window.amount = withdrawn;
+        env.storage().instance().set(&DataKey::Withdrawn(address.clone()), &window);
+    }
+
+    /// Take the reentrancy lock, panic if a call is already in progress
+    /// The lock lives in temporary storage, and a panic anywhere rolls it back
+    fn enter(env: &Env) {
+        if env.storage().temporary().has(&DataKey::Locked) {
+            panic!("Reentrant call");
+        }
+        env.storage().temporary().set(&DataKey::Locked, &true);
+    }
+
+    /// Release the reentrancy lock
+    fn exit(env: &Env) {
+        env.storage().temporary().remove(&DataKey::Locked);
+    }
+
     /// Check if caller is admin, panic if not
     fn check_admin(env: &Env, caller: &Address) {
         let admin: Address = env.storage()
@@ -336,7 +473,7 @@ impl PausableContract {
 #[cfg(test)]
 mod test {
     use super::*;
-    use soroban_sdk::{testutils::Address as _, Address, Env};
+    use soroban_sdk::{testutils::{Address as _, Ledger, LedgerInfo}, Address, Env};
 
     #[test]
     fn test_initialize() {
@@ -351,6 +488,35 @@ mod test {
         assert_eq!(client.get_admin(), admin);
     }
 
+    #[test]
+    fn test_is_initialized() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, PausableContract);
+        let client = PausableContractClient::new(&env, &contract_id);
+        
+        assert!(!client.is_initialized());
+        
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        
+        assert!(client.is_initialized());
+    }
+
+    #[test]
+    #[should_panic(expected = "Already initialized")]
+    fn test_initialize_twice() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, PausableContract);
+        let client = PausableContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        
+        // A second initialize must not replace the admin
+        let attacker = Address::generate(&env);
+        client.initialize(&attacker);
+    }
+
     #[test]
     fn test_pause_unpause() {
         let env = Env::default();
@@ -524,4 +690,200 @@ mod test {
         assert_eq!(client.paused(), true);
         assert_eq!(client.get_admin(), admin);
     }
-}
\ No newline at end of file
+
+    fn set_ledger(env: &Env, sequence_number: u32) {
+        env.ledger().set(LedgerInfo {
+            sequence_number,
+            ..env.ledger().get()
+        });
+    }
+
+    #[test]
+    fn test_claims_within_withdraw_limit() {
+        let env = Env::default();
+        env.mock_all_auths();
+        
+        let contract_id = env.register_contract(None, PausableContract);
+        let client = PausableContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        
+        client.initialize(&admin);
+        client.deposit(&user, &1000);
+        client.set_withdraw_limit(&admin, &500, &100);
+        set_ledger(&env, 10);
+        
+        // Claims add up to exactly the cap
+        client.claim(&user, &200);
+        client.claim(&user, &300);
+        assert_eq!(client.balance_of(&user), 500);
+        assert_eq!(client.withdraw_allowance(&user), Some(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Withdrawal limit exceeded")]
+    fn test_claim_over_withdraw_limit() {
+        let env = Env::default();
+        env.mock_all_auths();
+        
+        let contract_id = env.register_contract(None, PausableContract);
+        let client = PausableContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        
+        client.initialize(&admin);
+        client.deposit(&user, &1000);
+        client.set_withdraw_limit(&admin, &500, &100);
+        set_ledger(&env, 10);
+        
+        client.claim(&user, &400);
+        // Still inside the window, so this would take the user past the cap
+        set_ledger(&env, 109);
+        client.claim(&user, &101);
+    }
+
+    #[test]
+    fn test_withdraw_limit_resets_after_window() {
+        let env = Env::default();
+        env.mock_all_auths();
+        
+        let contract_id = env.register_contract(None, PausableContract);
+        let client = PausableContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        
+        client.initialize(&admin);
+        client.deposit(&user, &1000);
+        client.set_withdraw_limit(&admin, &500, &100);
+        set_ledger(&env, 10);
+        
+        client.claim(&user, &500);
+        
+        // The window started at ledger 10, so ledger 110 begins a new one
+        set_ledger(&env, 110);
+        assert_eq!(client.withdraw_allowance(&user), Some(500));
+        client.claim(&user, &500);
+        assert_eq!(client.balance_of(&user), 0);
+    }
+
+    #[test]
+    fn test_emergency_rescue_ignores_withdraw_limit() {
+        let env = Env::default();
+        env.mock_all_auths();
+        
+        let contract_id = env.register_contract(None, PausableContract);
+        let client = PausableContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        
+        client.initialize(&admin);
+        client.set_withdraw_limit(&admin, &500, &100);
+        
+        client.emergency_rescue(&admin, &user, &5000);
+        assert_eq!(client.balance_of(&user), 5000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Reentrant call")]
+    fn test_reentrant_claim_panics() {
+        let env = Env::default();
+        env.mock_all_auths();
+        
+        let contract_id = env.register_contract(None, PausableContract);
+        let client = PausableContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        
+        client.initialize(&admin);
+        client.deposit(&user, &1000);
+        
+        // Act as a callback made while a deposit is still running: the lock is
+        // held when the re-entering claim arrives
+        env.as_contract(&contract_id, || {
+            env.storage().temporary().set(&DataKey::Locked, &true);
+            PausableContract::claim(env.clone(), user.clone(), 500);
+        });
+    }
+
+    #[test]
+    fn test_lock_released_after_call() {
+        let env = Env::default();
+        env.mock_all_auths();
+        
+        let contract_id = env.register_contract(None, PausableContract);
+        let client = PausableContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        
+        client.initialize(&admin);
+        client.mint(&admin, &user, &1000);
+        client.deposit(&user, &1000);
+        client.claim(&user, &500);
+        
+        let locked = env.as_contract(&contract_id, || env.storage().temporary().has(&DataKey::Locked));
+        assert!(!locked);
+        assert_eq!(client.balance_of(&user), 1500);
+    }
+
+    #[test]
+    #[should_panic(expected = "balance overflow")]
+    fn test_deposit_near_max_balance_overflows() {
+        let env = Env::default();
+        env.mock_all_auths();
+        
+        let contract_id = env.register_contract(None, PausableContract);
+        let client = PausableContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        
+        client.initialize(&admin);
+        client.deposit(&user, &(i128::MAX - 10));
+        
+        // Must panic rather than wrap to a negative balance
+        client.deposit(&user, &11);
+    }
+
+    #[test]
+    #[should_panic(expected = "supply overflow")]
+    fn test_mint_near_max_supply_overflows() {
+        let env = Env::default();
+        env.mock_all_auths();
+        
+        let contract_id = env.register_contract(None, PausableContract);
+        let client = PausableContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        let (first, second) = (Address::generate(&env), Address::generate(&env));
+        
+        client.initialize(&admin);
+        client.mint(&admin, &first, &i128::MAX);
+        
+        // The second balance is fine on its own, only the supply overflows
+        client.mint(&admin, &second, &1);
+    }
+
+    #[test]
+    fn test_deposit_up_to_max_balance() {
+        let env = Env::default();
+        env.mock_all_auths();
+        
+        let contract_id = env.register_contract(None, PausableContract);
+        let client = PausableContractClient::new(&env, &contract_id);
+        
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        
+        client.initialize(&admin);
+        client.deposit(&user, &(i128::MAX - 10));
+        client.deposit(&user, &10);
+        assert_eq!(client.balance_of(&user), i128::MAX);
+        assert_eq!(client.total_supply(), i128::MAX);
+    }
+}
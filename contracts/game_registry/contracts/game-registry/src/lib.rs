@@ -1,5 +1,5 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String, Symbol};
+use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, String, Symbol, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -8,6 +8,10 @@ pub struct GameResult {
     pub white: Address,
     pub black: Address,
     pub timestamp: u64,
+    /// Half-moves played
+    pub move_count: u32,
+    /// SHA-256 of the game's PGN, so an off-chain copy can be checked against this record
+    pub pgn_hash: BytesN<32>,
 }
 
 #[contracttype]
@@ -15,27 +19,62 @@ pub enum DataKey {
     Admin,
     Server,
     Game(String),
+    /// Number of games recorded so far
+    GameCount,
+    /// Id of the n-th recorded game, in recording order
+    GameList(u32),
+    /// `TtlConfig` the contract was initialized with (instance storage)
+    TtlConfig,
 }
 
+/// When persistent entries are extended: once an entry's TTL drops below
+/// `threshold` ledgers, it is extended to `extend_to` ledgers.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TtlConfig {
+    pub threshold: u32,
+    pub extend_to: u32,
+}
+
+/// TTL settings used when `initialize` is not given any.
+pub const DEFAULT_TTL_THRESHOLD: u32 = 100_000;
+pub const DEFAULT_TTL_EXTEND_TO: u32 = 500_000;
+
+/// Most games `admin_list_games` returns in one call. Each game reads two
+/// ledger entries, so this keeps a page well inside the per-invocation footprint limit.
+pub const MAX_LIST_LIMIT: u32 = 25;
+
 #[contract]
 pub struct GameRegistry;
 
 #[contractimpl]
 impl GameRegistry {
     /// Initialize the contract with an admin and an authorized server address.
-    pub fn initialize(env: Env, admin: Address, server: Address) {
+    /// `ttl` sets how records are kept alive; None uses the default thresholds.
+    pub fn initialize(env: Env, admin: Address, server: Address, ttl: Option<TtlConfig>) {
         if env.storage().persistent().has(&DataKey::Admin) {
             panic!("Already initialized");
         }
+        let ttl = ttl.unwrap_or(TtlConfig {
+            threshold: DEFAULT_TTL_THRESHOLD,
+            extend_to: DEFAULT_TTL_EXTEND_TO,
+        });
+        if ttl.threshold > ttl.extend_to {
+            panic!("TTL threshold must not exceed extend_to");
+        }
+        env.storage().instance().set(&DataKey::TtlConfig, &ttl);
+        env.storage().instance().extend_ttl(ttl.threshold, ttl.extend_to);
+
         env.storage().persistent().set(&DataKey::Admin, &admin);
         env.storage().persistent().set(&DataKey::Server, &server);
 
         // Extend TTL for Admin and Server keys to prevent expiration
-        env.storage().persistent().extend_ttl(&DataKey::Admin, 100_000, 500_000);
-        env.storage().persistent().extend_ttl(&DataKey::Server, 100_000, 500_000);
+        Self::extend_ttl(&env, &DataKey::Admin);
+        Self::extend_ttl(&env, &DataKey::Server);
     }
 
     /// Records a game result. Only the authorized server can call this.
+    #[allow(clippy::too_many_arguments)]
     pub fn record_game(
         env: Env,
         game_id: String,
@@ -43,6 +82,8 @@ impl GameRegistry {
         white: Address,
         black: Address,
         timestamp: u64,
+        move_count: u32,
+        pgn_hash: BytesN<32>,
     ) {
         let server: Address = env.storage().persistent().get(&DataKey::Server).expect("Not initialized");
         server.require_auth();
@@ -56,29 +97,83 @@ impl GameRegistry {
             white,
             black,
             timestamp,
+            move_count,
+            pgn_hash: pgn_hash.clone(),
         };
 
         let key = DataKey::Game(game_id.clone());
         env.storage().persistent().set(&key, &result);
         
         // Extend TTL for the game record to ensure it stays active.
-        // We use some reasonable defaults for persistent storage.
-        env.storage().persistent().extend_ttl(&key, 100_000, 500_000);
+        Self::extend_ttl(&env, &key);
+
+        // Index the game so it can be listed without knowing its id
+        let count: u32 = env.storage().persistent().get(&DataKey::GameCount).unwrap_or(0);
+        let index_key = DataKey::GameList(count);
+        env.storage().persistent().set(&index_key, &game_id);
+        Self::extend_ttl(&env, &index_key);
+        env.storage().persistent().set(&DataKey::GameCount, &(count + 1));
+        Self::extend_ttl(&env, &DataKey::GameCount);
+
+        // The TTL config lives in instance storage, which must outlive the records
+        let ttl = Self::ttl_config(env.clone());
+        env.storage().instance().extend_ttl(ttl.threshold, ttl.extend_to);
 
         // Emit GameFinalized event
         // (Topic, Data)
         env.events().publish(
             (Symbol::new(&env, "GameFinalized"), game_id),
-            (winner, timestamp),
+            (winner, timestamp, pgn_hash),
         );
     }
 
-    /// Retrieves a recorded game result.
+    /// Retrieves a recorded game result. Panics with "Game not found" for an
+    /// unknown id, so callers that cannot be sure it was recorded should use
+    /// `find_game` instead.
     pub fn get_game(env: Env, game_id: String) -> GameResult {
-        env.storage()
-            .persistent()
-            .get(&DataKey::Game(game_id))
-            .expect("Game not found")
+        Self::find_game(env, game_id).expect("Game not found")
+    }
+
+    /// Retrieves a recorded game result, or None if no game has this id.
+    pub fn find_game(env: Env, game_id: String) -> Option<GameResult> {
+        env.storage().persistent().get(&DataKey::Game(game_id))
+    }
+
+    /// Lists up to `limit` recorded games, in recording order, starting at the
+    /// `start`-th. Only the admin can call this. `limit` is capped at `MAX_LIST_LIMIT`.
+    pub fn admin_list_games(env: Env, admin: Address, start: u32, limit: u32) -> Vec<GameResult> {
+        let stored_admin: Address = env.storage().persistent().get(&DataKey::Admin).expect("Not initialized");
+        if admin != stored_admin {
+            panic!("Not admin");
+        }
+        admin.require_auth();
+
+        let count: u32 = env.storage().persistent().get(&DataKey::GameCount).unwrap_or(0);
+        let end = start.saturating_add(limit.min(MAX_LIST_LIMIT)).min(count);
+
+        let mut games = Vec::new(&env);
+        for index in start..end {
+            let game_id: String = env
+                .storage()
+                .persistent()
+                .get(&DataKey::GameList(index))
+                .expect("Game index missing");
+            games.push_back(Self::get_game(env.clone(), game_id));
+        }
+        games
+    }
+
+    /// TTL thresholds applied to the contract's records.
+    pub fn ttl_config(env: Env) -> TtlConfig {
+        env.storage().instance().get(&DataKey::TtlConfig).unwrap_or(TtlConfig {
+            threshold: DEFAULT_TTL_THRESHOLD,
+            extend_to: DEFAULT_TTL_EXTEND_TO,
+        })
+    }
+
+    /// Number of games recorded so far.
+    pub fn game_count(env: Env) -> u32 {
+        env.storage().persistent().get(&DataKey::GameCount).unwrap_or(0)
     }
 
     /// Updates the authorized server address. Only the admin can call this.
@@ -86,7 +181,7 @@ impl GameRegistry {
         let admin: Address = env.storage().persistent().get(&DataKey::Admin).expect("Not initialized");
         admin.require_auth();
         env.storage().persistent().set(&DataKey::Server, &new_server);
-        env.storage().persistent().extend_ttl(&DataKey::Server, 100_000, 500_000);
+        Self::extend_ttl(&env, &DataKey::Server);
     }
 
     /// Updates the admin address. Only the current admin can call this.
@@ -94,7 +189,12 @@ impl GameRegistry {
         let admin: Address = env.storage().persistent().get(&DataKey::Admin).expect("Not initialized");
         admin.require_auth();
         env.storage().persistent().set(&DataKey::Admin, &new_admin);
-        env.storage().persistent().extend_ttl(&DataKey::Admin, 100_000, 500_000);
+        Self::extend_ttl(&env, &DataKey::Admin);
+    }
+
+    fn extend_ttl(env: &Env, key: &DataKey) {
+        let ttl = Self::ttl_config(env.clone());
+        env.storage().persistent().extend_ttl(key, ttl.threshold, ttl.extend_to);
     }
 }
 
@@ -1,8 +1,12 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::testutils::Address as _;
-use soroban_sdk::{Address, Env, String};
+use soroban_sdk::testutils::{storage::Persistent as _, Address as _, Events};
+use soroban_sdk::{vec, Address, BytesN, Env, IntoVal, String, Symbol};
+
+fn pgn_hash(env: &Env) -> BytesN<32> {
+    BytesN::from_array(env, &[7; 32])
+}
 
 #[test]
 fn test_game_registry_success() {
@@ -18,13 +22,13 @@ fn test_game_registry_success() {
     let client = GameRegistryClient::new(&env, &contract_id);
 
     // Initialize
-    client.initialize(&admin, &server);
+    client.initialize(&admin, &server, &None);
 
     let game_id = String::from_str(&env, "game-123");
     let timestamp = 1737500000u64;
     
     // Record game by server
-    client.record_game(&game_id, &player1, &player1, &player2, &timestamp);
+    client.record_game(&game_id, &player1, &player1, &player2, &timestamp, &40, &pgn_hash(&env));
 
     // Verify game retrieval
     let recorded_game = client.get_game(&game_id);
@@ -47,11 +51,11 @@ fn test_unauthorized_record() {
     let contract_id = env.register(GameRegistry, ());
     let client = GameRegistryClient::new(&env, &contract_id);
 
-    client.initialize(&admin, &server);
+    client.initialize(&admin, &server, &None);
 
     let game_id = String::from_str(&env, "fail");
     // This should panic because 'server' has not authorized the call.
-    client.record_game(&game_id, &player, &player, &player, &1);
+    client.record_game(&game_id, &player, &player, &player, &1, &40, &pgn_hash(&env));
 }
 
 #[test]
@@ -67,7 +71,7 @@ fn test_update_server_and_admin() {
     let contract_id = env.register(GameRegistry, ());
     let client = GameRegistryClient::new(&env, &contract_id);
 
-    client.initialize(&admin, &server);
+    client.initialize(&admin, &server, &None);
 
     // Change server
     client.set_server(&new_server);
@@ -77,7 +81,7 @@ fn test_update_server_and_admin() {
 
     // Verify we can still record with new server
     let game_id = String::from_str(&env, "game-456");
-    client.record_game(&game_id, &new_admin, &new_admin, &new_admin, &456);
+    client.record_game(&game_id, &new_admin, &new_admin, &new_admin, &456, &40, &pgn_hash(&env));
 }
 
 #[test]
@@ -90,6 +94,194 @@ fn test_double_initialize() {
     let contract_id = env.register(GameRegistry, ());
     let client = GameRegistryClient::new(&env, &contract_id);
 
-    client.initialize(&admin, &server);
-    client.initialize(&admin, &server);
+    client.initialize(&admin, &server, &None);
+    client.initialize(&admin, &server, &None);
+}
+
+#[test]
+fn test_admin_lists_games_in_pages() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let server = Address::generate(&env);
+    let white = Address::generate(&env);
+    let black = Address::generate(&env);
+
+    let contract_id = env.register(GameRegistry, ());
+    let client = GameRegistryClient::new(&env, &contract_id);
+
+    client.initialize(&admin, &server, &None);
+
+    let ids = ["game-0", "game-1", "game-2", "game-3", "game-4"];
+    for (i, id) in ids.iter().enumerate() {
+        client.record_game(&String::from_str(&env, id), &white, &white, &black, &(i as u64), &40, &pgn_hash(&env));
+    }
+    assert_eq!(client.game_count(), 5);
+
+    let first = client.admin_list_games(&admin, &0, &2);
+    assert_eq!(first.len(), 2);
+    assert_eq!(first.get(0).unwrap().timestamp, 0);
+    assert_eq!(first.get(1).unwrap().timestamp, 1);
+
+    // The last page is short, and paging past the end is empty
+    let last = client.admin_list_games(&admin, &4, &2);
+    assert_eq!(last.len(), 1);
+    assert_eq!(last.get(0).unwrap().timestamp, 4);
+    assert_eq!(client.admin_list_games(&admin, &5, &2).len(), 0);
+}
+
+#[test]
+fn test_admin_list_games_caps_limit() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let server = Address::generate(&env);
+    let player = Address::generate(&env);
+
+    let contract_id = env.register(GameRegistry, ());
+    let client = GameRegistryClient::new(&env, &contract_id);
+
+    client.initialize(&admin, &server, &None);
+
+    for i in 0..MAX_LIST_LIMIT + 5 {
+        let id = [b'a' + (i / 26) as u8, b'a' + (i % 26) as u8];
+        let id = core::str::from_utf8(&id).unwrap();
+        client.record_game(&String::from_str(&env, id), &player, &player, &player, &(i as u64), &40, &pgn_hash(&env));
+    }
+
+    assert_eq!(client.admin_list_games(&admin, &0, &u32::MAX).len(), MAX_LIST_LIMIT);
+}
+
+#[test]
+#[should_panic(expected = "Not admin")]
+fn test_non_admin_cannot_list_games() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let server = Address::generate(&env);
+    let outsider = Address::generate(&env);
+
+    let contract_id = env.register(GameRegistry, ());
+    let client = GameRegistryClient::new(&env, &contract_id);
+
+    client.initialize(&admin, &server, &None);
+    client.record_game(&String::from_str(&env, "game-1"), &admin, &admin, &server, &1, &40, &pgn_hash(&env));
+
+    client.admin_list_games(&outsider, &0, &10);
+}
+
+#[test]
+fn test_record_game_with_pgn_hash() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let server = Address::generate(&env);
+    let white = Address::generate(&env);
+    let black = Address::generate(&env);
+
+    let contract_id = env.register(GameRegistry, ());
+    let client = GameRegistryClient::new(&env, &contract_id);
+
+    client.initialize(&admin, &server, &None);
+
+    let game_id = String::from_str(&env, "game-pgn");
+    let hash = BytesN::from_array(&env, &[0xab; 32]);
+    client.record_game(&game_id, &black, &white, &black, &1737500000, &57, &hash);
+
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (Symbol::new(&env, "GameFinalized"), game_id.clone()).into_val(&env),
+                (black.clone(), 1737500000u64, hash.clone()).into_val(&env),
+            ),
+        ]
+    );
+
+    let recorded_game = client.get_game(&game_id);
+    assert_eq!(recorded_game.move_count, 57);
+    assert_eq!(recorded_game.pgn_hash, hash);
+}
+
+#[test]
+fn test_records_use_configured_ttl() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let server = Address::generate(&env);
+    let new_server = Address::generate(&env);
+    let player = Address::generate(&env);
+
+    let contract_id = env.register(GameRegistry, ());
+    let client = GameRegistryClient::new(&env, &contract_id);
+
+    let ttl = TtlConfig { threshold: 10_000, extend_to: 20_000 };
+    client.initialize(&admin, &server, &Some(ttl.clone()));
+    assert_eq!(client.ttl_config(), ttl);
+
+    let game_id = String::from_str(&env, "game-ttl");
+    client.record_game(&game_id, &player, &player, &player, &1, &40, &pgn_hash(&env));
+    client.set_server(&new_server);
+
+    env.as_contract(&contract_id, || {
+        let persistent = env.storage().persistent();
+        assert_eq!(persistent.get_ttl(&DataKey::Game(game_id.clone())), 20_000);
+        assert_eq!(persistent.get_ttl(&DataKey::GameList(0)), 20_000);
+        assert_eq!(persistent.get_ttl(&DataKey::Server), 20_000);
+        assert_eq!(persistent.get_ttl(&DataKey::Admin), 20_000);
+    });
+}
+
+#[test]
+#[should_panic(expected = "TTL threshold must not exceed extend_to")]
+fn test_initialize_rejects_inverted_ttl() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let server = Address::generate(&env);
+
+    let contract_id = env.register(GameRegistry, ());
+    let client = GameRegistryClient::new(&env, &contract_id);
+
+    client.initialize(&admin, &server, &Some(TtlConfig { threshold: 20_000, extend_to: 10_000 }));
+}
+
+#[test]
+fn test_find_game_for_missing_game() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let server = Address::generate(&env);
+
+    let contract_id = env.register(GameRegistry, ());
+    let client = GameRegistryClient::new(&env, &contract_id);
+
+    client.initialize(&admin, &server, &None);
+    let game_id = String::from_str(&env, "game-1");
+    assert_eq!(client.find_game(&game_id), None);
+
+    client.record_game(&game_id, &admin, &admin, &server, &1, &40, &pgn_hash(&env));
+    assert_eq!(client.find_game(&game_id), Some(client.get_game(&game_id)));
+    assert_eq!(client.find_game(&String::from_str(&env, "game-2")), None);
+}
+
+#[test]
+#[should_panic(expected = "Game not found")]
+fn test_get_game_panics_for_missing_game() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let server = Address::generate(&env);
+
+    let contract_id = env.register(GameRegistry, ());
+    let client = GameRegistryClient::new(&env, &contract_id);
+
+    client.initialize(&admin, &server, &None);
+    client.get_game(&String::from_str(&env, "missing"));
 }